@@ -0,0 +1,35 @@
+// With the regen-uapi feature, generates the Landlock UAPI bindings from the installed kernel
+// headers instead of using the bundled src/uapi/landlock.rs file.
+
+#[cfg(feature = "regen-uapi")]
+fn regen_uapi() {
+    use std::env;
+    use std::path::PathBuf;
+
+    const HEADER_VAR: &str = "LANDLOCK_UAPI_HEADER";
+
+    println!("cargo:rerun-if-env-changed={HEADER_VAR}");
+    let builder = match env::var(HEADER_VAR) {
+        Ok(header) => {
+            println!("cargo:rerun-if-changed={header}");
+            bindgen::Builder::default().header(header)
+        }
+        Err(_) => bindgen::Builder::default()
+            .header_contents("landlock_wrapper.h", "#include <linux/landlock.h>\n"),
+    };
+    let out = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is not set")).join("landlock.rs");
+    builder
+        .allowlist_type("landlock_.*")
+        .allowlist_var("LANDLOCK_.*")
+        .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
+        .generate()
+        .expect("failed to generate the Landlock bindings from <linux/landlock.h>")
+        .write_to_file(out)
+        .expect("failed to write the Landlock bindings");
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "regen-uapi")]
+    regen_uapi();
+}
@@ -0,0 +1,125 @@
+#ifndef LANDLOCK_RS_H
+#define LANDLOCK_RS_H
+
+/* Generated with cbindgen from src/capi.rs, do not edit. */
+
+#include <stdint.h>
+
+/*
+ Best-effort compatibility level, see [`CompatLevel::BestEffort`].
+ */
+#define LANDLOCK_RS_BEST_EFFORT 0
+
+/*
+ Soft requirement compatibility level, see [`CompatLevel::SoftRequirement`].
+ */
+#define LANDLOCK_RS_SOFT_REQUIREMENT 1
+
+/*
+ Hard requirement compatibility level, see [`CompatLevel::HardRequirement`].
+ */
+#define LANDLOCK_RS_HARD_REQUIREMENT 2
+
+/*
+ Warn-only compatibility level, see [`CompatLevel::WarnOnly`].
+ */
+#define LANDLOCK_RS_WARN_ONLY 3
+
+/*
+ Returned by [`landlock_rs_ruleset_restrict_self()`], see [`RulesetStatus::NotEnforced`].
+ */
+#define LANDLOCK_RS_NOT_ENFORCED 0
+
+/*
+ Returned by [`landlock_rs_ruleset_restrict_self()`],
+ see [`RulesetStatus::PartiallyEnforced`].
+ */
+#define LANDLOCK_RS_PARTIALLY_ENFORCED 1
+
+/*
+ Returned by [`landlock_rs_ruleset_restrict_self()`], see [`RulesetStatus::FullyEnforced`].
+ */
+#define LANDLOCK_RS_FULLY_ENFORCED 2
+
+/*
+ Ruleset being built, opaque to C callers.
+
+ If adding a rule fails, the ruleset cannot be used anymore but must still be freed.
+ */
+struct landlock_rs_ruleset;
+
+/*
+ Gets the Landlock ABI version supported by the running kernel, or 0 if Landlock is not
+ supported.
+ */
+int landlock_rs_abi_version(void);
+
+/*
+ Gets the last error message of the calling thread, or NULL if no error happened.
+
+ The returned string is valid until the next call to a `landlock_rs_*` function
+ on the same thread.
+ */
+const char *landlock_rs_last_error(void);
+
+/*
+ Creates a ruleset handling the `handled_fs` and `handled_net` access rights
+ (i.e. `LANDLOCK_ACCESS_FS_*` and `LANDLOCK_ACCESS_NET_*` bits)
+ with the `compat_level` compatibility level (e.g. `LANDLOCK_RS_BEST_EFFORT`).
+
+ Returns NULL on error.  The returned ruleset must be freed with
+ [`landlock_rs_ruleset_free()`].
+ */
+struct landlock_rs_ruleset *landlock_rs_ruleset_new(uint64_t handled_fs,
+                                                    uint64_t handled_net,
+                                                    int compat_level);
+
+/*
+ Allows the `access` file system rights beneath `path`.
+
+ Access rights only applying to directories are ignored if `path` is a file.
+ Returns 0 on success or -1 on error.
+
+ # Safety
+
+ `ruleset` must come from [`landlock_rs_ruleset_new()`] and `path` must be a valid C string.
+ */
+int landlock_rs_ruleset_add_path(struct landlock_rs_ruleset *ruleset,
+                                 const char *path,
+                                 uint64_t access);
+
+/*
+ Allows the `access` network rights for the TCP `port`.
+
+ Returns 0 on success or -1 on error.
+
+ # Safety
+
+ `ruleset` must come from [`landlock_rs_ruleset_new()`].
+ */
+int landlock_rs_ruleset_add_port(struct landlock_rs_ruleset *ruleset,
+                                 uint16_t port,
+                                 uint64_t access);
+
+/*
+ Enforces the ruleset on the calling thread, with `no_new_privs`.
+
+ Returns the enforcement status (e.g. `LANDLOCK_RS_FULLY_ENFORCED`) or -1 on error.
+ The ruleset cannot be used anymore but must still be freed.
+
+ # Safety
+
+ `ruleset` must come from [`landlock_rs_ruleset_new()`].
+ */
+int landlock_rs_ruleset_restrict_self(struct landlock_rs_ruleset *ruleset);
+
+/*
+ Frees a ruleset and closes its file descriptor.  Does nothing if `ruleset` is NULL.
+
+ # Safety
+
+ `ruleset` must come from [`landlock_rs_ruleset_new()`] and must not be used afterwards.
+ */
+void landlock_rs_ruleset_free(struct landlock_rs_ruleset *ruleset);
+
+#endif  /* LANDLOCK_RS_H */
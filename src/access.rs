@@ -1,6 +1,7 @@
 use crate::{
-    AccessError, AddRuleError, AddRulesError, BitFlags, CompatError, CompatResult,
-    HandleAccessError, HandleAccessesError, Ruleset, TailoredCompatLevel, TryCompat, ABI,
+    AccessError, AddRuleError, AddRulesError, BitFlags, CompatError, CompatResult, Downgrade,
+    HandleAccessError, HandleAccessesError, ParseAccessError, Ruleset, TailoredCompatLevel,
+    TryCompat, ABI,
 };
 use enumflags2::BitFlag;
 
@@ -9,25 +10,76 @@ use crate::{make_bitflags, AccessFs, CompatLevel, CompatState, Compatibility};
 
 pub trait Access: PrivateAccess {
     /// Gets the access rights defined by a specific [`ABI`].
-    /// Union of [`from_read()`](Access::from_read) and [`from_write()`](Access::from_write).
-    fn from_all(abi: ABI) -> BitFlags<Self> {
-        // An empty access-right would be an error if passed to the kernel, but because the kernel
-        // doesn't support Landlock, no Landlock syscall should be called.  try_compat() should
-        // also return RestrictionStatus::Unrestricted when called with unsupported/empty
-        // access-righs.
-        Self::from_read(abi) | Self::from_write(abi)
+    ///
+    /// An empty access-right would be an error if passed to the kernel, but because the kernel
+    /// doesn't support the related Landlock features, no Landlock syscall should be called.
+    /// try_compat() should also return RestrictionStatus::Unrestricted when called with
+    /// unsupported/empty access-rights.
+    fn from_all(abi: ABI) -> BitFlags<Self>;
+
+    /// Parses a set of access rights from their names separated with `|`
+    /// (e.g. `"execute|read_file"`), as printed by [`to_names()`](Access::to_names).
+    ///
+    /// Names are the snake case versions of the variants (e.g. `read_file` for
+    /// [`AccessFs::ReadFile`](crate::AccessFs::ReadFile)), and are stable across versions.
+    /// Whitespaces around names are ignored, and an empty string gives an empty set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{make_bitflags, Access, AccessFs};
+    ///
+    /// assert_eq!(
+    ///     AccessFs::from_names("execute | read_file").unwrap(),
+    ///     make_bitflags!(AccessFs::{Execute | ReadFile})
+    /// );
+    /// assert!(AccessFs::from_names("read_file|fly").is_err());
+    /// ```
+    fn from_names(names: &str) -> Result<BitFlags<Self>, ParseAccessError> {
+        if names.trim().is_empty() {
+            return Ok(BitFlags::EMPTY);
+        }
+        names
+            .split('|')
+            .map(|name| parse_name(name.trim()).map(BitFlags::from_flag))
+            .collect()
     }
 
-    /// Gets the access rights identified as read-only according to a specific ABI.
-    /// Exclusive with [`from_write()`](Access::from_write).
-    fn from_read(abi: ABI) -> BitFlags<Self>;
+    /// Converts a raw mask (e.g. from a configuration or an FFI caller) to a set of access rights,
+    /// with the same bit values as the kernel's `LANDLOCK_ACCESS_*` constants.
+    ///
+    /// Unlike `BitFlags::from_bits_truncate()`, bits unknown to this crate are not ignored
+    /// but returned in a [`ParseAccessError::UnknownBits`] error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Access, AccessFs, AccessNet, ParseAccessError};
+    ///
+    /// assert_eq!(
+    ///     AccessFs::try_from_bits(0b101).unwrap(),
+    ///     AccessFs::Execute | AccessFs::ReadFile
+    /// );
+    /// assert!(matches!(
+    ///     AccessNet::try_from_bits(0b111),
+    ///     Err(ParseAccessError::UnknownBits { unknown: 0b100, .. })
+    /// ));
+    /// ```
+    fn try_from_bits(bits: u64) -> Result<BitFlags<Self>, ParseAccessError> {
+        BitFlags::<Self>::from_bits(bits).map_err(|e| ParseAccessError::UnknownBits {
+            bits,
+            unknown: e.invalid_bits(),
+        })
+    }
 
-    /// Gets the access rights identified as write-only according to a specific ABI.
-    /// Exclusive with [`from_read()`](Access::from_read).
-    fn from_write(abi: ABI) -> BitFlags<Self>;
+    /// Formats a set of access rights as their names separated with `|`,
+    /// in the order of their bits (e.g. `"execute|read_file"`).
+    fn to_names(access: BitFlags<Self>) -> String {
+        access.iter().map(Self::name).collect::<Vec<_>>().join("|")
+    }
 }
 
-pub trait PrivateAccess: BitFlag {
+pub trait PrivateAccess: BitFlag<Numeric = u64> {
     fn ruleset_handle_access(
         ruleset: &mut Ruleset,
         access: BitFlags<Self>,
@@ -42,6 +94,99 @@ pub trait PrivateAccess: BitFlag {
     fn into_handle_accesses_error(error: HandleAccessError<Self>) -> HandleAccessesError
     where
         Self: Access;
+
+    fn into_downgrade(error: CompatError<Self>) -> Downgrade
+    where
+        Self: Access;
+
+    // Stable name of an access right, used for parsing and formatting.
+    fn name(self) -> &'static str;
+}
+
+// Parses the name of a single access right.
+pub(crate) fn parse_name<A>(name: &str) -> Result<A, ParseAccessError>
+where
+    A: Access,
+{
+    BitFlags::<A>::all()
+        .iter()
+        .find(|a| a.name() == name)
+        .ok_or_else(|| ParseAccessError::UnknownName {
+            name: name.to_string(),
+        })
+}
+
+#[test]
+fn access_try_from_bits() {
+    use crate::AccessNet;
+
+    let all = BitFlags::<AccessFs>::all();
+    assert_eq!(AccessFs::try_from_bits(all.bits()).unwrap(), all);
+    assert_eq!(AccessFs::try_from_bits(0).unwrap(), BitFlags::EMPTY);
+    assert_eq!(
+        AccessNet::try_from_bits(
+            (crate::uapi::LANDLOCK_ACCESS_NET_BIND_TCP
+                | crate::uapi::LANDLOCK_ACCESS_NET_CONNECT_TCP)
+                .into()
+        )
+        .unwrap(),
+        AccessNet::BindTcp | AccessNet::ConnectTcp
+    );
+
+    let unknown = 1 << 63;
+    assert_eq!(
+        AccessFs::try_from_bits(all.bits() | unknown).unwrap_err(),
+        ParseAccessError::UnknownBits {
+            bits: all.bits() | unknown,
+            unknown,
+        }
+    );
+    assert_eq!(
+        AccessNet::try_from_bits(0x4).unwrap_err().to_string(),
+        "unknown access-right bits 0x4 in 0x4"
+    );
+}
+
+#[test]
+fn access_names() {
+    use crate::AccessNet;
+    use std::str::FromStr;
+
+    for access in BitFlags::<AccessFs>::all() {
+        assert_eq!(AccessFs::from_str(&access.to_string()).unwrap(), access);
+    }
+    for access in BitFlags::<AccessNet>::all() {
+        assert_eq!(access.to_string().parse::<AccessNet>().unwrap(), access);
+    }
+    assert_eq!(AccessFs::MakeSym.to_string(), "make_sym");
+    assert_eq!(AccessNet::ConnectTcp.to_string(), "connect_tcp");
+
+    let all = BitFlags::<AccessFs>::all();
+    assert_eq!(AccessFs::from_names(&AccessFs::to_names(all)).unwrap(), all);
+    assert_eq!(
+        AccessFs::to_names(AccessFs::ReadFile | AccessFs::Execute),
+        "execute|read_file"
+    );
+    assert_eq!(
+        AccessNet::from_names(" bind_tcp |connect_tcp ").unwrap(),
+        AccessNet::BindTcp | AccessNet::ConnectTcp
+    );
+    assert_eq!(AccessFs::from_names("").unwrap(), BitFlags::EMPTY);
+    assert_eq!(AccessFs::to_names(BitFlags::EMPTY), "");
+
+    // Names are not shared between access types, and must not be empty.
+    assert_eq!(
+        AccessFs::from_names("read_file|bind_tcp").unwrap_err(),
+        ParseAccessError::UnknownName {
+            name: "bind_tcp".into()
+        }
+    );
+    assert!(AccessFs::from_names("read_file|").is_err());
+    assert!("ReadFile".parse::<AccessFs>().is_err());
+    assert_eq!(
+        "fly".parse::<AccessNet>().unwrap_err().to_string(),
+        "unknown access right \"fly\""
+    );
 }
 
 // Creates an illegal/overflowed BitFlags<T> with all its bits toggled, including undefined ones.
@@ -0,0 +1,264 @@
+use crate::{Access, BitFlags, ParseAccessError, ABI};
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Set of access rights of the same type (e.g. `AccessSet<AccessFs>`).
+///
+/// `AccessSet` is owned by this crate, unlike [`BitFlags`] which comes from the `enumflags2`
+/// crate, and will replace it in the public API in a future major version.
+/// Meanwhile, an `AccessSet` can be passed to any method taking access rights
+/// (i.e. `Into<BitFlags<_>>`), and converted from and to a `BitFlags` with [`From`].
+///
+/// Unlike `BitFlags`, there is no negation nor `all()` constructor,
+/// which would include access rights unknown to the caller
+/// (see [`ABI`] for the rationale): use a difference with [`from_abi()`](AccessSet::from_abi)
+/// instead.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{AccessFs, AccessSet, ABI};
+///
+/// let read: AccessSet<AccessFs> = AccessFs::from_read(ABI::V1).into();
+/// let read_no_exec = read - AccessFs::Execute;
+/// assert!(read.contains(read_no_exec));
+/// assert_eq!(read_no_exec.to_string(), "read_file|read_dir");
+/// assert_eq!("read_file|read_dir".parse(), Ok(read_no_exec));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AccessSet<A>(BitFlags<A>)
+where
+    A: Access;
+
+impl<A> AccessSet<A>
+where
+    A: Access,
+{
+    /// Set without any access right.
+    pub const EMPTY: Self = AccessSet(BitFlags::EMPTY);
+
+    /// Gets all the access rights defined by a specific [`ABI`],
+    /// see [`Access::from_all()`].
+    pub fn from_abi(abi: ABI) -> Self {
+        AccessSet(A::from_all(abi))
+    }
+
+    /// Returns true if this set doesn't contain any access right.
+    pub fn is_empty(self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Gets the number of access rights in this set.
+    pub fn len(self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if all the access rights of `other` are in this set.
+    pub fn contains<T>(self, other: T) -> bool
+    where
+        T: Into<Self>,
+    {
+        self.0.contains(other.into().0)
+    }
+
+    /// Returns true if at least one access right of `other` is in this set.
+    pub fn intersects<T>(self, other: T) -> bool
+    where
+        T: Into<Self>,
+    {
+        self.0.intersects(other.into().0)
+    }
+
+    /// Iterates over the access rights of this set, in the order of their bits.
+    pub fn iter(self) -> impl Iterator<Item = A> + Clone {
+        self.0.iter()
+    }
+
+    /// Gets the raw value of this set,
+    /// with the same bit values as the kernel's `LANDLOCK_ACCESS_*` constants.
+    pub fn bits(self) -> u64 {
+        self.0.bits()
+    }
+}
+
+impl<A> Default for AccessSet<A>
+where
+    A: Access,
+{
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl<A> From<A> for AccessSet<A>
+where
+    A: Access,
+{
+    fn from(access: A) -> Self {
+        AccessSet(access.into())
+    }
+}
+
+impl<A> From<BitFlags<A>> for AccessSet<A>
+where
+    A: Access,
+{
+    fn from(access: BitFlags<A>) -> Self {
+        AccessSet(access)
+    }
+}
+
+impl<A> From<AccessSet<A>> for BitFlags<A>
+where
+    A: Access,
+{
+    fn from(access: AccessSet<A>) -> Self {
+        access.0
+    }
+}
+
+/// Same as [`Access::try_from_bits()`].
+impl<A> TryFrom<u64> for AccessSet<A>
+where
+    A: Access,
+{
+    type Error = ParseAccessError;
+
+    fn try_from(bits: u64) -> Result<Self, Self::Error> {
+        A::try_from_bits(bits).map(AccessSet)
+    }
+}
+
+impl<A> FromIterator<A> for AccessSet<A>
+where
+    A: Access,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+    {
+        AccessSet(iter.into_iter().collect())
+    }
+}
+
+/// Prints the names of the access rights, see [`Access::to_names()`].
+impl<A> fmt::Display for AccessSet<A>
+where
+    A: Access,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&A::to_names(self.0))
+    }
+}
+
+impl<A> fmt::Debug for AccessSet<A>
+where
+    A: Access,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AccessSet({self})")
+    }
+}
+
+/// Parses the names of the access rights, see [`Access::from_names()`].
+impl<A> FromStr for AccessSet<A>
+where
+    A: Access,
+{
+    type Err = ParseAccessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        A::from_names(s).map(AccessSet)
+    }
+}
+
+macro_rules! impl_set_op {
+    ($op:ident, $method:ident, $op_assign:ident, $method_assign:ident, |$a:ident, $b:ident| $e:expr) => {
+        impl<A, T> $op<T> for AccessSet<A>
+        where
+            A: Access,
+            T: Into<AccessSet<A>>,
+        {
+            type Output = Self;
+
+            fn $method(self, other: T) -> Self {
+                let ($a, $b) = (self.0, other.into().0);
+                AccessSet($e)
+            }
+        }
+
+        impl<A, T> $op_assign<T> for AccessSet<A>
+        where
+            A: Access,
+            T: Into<AccessSet<A>>,
+        {
+            fn $method_assign(&mut self, other: T) {
+                *self = $op::$method(*self, other);
+            }
+        }
+    };
+}
+
+impl_set_op!(BitOr, bitor, BitOrAssign, bitor_assign, |a, b| a | b);
+impl_set_op!(BitAnd, bitand, BitAndAssign, bitand_assign, |a, b| a & b);
+impl_set_op!(Sub, sub, SubAssign, sub_assign, |a, b| a & !b);
+
+#[cfg(feature = "serde")]
+impl<A> serde::Serialize for AccessSet<A>
+where
+    A: Access,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        crate::serde_names::serialize(&self.0, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, A> serde::Deserialize<'de> for AccessSet<A>
+where
+    A: Access,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        crate::serde_names::deserialize(deserializer).map(AccessSet)
+    }
+}
+
+#[test]
+fn access_set() {
+    use crate::{AccessFs, AccessNet, PathBeneath, PathFd};
+
+    let read = AccessSet::<AccessFs>::from_abi(ABI::V1) - AccessFs::from_write(ABI::V1);
+    assert_eq!(BitFlags::from(read), AccessFs::from_read(ABI::V1));
+    assert_eq!(read.len(), 3);
+    assert!(read.contains(AccessFs::ReadDir));
+    assert!(!read.contains(AccessFs::ReadDir | AccessFs::WriteFile));
+    assert!(read.intersects(AccessFs::ReadDir | AccessFs::WriteFile));
+    assert_eq!(read & AccessFs::ReadDir, AccessFs::ReadDir.into());
+    assert_eq!(
+        read.iter().collect::<AccessSet<_>>(),
+        read,
+        "collected from iter()"
+    );
+
+    let mut set = AccessSet::default();
+    assert!(set.is_empty());
+    set |= AccessNet::BindTcp;
+    set |= AccessNet::ConnectTcp;
+    set -= AccessNet::BindTcp;
+    assert_eq!(set, AccessNet::ConnectTcp.into());
+    assert_eq!(format!("{set:?}"), "AccessSet(connect_tcp)");
+    assert_eq!(AccessSet::<AccessNet>::try_from(set.bits()).unwrap(), set);
+    assert!(AccessSet::<AccessNet>::try_from(1 << 63).is_err());
+
+    // Can be used with the current API.
+    let _ = PathBeneath::new(PathFd::new("/").unwrap(), read);
+}
@@ -0,0 +1,301 @@
+use crate::{AccessNet, ListenFdsError, NetPort};
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::io::Error;
+use std::mem;
+use std::os::raw::c_int;
+use std::os::unix::io::{BorrowedFd, RawFd};
+
+/// Socket (or other file descriptor) passed by the service manager, see [`ListenFds`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ListenFd {
+    /// File descriptor, owned by the process and kept open.
+    pub fd: RawFd,
+    /// Name of the file descriptor (e.g. set with `FileDescriptorName=`), if any.
+    pub name: Option<String>,
+    /// Local port of an IPv4 or IPv6 stream socket, if any.
+    pub tcp_port: Option<u16>,
+}
+
+/// File descriptors passed by systemd (or another service manager) to a socket-activated
+/// service, as described by `sd_listen_fds(3)`.
+///
+/// Landlock doesn't restrict already bound sockets,
+/// which then stay usable (e.g. to accept connections) after enforcing a ruleset.
+/// [`bind_rules()`](ListenFds::bind_rules) allows to bind the same ports again,
+/// e.g. to create another socket after a reload.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{
+///     Access, AccessNet, ListenFds, Ruleset, RulesetAttr, RulesetCreatedAttr, RulesetError, ABI,
+/// };
+///
+/// fn restrict_bind() -> Result<(), Box<dyn std::error::Error>> {
+///     let listen_fds = ListenFds::from_env()?;
+///     let status = Ruleset::default()
+///         .handle_access(AccessNet::BindTcp)?
+///         .create()?
+///         .add_rules(listen_fds.bind_rules().map(Ok::<_, RulesetError>))?
+///         .restrict_self()?;
+///     println!("bindable ports restricted: {:?}", status.ruleset);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListenFds {
+    fds: Vec<ListenFd>,
+}
+
+impl ListenFds {
+    /// First file descriptor passed by the service manager (i.e. `SD_LISTEN_FDS_START`).
+    pub const START_FD: RawFd = 3;
+    /// Name of the environment variable identifying the process for which the file descriptors
+    /// are intended.
+    pub const ENV_PID_NAME: &'static str = "LISTEN_PID";
+    /// Name of the environment variable containing the number of passed file descriptors.
+    pub const ENV_FDS_NAME: &'static str = "LISTEN_FDS";
+    /// Name of the environment variable listing the names of the passed file descriptors.
+    pub const ENV_FDNAMES_NAME: &'static str = "LISTEN_FDNAMES";
+
+    /// Gets the file descriptors passed to the calling process
+    /// according to the `LISTEN_PID`, `LISTEN_FDS`, and `LISTEN_FDNAMES` environment variables.
+    ///
+    /// If `LISTEN_PID` is not set or identifies another process (e.g. the parent),
+    /// no file descriptor is returned.
+    /// The passed file descriptors are set close-on-exec, as with `sd_listen_fds(3)`,
+    /// but the environment variables are not removed.
+    pub fn from_env() -> Result<Self, ListenFdsError> {
+        Self::from_vars(std::process::id(), Self::START_FD, |name| {
+            std::env::var_os(name)
+        })
+    }
+
+    fn from_vars<F>(pid: u32, start_fd: RawFd, var: F) -> Result<Self, ListenFdsError>
+    where
+        F: Fn(&str) -> Option<OsString>,
+    {
+        let parse = |name: &str| {
+            var(name)
+                .map(|value| {
+                    value
+                        .to_str()
+                        .and_then(|v| v.parse::<u32>().ok())
+                        .ok_or_else(|| ListenFdsError::InvalidVar {
+                            name: name.to_string(),
+                            value: value.to_string_lossy().into_owned(),
+                        })
+                })
+                .transpose()
+        };
+        if parse(Self::ENV_PID_NAME)? != Some(pid) {
+            return Ok(Self::default());
+        }
+        let count = parse(Self::ENV_FDS_NAME)?.unwrap_or(0) as usize;
+        let names = match var(Self::ENV_FDNAMES_NAME) {
+            Some(value) => {
+                let names: Vec<_> = value
+                    .to_string_lossy()
+                    .split(':')
+                    .map(String::from)
+                    .collect();
+                if names.len() != count {
+                    return Err(ListenFdsError::InvalidVar {
+                        name: Self::ENV_FDNAMES_NAME.to_string(),
+                        value: value.to_string_lossy().into_owned(),
+                    });
+                }
+                names.into_iter().map(Some).collect()
+            }
+            None => vec![None; count],
+        };
+
+        let mut fds = Vec::with_capacity(count);
+        for (fd, name) in (start_fd..).zip(names) {
+            // Safety: the file descriptor is owned by the process according to the service
+            // manager, and its validity is checked by fcntl(2).
+            crate::ruleset::set_cloexec(unsafe { BorrowedFd::borrow_raw(fd) }, true)
+                .map_err(|source| ListenFdsError::FdCall { fd, source })?;
+            let tcp_port = tcp_port(fd).map_err(|source| ListenFdsError::FdCall { fd, source })?;
+            fds.push(ListenFd { fd, name, tcp_port });
+        }
+        Ok(ListenFds { fds })
+    }
+
+    /// Gets the passed file descriptors, in order.
+    pub fn fds(&self) -> &[ListenFd] {
+        &self.fds
+    }
+
+    /// Gets a rule allowing [`AccessNet::BindTcp`] for each distinct TCP port
+    /// of the passed sockets.
+    pub fn bind_rules(&self) -> impl Iterator<Item = NetPort> {
+        self.fds
+            .iter()
+            .filter_map(|fd| fd.tcp_port)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|port| NetPort::new(port, AccessNet::BindTcp))
+    }
+}
+
+// Gets the local port of an IPv4 or IPv6 stream socket.
+fn tcp_port(fd: RawFd) -> Result<Option<u16>, Error> {
+    let mut sock_type: c_int = 0;
+    let mut len = mem::size_of_val(&sock_type) as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_TYPE,
+            (&mut sock_type as *mut c_int).cast(),
+            &mut len,
+        )
+    };
+    if ret != 0 {
+        let err = Error::last_os_error();
+        // E.g. a FIFO passed with ListenFIFO=.
+        if err.raw_os_error() == Some(libc::ENOTSOCK) {
+            return Ok(None);
+        }
+        return Err(err);
+    }
+    if sock_type != libc::SOCK_STREAM {
+        return Ok(None);
+    }
+
+    let mut addr: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let mut len = mem::size_of_val(&addr) as libc::socklen_t;
+    if unsafe {
+        libc::getsockname(
+            fd,
+            (&mut addr as *mut libc::sockaddr_storage).cast(),
+            &mut len,
+        )
+    } != 0
+    {
+        return Err(Error::last_os_error());
+    }
+    // Safety: the address is initialized according to its family.
+    let port = match addr.ss_family as c_int {
+        libc::AF_INET => unsafe {
+            (*(&addr as *const libc::sockaddr_storage).cast::<libc::sockaddr_in>()).sin_port
+        },
+        libc::AF_INET6 => unsafe {
+            (*(&addr as *const libc::sockaddr_storage).cast::<libc::sockaddr_in6>()).sin6_port
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(u16::from_be(port)))
+}
+
+#[test]
+fn listen_fds() {
+    use std::net::{TcpListener, UdpSocket};
+    use std::os::unix::io::AsRawFd;
+
+    let vars = |vars: &'static [(&str, &str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| OsString::from(v))
+        }
+    };
+    let pid = std::process::id();
+
+    // Not intended for this process.
+    assert_eq!(
+        ListenFds::from_vars(
+            pid + 1,
+            3,
+            vars(&[("LISTEN_PID", "1"), ("LISTEN_FDS", "1")])
+        )
+        .unwrap(),
+        ListenFds::default()
+    );
+    assert_eq!(
+        ListenFds::from_vars(pid, 3, vars(&[])).unwrap(),
+        ListenFds::default()
+    );
+
+    // Passes a TCP listener, a UDP socket, and another TCP listener to consecutive (and
+    // arbitrary) file descriptors.
+    let tcp1 = TcpListener::bind("127.0.0.1:0").unwrap();
+    let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let tcp2 = TcpListener::bind("127.0.0.1:0").unwrap();
+    let start_fd = 900;
+    for (i, fd) in [tcp1.as_raw_fd(), udp.as_raw_fd(), tcp2.as_raw_fd()]
+        .into_iter()
+        .enumerate()
+    {
+        assert_eq!(
+            unsafe { libc::dup2(fd, start_fd + i as RawFd) },
+            start_fd + i as RawFd
+        );
+    }
+    let pid_var = pid.to_string();
+    let listen_fds = ListenFds::from_vars(pid, start_fd, |name| match name {
+        "LISTEN_PID" => Some(pid_var.clone().into()),
+        "LISTEN_FDS" => Some("3".into()),
+        "LISTEN_FDNAMES" => Some("http:dns:http".into()),
+        _ => None,
+    })
+    .unwrap();
+    let port1 = tcp1.local_addr().unwrap().port();
+    let port2 = tcp2.local_addr().unwrap().port();
+    assert_eq!(
+        listen_fds.fds(),
+        [
+            ListenFd {
+                fd: start_fd,
+                name: Some("http".into()),
+                tcp_port: Some(port1),
+            },
+            ListenFd {
+                fd: start_fd + 1,
+                name: Some("dns".into()),
+                tcp_port: None,
+            },
+            ListenFd {
+                fd: start_fd + 2,
+                name: Some("http".into()),
+                tcp_port: Some(port2),
+            },
+        ]
+    );
+    let mut ports = [port1, port2];
+    ports.sort_unstable();
+    assert_eq!(
+        listen_fds
+            .bind_rules()
+            .map(|rule| format!("{rule:?}"))
+            .collect::<Vec<_>>(),
+        ports
+            .iter()
+            .map(|port| format!("{:?}", NetPort::new(*port, AccessNet::BindTcp)))
+            .collect::<Vec<_>>()
+    );
+    let flags = unsafe { libc::fcntl(start_fd, libc::F_GETFD) };
+    assert_eq!(flags & libc::FD_CLOEXEC, libc::FD_CLOEXEC);
+
+    // The number of names must match.
+    assert!(matches!(
+        ListenFds::from_vars(pid, start_fd, |name| match name {
+            "LISTEN_PID" => Some(pid_var.clone().into()),
+            "LISTEN_FDS" => Some("3".into()),
+            "LISTEN_FDNAMES" => Some("http".into()),
+            _ => None,
+        }),
+        Err(ListenFdsError::InvalidVar { name, .. }) if name == "LISTEN_FDNAMES"
+    ));
+    assert!(matches!(
+        ListenFds::from_vars(pid, start_fd, vars(&[("LISTEN_PID", "self")])),
+        Err(ListenFdsError::InvalidVar { name, value }) if name == "LISTEN_PID" && value == "self"
+    ));
+
+    for i in 0..3 {
+        unsafe { libc::close(start_fd + i) };
+    }
+}
@@ -0,0 +1,490 @@
+//! Parser of the Landlock audit records, to explain access denials.
+//!
+//! Since Linux 6.15, the kernel logs Landlock denials with `AUDIT_LANDLOCK_ACCESS` records,
+//! which can be read with auditd (e.g. in `/var/log/audit/audit.log`)
+//! or from the kernel log with journald (e.g. `journalctl -k`).
+//! Such a record identifies the denied access rights (i.e. the blockers),
+//! the accessed path or TCP port, and the Landlock domain which denied the access,
+//! whereas the process is identified by the related `SYSCALL` record.
+//! A [`Denial`] can then be compared with the [`Policy`] of this process
+//! to find the missing rules, or aggregated with a [`Learner`] to suggest them.
+//!
+//! # Example
+//!
+//! ```
+//! use landlock::audit::{parse_log, DenialTarget};
+//! use landlock::AccessFs;
+//!
+//! let log = r#"
+//! type=LANDLOCK_ACCESS msg=audit(1729738800.268:30): domain=195ba459b blockers=fs.read_file path="/etc/passwd" dev="vda2" ino=351
+//! type=SYSCALL msg=audit(1729738800.268:30): arch=c000003e syscall=257 success=no exit=-13 pid=300 comm="cat"
+//! "#;
+//! let denials = parse_log(log.lines()).unwrap();
+//! assert_eq!(denials.len(), 1);
+//! assert_eq!(denials[0].pid, Some(300));
+//! assert_eq!(denials[0].fs, AccessFs::ReadFile);
+//! assert_eq!(denials[0].target, DenialTarget::Path("/etc/passwd".into()));
+//! ```
+
+use crate::{Access, AccessFs, AccessNet, BitFlags, ParseAuditError, PathRule, Policy, PortRule};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const TYPE_ACCESS: [&str; 2] = ["LANDLOCK_ACCESS", "1423"];
+const TYPE_SYSCALL: [&str; 2] = ["SYSCALL", "1300"];
+
+/// Object of a denied access, see [`Denial::target`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DenialTarget {
+    /// File or directory.
+    Path(PathBuf),
+    /// TCP port.
+    Port(u16),
+    /// Other object (e.g. a signaled process), not described by this crate.
+    Unknown,
+}
+
+/// Access denied by Landlock, parsed from an `AUDIT_LANDLOCK_ACCESS` record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Denial {
+    /// Time of the audit event, since the Unix epoch.
+    pub time: Duration,
+    /// Serial number of the audit event, shared with the related records (e.g. `SYSCALL`).
+    pub serial: u64,
+    /// Identifier of the Landlock domain which denied the access.
+    pub domain: u64,
+    /// Identifier of the denied process, if known from a related `SYSCALL` record.
+    pub pid: Option<u32>,
+    /// Denied file system access rights.
+    pub fs: BitFlags<AccessFs>,
+    /// Denied network access rights.
+    pub net: BitFlags<AccessNet>,
+    /// Names of the blockers unknown to this crate (e.g. `scope.signal`).
+    pub other_blockers: Vec<String>,
+    /// Object of the denied access.
+    pub target: DenialTarget,
+}
+
+// Audit record split into its type, timestamp, serial number and fields.
+struct Record<'a> {
+    kind: &'a str,
+    time: Duration,
+    serial: u64,
+    // Name, value, and whether the value is quoted.
+    fields: Vec<(&'a str, &'a str, bool)>,
+}
+
+impl<'a> Record<'a> {
+    // Gets the type of the record, if line is an audit record.
+    fn kind(line: &str) -> Option<&str> {
+        let start = line.find("type=")? + "type=".len();
+        line[start..].split_whitespace().next()
+    }
+
+    // Returns None if the line is not an audit record.
+    fn parse(line: &'a str) -> Result<Option<Self>, ParseAuditError> {
+        let (start, kind) = match (line.find("type="), Self::kind(line)) {
+            (Some(start), Some(kind)) => (start, kind),
+            _ => return Ok(None),
+        };
+        let line = &line[start..];
+        let invalid = || ParseAuditError::InvalidRecord {
+            record: line.to_string(),
+        };
+        // Matches "msg=audit(1729738800.268:30):" and "audit(1729738800.268:30):".
+        let stamp_start = line.find("audit(").ok_or_else(invalid)? + "audit(".len();
+        let stamp_len = line[stamp_start..].find("):").ok_or_else(invalid)?;
+        let (time, serial) = line[stamp_start..stamp_start + stamp_len]
+            .split_once(':')
+            .ok_or_else(invalid)?;
+        let (secs, millis) = time.split_once('.').unwrap_or((time, "0"));
+        let time = Duration::from_secs(secs.parse().map_err(|_| invalid())?)
+            + Duration::from_millis(millis.parse().map_err(|_| invalid())?);
+        let serial = serial.parse().map_err(|_| invalid())?;
+        let fields = split_fields(&line[stamp_start + stamp_len + "):".len()..]);
+        Ok(Some(Record {
+            kind,
+            time,
+            serial,
+            fields,
+        }))
+    }
+
+    fn get(&self, name: &str) -> Option<&'a str> {
+        self.fields
+            .iter()
+            .find(|(key, _, _)| *key == name)
+            .map(|(_, value, _)| *value)
+    }
+
+    fn require(&self, name: &'static str) -> Result<&'a str, ParseAuditError> {
+        self.require_raw(name).map(|(value, _)| value)
+    }
+
+    fn require_raw(&self, name: &'static str) -> Result<(&'a str, bool), ParseAuditError> {
+        self.fields
+            .iter()
+            .find(|(key, _, _)| *key == name)
+            .map(|(_, value, quoted)| (*value, *quoted))
+            .ok_or(ParseAuditError::MissingField { name })
+    }
+}
+
+// Splits "key=value" fields separated by spaces, where values may be quoted.
+fn split_fields(mut s: &str) -> Vec<(&str, &str, bool)> {
+    let mut fields = Vec::new();
+    loop {
+        s = s.trim_start();
+        let (key, rest) = match s.split_once('=') {
+            Some(field) => field,
+            None => return fields,
+        };
+        let (value, rest, quoted) = match rest.strip_prefix('"') {
+            Some(quoted) => match quoted.split_once('"') {
+                Some((value, rest)) => (value, rest, true),
+                None => (quoted, "", true),
+            },
+            None => {
+                let (value, rest) = rest.split_at(rest.find(' ').unwrap_or(rest.len()));
+                (value, rest, false)
+            }
+        };
+        fields.push((key, value, quoted));
+        s = rest;
+    }
+}
+
+// Decodes an untrusted string field, which audit encodes in hexadecimal
+// unless it is quoted (i.e. if it contains a space, a quote or a control character).
+fn decode_string(record: &Record, name: &'static str) -> Result<OsString, ParseAuditError> {
+    let (value, quoted) = record.require_raw(name)?;
+    if quoted {
+        return Ok(value.into());
+    }
+    let invalid = || ParseAuditError::InvalidField {
+        name,
+        value: value.to_string(),
+    };
+    if value.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| {
+            value
+                .get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(OsString::from_vec)
+}
+
+impl Denial {
+    /// Parses an `AUDIT_LANDLOCK_ACCESS` record as written by auditd
+    /// (`type=LANDLOCK_ACCESS msg=audit(…): …`) or by the kernel log
+    /// (`audit: type=1423 audit(…): …`).
+    ///
+    /// Returns `None` if `line` is another kind of audit record or not an audit record at all.
+    /// The [`pid`](Denial::pid) is always `None`, see [`parse_log()`] to get it.
+    pub fn parse(line: &str) -> Result<Option<Self>, ParseAuditError> {
+        match Record::kind(line) {
+            Some(kind) if TYPE_ACCESS.contains(&kind) => {}
+            _ => return Ok(None),
+        }
+        let record = match Record::parse(line)? {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+        let invalid = |name, value: &str| ParseAuditError::InvalidField {
+            name,
+            value: value.to_string(),
+        };
+        let domain = record.require("domain")?;
+        let domain = u64::from_str_radix(domain, 16).map_err(|_| invalid("domain", domain))?;
+
+        let mut fs = BitFlags::EMPTY;
+        let mut net = BitFlags::EMPTY;
+        let mut other_blockers = Vec::new();
+        for blocker in record.require("blockers")?.split(',') {
+            let known = match blocker.split_once('.') {
+                Some(("fs", name)) => AccessFs::from_names(name).map(|a| fs |= a).is_ok(),
+                Some(("net", name)) => AccessNet::from_names(name).map(|a| net |= a).is_ok(),
+                _ => false,
+            };
+            if !known {
+                other_blockers.push(blocker.to_string());
+            }
+        }
+
+        let target = if record.get("path").is_some() {
+            DenialTarget::Path(decode_string(&record, "path")?.into())
+        } else if let Some(port) = record.get("dest").or_else(|| record.get("src")) {
+            DenialTarget::Port(port.parse().map_err(|_| invalid("port", port))?)
+        } else {
+            DenialTarget::Unknown
+        };
+
+        Ok(Some(Denial {
+            time: record.time,
+            serial: record.serial,
+            domain,
+            pid: None,
+            fs,
+            net,
+            other_blockers,
+            target,
+        }))
+    }
+}
+
+/// Parses the Landlock denials of an audit log, ignoring the other records
+/// (even if they are malformed).
+///
+/// The [`pid`](Denial::pid) of each denial is taken from the `SYSCALL` record
+/// of the same audit event, if any.
+pub fn parse_log<I, S>(lines: I) -> Result<Vec<Denial>, ParseAuditError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut denials = Vec::new();
+    let mut pids = HashMap::new();
+    for line in lines {
+        let line = line.as_ref();
+        if let Some(denial) = Denial::parse(line)? {
+            denials.push(denial);
+        } else if let Ok(Some(record)) = Record::parse(line) {
+            if TYPE_SYSCALL.contains(&record.kind) {
+                if let Some(pid) = record.get("pid").and_then(|pid| pid.parse().ok()) {
+                    pids.insert(record.serial, pid);
+                }
+            }
+        }
+    }
+    for denial in &mut denials {
+        denial.pid = pids.get(&denial.serial).copied();
+    }
+    Ok(denials)
+}
+
+/// Aggregates the denials of a program into suggested rules.
+///
+/// This is a learning mode: run a program with a restrictive policy (e.g. denying everything),
+/// feed the Landlock denials of this run to a `Learner`,
+/// and then review the suggested rules before merging them into the policy
+/// with [`merge_into()`](Learner::merge_into).
+/// The rules allow the exact denied paths (i.e. the accessed files,
+/// or the parent directories for the creation and removal of files),
+/// which may be generalized to common parent directories by the reviewer.
+///
+/// # Example
+///
+/// ```
+/// use landlock::audit::{parse_log, Learner};
+/// use landlock::{Access, AccessFs, Policy, ABI};
+///
+/// let log = r#"
+/// type=LANDLOCK_ACCESS msg=audit(1729738800.268:30): domain=195ba459b blockers=fs.read_file path="/etc/hosts"
+/// type=LANDLOCK_ACCESS msg=audit(1729738800.270:31): domain=195ba459b blockers=fs.read_dir path="/etc"
+/// "#;
+/// let mut learner = Learner::default().for_domain(0x195ba459b);
+/// for denial in parse_log(log.lines()).unwrap() {
+///     learner.observe(&denial);
+/// }
+/// let policy = learner.merge_into(Policy::default().handle_fs(AccessFs::from_all(ABI::V1)));
+/// assert_eq!(policy.path_rules().len(), 2);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Learner {
+    domain: Option<u64>,
+    paths: BTreeMap<PathBuf, BitFlags<AccessFs>>,
+    ports: BTreeMap<u16, BitFlags<AccessNet>>,
+}
+
+impl Learner {
+    /// Only learns from the denials of the `domain` Landlock domain (see [`Denial::domain`]),
+    /// e.g. to ignore the other sandboxed programs logging denials at the same time.
+    pub fn for_domain(mut self, domain: u64) -> Self {
+        self.domain = Some(domain);
+        self
+    }
+
+    /// Records the access rights denied by `denial`.
+    ///
+    /// Denials without a path nor a port (e.g. for scoped signals) are ignored.
+    pub fn observe(&mut self, denial: &Denial) {
+        if self.domain.map(|d| d != denial.domain).unwrap_or(false) {
+            return;
+        }
+        match denial.target {
+            DenialTarget::Path(ref path) if !denial.fs.is_empty() => {
+                *self.paths.entry(path.clone()).or_insert(BitFlags::EMPTY) |= denial.fs;
+            }
+            DenialTarget::Port(port) if !denial.net.is_empty() => {
+                *self.ports.entry(port).or_insert(BitFlags::EMPTY) |= denial.net;
+            }
+            _ => {}
+        }
+    }
+
+    /// Gets the suggested path rules, sorted by path.
+    pub fn path_rules(&self) -> Vec<PathRule> {
+        self.paths
+            .iter()
+            .map(|(path, access)| PathRule {
+                path: path.clone(),
+                access: *access,
+            })
+            .collect()
+    }
+
+    /// Gets the suggested port rules, sorted by port.
+    pub fn port_rules(&self) -> Vec<PortRule> {
+        self.ports
+            .iter()
+            .map(|(port, access)| PortRule {
+                port: *port,
+                access: *access,
+            })
+            .collect()
+    }
+
+    /// Adds the suggested rules to `policy`.
+    ///
+    /// Rules already covered by `policy` are then reported by [`Policy::analyze()`].
+    pub fn merge_into(&self, policy: Policy) -> Policy {
+        let policy = self.paths.iter().fold(policy, |policy, (path, access)| {
+            policy.allow_path(path.clone(), *access)
+        });
+        self.ports.iter().fold(policy, |policy, (port, access)| {
+            policy.allow_port(*port, *access)
+        })
+    }
+}
+
+#[test]
+fn audit_parse_denial() {
+    let denial = Denial::parse(
+        "audit: type=1423 audit(1729738800.268:30): domain=195ba459b \
+         blockers=fs.write_file,fs.truncate,fs.ioctl_dev path=\"/dev/tty\" dev=\"devtmpfs\" ino=9",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        denial,
+        Denial {
+            time: Duration::from_millis(1_729_738_800_268),
+            serial: 30,
+            domain: 0x195ba459b,
+            pid: None,
+            fs: AccessFs::WriteFile | AccessFs::Truncate,
+            net: BitFlags::EMPTY,
+            other_blockers: vec!["fs.ioctl_dev".into()],
+            target: DenialTarget::Path("/dev/tty".into()),
+        }
+    );
+
+    // Paths with spaces are hex-encoded.
+    let denial = Denial::parse(
+        "type=LANDLOCK_ACCESS msg=audit(1.5:2): domain=1 blockers=fs.make_reg path=2F6120622F63",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(denial.target, DenialTarget::Path("/a b/c".into()));
+
+    let denial = Denial::parse(
+        "type=LANDLOCK_ACCESS msg=audit(1.5:2): domain=1 blockers=net.connect_tcp \
+         daddr=127.0.0.1 dest=443",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(denial.net, AccessNet::ConnectTcp);
+    assert_eq!(denial.target, DenialTarget::Port(443));
+
+    let denial = Denial::parse(
+        "type=LANDLOCK_ACCESS msg=audit(1.5:2): domain=1 blockers=scope.signal opid=1 ocomm=\"init\"",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(denial.other_blockers, ["scope.signal"]);
+    assert_eq!(denial.target, DenialTarget::Unknown);
+
+    // Other records are ignored.
+    assert_eq!(
+        Denial::parse("type=SYSCALL msg=audit(1.5:2): pid=3"),
+        Ok(None)
+    );
+    assert_eq!(Denial::parse("hello"), Ok(None));
+
+    assert_eq!(
+        Denial::parse("type=LANDLOCK_ACCESS msg=audit(1.5:2): blockers=fs.execute"),
+        Err(ParseAuditError::MissingField { name: "domain" })
+    );
+    assert!(matches!(
+        Denial::parse("type=LANDLOCK_ACCESS msg=audit(x): domain=1 blockers=fs.execute"),
+        Err(ParseAuditError::InvalidRecord { .. })
+    ));
+    assert!(matches!(
+        Denial::parse(
+            "type=LANDLOCK_ACCESS msg=audit(1.5:2): domain=1 blockers=fs.execute path=2F6"
+        ),
+        Err(ParseAuditError::InvalidField { name: "path", .. })
+    ));
+}
+
+#[test]
+fn audit_parse_log() {
+    let log = [
+        "type=SYSCALL msg=audit(1.5:2): arch=c000003e syscall=257 pid=42 comm=\"cat\"",
+        "type=LANDLOCK_ACCESS msg=audit(1.5:2): domain=1 blockers=fs.read_file path=\"/etc/shadow\"",
+        "type=LANDLOCK_DOMAIN msg=audit(1.5:2): domain=1 status=allocated mode=enforcing pid=40",
+        "type=LANDLOCK_ACCESS msg=audit(1.6:3): domain=1 blockers=fs.read_dir path=\"/root\"",
+        "type=PROCTITLE msg=audit(1.6:3): proctitle=6C73",
+        "type=BOGUS",
+    ];
+    let denials = parse_log(log).unwrap();
+    assert_eq!(denials.len(), 2);
+    assert_eq!(denials[0].pid, Some(42));
+    assert_eq!(denials[1].pid, None);
+    assert_eq!(denials[1].fs, AccessFs::ReadDir);
+}
+
+#[test]
+fn audit_learner() {
+    let log = [
+        "type=LANDLOCK_ACCESS msg=audit(1.5:2): domain=1 blockers=fs.read_file path=\"/etc/hosts\"",
+        "type=LANDLOCK_ACCESS msg=audit(1.5:3): domain=2 blockers=fs.read_file path=\"/root\"",
+        "type=LANDLOCK_ACCESS msg=audit(1.5:4): domain=1 blockers=fs.execute path=\"/etc/hosts\"",
+        "type=LANDLOCK_ACCESS msg=audit(1.5:5): domain=1 blockers=net.bind_tcp src=80",
+        "type=LANDLOCK_ACCESS msg=audit(1.5:6): domain=1 blockers=scope.signal opid=1",
+    ];
+    let mut learner = Learner::default().for_domain(1);
+    for denial in parse_log(log).unwrap() {
+        learner.observe(&denial);
+    }
+    assert_eq!(
+        learner.path_rules(),
+        [PathRule {
+            path: "/etc/hosts".into(),
+            access: AccessFs::Execute | AccessFs::ReadFile,
+        }]
+    );
+    assert_eq!(
+        learner.port_rules(),
+        [PortRule {
+            port: 80,
+            access: AccessNet::BindTcp.into(),
+        }]
+    );
+
+    let policy = learner.merge_into(Policy::default().allow_path("/etc", AccessFs::ReadFile));
+    assert_eq!(policy.path_rules().len(), 2);
+    assert_eq!(policy.path_rules()[1], learner.path_rules()[0]);
+    assert_eq!(policy.port_rules(), learner.port_rules());
+}
@@ -0,0 +1,143 @@
+use crate::{HostFs, Policy, PolicyError, PolicyFs, RulesetStatus, ABI};
+
+#[cfg(test)]
+use crate::{uapi, Access, AccessFs};
+
+/// Mechanism enforcing a [`Policy`] on the calling thread.
+///
+/// A `Policy` describes the requested restrictions independently of Landlock,
+/// and a `SandboxBackend` enforces them.
+/// This crate implements this trait with [`LandlockBackend`],
+/// and downstream frameworks can implement it with other mechanisms
+/// (e.g. an allowlist of opened files, or Capsicum on FreeBSD)
+/// to fall back on systems without Landlock while keeping the same policy model.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{
+///     ABI, Access, AccessFs, LandlockBackend, Policy, PolicyError, RulesetStatus,
+///     SandboxBackend,
+/// };
+///
+/// struct OpenFilesBackend;
+///
+/// impl SandboxBackend for OpenFilesBackend {
+///     type Error = PolicyError;
+///
+///     fn name(&self) -> &'static str {
+///         "open-files"
+///     }
+///
+///     fn is_available(&self) -> bool {
+///         true
+///     }
+///
+///     fn apply(&self, policy: &Policy) -> Result<RulesetStatus, PolicyError> {
+///         // Opens policy.path_rules() and drops access to everything else…
+///         Ok(RulesetStatus::PartiallyEnforced)
+///     }
+/// }
+///
+/// fn sandbox() -> Result<RulesetStatus, PolicyError> {
+///     let abi = ABI::V1;
+///     let policy = Policy::default()
+///         .handle_fs(AccessFs::from_all(abi))
+///         .allow_path("/usr", AccessFs::from_read(abi));
+///     if LandlockBackend::new().is_available() {
+///         LandlockBackend::new().apply(&policy)
+///     } else {
+///         OpenFilesBackend.apply(&policy)
+///     }
+/// }
+/// ```
+pub trait SandboxBackend {
+    /// Error returned when the policy cannot be enforced.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Gets a short name identifying this backend (e.g. for logs).
+    fn name(&self) -> &'static str;
+
+    /// Returns true if the running system supports this backend.
+    ///
+    /// This must not change the state of the calling thread.
+    fn is_available(&self) -> bool;
+
+    /// Enforces `policy` on the calling thread.
+    ///
+    /// A backend may not be able to enforce all the requested restrictions,
+    /// which should then be reflected by the returned [`RulesetStatus`].
+    fn apply(&self, policy: &Policy) -> Result<RulesetStatus, Self::Error>;
+}
+
+/// [`SandboxBackend`] enforcing a [`Policy`] with Landlock.
+///
+/// [`apply()`](SandboxBackend::apply) is the same as [`Policy::restrict_self()`],
+/// but only returns the [`RulesetStatus`].
+/// The paths are opened with `F`, which is the host file system by default.
+#[derive(Debug, Clone, Default)]
+pub struct LandlockBackend<F = HostFs> {
+    fs: F,
+}
+
+impl LandlockBackend {
+    /// Creates a backend opening the paths on the host file system.
+    pub fn new() -> Self {
+        LandlockBackend { fs: HostFs }
+    }
+}
+
+impl<F> LandlockBackend<F>
+where
+    F: PolicyFs,
+{
+    /// Creates a backend opening the paths with `fs`,
+    /// see [`Policy::create_in()`].
+    pub fn with_fs(fs: F) -> Self {
+        LandlockBackend { fs }
+    }
+}
+
+impl<F> SandboxBackend for LandlockBackend<F>
+where
+    F: PolicyFs,
+{
+    type Error = PolicyError;
+
+    fn name(&self) -> &'static str {
+        "landlock"
+    }
+
+    fn is_available(&self) -> bool {
+        ABI::new_current() != ABI::Unsupported
+    }
+
+    fn apply(&self, policy: &Policy) -> Result<RulesetStatus, PolicyError> {
+        Ok(policy.create_in(&self.fs)?.restrict_self()?.ruleset)
+    }
+}
+
+#[test]
+fn landlock_backend() {
+    let abi = ABI::V1;
+    let policy = Policy::default()
+        .handle_fs(AccessFs::from_all(abi))
+        .allow_path("/usr", AccessFs::from_read(abi));
+    let backend = LandlockBackend::new();
+    assert_eq!(backend.name(), "landlock");
+
+    std::thread::spawn(move || {
+        let _kernel = uapi::MockKernel::new(ABI::Unsupported).install();
+        assert!(!backend.is_available());
+        assert_eq!(backend.apply(&policy).unwrap(), RulesetStatus::NotEnforced);
+
+        let _kernel = uapi::MockKernel::new(abi).install();
+        assert!(backend.is_available());
+        assert_eq!(
+            backend.apply(&policy).unwrap(),
+            RulesetStatus::FullyEnforced
+        );
+    })
+    .join()
+    .unwrap();
+}
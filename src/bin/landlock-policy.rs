@@ -0,0 +1,271 @@
+// Checks policy files written in the Policy::canonical() format and prints their canonical form,
+// e.g. to lint them in a CI pipeline or to review their changes.
+
+use landlock::{Access, AccessFs, AccessNet, Policy, Ruleset, RulesetAttr, ABI};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read};
+use std::process::exit;
+
+const PROGRAM_NAME: &str = "landlock-policy";
+
+// Landlock ABIs known by this program, from the oldest.
+const KNOWN_ABIS: [ABI; 4] = [ABI::V1, ABI::V2, ABI::V3, ABI::V4];
+
+// Exit codes similar to the lint tools ones.
+const EXIT_FINDINGS: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+
+fn usage() -> String {
+    format!(
+        "usage: {PROGRAM_NAME} [OPTION]... <FILE>...\n\
+        \n\
+        Check Landlock policy files and print their canonical form.\n\
+        \n\
+        Each line of a policy file is a comment starting with #, or an element:\n\
+        \x20 abi=v4\n\
+        \x20 handled_fs=execute|read_file|read_dir\n\
+        \x20 handled_net=connect_tcp\n\
+        \x20 path \"/usr\"=execute|read_file|read_dir\n\
+        \x20 port 443=connect_tcp\n\
+        \n\
+        The syntax, the access right names, the existence of the paths, the redundant\n\
+        rules, and the required Landlock ABI are checked.  The exit status is 1 if any\n\
+        issue is found.  The file - is the standard input.\n\
+        \n\
+        Options:\n\
+        \x20 --abi ABI         compare with ABI (e.g. v3) instead of the running kernel\n\
+        \x20 --skip-paths      don't check that the paths exist"
+    )
+}
+
+#[derive(Debug)]
+struct Args {
+    abi: Option<ABI>,
+    check_paths: bool,
+    files: Vec<OsString>,
+}
+
+fn parse_args<I>(args: I) -> Result<Args, String>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let mut args = args.into_iter();
+    let mut abi = None;
+    let mut check_paths = true;
+
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some("--") => break,
+            Some("--skip-paths") => check_paths = false,
+            Some("--abi") => {
+                let value = args.next().ok_or("missing value for --abi")?;
+                abi = Some(
+                    value
+                        .to_str()
+                        .unwrap_or_default()
+                        .parse::<ABI>()
+                        .map_err(|e| e.to_string())?,
+                );
+            }
+            Some(flag) if flag.starts_with("--") => {
+                return Err(format!("unknown option: {flag}"));
+            }
+            _ => {
+                let mut files = vec![arg];
+                files.extend(args);
+                return Ok(Args {
+                    abi,
+                    check_paths,
+                    files,
+                });
+            }
+        }
+    }
+    Ok(Args {
+        abi,
+        check_paths,
+        files: args.collect(),
+    })
+}
+
+// Gets the Landlock ABI supported by the running kernel.
+fn current_abi() -> ABI {
+    Ruleset::default()
+        .handle_access(AccessFs::Execute)
+        .and_then(|ruleset| ruleset.create())
+        .map(|ruleset| ruleset.compat_report().abi)
+        .unwrap_or(ABI::Unsupported)
+}
+
+// Gets the oldest ABI supporting all the access rights handled by policy.
+fn required_abi(policy: &Policy) -> ABI {
+    KNOWN_ABIS
+        .iter()
+        .copied()
+        .find(|abi| {
+            AccessFs::from_all(*abi).contains(policy.handled_fs())
+                && AccessNet::from_all(*abi).contains(policy.handled_net())
+        })
+        .unwrap_or(ABI::V4)
+}
+
+// Checks a policy description against the abi Landlock version, and returns its canonical form
+// (if it can be parsed) and the found issues.
+fn lint(description: &str, abi: ABI, check_paths: bool) -> (Option<String>, Vec<String>) {
+    let policy = match Policy::from_canonical(description) {
+        Ok(policy) => policy,
+        Err(e) => return (None, vec![format!("error: {e}")]),
+    };
+    let mut issues = Vec::new();
+
+    if check_paths {
+        for rule in policy.path_rules() {
+            if !rule.path.exists() {
+                issues.push(format!(
+                    "warning: path {:?} doesn't exist",
+                    rule.path.display()
+                ));
+            }
+        }
+    }
+    issues.extend(
+        policy
+            .analyze()
+            .iter()
+            .map(|finding| format!("warning: {finding}")),
+    );
+
+    let required = required_abi(&policy);
+    let dropped_fs = policy.handled_fs() & !AccessFs::from_all(abi);
+    let dropped_net = policy.handled_net() & !AccessNet::from_all(abi);
+    if abi == ABI::Unsupported {
+        issues.push("warning: Landlock is not supported by the kernel".into());
+    } else if !dropped_fs.is_empty() || !dropped_net.is_empty() {
+        let names = [
+            AccessFs::to_names(dropped_fs),
+            AccessNet::to_names(dropped_net),
+        ]
+        .iter()
+        .filter(|names| !names.is_empty())
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("|");
+        issues.push(format!(
+            "warning: requires Landlock ABI {required} but the kernel only supports {abi}, \
+            these access rights would not be restricted: {names}"
+        ));
+    }
+    (Some(policy.canonical(required)), issues)
+}
+
+fn read_file(file: &OsString) -> io::Result<String> {
+    if file == "-" {
+        let mut description = String::new();
+        io::stdin().read_to_string(&mut description)?;
+        Ok(description)
+    } else {
+        fs::read_to_string(file)
+    }
+}
+
+fn main() {
+    let args = match parse_args(env::args_os().skip(1)) {
+        Ok(args) if !args.files.is_empty() => args,
+        Ok(_) => {
+            eprintln!("{}", usage());
+            exit(EXIT_USAGE);
+        }
+        Err(e) => {
+            eprintln!("{PROGRAM_NAME}: {e}\n\n{}", usage());
+            exit(EXIT_USAGE);
+        }
+    };
+
+    let abi = args.abi.unwrap_or_else(current_abi);
+    let mut found = false;
+    for file in &args.files {
+        let name = file.to_string_lossy();
+        let (canonical, issues) = match read_file(file) {
+            Ok(description) => lint(&description, abi, args.check_paths),
+            Err(e) => (None, vec![format!("error: {e}")]),
+        };
+        for issue in &issues {
+            eprintln!("{name}: {issue}");
+        }
+        found |= !issues.is_empty();
+        if let Some(canonical) = canonical {
+            // Keeps the output a valid policy description.
+            if args.files.len() > 1 {
+                println!("# {name}");
+            }
+            print!("{canonical}");
+        }
+    }
+    if found {
+        exit(EXIT_FINDINGS);
+    }
+}
+
+#[test]
+fn parse_args_flags() {
+    let args = |a: &[&str]| parse_args(a.iter().map(OsString::from));
+
+    let parsed = args(&["--abi", "v2", "--skip-paths", "a.policy", "--abi"]).unwrap();
+    assert_eq!(parsed.abi, Some(ABI::V2));
+    assert!(!parsed.check_paths);
+    assert_eq!(parsed.files, ["a.policy", "--abi"]);
+
+    let parsed = args(&["--", "--skip-paths"]).unwrap();
+    assert_eq!(parsed.abi, None);
+    assert!(parsed.check_paths);
+    assert_eq!(parsed.files, ["--skip-paths"]);
+
+    assert!(args(&["--abi", "v42", "a.policy"]).is_err());
+    assert!(args(&["--abi"]).is_err());
+    assert!(args(&["--foo", "a.policy"]).is_err());
+}
+
+#[test]
+fn lint_policy() {
+    let description = "# Test\n\
+        handled_fs=execute|read_file|refer\n\
+        handled_net=connect_tcp\n\
+        path \"/\"=read_file\n\
+        path \"/nonexistent\"=read_file\n\
+        port 443=connect_tcp\n";
+
+    let (canonical, issues) = lint(description, ABI::V4, false);
+    assert_eq!(
+        canonical.unwrap(),
+        "abi=v4\n\
+        handled_fs=execute|read_file|refer\n\
+        handled_net=connect_tcp\n\
+        path \"/\"=read_file\n\
+        path \"/nonexistent\"=read_file\n\
+        port 443=connect_tcp\n"
+    );
+    assert_eq!(
+        issues,
+        ["warning: path rule #1 is redundant with path rule #0"]
+    );
+
+    let (_, issues) = lint(description, ABI::V1, true);
+    assert_eq!(
+        issues,
+        [
+            "warning: path \"/nonexistent\" doesn't exist",
+            "warning: path rule #1 is redundant with path rule #0",
+            "warning: requires Landlock ABI v4 but the kernel only supports v1, \
+            these access rights would not be restricted: refer|connect_tcp",
+        ]
+    );
+
+    let (canonical, issues) = lint("handled_fs=read_mind\n", ABI::V4, true);
+    assert!(canonical.is_none());
+    assert_eq!(
+        issues,
+        ["error: line 1: unknown access right \"read_mind\""]
+    );
+}
@@ -0,0 +1,216 @@
+// Installable version of the sandboxer example, see examples/sandboxer.rs
+
+use landlock::{Access, AccessFs, AccessNet, CompatLevel, Compatible, Policy, RulesetStatus, ABI};
+use std::env;
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use std::os::unix::process::CommandExt;
+use std::process::{exit, Command};
+
+const PROGRAM_NAME: &str = "landlock-restrict";
+
+// Highest Landlock ABI tested with this program.
+const ABI: ABI = ABI::V4;
+
+// Exit codes similar to the shell ones.
+const EXIT_USAGE: i32 = 2;
+const EXIT_SANDBOX: i32 = 125;
+const EXIT_NOT_EXECUTABLE: i32 = 126;
+const EXIT_NOT_FOUND: i32 = 127;
+
+fn usage() -> String {
+    format!(
+        "usage: {PROGRAM_NAME} [OPTION]... [--] <cmd> [args]...\n\
+        \n\
+        Launch a command in a restricted environment.\n\
+        \n\
+        Options (each may be repeated):\n\
+        \x20 --ro PATH               allow PATH to be used in a read-only way\n\
+        \x20 --rw PATH               allow PATH to be used in a read-write way\n\
+        \x20 --bind-tcp PORT         allow to bind on the TCP PORT\n\
+        \x20 --connect-tcp PORT      allow to connect to the TCP PORT\n\
+        \x20 --compat-level LEVEL    best-effort (default), warn-only, soft-requirement,\n\
+        \x20                          or hard-requirement\n\
+        \n\
+        Without path nor port option, the {ro}, {rw}, {bind}, and {connect} environment\n\
+        variables are used instead, as for the kernel's sandboxer.\n\
+        \n\
+        example:\n\
+        \x20 {PROGRAM_NAME} --ro /bin --ro /lib --ro /usr --ro /proc --ro /etc \
+        --rw /dev/null --rw /tmp -- bash -i",
+        ro = Policy::ENV_FS_RO_NAME,
+        rw = Policy::ENV_FS_RW_NAME,
+        bind = Policy::ENV_TCP_BIND_NAME,
+        connect = Policy::ENV_TCP_CONNECT_NAME,
+    )
+}
+
+#[derive(Debug)]
+struct Args {
+    policy: Option<Policy>,
+    compat_level: Option<CompatLevel>,
+    command: Vec<OsString>,
+}
+
+fn parse_args<I>(args: I) -> Result<Args, String>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let mut args = args.into_iter();
+    let mut policy = None;
+    let mut compat_level = None;
+
+    while let Some(arg) = args.next() {
+        let flag = match arg.to_str() {
+            Some("--") => break,
+            Some(flag) if flag.starts_with("--") => flag.to_string(),
+            _ => {
+                let mut command = vec![arg];
+                command.extend(args);
+                return Ok(Args {
+                    policy,
+                    compat_level,
+                    command,
+                });
+            }
+        };
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        let port = || {
+            value
+                .to_str()
+                .and_then(|v| v.parse::<u16>().ok())
+                .ok_or_else(|| format!("invalid port for {flag}: {}", value.to_string_lossy()))
+        };
+        if flag == "--compat-level" {
+            compat_level = Some(match value.to_str() {
+                Some("best-effort") => CompatLevel::BestEffort,
+                Some("warn-only") => CompatLevel::WarnOnly,
+                Some("soft-requirement") => CompatLevel::SoftRequirement,
+                Some("hard-requirement") => CompatLevel::HardRequirement,
+                _ => {
+                    return Err(format!(
+                        "invalid compatibility level: {}",
+                        value.to_string_lossy()
+                    ))
+                }
+            });
+            continue;
+        }
+        let current =
+            policy.unwrap_or_else(|| Policy::default().handle_fs(AccessFs::from_all(ABI)));
+        policy = Some(match flag.as_str() {
+            "--ro" => current.allow_path(&value, AccessFs::from_read(ABI)),
+            "--rw" => current.allow_path(&value, AccessFs::from_all(ABI)),
+            "--bind-tcp" => current
+                .handle_net(AccessNet::BindTcp)
+                .allow_port(port()?, AccessNet::BindTcp),
+            "--connect-tcp" => current
+                .handle_net(AccessNet::ConnectTcp)
+                .allow_port(port()?, AccessNet::ConnectTcp),
+            _ => return Err(format!("unknown option: {flag}")),
+        });
+    }
+    Ok(Args {
+        policy,
+        compat_level,
+        command: args.collect(),
+    })
+}
+
+fn main() {
+    let args = match parse_args(env::args_os().skip(1)) {
+        Ok(args) if !args.command.is_empty() => args,
+        Ok(_) => {
+            eprintln!("{}", usage());
+            exit(EXIT_USAGE);
+        }
+        Err(e) => {
+            eprintln!("{PROGRAM_NAME}: {e}\n\n{}", usage());
+            exit(EXIT_USAGE);
+        }
+    };
+
+    let mut policy = match args.policy {
+        Some(policy) => policy,
+        None => Policy::from_sandboxer_env(ABI).unwrap_or_else(|e| {
+            eprintln!("{PROGRAM_NAME}: {e}\n\n{}", usage());
+            exit(EXIT_USAGE);
+        }),
+    };
+    if let Some(level) = args.compat_level {
+        policy = policy.set_compatibility(level);
+    }
+    for finding in policy.analyze() {
+        eprintln!("{PROGRAM_NAME}: warning: {finding}");
+    }
+    // The mount table may not be available (e.g. without procfs).
+    for warning in policy.check_mounts().unwrap_or_default() {
+        eprintln!("{PROGRAM_NAME}: warning: {warning}");
+    }
+
+    match policy.restrict_self() {
+        Ok(status) => {
+            for warning in &status.warnings {
+                eprintln!("{PROGRAM_NAME}: warning: {warning}");
+            }
+            match status.ruleset {
+                RulesetStatus::FullyEnforced => {}
+                RulesetStatus::PartiallyEnforced => eprintln!(
+                    "{PROGRAM_NAME}: warning: the sandbox is only partially enforced \
+                    because of missing features from the running kernel ({:?}): {:?} {:?}",
+                    status.abi, status.dropped_fs, status.dropped_net,
+                ),
+                RulesetStatus::NotEnforced => eprintln!(
+                    "{PROGRAM_NAME}: warning: the sandbox is not enforced \
+                    because Landlock is not supported by the running kernel"
+                ),
+            }
+        }
+        Err(e) => {
+            eprintln!("{PROGRAM_NAME}: failed to enforce the sandbox: {e}");
+            exit(EXIT_SANDBOX);
+        }
+    }
+
+    let mut command = args.command.into_iter();
+    // The command is not empty, see above.
+    let program = command.next().unwrap_or_default();
+    let error = Command::new(&program)
+        .args(command)
+        .env_remove(Policy::ENV_FS_RO_NAME)
+        .env_remove(Policy::ENV_FS_RW_NAME)
+        .env_remove(Policy::ENV_TCP_BIND_NAME)
+        .env_remove(Policy::ENV_TCP_CONNECT_NAME)
+        .exec();
+    eprintln!(
+        "{PROGRAM_NAME}: failed to execute {}: {error}",
+        program.to_string_lossy()
+    );
+    exit(match error.kind() {
+        ErrorKind::NotFound => EXIT_NOT_FOUND,
+        _ => EXIT_NOT_EXECUTABLE,
+    });
+}
+
+#[test]
+fn parse_args_flags() {
+    let args = |a: &[&str]| parse_args(a.iter().map(OsString::from));
+
+    let parsed = args(&["--ro", "/usr", "--connect-tcp", "443", "--", "ls", "-l"]).unwrap();
+    let policy = parsed.policy.unwrap();
+    assert_eq!(policy.path_rules().len(), 1);
+    assert_eq!(policy.handled_net(), AccessNet::ConnectTcp);
+    assert_eq!(parsed.command, ["ls", "-l"]);
+
+    // Without path nor port option, the environment is used.
+    let parsed = args(&["--compat-level", "hard-requirement", "ls", "--ro"]).unwrap();
+    assert!(parsed.policy.is_none());
+    assert_eq!(parsed.compat_level, Some(CompatLevel::HardRequirement));
+    assert_eq!(parsed.command, ["ls", "--ro"]);
+
+    assert!(args(&["--bind-tcp", "http", "ls"]).is_err());
+    assert!(args(&["--ro"]).is_err());
+    assert!(args(&["--foo", "bar", "ls"]).is_err());
+}
@@ -0,0 +1,402 @@
+// Learning mode: runs a command with a restrictive ruleset logging its Landlock denials, and
+// prints the sandboxer environment variables allowing them, to be used with landlock-restrict.
+
+use landlock::audit::{parse_log, Learner};
+use landlock::{Access, AccessFs, AccessNet, Policy, ABI};
+use std::collections::{BTreeSet, HashSet};
+use std::env;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Seek, SeekFrom};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::{Path, PathBuf};
+use std::process::{exit, Command};
+use std::thread::sleep;
+use std::time::Duration;
+
+const PROGRAM_NAME: &str = "landlock-trace";
+
+// Highest Landlock ABI tested with this program.
+const ABI: ABI = ABI::V4;
+
+const DEFAULT_AUDIT_LOG: &str = "/var/log/audit/audit.log";
+
+// Logs the denials of the programs executed after landlock_restrict_self(2), since Linux 6.15
+// (Landlock ABI 7), which is not part of the bundled UAPI yet.
+#[cfg(target_os = "linux")]
+const LANDLOCK_RESTRICT_SELF_LOG_NEW_EXEC_ON: u32 = 1 << 1;
+
+// Types of the audit records describing a new Landlock domain.
+const TYPE_DOMAIN: [&str; 2] = ["type=LANDLOCK_DOMAIN", "type=1424"];
+
+// Time given to the audit daemon to write the last records.
+const AUDIT_FLUSH_DELAY: Duration = Duration::from_millis(500);
+
+// Exit codes similar to the shell ones.
+const EXIT_USAGE: i32 = 2;
+const EXIT_SANDBOX: i32 = 125;
+const EXIT_NOT_EXECUTABLE: i32 = 126;
+const EXIT_NOT_FOUND: i32 = 127;
+
+fn usage() -> String {
+    format!(
+        "usage: {PROGRAM_NAME} [OPTION]... [--] <cmd> [args]...\n\
+        \n\
+        Run a command with a restrictive sandbox logging its Landlock denials,\n\
+        and print the {ro}, {rw}, {bind}, and {connect} environment variables\n\
+        allowing them once the command exits, as expected by landlock-restrict.\n\
+        \n\
+        The denied accesses fail: the command may then stop early.  Run it again\n\
+        with the printed paths passed as --ro or --rw options until it succeeds,\n\
+        and review the result before using it.  This requires Linux 6.15 or newer\n\
+        and an audit daemon, and usually root privileges to read its log.\n\
+        \n\
+        Options (--ro and --rw may be repeated):\n\
+        \x20 --ro PATH               allow PATH to be used in a read-only way\n\
+        \x20 --rw PATH               allow PATH to be used in a read-write way\n\
+        \x20 --audit-log FILE        audit log to read the denials from\n\
+        \x20                          (default: {DEFAULT_AUDIT_LOG})\n\
+        \n\
+        example:\n\
+        \x20 {PROGRAM_NAME} --ro /usr -- ls /etc > ls.env\n\
+        \x20 (set -a; . ./ls.env; landlock-restrict ls /etc)",
+        ro = Policy::ENV_FS_RO_NAME,
+        rw = Policy::ENV_FS_RW_NAME,
+        bind = Policy::ENV_TCP_BIND_NAME,
+        connect = Policy::ENV_TCP_CONNECT_NAME,
+    )
+}
+
+#[derive(Debug)]
+struct Args {
+    policy: Policy,
+    audit_log: PathBuf,
+    command: Vec<OsString>,
+}
+
+fn parse_args<I>(args: I) -> Result<Args, String>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let mut args = args.into_iter();
+    let mut policy = Policy::default()
+        .handle_fs(AccessFs::from_all(ABI))
+        .handle_net(AccessNet::from_all(ABI));
+    let mut audit_log = PathBuf::from(DEFAULT_AUDIT_LOG);
+
+    while let Some(arg) = args.next() {
+        let flag = match arg.to_str() {
+            Some("--") => break,
+            Some(flag) if flag.starts_with("--") => flag.to_string(),
+            _ => {
+                let mut command = vec![arg];
+                command.extend(args);
+                return Ok(Args {
+                    policy,
+                    audit_log,
+                    command,
+                });
+            }
+        };
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--ro" => policy = policy.allow_path(&value, AccessFs::from_read(ABI)),
+            "--rw" => policy = policy.allow_path(&value, AccessFs::from_all(ABI)),
+            "--audit-log" => audit_log = value.into(),
+            _ => return Err(format!("unknown option: {flag}")),
+        }
+    }
+    Ok(Args {
+        policy,
+        audit_log,
+        command: args.collect(),
+    })
+}
+
+// Gets the value of a field of an audit record.
+fn audit_field<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    line.split_whitespace()
+        .find_map(|field| field.strip_prefix(name)?.strip_prefix('='))
+}
+
+// Gets the Landlock domains created by the process pid, i.e. the traced command.
+fn created_domains(log: &str, pid: u32) -> HashSet<u64> {
+    let pid = pid.to_string();
+    log.lines()
+        .filter(|line| TYPE_DOMAIN.iter().any(|t| line.contains(t)))
+        .filter(|line| audit_field(line, "pid") == Some(pid.as_str()))
+        .filter_map(|line| u64::from_str_radix(audit_field(line, "domain")?, 16).ok())
+        .collect()
+}
+
+// Quotes a value for a POSIX shell.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+// Formats the rules of policy as sandboxer environment variables, see
+// Policy::from_sandboxer_env().  Paths not only read are considered read-write.
+fn to_sandboxer_env(policy: &Policy) -> String {
+    let mut ro = BTreeSet::new();
+    let mut rw = BTreeSet::new();
+    for rule in policy.path_rules() {
+        if AccessFs::from_read(ABI).contains(rule.access) {
+            ro.insert(rule.path.as_path());
+        } else {
+            rw.insert(rule.path.as_path());
+        }
+    }
+    let ro = ro.difference(&rw).copied().collect::<Vec<_>>();
+    let rw = rw.into_iter().collect::<Vec<_>>();
+    let join_paths = |paths: &[&Path]| {
+        paths
+            .iter()
+            .map(|p| p.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(":")
+    };
+    let join_ports = |access: AccessNet| {
+        policy
+            .port_rules()
+            .iter()
+            .filter(|rule| rule.access.contains(access))
+            .map(|rule| rule.port.to_string())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>()
+            .join(":")
+    };
+    [
+        (Policy::ENV_FS_RO_NAME, join_paths(&ro)),
+        (Policy::ENV_FS_RW_NAME, join_paths(&rw)),
+        (Policy::ENV_TCP_BIND_NAME, join_ports(AccessNet::BindTcp)),
+        (
+            Policy::ENV_TCP_CONNECT_NAME,
+            join_ports(AccessNet::ConnectTcp),
+        ),
+    ]
+    .iter()
+    .map(|(name, value)| format!("{name}={}\n", shell_quote(value)))
+    .collect()
+}
+
+// Enforces the ruleset with a raw system call because of the logging flag.  This is
+// async-signal-safe, to be called between fork(2) and execve(2).
+#[cfg(target_os = "linux")]
+fn restrict_self_logged(ruleset_fd: RawFd) -> std::io::Result<()> {
+    unsafe {
+        if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+            return Err(Error::last_os_error());
+        }
+        if libc::syscall(
+            libc::SYS_landlock_restrict_self,
+            ruleset_fd,
+            LANDLOCK_RESTRICT_SELF_LOG_NEW_EXEC_ON,
+        ) != 0
+        {
+            return Err(Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+// Landlock is specific to Linux.
+#[cfg(not(target_os = "linux"))]
+fn restrict_self_logged(_ruleset_fd: RawFd) -> std::io::Result<()> {
+    Err(Error::from_raw_os_error(libc::ENOSYS))
+}
+
+// Reads the audit log from offset, or from the start if it was rotated in the meantime.
+fn read_log_from(path: &Path, offset: u64) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    if file.metadata()?.len() >= offset {
+        file.seek(SeekFrom::Start(offset))?;
+    }
+    let mut log = Vec::new();
+    file.read_to_end(&mut log)?;
+    Ok(String::from_utf8_lossy(&log).into_owned())
+}
+
+fn main() {
+    let args = match parse_args(env::args_os().skip(1)) {
+        Ok(args) if !args.command.is_empty() => args,
+        Ok(_) => {
+            eprintln!("{}", usage());
+            exit(EXIT_USAGE);
+        }
+        Err(e) => {
+            eprintln!("{PROGRAM_NAME}: {e}\n\n{}", usage());
+            exit(EXIT_USAGE);
+        }
+    };
+
+    // Only records the denials of this run.
+    let offset = match File::open(&args.audit_log).and_then(|f| f.metadata()) {
+        Ok(metadata) => metadata.len(),
+        Err(e) => {
+            eprintln!(
+                "{PROGRAM_NAME}: failed to open the audit log {}: {e}",
+                args.audit_log.display()
+            );
+            exit(EXIT_SANDBOX);
+        }
+    };
+
+    let ruleset = match args.policy.create() {
+        Ok(ruleset) => ruleset,
+        Err(e) => {
+            eprintln!("{PROGRAM_NAME}: failed to create the sandbox: {e}");
+            exit(EXIT_SANDBOX);
+        }
+    };
+    let ruleset_fd = match ruleset.as_fd() {
+        Some(fd) => fd.as_raw_fd(),
+        None => {
+            eprintln!("{PROGRAM_NAME}: Landlock is not supported by the running kernel");
+            exit(EXIT_SANDBOX);
+        }
+    };
+
+    let mut command_args = args.command.into_iter();
+    // The command is not empty, see above.
+    let program = command_args.next().unwrap_or_default();
+    let mut command = Command::new(&program);
+    command
+        .args(command_args)
+        .env_remove(Policy::ENV_FS_RO_NAME)
+        .env_remove(Policy::ENV_FS_RW_NAME)
+        .env_remove(Policy::ENV_TCP_BIND_NAME)
+        .env_remove(Policy::ENV_TCP_CONNECT_NAME);
+    // Only the child is sandboxed, to still be able to read the audit log.
+    unsafe {
+        command.pre_exec(move || restrict_self_logged(ruleset_fd));
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) if e.raw_os_error() == Some(libc::EINVAL) => {
+            eprintln!(
+                "{PROGRAM_NAME}: failed to enforce the sandbox: \
+                Landlock audit requires Linux 6.15 or newer"
+            );
+            exit(EXIT_SANDBOX);
+        }
+        Err(e) => {
+            eprintln!(
+                "{PROGRAM_NAME}: failed to execute {}: {e}",
+                program.to_string_lossy()
+            );
+            exit(match e.kind() {
+                ErrorKind::NotFound => EXIT_NOT_FOUND,
+                _ => EXIT_NOT_EXECUTABLE,
+            });
+        }
+    };
+    let pid = child.id();
+    let code = match child.wait() {
+        Ok(status) => status
+            .code()
+            .or_else(|| status.signal().map(|s| 128 + s))
+            .unwrap_or(EXIT_SANDBOX),
+        Err(e) => {
+            eprintln!("{PROGRAM_NAME}: failed to wait for the command: {e}");
+            EXIT_SANDBOX
+        }
+    };
+    drop(ruleset);
+
+    sleep(AUDIT_FLUSH_DELAY);
+    let log = match read_log_from(&args.audit_log, offset) {
+        Ok(log) => log,
+        Err(e) => {
+            eprintln!(
+                "{PROGRAM_NAME}: failed to read the audit log {}: {e}",
+                args.audit_log.display()
+            );
+            exit(EXIT_SANDBOX);
+        }
+    };
+    let denials = match parse_log(log.lines()) {
+        Ok(denials) => denials,
+        Err(e) => {
+            eprintln!("{PROGRAM_NAME}: failed to parse the audit log: {e}");
+            exit(EXIT_SANDBOX);
+        }
+    };
+
+    // The denials of the command's children are logged with the same domain, but not
+    // necessarily with its pid.
+    let mut domains = created_domains(&log, pid);
+    domains.extend(
+        denials
+            .iter()
+            .filter(|d| d.pid == Some(pid))
+            .map(|d| d.domain),
+    );
+    let mut learner = Learner::default();
+    for denial in denials.iter().filter(|d| domains.contains(&d.domain)) {
+        learner.observe(denial);
+    }
+    let unsupported = denials
+        .iter()
+        .filter(|d| domains.contains(&d.domain) && !d.other_blockers.is_empty())
+        .count();
+    if unsupported > 0 {
+        eprintln!(
+            "{PROGRAM_NAME}: warning: {unsupported} denials not related to \
+            file system or TCP accesses are ignored"
+        );
+    }
+
+    print!("{}", to_sandboxer_env(&learner.merge_into(args.policy)));
+    exit(code);
+}
+
+#[test]
+fn parse_args_flags() {
+    let args = |a: &[&str]| parse_args(a.iter().map(OsString::from));
+
+    let parsed = args(&["--ro", "/usr", "--audit-log", "/tmp/audit.log", "ls"]).unwrap();
+    assert_eq!(parsed.policy.path_rules().len(), 1);
+    assert_eq!(parsed.policy.handled_net(), AccessNet::from_all(ABI));
+    assert_eq!(parsed.audit_log, Path::new("/tmp/audit.log"));
+    assert_eq!(parsed.command, ["ls"]);
+
+    let parsed = args(&["--", "--ro"]).unwrap();
+    assert_eq!(parsed.audit_log, Path::new(DEFAULT_AUDIT_LOG));
+    assert_eq!(parsed.command, ["--ro"]);
+
+    assert!(args(&["--rw"]).is_err());
+    assert!(args(&["--bind-tcp", "80", "ls"]).is_err());
+}
+
+#[test]
+fn sandboxer_env_from_log() {
+    let log = r#"
+type=LANDLOCK_DOMAIN msg=audit(1729738800.268:29): domain=195ba459b status=allocated mode=enforcing pid=300 uid=0 exe="/usr/bin/cat" comm="cat"
+type=LANDLOCK_ACCESS msg=audit(1729738800.268:30): domain=195ba459b blockers=fs.read_file path="/etc/it's"
+type=LANDLOCK_ACCESS msg=audit(1729738800.268:31): domain=195ba459b blockers=fs.make_reg path="/tmp"
+type=LANDLOCK_ACCESS msg=audit(1729738800.268:32): domain=195ba459b blockers=net.connect_tcp dest=443
+type=LANDLOCK_ACCESS msg=audit(1729738800.268:33): domain=1a2b3c4d5 blockers=fs.read_file path="/root"
+"#;
+    let domains = created_domains(log, 300);
+    assert_eq!(domains, [0x195ba459b].into_iter().collect());
+    assert!(created_domains(log, 301).is_empty());
+
+    let mut learner = Learner::default().for_domain(0x195ba459b);
+    for denial in parse_log(log.lines()).unwrap() {
+        learner.observe(&denial);
+    }
+    let policy = Policy::default()
+        .allow_path("/usr", AccessFs::from_read(ABI))
+        .allow_path("/tmp", AccessFs::ReadDir);
+    assert_eq!(
+        to_sandboxer_env(&learner.merge_into(policy)),
+        "LL_FS_RO='/etc/it'\\''s:/usr'\n\
+        LL_FS_RW='/tmp'\n\
+        LL_TCP_BIND=''\n\
+        LL_TCP_CONNECT='443'\n"
+    );
+}
@@ -0,0 +1,367 @@
+//! Delegation of file opening to a less restricted process.
+//!
+//! Some paths cannot be known when a sandbox is created (e.g., files selected by a user).
+//! The broker pattern solves this issue:
+//! a heavily sandboxed process sends open requests with a [`BrokerClient`]
+//! to a less restricted process running a [`Broker`],
+//! which validates each request against a [`Policy`]
+//! and passes back the opened file descriptor over a Unix socket (with `SCM_RIGHTS`).
+//!
+//! This is only available with the `broker` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use landlock::broker::{Broker, BrokerClient};
+//! use landlock::{Access, AccessFs, Policy, ABI};
+//! use std::os::unix::net::UnixStream;
+//!
+//! let (broker_stream, client_stream) = UnixStream::pair().unwrap();
+//! let broker_policy = Policy::default().allow_path("/usr", AccessFs::from_read(ABI::V1));
+//! let broker = std::thread::spawn(move || Broker::new(broker_stream, &broker_policy).serve());
+//!
+//! // The client would typically be in a sandboxed child process.
+//! let client = BrokerClient::new(client_stream);
+//! assert!(client.open("/usr", AccessFs::ReadDir).is_ok());
+//! assert!(client.open("/etc/passwd", AccessFs::ReadFile).is_err());
+//!
+//! drop(client);
+//! broker.join().unwrap().unwrap();
+//! ```
+
+use crate::fs::openat2;
+use crate::scm::{recv_msg, send_msg};
+use crate::{AccessFs, BitFlags, PathFd, Policy, Resolve};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsFd, OwnedFd};
+use std::os::unix::net::UnixStream;
+use std::path::{Component, Path, PathBuf};
+
+// Access rights that can be requested to a broker, which only opens files and directories.
+const BROKER_ACCESS: BitFlags<AccessFs> = enumflags2::make_bitflags!(AccessFs::{
+    ReadFile | WriteFile | ReadDir
+});
+
+// Same as the kernel's PATH_MAX.
+const MAX_PATH_LEN: usize = 4096;
+
+/// Process validating and serving open requests from a [`BrokerClient`].
+///
+/// A request is accepted if the requested access rights are a subset of the ones allowed
+/// by the path rules of the policy matching the requested path
+/// (i.e., the rules identifying the path or one of its parent directories).
+/// Only [`AccessFs::ReadFile`], [`AccessFs::WriteFile`], and [`AccessFs::ReadDir`]
+/// can be requested.
+///
+/// Requested paths are opened with `openat2(2)` beneath the file hierarchy of the most specific
+/// matching rule, without following symbolic links,
+/// which prevents a client concurrently modifying this hierarchy to escape it.
+/// Requested paths with `..` components are denied,
+/// and so are all requests on kernels without `openat2(2)` (i.e. Linux < 5.6).
+#[derive(Debug)]
+pub struct Broker {
+    stream: UnixStream,
+    rules: Vec<BrokerRule>,
+}
+
+#[derive(Debug)]
+struct BrokerRule {
+    // Canonicalized path of the rule, matched against the requested paths.
+    path: PathBuf,
+    // Parent directory of the rule's path, opened when the broker is created.
+    parent: OwnedFd,
+    // Last component of the rule's path (or "." for the root directory), resolved from parent.
+    name: PathBuf,
+    access: BitFlags<AccessFs>,
+}
+
+impl BrokerRule {
+    fn new(path: &Path, access: BitFlags<AccessFs>) -> Option<Self> {
+        let path = fs::canonicalize(path).ok()?;
+        let (parent, name) = match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => (parent, Path::new(name)),
+            _ => (path.as_path(), Path::new(".")),
+        };
+        Some(BrokerRule {
+            parent: PathFd::new(parent).ok()?.into(),
+            name: name.into(),
+            path,
+            access,
+        })
+    }
+}
+
+impl Broker {
+    /// Creates a broker serving the requests received on `stream`
+    /// according to the path rules of `policy`.
+    ///
+    /// Handled access rights and network rules of `policy` are ignored.
+    /// Rules whose path cannot be canonicalized (e.g. because it doesn't exist) are ignored.
+    pub fn new(stream: UnixStream, policy: &Policy) -> Self {
+        let rules = policy
+            .path_rules()
+            .iter()
+            .filter_map(|rule| BrokerRule::new(&rule.path, rule.access))
+            .collect();
+        Broker { stream, rules }
+    }
+
+    /// Serves requests until the client closes its socket.
+    pub fn serve(&self) -> io::Result<()> {
+        while self.serve_one()? {}
+        Ok(())
+    }
+
+    /// Serves one request, and returns false if the client closed its socket.
+    pub fn serve_one(&self) -> io::Result<bool> {
+        let mut header = [0; 12];
+        match (&self.stream).read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(e) => return Err(e),
+        }
+        let mut bits = [0; 8];
+        bits.copy_from_slice(&header[..8]);
+        let mut len = [0; 4];
+        len.copy_from_slice(&header[8..]);
+        let len = u32::from_le_bytes(len) as usize;
+        if len > MAX_PATH_LEN {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+        let mut path = vec![0; len];
+        (&self.stream).read_exact(&mut path)?;
+
+        let access = BitFlags::<AccessFs>::from_bits(u64::from_le_bytes(bits));
+        let path = Path::new(std::ffi::OsStr::from_bytes(&path));
+        match access.map_err(|_| io::Error::from_raw_os_error(libc::EINVAL)) {
+            Ok(access) => match self.open(path, access) {
+                Ok(fd) => send_msg(&self.stream, &0i32.to_le_bytes(), Some(fd.as_fd()))?,
+                Err(e) => send_error(&self.stream, &e)?,
+            },
+            Err(e) => send_error(&self.stream, &e)?,
+        }
+        Ok(true)
+    }
+
+    fn open(&self, path: &Path, access: BitFlags<AccessFs>) -> io::Result<OwnedFd> {
+        if access.is_empty() || !BROKER_ACCESS.contains(access) || !path.is_absolute() {
+            return Err(io::Error::from_raw_os_error(libc::EINVAL));
+        }
+        // Parent components could escape the file hierarchy of the matching rule.
+        if path.components().any(|c| c == Component::ParentDir) {
+            return Err(io::Error::from_raw_os_error(libc::EACCES));
+        }
+        let matching = || {
+            self.rules
+                .iter()
+                .filter(|rule| path.starts_with(&rule.path))
+        };
+        let allowed = matching().fold(BitFlags::EMPTY, |allowed, rule| allowed | rule.access);
+        let rule = match matching().max_by_key(|rule| rule.path.components().count()) {
+            Some(rule) if allowed.contains(access) => rule,
+            _ => return Err(io::Error::from_raw_os_error(libc::EACCES)),
+        };
+        let mut flags = libc::O_CLOEXEC | libc::O_NOFOLLOW | libc::O_NOCTTY;
+        flags |= if access.contains(AccessFs::ReadFile | AccessFs::WriteFile) {
+            libc::O_RDWR
+        } else if access.contains(AccessFs::WriteFile) {
+            libc::O_WRONLY
+        } else {
+            libc::O_RDONLY
+        };
+        if access.contains(AccessFs::ReadDir) {
+            flags |= libc::O_DIRECTORY;
+        }
+        // The path was matched with starts_with().
+        let beneath = path.strip_prefix(&rule.path).unwrap_or(path);
+        let relative = if beneath.as_os_str().is_empty() {
+            rule.name.clone()
+        } else {
+            rule.name.join(beneath)
+        };
+        // Doesn't fall back to open(2) without openat2(2).
+        openat2(
+            Some(rule.parent.as_fd()),
+            &relative,
+            flags,
+            Resolve::Beneath | Resolve::NoSymlinks | Resolve::NoMagiclinks,
+        )
+    }
+}
+
+/// Client sending open requests to a [`Broker`].
+#[derive(Debug)]
+pub struct BrokerClient {
+    stream: UnixStream,
+}
+
+impl BrokerClient {
+    /// Creates a client sending its requests on `stream`.
+    pub fn new(stream: UnixStream) -> Self {
+        BrokerClient { stream }
+    }
+
+    /// Requests the broker to open `path` with `access`,
+    /// which must be a combination of [`AccessFs::ReadFile`], [`AccessFs::WriteFile`],
+    /// and [`AccessFs::ReadDir`].
+    ///
+    /// Returns the error of the broker, e.g. with the `EACCES` errno
+    /// if its policy doesn't allow this request.
+    pub fn open<P, A>(&self, path: P, access: A) -> io::Result<File>
+    where
+        P: AsRef<Path>,
+        A: Into<BitFlags<AccessFs>>,
+    {
+        let path = path.as_ref().as_os_str().as_bytes();
+        if path.len() > MAX_PATH_LEN {
+            return Err(io::Error::from_raw_os_error(libc::ENAMETOOLONG));
+        }
+        let mut request = Vec::with_capacity(12 + path.len());
+        request.extend_from_slice(&access.into().bits().to_le_bytes());
+        request.extend_from_slice(&(path.len() as u32).to_le_bytes());
+        request.extend_from_slice(path);
+        (&self.stream).write_all(&request)?;
+
+        let mut errno = [0; 4];
+        let fd = recv_msg(&self.stream, &mut errno)?;
+        match (i32::from_le_bytes(errno), fd) {
+            (0, Some(fd)) => Ok(File::from(fd)),
+            (0, None) => Err(io::ErrorKind::InvalidData.into()),
+            (errno, _) => Err(io::Error::from_raw_os_error(errno)),
+        }
+    }
+}
+
+fn send_error(stream: &UnixStream, error: &io::Error) -> io::Result<()> {
+    let errno = error.raw_os_error().unwrap_or(libc::EIO);
+    send_msg(stream, &errno.to_le_bytes(), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn serve(policy: Policy) -> (BrokerClient, std::thread::JoinHandle<io::Result<()>>) {
+        let (broker_stream, client_stream) = UnixStream::pair().unwrap();
+        let broker = std::thread::spawn(move || Broker::new(broker_stream, &policy).serve());
+        (BrokerClient::new(client_stream), broker)
+    }
+
+    #[test]
+    fn broker_open() {
+        let abi = ABI::V1;
+        let (client, broker) = serve(
+            Policy::default()
+                .allow_path("/usr", AccessFs::from_read(abi))
+                .allow_path("/dev/null", AccessFs::ReadFile | AccessFs::WriteFile),
+        );
+
+        let mut dir = client.open("/usr", AccessFs::ReadDir).unwrap();
+        assert!(dir.metadata().unwrap().is_dir());
+        // Reading a directory is not supported.
+        assert!(dir.read(&mut [0; 1]).is_err());
+
+        let mut null = client
+            .open("/dev/null", AccessFs::ReadFile | AccessFs::WriteFile)
+            .unwrap();
+        null.write_all(b"foo").unwrap();
+
+        let errno = |e: io::Error| e.raw_os_error();
+        assert_eq!(
+            client
+                .open("/usr", AccessFs::WriteFile)
+                .map_err(errno)
+                .unwrap_err(),
+            Some(libc::EACCES)
+        );
+        assert_eq!(
+            client
+                .open("/usr/../etc/passwd", AccessFs::ReadFile)
+                .map_err(errno)
+                .unwrap_err(),
+            Some(libc::EACCES)
+        );
+        assert_eq!(
+            client
+                .open("/usr", AccessFs::Execute)
+                .map_err(errno)
+                .unwrap_err(),
+            Some(libc::EINVAL)
+        );
+        assert_eq!(
+            client
+                .open("usr", AccessFs::ReadDir)
+                .map_err(errno)
+                .unwrap_err(),
+            Some(libc::EINVAL)
+        );
+        assert_eq!(
+            client
+                .open("/usr/does-not-exist", AccessFs::ReadFile)
+                .map_err(errno)
+                .unwrap_err(),
+            Some(libc::ENOENT)
+        );
+
+        drop(client);
+        broker.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn broker_symlink() {
+        let dir = std::env::temp_dir().join(format!("landlock-broker-{}", std::process::id()));
+        std::fs::create_dir(&dir).unwrap();
+        std::os::unix::fs::symlink("/etc", dir.join("etc")).unwrap();
+        std::fs::write(dir.join("file"), b"foo").unwrap();
+        let (client, broker) = serve(Policy::default().allow_path(&dir, AccessFs::ReadFile));
+
+        let mut content = Vec::new();
+        client
+            .open(dir.join("file"), AccessFs::ReadFile)
+            .unwrap()
+            .read_to_end(&mut content)
+            .unwrap();
+        assert_eq!(content, b"foo");
+        // The symbolic link could be replaced by the client once the request is checked.
+        assert_eq!(
+            client
+                .open(dir.join("etc/passwd"), AccessFs::ReadFile)
+                .unwrap_err()
+                .raw_os_error(),
+            Some(libc::ELOOP)
+        );
+
+        drop(client);
+        broker.join().unwrap().unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn broker_sandboxed_client() {
+        if !can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+            return;
+        }
+        let abi = ABI::V1;
+        let (client, broker) = serve(Policy::default().allow_path("/etc", AccessFs::ReadFile));
+
+        let sandbox = Policy::default().handle_fs(AccessFs::from_all(abi));
+        let read = thread::spawn_restricted(&sandbox, move || {
+            let denied = std::fs::read("/etc/passwd").is_err();
+            let mut content = Vec::new();
+            client
+                .open("/etc/passwd", AccessFs::ReadFile)
+                .unwrap()
+                .read_to_end(&mut content)
+                .unwrap();
+            denied && !content.is_empty()
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+        assert!(read);
+        broker.join().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,351 @@
+//! C API, to use this crate's compatibility handling from C or C++ programs.
+//!
+//! The functions are exported with the `landlock_rs_` prefix, to not conflict with the
+//! kernel's system call wrappers, and are declared in the `include/landlock_rs.h` header
+//! (generated with
+//! `cbindgen --config cbindgen.toml --output include/landlock_rs.h src/capi.rs`).
+//! A shared library can be built with
+//! `cargo rustc --release --lib --features capi --crate-type cdylib`.
+//!
+//! Access rights are the same bits as the kernel's `LANDLOCK_ACCESS_*` constants.
+//! Functions returning an `int` return a negative value on error,
+//! and the last error message of the calling thread can then be read with
+//! [`landlock_rs_last_error()`].
+//!
+//! This is only available with the `capi` feature.
+//!
+//! # Example
+//!
+//! ```c
+//! #include <linux/landlock.h>
+//! #include <stdio.h>
+//! #include "landlock_rs.h"
+//!
+//! int sandbox(void)
+//! {
+//!     struct landlock_rs_ruleset *ruleset = landlock_rs_ruleset_new(
+//!         LANDLOCK_ACCESS_FS_EXECUTE | LANDLOCK_ACCESS_FS_READ_FILE, 0,
+//!         LANDLOCK_RS_BEST_EFFORT);
+//!     if (!ruleset
+//!         || landlock_rs_ruleset_add_path(ruleset, "/usr", LANDLOCK_ACCESS_FS_EXECUTE) < 0
+//!         || landlock_rs_ruleset_restrict_self(ruleset) < 0) {
+//!         fprintf(stderr, "Failed to sandbox: %s\n", landlock_rs_last_error());
+//!         landlock_rs_ruleset_free(ruleset);
+//!         return -1;
+//!     }
+//!     landlock_rs_ruleset_free(ruleset);
+//!     return 0;
+//! }
+//! ```
+
+use crate::{
+    is_file, Access, AccessFs, AccessNet, CompatLevel, Compatible, NetPort, PathBeneath, PathFd,
+    Ruleset, RulesetAttr, RulesetCreated, RulesetCreatedAttr, RulesetStatus, ABI, ACCESS_FILE,
+};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, OsStr};
+use std::os::raw::{c_char, c_int};
+use std::os::unix::ffi::OsStrExt;
+use std::ptr;
+
+/// Best-effort compatibility level, see [`CompatLevel::BestEffort`].
+pub const LANDLOCK_RS_BEST_EFFORT: c_int = 0;
+/// Soft requirement compatibility level, see [`CompatLevel::SoftRequirement`].
+pub const LANDLOCK_RS_SOFT_REQUIREMENT: c_int = 1;
+/// Hard requirement compatibility level, see [`CompatLevel::HardRequirement`].
+pub const LANDLOCK_RS_HARD_REQUIREMENT: c_int = 2;
+/// Warn-only compatibility level, see [`CompatLevel::WarnOnly`].
+pub const LANDLOCK_RS_WARN_ONLY: c_int = 3;
+
+/// Returned by [`landlock_rs_ruleset_restrict_self()`], see [`RulesetStatus::NotEnforced`].
+pub const LANDLOCK_RS_NOT_ENFORCED: c_int = 0;
+/// Returned by [`landlock_rs_ruleset_restrict_self()`],
+/// see [`RulesetStatus::PartiallyEnforced`].
+pub const LANDLOCK_RS_PARTIALLY_ENFORCED: c_int = 1;
+/// Returned by [`landlock_rs_ruleset_restrict_self()`], see [`RulesetStatus::FullyEnforced`].
+pub const LANDLOCK_RS_FULLY_ENFORCED: c_int = 2;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error<E>(error: E)
+where
+    E: ToString,
+{
+    // An error message cannot contain a nul byte, except from a malformed path.
+    let message = CString::new(error.to_string().replace('\0', "\\0"))
+        .expect("no nul byte in the error message");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+// Returns ret, or sets the last error and returns -1.
+fn status<T, E>(ret: Result<T, E>) -> c_int
+where
+    E: ToString,
+{
+    match ret {
+        Ok(_) => 0,
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+/// Ruleset being built, opaque to C callers.
+///
+/// If adding a rule fails, the ruleset cannot be used anymore but must still be freed.
+#[allow(non_camel_case_types)]
+pub struct landlock_rs_ruleset {
+    // None once enforced or after an error, as for the consuming builder API.
+    inner: Option<RulesetCreated>,
+}
+
+impl landlock_rs_ruleset {
+    fn update<F>(&mut self, f: F) -> c_int
+    where
+        F: FnOnce(RulesetCreated) -> Result<RulesetCreated, String>,
+    {
+        status(
+            self.inner
+                .take()
+                .ok_or_else(|| "the ruleset was already enforced or failed".to_string())
+                .and_then(f)
+                .map(|ruleset| self.inner = Some(ruleset)),
+        )
+    }
+}
+
+/// Gets the Landlock ABI version supported by the running kernel, or 0 if Landlock is not
+/// supported.
+#[no_mangle]
+pub extern "C" fn landlock_rs_abi_version() -> c_int {
+    ABI::new_current() as c_int
+}
+
+/// Gets the last error message of the calling thread, or NULL if no error happened.
+///
+/// The returned string is valid until the next call to a `landlock_rs_*` function
+/// on the same thread.
+#[no_mangle]
+pub extern "C" fn landlock_rs_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map(|message| message.as_ptr())
+            .unwrap_or(ptr::null())
+    })
+}
+
+/// Creates a ruleset handling the `handled_fs` and `handled_net` access rights
+/// (i.e. `LANDLOCK_ACCESS_FS_*` and `LANDLOCK_ACCESS_NET_*` bits)
+/// with the `compat_level` compatibility level (e.g. `LANDLOCK_RS_BEST_EFFORT`).
+///
+/// Returns NULL on error.  The returned ruleset must be freed with
+/// [`landlock_rs_ruleset_free()`].
+#[no_mangle]
+pub extern "C" fn landlock_rs_ruleset_new(
+    handled_fs: u64,
+    handled_net: u64,
+    compat_level: c_int,
+) -> *mut landlock_rs_ruleset {
+    let create = || -> Result<RulesetCreated, String> {
+        let level = match compat_level {
+            LANDLOCK_RS_BEST_EFFORT => CompatLevel::BestEffort,
+            LANDLOCK_RS_SOFT_REQUIREMENT => CompatLevel::SoftRequirement,
+            LANDLOCK_RS_HARD_REQUIREMENT => CompatLevel::HardRequirement,
+            LANDLOCK_RS_WARN_ONLY => CompatLevel::WarnOnly,
+            _ => return Err(format!("unknown compatibility level {compat_level}")),
+        };
+        let handled_fs = AccessFs::try_from_bits(handled_fs).map_err(|e| e.to_string())?;
+        let handled_net = AccessNet::try_from_bits(handled_net).map_err(|e| e.to_string())?;
+        let mut ruleset = Ruleset::default().set_compatibility(level);
+        if !handled_fs.is_empty() {
+            ruleset = ruleset
+                .handle_access(handled_fs)
+                .map_err(|e| e.to_string())?;
+        }
+        if !handled_net.is_empty() {
+            ruleset = ruleset
+                .handle_access(handled_net)
+                .map_err(|e| e.to_string())?;
+        }
+        ruleset.create().map_err(|e| e.to_string())
+    };
+    match create() {
+        Ok(ruleset) => Box::into_raw(Box::new(landlock_rs_ruleset {
+            inner: Some(ruleset),
+        })),
+        Err(e) => {
+            set_last_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Allows the `access` file system rights beneath `path`.
+///
+/// Access rights only applying to directories are ignored if `path` is a file.
+/// Returns 0 on success or -1 on error.
+///
+/// # Safety
+///
+/// `ruleset` must come from [`landlock_rs_ruleset_new()`] and `path` must be a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn landlock_rs_ruleset_add_path(
+    ruleset: *mut landlock_rs_ruleset,
+    path: *const c_char,
+    access: u64,
+) -> c_int {
+    let ruleset = match ruleset.as_mut() {
+        Some(ruleset) => ruleset,
+        None => {
+            set_last_error("null ruleset");
+            return -1;
+        }
+    };
+    if path.is_null() {
+        set_last_error("null path");
+        return -1;
+    }
+    let path = OsStr::from_bytes(CStr::from_ptr(path).to_bytes());
+    ruleset.update(|inner| {
+        let access = AccessFs::try_from_bits(access).map_err(|e| e.to_string())?;
+        let fd = PathFd::new(path).map_err(|e| e.to_string())?;
+        let access = match is_file(&fd) {
+            Ok(true) => access & ACCESS_FILE,
+            Err(_) | Ok(false) => access,
+        };
+        inner
+            .add_rule(PathBeneath::new(fd, access))
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// Allows the `access` network rights for the TCP `port`.
+///
+/// Returns 0 on success or -1 on error.
+///
+/// # Safety
+///
+/// `ruleset` must come from [`landlock_rs_ruleset_new()`].
+#[no_mangle]
+pub unsafe extern "C" fn landlock_rs_ruleset_add_port(
+    ruleset: *mut landlock_rs_ruleset,
+    port: u16,
+    access: u64,
+) -> c_int {
+    let ruleset = match ruleset.as_mut() {
+        Some(ruleset) => ruleset,
+        None => {
+            set_last_error("null ruleset");
+            return -1;
+        }
+    };
+    ruleset.update(|inner| {
+        let access = AccessNet::try_from_bits(access).map_err(|e| e.to_string())?;
+        inner
+            .add_rule(NetPort::new(port, access))
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// Enforces the ruleset on the calling thread, with `no_new_privs`.
+///
+/// Returns the enforcement status (e.g. `LANDLOCK_RS_FULLY_ENFORCED`) or -1 on error.
+/// The ruleset cannot be used anymore but must still be freed.
+///
+/// # Safety
+///
+/// `ruleset` must come from [`landlock_rs_ruleset_new()`].
+#[no_mangle]
+pub unsafe extern "C" fn landlock_rs_ruleset_restrict_self(
+    ruleset: *mut landlock_rs_ruleset,
+) -> c_int {
+    let ruleset = match ruleset.as_mut() {
+        Some(ruleset) => ruleset,
+        None => {
+            set_last_error("null ruleset");
+            return -1;
+        }
+    };
+    match ruleset
+        .inner
+        .take()
+        .ok_or_else(|| "the ruleset was already enforced or failed".to_string())
+        .and_then(|inner| inner.restrict_self().map_err(|e| e.to_string()))
+    {
+        Ok(status) => match status.ruleset {
+            RulesetStatus::NotEnforced => LANDLOCK_RS_NOT_ENFORCED,
+            RulesetStatus::PartiallyEnforced => LANDLOCK_RS_PARTIALLY_ENFORCED,
+            RulesetStatus::FullyEnforced => LANDLOCK_RS_FULLY_ENFORCED,
+        },
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+/// Frees a ruleset and closes its file descriptor.  Does nothing if `ruleset` is NULL.
+///
+/// # Safety
+///
+/// `ruleset` must come from [`landlock_rs_ruleset_new()`] and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn landlock_rs_ruleset_free(ruleset: *mut landlock_rs_ruleset) {
+    if !ruleset.is_null() {
+        drop(Box::from_raw(ruleset));
+    }
+}
+
+#[test]
+fn capi_ruleset() {
+    use crate::uapi;
+
+    std::thread::spawn(|| {
+        let kernel = uapi::MockKernel::new(ABI::V4).install();
+        assert_eq!(landlock_rs_abi_version(), 4);
+        assert!(landlock_rs_last_error().is_null());
+
+        let execute = AccessFs::Execute as u64;
+        assert!(landlock_rs_ruleset_new(execute, 0, 4).is_null());
+        let error = unsafe { CStr::from_ptr(landlock_rs_last_error()) };
+        assert_eq!(error.to_str().unwrap(), "unknown compatibility level 4");
+        assert!(landlock_rs_ruleset_new(1 << 63, 0, LANDLOCK_RS_BEST_EFFORT).is_null());
+
+        let ruleset = landlock_rs_ruleset_new(
+            execute,
+            AccessNet::BindTcp as u64,
+            LANDLOCK_RS_HARD_REQUIREMENT,
+        );
+        assert!(!ruleset.is_null());
+        let usr = CString::new("/usr").unwrap();
+        unsafe {
+            assert_eq!(
+                landlock_rs_ruleset_add_path(ruleset, usr.as_ptr(), execute),
+                0
+            );
+            assert_eq!(
+                landlock_rs_ruleset_add_port(ruleset, 80, AccessNet::BindTcp as u64),
+                0
+            );
+            assert_eq!(kernel.rules(), 2);
+            assert_eq!(
+                landlock_rs_ruleset_restrict_self(ruleset),
+                LANDLOCK_RS_FULLY_ENFORCED
+            );
+            assert_eq!(landlock_rs_ruleset_restrict_self(ruleset), -1);
+            landlock_rs_ruleset_free(ruleset);
+            landlock_rs_ruleset_free(ptr::null_mut());
+        }
+        let error = unsafe { CStr::from_ptr(landlock_rs_last_error()) };
+        assert_eq!(
+            error.to_str().unwrap(),
+            "the ruleset was already enforced or failed"
+        );
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,360 @@
+//! Capability dropping along with the ruleset enforcement.
+//!
+//! Landlock restricts the access rights of a thread,
+//! but a privileged thread can still use its capabilities to bypass other security mechanisms
+//! (e.g. `CAP_SYS_ADMIN` to mount file systems, or `CAP_NET_RAW` to forge packets).
+//! [`CapsDrop`] drops the selected capabilities from the calling thread
+//! (and then from its future children) right before enforcing a ruleset,
+//! and returns a single [`CapsStatus`] for both steps.
+//!
+//! Capabilities are per-thread attributes, as Landlock restrictions.
+//!
+//! This is only available with the `caps` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use landlock::caps::{Capability, CapsDrop};
+//! use landlock::{Access, AccessFs, CapsError, Ruleset, RulesetAttr, ABI};
+//!
+//! fn restrict_thread() -> Result<(), CapsError> {
+//!     let ruleset = Ruleset::default()
+//!         .handle_access(AccessFs::from_all(ABI::V1))?
+//!         .create()?;
+//!     let status =
+//!         CapsDrop::new(Capability::SysAdmin | Capability::NetRaw).restrict_self(ruleset)?;
+//!     println!("dropped capabilities: {:?}", status.dropped);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{BitFlags, CapsError, RestrictionStatus, RulesetCreated};
+use enumflags2::bitflags;
+
+/// Linux capability, see [capabilities(7)](https://man7.org/linux/man-pages/man7/capabilities.7.html).
+///
+/// Each variant's bit is the capability number (e.g. `CAP_SYS_ADMIN` is 21).
+#[bitflags]
+#[repr(u64)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Capability {
+    /// `CAP_CHOWN`
+    Chown = 1 << 0,
+    /// `CAP_DAC_OVERRIDE`
+    DacOverride = 1 << 1,
+    /// `CAP_DAC_READ_SEARCH`
+    DacReadSearch = 1 << 2,
+    /// `CAP_FOWNER`
+    Fowner = 1 << 3,
+    /// `CAP_FSETID`
+    Fsetid = 1 << 4,
+    /// `CAP_KILL`
+    Kill = 1 << 5,
+    /// `CAP_SETGID`
+    Setgid = 1 << 6,
+    /// `CAP_SETUID`
+    Setuid = 1 << 7,
+    /// `CAP_SETPCAP`, required to drop capabilities from the bounding set.
+    Setpcap = 1 << 8,
+    /// `CAP_LINUX_IMMUTABLE`
+    LinuxImmutable = 1 << 9,
+    /// `CAP_NET_BIND_SERVICE`
+    NetBindService = 1 << 10,
+    /// `CAP_NET_BROADCAST`
+    NetBroadcast = 1 << 11,
+    /// `CAP_NET_ADMIN`
+    NetAdmin = 1 << 12,
+    /// `CAP_NET_RAW`
+    NetRaw = 1 << 13,
+    /// `CAP_IPC_LOCK`
+    IpcLock = 1 << 14,
+    /// `CAP_IPC_OWNER`
+    IpcOwner = 1 << 15,
+    /// `CAP_SYS_MODULE`
+    SysModule = 1 << 16,
+    /// `CAP_SYS_RAWIO`
+    SysRawio = 1 << 17,
+    /// `CAP_SYS_CHROOT`
+    SysChroot = 1 << 18,
+    /// `CAP_SYS_PTRACE`
+    SysPtrace = 1 << 19,
+    /// `CAP_SYS_PACCT`
+    SysPacct = 1 << 20,
+    /// `CAP_SYS_ADMIN`, which enables to enforce a ruleset without `no_new_privs`.
+    SysAdmin = 1 << 21,
+    /// `CAP_SYS_BOOT`
+    SysBoot = 1 << 22,
+    /// `CAP_SYS_NICE`
+    SysNice = 1 << 23,
+    /// `CAP_SYS_RESOURCE`
+    SysResource = 1 << 24,
+    /// `CAP_SYS_TIME`
+    SysTime = 1 << 25,
+    /// `CAP_SYS_TTY_CONFIG`
+    SysTtyConfig = 1 << 26,
+    /// `CAP_MKNOD`
+    Mknod = 1 << 27,
+    /// `CAP_LEASE`
+    Lease = 1 << 28,
+    /// `CAP_AUDIT_WRITE`
+    AuditWrite = 1 << 29,
+    /// `CAP_AUDIT_CONTROL`
+    AuditControl = 1 << 30,
+    /// `CAP_SETFCAP`
+    Setfcap = 1 << 31,
+    /// `CAP_MAC_OVERRIDE`
+    MacOverride = 1 << 32,
+    /// `CAP_MAC_ADMIN`
+    MacAdmin = 1 << 33,
+    /// `CAP_SYSLOG`
+    Syslog = 1 << 34,
+    /// `CAP_WAKE_ALARM`
+    WakeAlarm = 1 << 35,
+    /// `CAP_BLOCK_SUSPEND`
+    BlockSuspend = 1 << 36,
+    /// `CAP_AUDIT_READ`
+    AuditRead = 1 << 37,
+    /// `CAP_PERFMON`
+    Perfmon = 1 << 38,
+    /// `CAP_BPF`
+    Bpf = 1 << 39,
+    /// `CAP_CHECKPOINT_RESTORE`
+    CheckpointRestore = 1 << 40,
+}
+
+impl Capability {
+    // Gets the capability number, as used by prctl(2).
+    #[cfg_attr(not(any(target_os = "linux", target_os = "android")), allow(dead_code))]
+    fn number(self) -> u32 {
+        (self as u64).trailing_zeros()
+    }
+}
+
+/// Hardening step dropping capabilities from the calling thread
+/// before enforcing a ruleset with [`restrict_self()`](CapsDrop::restrict_self).
+///
+/// The capabilities are removed from the effective, permitted and inheritable sets,
+/// and from the bounding set if the thread has `CAP_SETPCAP`
+/// (otherwise, `no_new_privs` already prevents `execve(2)` from granting them back).
+/// The ambient set is cleared by default.
+///
+/// Dropping [`Capability::SysAdmin`] requires `no_new_privs` to enforce the ruleset,
+/// which is set by default
+/// (see [`set_no_new_privs()`](crate::RulesetCreatedAttr::set_no_new_privs)).
+#[derive(Debug, Clone)]
+pub struct CapsDrop {
+    capabilities: BitFlags<Capability>,
+    clear_ambient: bool,
+}
+
+impl CapsDrop {
+    /// Drops `capabilities` and clears the ambient set.
+    pub fn new<C>(capabilities: C) -> Self
+    where
+        C: Into<BitFlags<Capability>>,
+    {
+        CapsDrop {
+            capabilities: capabilities.into(),
+            clear_ambient: true,
+        }
+    }
+
+    /// Drops all the capabilities known by this crate and clears the ambient set.
+    pub fn all() -> Self {
+        Self::new(BitFlags::ALL)
+    }
+
+    /// Configures whether the ambient set is cleared (which is the default).
+    pub fn clear_ambient(mut self, clear_ambient: bool) -> Self {
+        self.clear_ambient = clear_ambient;
+        self
+    }
+
+    /// Drops the capabilities and then enforces `ruleset`
+    /// with [`RulesetCreated::restrict_self()`].
+    ///
+    /// If dropping the capabilities fails, the ruleset is not enforced.
+    pub fn restrict_self(&self, ruleset: RulesetCreated) -> Result<CapsStatus, CapsError> {
+        let (dropped, bounding_dropped, ambient_cleared) = self.drop_caps()?;
+        Ok(CapsStatus {
+            dropped,
+            bounding_dropped,
+            ambient_cleared,
+            ruleset: ruleset.restrict_self()?,
+        })
+    }
+
+    fn drop_caps(&self) -> Result<(BitFlags<Capability>, BitFlags<Capability>, bool), CapsError> {
+        let mut sets = sys::get().map_err(|source| CapsError::CapgetCall { source })?;
+
+        // The ambient set is not supported before Linux 4.3.
+        let ambient_cleared = self.clear_ambient
+            && match sys::clear_ambient() {
+                Ok(()) => true,
+                Err(e) if e.raw_os_error() == Some(libc::EINVAL) => false,
+                Err(source) => return Err(CapsError::AmbientCall { source }),
+            };
+
+        // Must be done before dropping CAP_SETPCAP from the effective set.
+        let mut bounding_dropped = BitFlags::EMPTY;
+        if sets.effective & Capability::Setpcap as u64 != 0 {
+            for capability in self.capabilities.iter() {
+                // The running kernel may not know this capability.
+                if sys::bounding_contains(capability) {
+                    sys::drop_bounding(capability)
+                        .map_err(|source| CapsError::BoundingCall { capability, source })?;
+                    bounding_dropped |= capability;
+                }
+            }
+        }
+
+        let held = sets.effective | sets.permitted | sets.inheritable;
+        let dropped = BitFlags::from_bits_truncate(held) & self.capabilities;
+        if !dropped.is_empty() {
+            let mask = !dropped.bits();
+            sets.effective &= mask;
+            sets.permitted &= mask;
+            sets.inheritable &= mask;
+            sys::set(&sets).map_err(|source| CapsError::CapsetCall { source })?;
+        }
+        Ok((dropped, bounding_dropped, ambient_cleared))
+    }
+}
+
+/// Status of the capability dropping and of the ruleset enforcement,
+/// returned by [`CapsDrop::restrict_self()`].
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CapsStatus {
+    /// Capabilities which were held by the thread and are now dropped.
+    pub dropped: BitFlags<Capability>,
+    /// Capabilities dropped from the bounding set.
+    pub bounding_dropped: BitFlags<Capability>,
+    /// True if the ambient set was cleared.
+    pub ambient_cleared: bool,
+    /// Status of the ruleset enforcement.
+    pub ruleset: RestrictionStatus,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sys {
+    use super::Capability;
+    use crate::uapi::linux::{capget, capset, CapSets};
+    use std::io::Error;
+
+    pub(super) fn get() -> Result<CapSets, Error> {
+        let mut sets = CapSets::default();
+        match unsafe { capget(&mut sets) } {
+            0 => Ok(sets),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+
+    pub(super) fn set(sets: &CapSets) -> Result<(), Error> {
+        match unsafe { capset(sets) } {
+            0 => Ok(()),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+
+    pub(super) fn clear_ambient() -> Result<(), Error> {
+        let ret = unsafe {
+            libc::prctl(
+                libc::PR_CAP_AMBIENT,
+                libc::PR_CAP_AMBIENT_CLEAR_ALL,
+                0,
+                0,
+                0,
+            )
+        };
+        match ret {
+            0 => Ok(()),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+
+    pub(super) fn bounding_contains(capability: Capability) -> bool {
+        unsafe { libc::prctl(libc::PR_CAPBSET_READ, capability.number(), 0, 0, 0) == 1 }
+    }
+
+    pub(super) fn drop_bounding(capability: Capability) -> Result<(), Error> {
+        match unsafe { libc::prctl(libc::PR_CAPBSET_DROP, capability.number(), 0, 0, 0) } {
+            0 => Ok(()),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+}
+
+// Capabilities are specific to Linux.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+mod sys {
+    use super::Capability;
+    use std::io::Error;
+
+    #[derive(Default)]
+    pub(super) struct CapSets {
+        pub(super) effective: u64,
+        pub(super) permitted: u64,
+        pub(super) inheritable: u64,
+    }
+
+    pub(super) fn get() -> Result<CapSets, Error> {
+        Err(Error::from_raw_os_error(libc::ENOSYS))
+    }
+
+    pub(super) fn set(_sets: &CapSets) -> Result<(), Error> {
+        Err(Error::from_raw_os_error(libc::ENOSYS))
+    }
+
+    pub(super) fn clear_ambient() -> Result<(), Error> {
+        Err(Error::from_raw_os_error(libc::ENOSYS))
+    }
+
+    pub(super) fn bounding_contains(_capability: Capability) -> bool {
+        false
+    }
+
+    pub(super) fn drop_bounding(_capability: Capability) -> Result<(), Error> {
+        Err(Error::from_raw_os_error(libc::ENOSYS))
+    }
+}
+
+#[test]
+fn capability_number() {
+    assert_eq!(Capability::Chown.number(), 0);
+    assert_eq!(Capability::SysAdmin.number(), 21);
+    assert_eq!(Capability::CheckpointRestore.number(), 40);
+}
+
+#[test]
+fn caps_drop_restrict_self() {
+    use crate::{Access, AccessFs, Ruleset, RulesetAttr, RulesetCreatedAttr, RulesetStatus, ABI};
+
+    let before = sys::get().unwrap();
+    let ruleset = Ruleset::from(ABI::Unsupported)
+        .handle_access(AccessFs::from_all(ABI::V1))
+        .unwrap()
+        .create()
+        .unwrap()
+        .set_no_new_privs(false);
+    let status = CapsDrop::new(Capability::Mknod | Capability::SysBoot)
+        .restrict_self(ruleset)
+        .unwrap();
+    assert_eq!(status.ruleset.ruleset, RulesetStatus::NotEnforced);
+
+    let held = BitFlags::<Capability>::from_bits_truncate(
+        before.effective | before.permitted | before.inheritable,
+    );
+    assert_eq!(
+        status.dropped,
+        held & (Capability::Mknod | Capability::SysBoot)
+    );
+    let after = sys::get().unwrap();
+    assert_eq!(after.effective & Capability::Mknod as u64, 0);
+    assert_eq!(after.permitted & Capability::Mknod as u64, 0);
+    if before.effective & Capability::Setpcap as u64 != 0 {
+        assert!(status.bounding_dropped.contains(Capability::Mknod));
+        assert!(!sys::bounding_contains(Capability::Mknod));
+    }
+}
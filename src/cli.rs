@@ -0,0 +1,117 @@
+use crate::{Access, AccessFs, AccessNet, CompatLevel, Compatible, Policy, ABI};
+use std::path::PathBuf;
+
+/// Command line flags describing a sandbox, to be flattened in a [`clap`] parser.
+///
+/// This is only available with the `clap` feature.
+///
+/// As for [`Policy::from_sandboxer_env()`], all file system access rights defined by the ABI
+/// passed to [`to_policy()`](SandboxArgs::to_policy) are handled,
+/// whereas a network access right is only handled if its related flag is used at least once.
+///
+/// # Example
+///
+/// ```
+/// use clap::Parser;
+/// use landlock::{SandboxArgs, ABI};
+///
+/// #[derive(Parser)]
+/// struct Cli {
+///     #[command(flatten)]
+///     sandbox: SandboxArgs,
+///     command: Vec<String>,
+/// }
+///
+/// let cli = Cli::parse_from(["prog", "--ro", "/usr", "--rw", "/tmp", "--", "ls"]);
+/// let policy = cli.sandbox.to_policy(ABI::V4);
+/// assert_eq!(policy.path_rules().len(), 2);
+/// ```
+#[derive(Debug, Clone, Default, clap::Args)]
+#[non_exhaustive]
+pub struct SandboxArgs {
+    /// Path allowed to be used in a read-only way (may be repeated).
+    #[arg(long = "ro", value_name = "PATH")]
+    pub ro: Vec<PathBuf>,
+    /// Path allowed to be used in a read-write way (may be repeated).
+    #[arg(long = "rw", value_name = "PATH")]
+    pub rw: Vec<PathBuf>,
+    /// TCP port allowed to bind (may be repeated).
+    #[arg(long = "bind-tcp", value_name = "PORT")]
+    pub bind_tcp: Vec<u16>,
+    /// TCP port allowed to connect to (may be repeated).
+    #[arg(long = "connect-tcp", value_name = "PORT")]
+    pub connect_tcp: Vec<u16>,
+    /// Behavior when the running kernel doesn't support all requested restrictions
+    /// (best-effort, warn-only, soft-requirement, or hard-requirement).
+    #[arg(
+        long = "compat-level",
+        value_name = "LEVEL",
+        value_enum,
+        hide_possible_values = true
+    )]
+    pub compat_level: Option<CompatLevel>,
+}
+
+impl SandboxArgs {
+    /// Converts these flags to a [`Policy`] handling the access rights defined by `abi`.
+    pub fn to_policy(&self, abi: ABI) -> Policy {
+        let mut policy = Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .allow_paths(self.ro.iter().cloned(), AccessFs::from_read(abi))
+            .allow_paths(self.rw.iter().cloned(), AccessFs::from_all(abi));
+        for (ports, access) in [
+            (&self.bind_tcp, AccessNet::BindTcp),
+            (&self.connect_tcp, AccessNet::ConnectTcp),
+        ] {
+            if !ports.is_empty() {
+                policy = policy.handle_net(access);
+            }
+            for port in ports {
+                policy = policy.allow_port(*port, access);
+            }
+        }
+        if let Some(level) = self.compat_level {
+            policy = policy.set_compatibility(level);
+        }
+        policy
+    }
+}
+
+#[test]
+fn sandbox_args_to_policy() {
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        sandbox: SandboxArgs,
+    }
+
+    let abi = ABI::V4;
+    let args = Cli::try_parse_from([
+        "test",
+        "--ro",
+        "/usr",
+        "--ro",
+        "/etc",
+        "--rw",
+        "/tmp",
+        "--connect-tcp",
+        "443",
+        "--compat-level",
+        "hard-requirement",
+    ])
+    .unwrap()
+    .sandbox;
+    assert_eq!(args.compat_level, Some(CompatLevel::HardRequirement));
+
+    let policy = args.to_policy(abi);
+    assert_eq!(policy.handled_fs(), AccessFs::from_all(abi));
+    assert_eq!(policy.handled_net(), AccessNet::ConnectTcp);
+    assert_eq!(policy.path_rules().len(), 3);
+    assert_eq!(policy.path_rules()[2].access, AccessFs::from_all(abi));
+    assert_eq!(policy.port_rules().len(), 1);
+
+    assert!(Cli::try_parse_from(["test", "--bind-tcp", "http"]).is_err());
+    assert!(Cli::try_parse_from(["test", "--compat-level", "best"]).is_err());
+}
@@ -1,4 +1,8 @@
-use crate::{uapi, Access, CompatError};
+use crate::{uapi, Access, AccessFs, AccessNet, CompatError, ParseAbiError, RequireAbiError};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
 
 #[cfg(test)]
 use std::convert::TryInto;
@@ -39,11 +43,8 @@ use strum_macros::{EnumCount as EnumCountMacro, EnumIter};
 ///
 /// In a nutshell, test the access rights you request on a kernel that support them and
 /// on a kernel that doesn't support them.
-#[cfg_attr(
-    test,
-    derive(Debug, PartialEq, Eq, PartialOrd, EnumIter, EnumCountMacro)
-)]
-#[derive(Copy, Clone)]
+#[cfg_attr(test, derive(PartialOrd, EnumIter, EnumCountMacro))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum ABI {
     /// Kernel not supporting Landlock, either because it is not built with Landlock
@@ -58,33 +59,77 @@ pub enum ABI {
     /// Third Landlock ABI, introduced with
     /// [Linux 6.2](https://git.kernel.org/stable/c/299e2b1967578b1442128ba8b3e86ed3427d3651).
     V3 = 3,
+    /// Fourth Landlock ABI, introduced with
+    /// [Linux 6.7](https://git.kernel.org/stable/c/136cc1e1f5be75f57f1e0404b94ee1c8792cb07d).
+    V4 = 4,
+}
+
+// Cached result of the Landlock ABI probe, shared by all the threads, or PROBE_UNKNOWN.
+static ABI_PROBE: AtomicI32 = AtomicI32::new(PROBE_UNKNOWN);
+const PROBE_UNKNOWN: i32 = i32::MIN;
+
+// Returns the ABI version, or the negative errno value.
+fn probe_abi() -> i32 {
+    match unsafe {
+        uapi::landlock_create_ruleset(std::ptr::null(), 0, uapi::LANDLOCK_CREATE_RULESET_VERSION)
+    } {
+        // Landlock ABI version starts at 1 but errno is only set for negative values.
+        version if version >= 0 => version,
+        _ => -std::io::Error::last_os_error().raw_os_error().unwrap_or(0),
+    }
+}
+
+// Gets the cached result of the ABI probe, see probe_abi().
+fn current_probe() -> i32 {
+    // A mocked kernel is only visible to its thread.
+    if uapi::is_mocked() {
+        return probe_abi();
+    }
+    match ABI_PROBE.load(Ordering::Relaxed) {
+        PROBE_UNKNOWN => {
+            let version = probe_abi();
+            ABI_PROBE.store(version, Ordering::Relaxed);
+            version
+        }
+        version => version,
+    }
 }
 
 impl ABI {
     // Must remain private to avoid inconsistent behavior by passing Ok(self) to a builder method,
     // e.g. to make it impossible to call ruleset.handle_fs(ABI::new_current()?)
-    fn new_current() -> Self {
-        ABI::from(unsafe {
-            // Landlock ABI version starts at 1 but errno is only set for negative values.
-            uapi::landlock_create_ruleset(
-                std::ptr::null(),
-                0,
-                uapi::LANDLOCK_CREATE_RULESET_VERSION,
-            )
-        })
+    pub(crate) fn new_current() -> Self {
+        ABI::from(current_probe())
+    }
+
+    // Gets the error of the Landlock ABI probe, if the running kernel doesn't support Landlock:
+    // ENOSYS if the kernel is not built with Landlock, or EOPNOTSUPP if Landlock is disabled at
+    // boot time.
+    pub(crate) fn current_errno() -> Option<i32> {
+        match current_probe() {
+            errno if errno < 0 => Some(-errno),
+            _ => None,
+        }
+    }
+
+    // Forgets the cached ABI, which will be probed again by the next new_current() call.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub(crate) fn refresh() {
+        ABI_PROBE.store(PROBE_UNKNOWN, Ordering::Relaxed);
     }
 
     // There is no way to not publicly expose an implementation of an external trait such as
     // From<i32>.  See RFC https://github.com/rust-lang/rfcs/pull/2529
-    fn from(value: i32) -> ABI {
+    pub(crate) fn from(value: i32) -> ABI {
         match value {
             // The only possible error values should be EOPNOTSUPP and ENOSYS, but let's interpret
             // all kind of errors as unsupported.
             n if n <= 0 => ABI::Unsupported,
             1 => ABI::V1,
             2 => ABI::V2,
+            3 => ABI::V3,
             // Returns the greatest known ABI.
-            _ => ABI::V3,
+            _ => ABI::V4,
         }
     }
 
@@ -92,6 +137,206 @@ impl ABI {
     fn is_known(value: i32) -> bool {
         value > 0 && value < ABI::COUNT as i32
     }
+
+    /// Gets the ABI introduced by the mainline kernel release `version`
+    /// (e.g. `6.7` or `6.8.0-45-generic` as printed by `uname -r`).
+    ///
+    /// Releases newer than the latest known ABI get this latest ABI, and releases older than
+    /// Linux 5.13 get [`ABI::Unsupported`].
+    /// This is only an upper bound: Landlock may be disabled at build or boot time,
+    /// and distributions may backport newer ABIs to older kernels.
+    /// Prefer probing the running kernel (e.g. with [`Ruleset::create()`](crate::Ruleset::create))
+    /// when possible.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::ABI;
+    ///
+    /// assert_eq!(ABI::from_kernel_version("6.7").unwrap(), ABI::V4);
+    /// assert_eq!(ABI::from_kernel_version("6.1.0-18-amd64").unwrap(), ABI::V2);
+    /// assert_eq!(ABI::from_kernel_version("5.10").unwrap(), ABI::Unsupported);
+    /// ```
+    pub fn from_kernel_version(version: &str) -> Result<Self, ParseAbiError> {
+        let invalid = || ParseAbiError::InvalidKernelVersion {
+            version: version.to_string(),
+        };
+        let mut numbers = version.trim().splitn(3, '.').map(|n| {
+            // Ignores suffixes such as "-rc1" or "-generic".
+            let digits = n.find(|c: char| !c.is_ascii_digit()).unwrap_or(n.len());
+            n[..digits].parse::<u32>().ok()
+        });
+        let major = numbers.next().flatten().ok_or_else(invalid)?;
+        let minor = numbers.next().flatten().ok_or_else(invalid)?;
+        Ok(ABI_KERNELS
+            .iter()
+            .rev()
+            .find(|(_, kernel)| *kernel <= (major, minor))
+            .map(|(abi, _)| *abi)
+            .unwrap_or(ABI::Unsupported))
+    }
+
+    /// Gets the mainline kernel release (major and minor numbers) introducing this ABI,
+    /// or `None` for [`ABI::Unsupported`].
+    pub fn kernel_version(self) -> Option<(u32, u32)> {
+        ABI_KERNELS
+            .iter()
+            .find(|(abi, _)| *abi == self)
+            .map(|(_, kernel)| *kernel)
+    }
+
+    /// Checks that the running kernel supports at least this ABI,
+    /// e.g. to check the requirements of a program when it starts.
+    ///
+    /// This is a clearer alternative to requiring the access rights of this ABI
+    /// with [`CompatLevel::HardRequirement`].
+    /// See also [`Ruleset::require_abi()`](crate::Ruleset::require_abi).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{RequireAbiError, ABI};
+    ///
+    /// fn check_requirements() -> Result<(), RequireAbiError> {
+    ///     // Needs the Refer access right.
+    ///     ABI::V2.require()
+    /// }
+    /// ```
+    pub fn require(self) -> Result<(), RequireAbiError> {
+        require_abi(self, ABI::new_current())
+    }
+}
+
+// Checks that the actual ABI is at least the required one.
+pub(crate) fn require_abi(required: ABI, actual: ABI) -> Result<(), RequireAbiError> {
+    if actual == ABI::Unsupported && required != ABI::Unsupported {
+        Err(RequireAbiError::Unsupported { required })
+    } else if (actual as i32) < (required as i32) {
+        Err(RequireAbiError::Incompatible { required, actual })
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn abi_require() {
+    assert_eq!(require_abi(ABI::V2, ABI::V4), Ok(()));
+    assert_eq!(require_abi(ABI::V2, ABI::V2), Ok(()));
+    assert_eq!(require_abi(ABI::Unsupported, ABI::Unsupported), Ok(()));
+    assert_eq!(
+        require_abi(ABI::V3, ABI::V1),
+        Err(RequireAbiError::Incompatible {
+            required: ABI::V3,
+            actual: ABI::V1
+        })
+    );
+    assert_eq!(
+        require_abi(ABI::V1, ABI::Unsupported),
+        Err(RequireAbiError::Unsupported { required: ABI::V1 })
+    );
+
+    std::thread::spawn(|| {
+        let _kernel = uapi::MockKernel::new(ABI::V2).install();
+        assert!(ABI::V2.require().is_ok());
+        assert!(ABI::V3.require().is_err());
+    })
+    .join()
+    .unwrap();
+}
+
+// Kernel releases introducing each ABI, from the oldest to the newest.
+pub(crate) const ABI_KERNELS: &[(ABI, (u32, u32))] = &[
+    (ABI::V1, (5, 13)),
+    (ABI::V2, (5, 19)),
+    (ABI::V3, (6, 2)),
+    (ABI::V4, (6, 7)),
+];
+
+/// Prints the ABI as `unsupported` or `v1`, `v2`...
+impl fmt::Display for ABI {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ABI::Unsupported => f.write_str("unsupported"),
+            abi => write!(f, "v{}", *abi as i32),
+        }
+    }
+}
+
+/// Parses an ABI as printed by its [`Display`](fmt::Display) implementation,
+/// ignoring the case and an optional `v` prefix (e.g. `v4`, `V4` or `4`).
+/// Unlike the kernel probe, unknown versions are rejected instead of saturated.
+impl FromStr for ABI {
+    type Err = ParseAbiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unknown = || ParseAbiError::UnknownAbi {
+            name: s.to_string(),
+        };
+        if s.eq_ignore_ascii_case("unsupported") {
+            return Ok(ABI::Unsupported);
+        }
+        let number = s
+            .strip_prefix(|c| c == 'v' || c == 'V')
+            .unwrap_or(s)
+            .parse::<i32>()
+            .map_err(|_| unknown())?;
+        match ABI::from(number) {
+            abi if abi as i32 == number && number > 0 => Ok(abi),
+            _ => Err(unknown()),
+        }
+    }
+}
+
+#[test]
+fn abi_display_from_str() {
+    for abi in ABI::iter() {
+        assert_eq!(abi.to_string().parse::<ABI>().unwrap(), abi);
+    }
+    assert_eq!(ABI::V3.to_string(), "v3");
+    assert_eq!(ABI::Unsupported.to_string(), "unsupported");
+    assert_eq!("V2".parse::<ABI>().unwrap(), ABI::V2);
+    assert_eq!("1".parse::<ABI>().unwrap(), ABI::V1);
+    for s in ["0", "v0", "v99", "-1", "", "v", "latest"] {
+        assert_eq!(
+            s.parse::<ABI>().unwrap_err(),
+            ParseAbiError::UnknownAbi { name: s.into() }
+        );
+    }
+}
+
+#[test]
+fn abi_from_kernel_version() {
+    for (version, abi) in [
+        ("4.19", ABI::Unsupported),
+        ("5.12.19", ABI::Unsupported),
+        ("5.13", ABI::V1),
+        ("5.15.0-105-generic", ABI::V1),
+        ("5.19-rc1", ABI::V2),
+        ("6.1.0-18-amd64", ABI::V2),
+        ("6.2", ABI::V3),
+        ("6.6.30", ABI::V3),
+        ("6.7", ABI::V4),
+        ("10.0", ABI::V4),
+    ] {
+        assert_eq!(ABI::from_kernel_version(version).unwrap(), abi, "{version}");
+    }
+    for version in ["", "6", "6.", "x.y", ".7", "v6.7"] {
+        assert_eq!(
+            ABI::from_kernel_version(version).unwrap_err(),
+            ParseAbiError::InvalidKernelVersion {
+                version: version.into()
+            }
+        );
+    }
+    for abi in ABI::iter() {
+        match abi.kernel_version() {
+            Some((major, minor)) => assert_eq!(
+                ABI::from_kernel_version(&format!("{major}.{minor}")).unwrap(),
+                abi
+            ),
+            None => assert_eq!(abi, ABI::Unsupported),
+        }
+    }
 }
 
 #[test]
@@ -127,53 +372,100 @@ fn known_abi() {
     assert!(!ABI::is_known(last_i + 1));
 }
 
-#[cfg(test)]
-lazy_static! {
-    static ref TEST_ABI: ABI = match std::env::var("LANDLOCK_CRATE_TEST_ABI") {
+// Reads the tested ABI from the LANDLOCK_CRATE_TEST_ABI environment variable, if set.
+#[cfg(any(test, feature = "test-utils"))]
+pub(crate) fn env_test_abi() -> Option<ABI> {
+    match std::env::var("LANDLOCK_CRATE_TEST_ABI") {
         Ok(s) => {
-            let n = s.parse::<i32>().unwrap();
-            if ABI::is_known(n) || n == 0 {
-                ABI::from(n)
+            let n = s
+                .parse::<i32>()
+                .unwrap_or_else(|_| panic!("Invalid ABI: {s}"));
+            // ABI::from() saturates unknown values.
+            if ABI::from(n) as i32 == n {
+                Some(ABI::from(n))
             } else {
                 panic!("Unknown ABI: {n}");
             }
         }
-        Err(std::env::VarError::NotPresent) => ABI::iter().last().unwrap(),
+        Err(std::env::VarError::NotPresent) => None,
         Err(e) => panic!("Failed to read LANDLOCK_CRATE_TEST_ABI: {e}"),
-    };
+    }
 }
 
 #[cfg(test)]
-pub(crate) fn can_emulate(mock: ABI, partial_support: ABI, full_support: Option<ABI>) -> bool {
-    mock < partial_support
-        || mock <= *TEST_ABI
-        || if let Some(full) = full_support {
-            full <= *TEST_ABI
-        } else {
-            partial_support <= *TEST_ABI
-        }
+lazy_static! {
+    static ref TEST_ABI: ABI = env_test_abi().unwrap_or_else(|| ABI::iter().next_back().unwrap());
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+pub(crate) fn can_emulate_with(
+    test_abi: ABI,
+    mock: ABI,
+    partial_support: ABI,
+    full_support: Option<ABI>,
+) -> bool {
+    let (mock, test_abi) = (mock as i32, test_abi as i32);
+    mock < partial_support as i32
+        || mock <= test_abi
+        || full_support.unwrap_or(partial_support) as i32 <= test_abi
+}
+
+#[test]
+fn can_emulate_with_abi() {
+    // The mocked ABI doesn't support the feature.
+    assert!(can_emulate_with(ABI::V1, ABI::V1, ABI::V2, None));
+    // The tested ABI supports the mocked one.
+    assert!(can_emulate_with(ABI::V3, ABI::V2, ABI::V1, Some(ABI::V4)));
+    // The tested ABI fully supports the feature.
+    assert!(can_emulate_with(ABI::V2, ABI::V4, ABI::V1, Some(ABI::V2)));
+    assert!(!can_emulate_with(ABI::V1, ABI::V4, ABI::V1, Some(ABI::V2)));
+    assert!(!can_emulate_with(ABI::V1, ABI::V2, ABI::V2, None));
 }
 
 #[cfg(test)]
-pub(crate) fn get_errno_from_landlock_status() -> Option<i32> {
-    use std::io::Error;
+pub(crate) fn can_emulate(mock: ABI, partial_support: ABI, full_support: Option<ABI>) -> bool {
+    can_emulate_with(*TEST_ABI, mock, partial_support, full_support)
+}
 
+// Returns the errno of the ABI probe, if it failed.
+#[cfg(any(test, feature = "test-utils"))]
+pub(crate) fn landlock_errno() -> Option<i32> {
     if unsafe {
         uapi::landlock_create_ruleset(std::ptr::null(), 0, uapi::LANDLOCK_CREATE_RULESET_VERSION)
     } < 0
     {
-        match Error::last_os_error().raw_os_error() {
-            // Returns ENOSYS when the kernel is not built with Landlock support,
-            // or EOPNOTSUPP when Landlock is supported but disabled at boot time.
-            ret @ Some(libc::ENOSYS | libc::EOPNOTSUPP) => ret,
-            // Other values can only come from bogus seccomp filters or debug tampering.
-            _ => unreachable!(),
-        }
+        std::io::Error::last_os_error().raw_os_error()
     } else {
         None
     }
 }
 
+#[cfg(test)]
+pub(crate) fn get_errno_from_landlock_status() -> Option<i32> {
+    match landlock_errno() {
+        // Returns ENOSYS when the kernel is not built with Landlock support,
+        // or EOPNOTSUPP when Landlock is supported but disabled at boot time.
+        ret @ (None | Some(libc::ENOSYS | libc::EOPNOTSUPP)) => ret,
+        // Other values can only come from bogus seccomp filters or debug tampering.
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn abi_probe_cache() {
+    let current = ABI::new_current();
+    // Other tests may concurrently probe the ABI.
+    ABI::refresh();
+    assert_eq!(ABI::new_current(), current);
+    assert_ne!(ABI_PROBE.load(Ordering::Relaxed), PROBE_UNKNOWN);
+
+    // The cache is bypassed by a mocked kernel.
+    let _kernel = uapi::MockKernel::new(ABI::Unsupported).install();
+    assert_eq!(ABI::new_current(), ABI::Unsupported);
+    drop(_kernel);
+    assert_eq!(ABI::new_current(), current);
+}
+
 #[test]
 fn current_kernel_abi() {
     // Ensures that the tested Landlock ABI is the latest known version supported by the running
@@ -254,41 +546,189 @@ fn compat_state_update_2() {
     assert_eq!(state, CompatState::Partial);
 }
 
+#[test]
+fn compatibility_lazy_abi() {
+    let _kernel = uapi::MockKernel::new(ABI::V2).install();
+    let mut compat = Compatibility::new();
+    // The running kernel is probed even without handled access rights.
+    assert_eq!(compat.abi(), ABI::V2);
+    assert_eq!(compat.state, CompatState::Init);
+    compat.set_max_abi(ABI::V1);
+    assert_eq!(compat.abi(), ABI::V1);
+}
+
+/// Feature ignored because of the compatibility level,
+/// see [`Ruleset::on_downgrade()`](crate::Ruleset::on_downgrade).
+///
+/// The wrapped error describes the ignored access rights or rule,
+/// and why they were ignored (e.g., not supported by the running kernel).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Downgrade {
+    /// Ignored file system access rights or rule.
+    Fs(CompatError<AccessFs>),
+    /// Ignored network access rights or rule.
+    Net(CompatError<AccessNet>),
+}
+
+impl fmt::Display for Downgrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Downgrade::Fs(e) => write!(f, "ignored file system feature: {e}"),
+            Downgrade::Net(e) => write!(f, "ignored network feature: {e}"),
+        }
+    }
+}
+
+// Callback notified of the features ignored by TryCompat::try_compat_observed().
+//
+// DowngradeObserver is not public outside this crate.
+#[derive(Clone)]
+pub struct DowngradeObserver(Arc<dyn Fn(&Downgrade) + Send + Sync>);
+
+impl DowngradeObserver {
+    pub(crate) fn new<F>(f: F) -> Self
+    where
+        F: Fn(&Downgrade) + Send + Sync + 'static,
+    {
+        DowngradeObserver(Arc::new(f))
+    }
+
+    fn notify(&self, downgrade: &Downgrade) {
+        (self.0)(downgrade)
+    }
+}
+
+impl fmt::Debug for DowngradeObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DowngradeObserver").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+impl PartialEq for DowngradeObserver {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+// Features ignored by TryCompat::try_compat_observed().
+//
+// Downgrades is not public outside this crate.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone, Default)]
+pub struct Downgrades {
+    pub(crate) observer: Option<DowngradeObserver>,
+    // Descriptions of the features ignored with CompatLevel::WarnOnly.
+    pub(crate) warnings: Vec<String>,
+}
+
+impl Downgrades {
+    fn record(&mut self, level: CompatLevel, downgrade: Downgrade) {
+        if let Some(observer) = &self.observer {
+            observer.notify(&downgrade);
+        }
+        if level == CompatLevel::WarnOnly {
+            self.warnings.push(downgrade.to_string());
+        }
+    }
+}
+
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Clone)]
 pub(crate) struct Compatibility {
-    abi: ABI,
+    // None until the running kernel is probed.
+    pub(crate) abi: Option<ABI>,
+    max_abi: Option<ABI>,
     pub(crate) level: Option<CompatLevel>,
     pub(crate) state: CompatState,
+    pub(crate) downgrades: Downgrades,
 }
 
 impl From<ABI> for Compatibility {
     fn from(abi: ABI) -> Self {
-        Compatibility {
-            abi,
-            level: Default::default(),
-            state: match abi {
-                // Don't forces the state as Dummy because no_new_privs may still be legitimate.
-                ABI::Unsupported => CompatState::No,
-                _ => CompatState::Init,
-            },
-        }
+        let mut compat = Compatibility::new();
+        compat.set_abi(abi);
+        compat
     }
 }
 
 impl Compatibility {
-    // Compatibility is a semi-opaque struct.
+    // Compatibility is a semi-opaque struct.  The running kernel is only probed when required,
+    // see probe().
     #[allow(clippy::new_without_default)]
     pub(crate) fn new() -> Self {
-        ABI::new_current().into()
+        Compatibility {
+            abi: None,
+            max_abi: None,
+            level: Default::default(),
+            state: CompatState::Init,
+            downgrades: Default::default(),
+        }
+    }
+
+    pub(crate) fn from_parts(abi: ABI, level: Option<CompatLevel>, state: CompatState) -> Self {
+        Compatibility {
+            abi: Some(abi),
+            max_abi: None,
+            level,
+            state,
+            downgrades: Default::default(),
+        }
     }
 
     pub(crate) fn update(&mut self, state: CompatState) {
         self.state.update(state);
     }
 
+    fn capped(&self, abi: ABI) -> ABI {
+        match self.max_abi {
+            Some(max) if (max as i32) < (abi as i32) => max,
+            _ => abi,
+        }
+    }
+
+    fn set_abi(&mut self, abi: ABI) {
+        let abi = self.capped(abi);
+        self.abi = Some(abi);
+        if abi == ABI::Unsupported {
+            // Don't forces the state as Dummy because no_new_privs may still be legitimate.
+            self.state = CompatState::No;
+        }
+    }
+
+    // Caps the ABI used for the next compatibility decisions.
+    pub(crate) fn set_max_abi(&mut self, max: ABI) {
+        if let Some(current) = self.max_abi {
+            if (current as i32) < (max as i32) {
+                return;
+            }
+        }
+        self.max_abi = Some(max);
+        if let Some(abi) = self.abi {
+            self.set_abi(abi);
+        }
+    }
+
+    // Probes the running kernel, if not already done, and returns the ABI to use for
+    // compatibility decisions.
+    pub(crate) fn probe(&mut self) -> ABI {
+        match self.abi {
+            Some(abi) => abi,
+            None => {
+                self.set_abi(ABI::new_current());
+                self.abi()
+            }
+        }
+    }
+
+    // Probes the running kernel if not already done, which is cheap thanks to ABI_PROBE, but
+    // without updating the state as probe() does.
     pub(crate) fn abi(&self) -> ABI {
-        self.abi
+        match self.abi {
+            Some(abi) => abi,
+            None => self.capped(ABI::new_current()),
+        }
     }
 }
 
@@ -461,13 +901,30 @@ fn deprecated_set_best_effort() {
 
 /// See the [`Compatible`] documentation.
 #[cfg_attr(test, derive(EnumIter))]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
 pub enum CompatLevel {
     /// Takes into account the build requests if they are supported by the running system,
     /// or silently ignores them otherwise.
     /// Never returns a compatibility error.
     #[default]
     BestEffort,
+    /// Same as [`BestEffort`](CompatLevel::BestEffort),
+    /// but records a warning for each ignored build request,
+    /// which are then listed in the [`RestrictionStatus`](crate::RestrictionStatus)
+    /// returned by [`RulesetCreated::restrict_self()`](crate::RulesetCreated::restrict_self()).
+    /// Never returns a compatibility error.
+    ///
+    /// See also [`Ruleset::on_downgrade()`](crate::Ruleset::on_downgrade)
+    /// to log the ignored features when they are encountered.
+    WarnOnly,
     /// Takes into account the build requests if they are supported by the running system,
     /// or silently ignores the whole build object otherwise.
     /// Never returns a compatibility error.
@@ -595,6 +1052,7 @@ where
         _abi: ABI,
         _parent_level: L,
         _compat_state: &mut CompatState,
+        _downgrades: &mut Downgrades,
     ) -> Result<Option<Self>, CompatError<A>>
     where
         L: Into<CompatLevel>,
@@ -602,34 +1060,53 @@ where
         Ok(Some(self))
     }
 
-    // Update compat_state and return an error according to try_compat_*() error, or to the
-    // compatibility level, i.e. either route compatible object or error.
+    // Same as try_compat_observed() without recording the ignored features.
     fn try_compat<L>(
+        self,
+        abi: ABI,
+        parent_level: L,
+        compat_state: &mut CompatState,
+    ) -> Result<Option<Self>, CompatError<A>>
+    where
+        L: Into<CompatLevel>,
+    {
+        self.try_compat_observed(abi, parent_level, compat_state, &mut Default::default())
+    }
+
+    // Update compat_state and return an error according to try_compat_*() error, or to the
+    // compatibility level, i.e. either route compatible object or error.  Errors ignored because
+    // of the compatibility level are recorded in downgrades.
+    fn try_compat_observed<L>(
         mut self,
         abi: ABI,
         parent_level: L,
         compat_state: &mut CompatState,
+        downgrades: &mut Downgrades,
     ) -> Result<Option<Self>, CompatError<A>>
     where
         L: Into<CompatLevel>,
     {
         let compat_level = self.tailored_compat_level(parent_level);
-        let new_self = match self.try_compat_children(abi, compat_level, compat_state)? {
-            Some(n) => n,
-            None => return Ok(None),
-        };
+        let new_self =
+            match self.try_compat_children(abi, compat_level, compat_state, downgrades)? {
+                Some(n) => n,
+                None => return Ok(None),
+            };
+        let mut ignore = |error| downgrades.record(compat_level, A::into_downgrade(error));
         match new_self.try_compat_inner(abi) {
             Ok(CompatResult::Full(new_self)) => {
                 compat_state.update(CompatState::Full);
                 Ok(Some(new_self))
             }
             Ok(CompatResult::Partial(new_self, error)) => match compat_level {
-                CompatLevel::BestEffort => {
+                CompatLevel::BestEffort | CompatLevel::WarnOnly => {
                     compat_state.update(CompatState::Partial);
+                    ignore(error);
                     Ok(Some(new_self))
                 }
                 CompatLevel::SoftRequirement => {
                     compat_state.update(CompatState::Dummy);
+                    ignore(error);
                     Ok(None)
                 }
                 CompatLevel::HardRequirement => {
@@ -638,12 +1115,14 @@ where
                 }
             },
             Ok(CompatResult::No(error)) => match compat_level {
-                CompatLevel::BestEffort => {
+                CompatLevel::BestEffort | CompatLevel::WarnOnly => {
                     compat_state.update(CompatState::No);
+                    ignore(error);
                     Ok(None)
                 }
                 CompatLevel::SoftRequirement => {
                     compat_state.update(CompatState::Dummy);
+                    ignore(error);
                     Ok(None)
                 }
                 CompatLevel::HardRequirement => {
@@ -0,0 +1,332 @@
+use crate::{
+    Access, AccessFs, BitFlags, CompatLevel, Compatible, ConformanceError, PathBeneath, PathFd,
+    RestrictionStatus, Ruleset, RulesetAttr, RulesetCreatedAttr, RulesetStatus, ABI,
+};
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::os::raw::c_int;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+// Exit codes of the forked child.
+const EXIT_PERMITTED: c_int = 0;
+const EXIT_DENIED: c_int = 1;
+const EXIT_SETUP: c_int = 2;
+
+/// Result of one access right check done by [`run_conformance()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ConformanceOutcome {
+    /// Tested access right.
+    pub access: AccessFs,
+    /// True if the sandbox granted `access` to the scratch directory, false if it only handled it.
+    pub granted: bool,
+    /// True if the related operation was permitted by the running kernel.
+    pub permitted: bool,
+}
+
+impl ConformanceOutcome {
+    /// Returns true if the kernel permitted the operation if and only if it was granted.
+    pub fn is_conformant(&self) -> bool {
+        self.granted == self.permitted
+    }
+}
+
+/// Report of [`run_conformance()`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ConformanceReport {
+    /// Landlock ABI supported by the running kernel, which defines the tested access rights.
+    pub abi: ABI,
+    /// Results of all the checks, two for each supported access right (granted and denied).
+    pub outcomes: Vec<ConformanceOutcome>,
+}
+
+impl ConformanceReport {
+    /// Returns true if all the checks matched the expected outcomes.
+    pub fn is_conformant(&self) -> bool {
+        self.outcomes.iter().all(ConformanceOutcome::is_conformant)
+    }
+
+    /// Returns the checks that didn't match the expected outcomes.
+    pub fn failures(&self) -> impl Iterator<Item = &ConformanceOutcome> {
+        self.outcomes.iter().filter(|o| !o.is_conformant())
+    }
+}
+
+// File system operation exercising one access right, prepared before the fork to not allocate in
+// the child.
+enum Operation {
+    Open(CString, c_int),
+    Execute(CString),
+    Unlink(CString),
+    Rmdir(CString),
+    Mkdir(CString),
+    Mknod(CString, libc::mode_t),
+    Symlink(CString),
+    Rename(CString, CString),
+    Truncate(CString),
+}
+
+impl Operation {
+    // Returns the errno of the operation, or 0 if it succeeded.  Async-signal-safe.
+    fn run(&self) -> c_int {
+        let ret = unsafe {
+            match self {
+                Operation::Open(path, flags) => {
+                    let fd = libc::open(path.as_ptr(), *flags | libc::O_CLOEXEC);
+                    if fd >= 0 {
+                        libc::close(fd);
+                    }
+                    fd
+                }
+                Operation::Execute(path) => {
+                    let argv = [path.as_ptr(), ptr::null()];
+                    let envp = [ptr::null()];
+                    libc::execve(path.as_ptr(), argv.as_ptr(), envp.as_ptr())
+                }
+                Operation::Unlink(path) => libc::unlink(path.as_ptr()),
+                Operation::Rmdir(path) => libc::rmdir(path.as_ptr()),
+                Operation::Mkdir(path) => libc::mkdir(path.as_ptr(), 0o700),
+                Operation::Mknod(path, mode) => libc::mknod(path.as_ptr(), *mode | 0o600, 0),
+                Operation::Symlink(path) => {
+                    libc::symlink(b"target\0".as_ptr().cast(), path.as_ptr())
+                }
+                Operation::Rename(from, to) => libc::rename(from.as_ptr(), to.as_ptr()),
+                Operation::Truncate(path) => libc::truncate(path.as_ptr(), 0),
+            }
+        };
+        if ret < 0 {
+            io::Error::last_os_error()
+                .raw_os_error()
+                .unwrap_or(libc::EINVAL)
+        } else {
+            0
+        }
+    }
+}
+
+// Scratch tree used by one check:
+// - file: empty executable file
+// - dir/: empty directory
+// - a/file: empty file
+// - b/: empty directory
+struct Scratch {
+    root: PathBuf,
+}
+
+impl Scratch {
+    fn new(parent: &Path, name: &str) -> io::Result<Self> {
+        let root = parent.join(name);
+        if root.exists() {
+            fs::remove_dir_all(&root)?;
+        }
+        fs::create_dir_all(root.join("dir"))?;
+        fs::create_dir(root.join("a"))?;
+        fs::create_dir(root.join("b"))?;
+        fs::write(root.join("a").join("file"), b"")?;
+        let file = root.join("file");
+        fs::write(&file, b"")?;
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o755))?;
+        Ok(Scratch { root })
+    }
+
+    fn path(&self, name: &str) -> CString {
+        // The scratch parent directory cannot contain NUL bytes because it was created.
+        CString::new(self.root.join(name).as_os_str().as_bytes()).unwrap_or_default()
+    }
+
+    // Returns the operation exercising access, the access rights required by this operation in
+    // addition to access, and the errno returned when the operation is denied.
+    fn operation(&self, access: AccessFs) -> (Operation, BitFlags<AccessFs>, c_int) {
+        let empty = BitFlags::EMPTY;
+        match access {
+            // Executing a file also requires to open it for reading.  Executing an empty file is
+            // permitted but then fails with ENOEXEC.
+            AccessFs::Execute => (
+                Operation::Execute(self.path("file")),
+                AccessFs::ReadFile.into(),
+                libc::EACCES,
+            ),
+            AccessFs::WriteFile => (
+                Operation::Open(self.path("file"), libc::O_WRONLY),
+                empty,
+                libc::EACCES,
+            ),
+            AccessFs::ReadFile => (
+                Operation::Open(self.path("file"), libc::O_RDONLY),
+                empty,
+                libc::EACCES,
+            ),
+            AccessFs::ReadDir => (
+                Operation::Open(self.path("dir"), libc::O_RDONLY | libc::O_DIRECTORY),
+                empty,
+                libc::EACCES,
+            ),
+            AccessFs::RemoveDir => (Operation::Rmdir(self.path("dir")), empty, libc::EACCES),
+            AccessFs::RemoveFile => (Operation::Unlink(self.path("file")), empty, libc::EACCES),
+            // Creating devices may then fail with EPERM without CAP_MKNOD.
+            AccessFs::MakeChar => (
+                Operation::Mknod(self.path("node"), libc::S_IFCHR),
+                empty,
+                libc::EACCES,
+            ),
+            AccessFs::MakeDir => (Operation::Mkdir(self.path("node")), empty, libc::EACCES),
+            AccessFs::MakeReg => (
+                Operation::Mknod(self.path("node"), libc::S_IFREG),
+                empty,
+                libc::EACCES,
+            ),
+            AccessFs::MakeSock => (
+                Operation::Mknod(self.path("node"), libc::S_IFSOCK),
+                empty,
+                libc::EACCES,
+            ),
+            AccessFs::MakeFifo => (
+                Operation::Mknod(self.path("node"), libc::S_IFIFO),
+                empty,
+                libc::EACCES,
+            ),
+            AccessFs::MakeBlock => (
+                Operation::Mknod(self.path("node"), libc::S_IFBLK),
+                empty,
+                libc::EACCES,
+            ),
+            AccessFs::MakeSym => (Operation::Symlink(self.path("node")), empty, libc::EACCES),
+            // Reparenting a file also requires to remove and create it.
+            AccessFs::Refer => (
+                Operation::Rename(self.path("a/file"), self.path("b/file")),
+                AccessFs::RemoveFile | AccessFs::MakeReg,
+                libc::EXDEV,
+            ),
+            AccessFs::Truncate => (Operation::Truncate(self.path("file")), empty, libc::EACCES),
+        }
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+fn check(
+    parent: &Path,
+    abi: ABI,
+    access: AccessFs,
+    granted: bool,
+) -> Result<ConformanceOutcome, ConformanceError> {
+    let scratch = Scratch::new(
+        parent,
+        &format!("landlock-conformance-{}", std::process::id()),
+    )
+    .map_err(|source| ConformanceError::ScratchCall { source })?;
+    let (operation, required, denied_errno) = scratch.operation(access);
+    let allowed = if granted { required | access } else { required };
+
+    let mut ruleset = Ruleset::default()
+        .set_compatibility(CompatLevel::HardRequirement)
+        .handle_access(AccessFs::from_all(abi))?
+        .create()?;
+    if !allowed.is_empty() {
+        ruleset = ruleset.add_rule(PathBeneath::new(PathFd::new(&scratch.root)?, allowed))?;
+    }
+
+    let code = match unsafe { libc::fork() } {
+        -1 => {
+            return Err(ConformanceError::ForkCall {
+                source: io::Error::last_os_error(),
+            })
+        }
+        0 => {
+            // Only calls async-signal-safe functions in the child.
+            let code = match ruleset.restrict_self_async_signal_safe() {
+                Ok(RestrictionStatus {
+                    ruleset: RulesetStatus::FullyEnforced,
+                    ..
+                }) => match operation.run() {
+                    errno if errno == denied_errno => EXIT_DENIED,
+                    _ => EXIT_PERMITTED,
+                },
+                _ => EXIT_SETUP,
+            };
+            unsafe { libc::_exit(code) }
+        }
+        pid => {
+            let mut status = 0;
+            if unsafe { libc::waitpid(pid, &mut status, 0) } < 0 {
+                return Err(ConformanceError::ForkCall {
+                    source: io::Error::last_os_error(),
+                });
+            }
+            if libc::WIFEXITED(status) {
+                libc::WEXITSTATUS(status)
+            } else {
+                EXIT_SETUP
+            }
+        }
+    };
+    let permitted = match code {
+        EXIT_PERMITTED => true,
+        EXIT_DENIED => false,
+        _ => return Err(ConformanceError::Child { access }),
+    };
+    Ok(ConformanceOutcome {
+        access,
+        granted,
+        permitted,
+    })
+}
+
+/// Checks that the running kernel enforces all the supported file system access rights.
+///
+/// For each access right supported by the running kernel,
+/// this builds a scratch directory tree in `parent`,
+/// and checks in forked child processes
+/// that the related operation is permitted when the access right is granted,
+/// and denied otherwise.
+/// This is useful to validate the Landlock support of custom or exotic kernels.
+///
+/// `parent` must be a writable directory not mounted with `noexec`.
+/// If Landlock is not supported by the running kernel, the report doesn't contain any outcome.
+///
+/// This is only available with the `conformance` feature.
+///
+/// # Example
+///
+/// ```
+/// use landlock::run_conformance;
+///
+/// let report = run_conformance(std::env::temp_dir()).unwrap();
+/// for failure in report.failures() {
+///     eprintln!("non-conformant: {failure:?}");
+/// }
+/// ```
+pub fn run_conformance<P>(parent: P) -> Result<ConformanceReport, ConformanceError>
+where
+    P: AsRef<Path>,
+{
+    let abi = ABI::new_current();
+    let mut outcomes = Vec::new();
+    for access in AccessFs::from_all(abi) {
+        for granted in [true, false] {
+            outcomes.push(check(parent.as_ref(), abi, access, granted)?);
+        }
+    }
+    Ok(ConformanceReport { abi, outcomes })
+}
+
+#[test]
+fn conformance_suite() {
+    let report = run_conformance(std::env::temp_dir()).unwrap();
+    assert_eq!(report.abi, ABI::new_current());
+    assert!(report.is_conformant());
+    assert_eq!(report.failures().count(), 0);
+    assert_eq!(
+        report.outcomes.len(),
+        AccessFs::from_all(report.abi).iter().count() * 2
+    );
+}
@@ -0,0 +1,314 @@
+// Implements miette::Diagnostic for the main error types, with error codes and remediation hints.
+
+use crate::compat::ABI_KERNELS;
+use crate::{
+    Access, AccessError, AddRuleError, AddRulesError, BulkRulesError, CompatError,
+    CreateRulesetError, HandleAccessError, HandleAccessesError, PathBeneathError, PathFdError,
+    PolicyError, RequireAbiError, RestrictSelfError, RulesetError,
+};
+use enumflags2::BitFlags;
+use miette::Diagnostic;
+use std::any::type_name;
+use std::fmt::{Debug, Display};
+
+type Help<'a> = Option<Box<dyn Display + 'a>>;
+
+fn help<'a, S>(text: S) -> Help<'a>
+where
+    S: Display + 'a,
+{
+    Some(Box::new(text))
+}
+
+// Formats access rights as Rust paths, e.g. "AccessFs::Execute | AccessFs::ReadFile".
+fn format_access<T>(access: BitFlags<T>) -> String
+where
+    T: Access + Debug,
+{
+    let type_name = type_name::<T>().rsplit("::").next().unwrap_or_default();
+    access
+        .iter()
+        .map(|a| format!("{type_name}::{a:?}"))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn access_help<'a, T>(error: &AccessError<T>) -> Help<'a>
+where
+    T: Access + Debug,
+{
+    match error {
+        AccessError::Empty => help("request at least one access right"),
+        AccessError::Unknown { .. } => {
+            help("only use access rights defined by this crate, e.g. with Access::from_all()")
+        }
+        AccessError::Incompatible {
+            access: incompatible,
+        }
+        | AccessError::PartiallyCompatible { incompatible, .. } => {
+            let incompatible = *incompatible;
+            let requirement = ABI_KERNELS
+                .iter()
+                .find(|(abi, _)| T::from_all(*abi).contains(incompatible))
+                .map(|(abi, (major, minor))| {
+                    format!(
+                        "{} needs Linux {major}.{minor} (ABI::{abi:?}); ",
+                        format_access(incompatible)
+                    )
+                })
+                .unwrap_or_default();
+            help(format!(
+                "{requirement}see CompatLevel::BestEffort to ignore access rights \
+                unsupported by the running kernel"
+            ))
+        }
+    }
+}
+
+fn compat_help<'a, T>(error: &CompatError<T>) -> Help<'a>
+where
+    T: Access + Debug,
+{
+    match error {
+        CompatError::Access(e) => access_help(e),
+        CompatError::PathBeneath(PathBeneathError::DirectoryAccess { .. }) => help(
+            "only allow file-compatible access rights (e.g. not ReadDir) for a file, \
+            or see CompatLevel::BestEffort to remove them",
+        ),
+        CompatError::PathBeneath(PathBeneathError::StatCall { .. }) => None,
+    }
+}
+
+fn add_rule_help<'a, T>(error: &AddRuleError<T>) -> Help<'a>
+where
+    T: Access + Debug,
+{
+    match error {
+        AddRuleError::UnhandledAccess { incompatible, .. } => help(format!(
+            "handle {} with RulesetAttr::handle_access() before adding this rule",
+            format_access(*incompatible)
+        )),
+        AddRuleError::Compat(e) => compat_help(e),
+        AddRuleError::AddRuleCall { .. } => None,
+    }
+}
+
+impl Diagnostic for RulesetError {
+    fn code<'a>(&'a self) -> Help<'a> {
+        let code = match self {
+            RulesetError::HandleAccesses(_) => "landlock::handle_access",
+            RulesetError::CreateRuleset(_) => "landlock::create_ruleset",
+            RulesetError::AddRules(_) => "landlock::add_rule",
+            RulesetError::RestrictSelf(_) => "landlock::restrict_self",
+            RulesetError::RequireAbi(_) => "landlock::require_abi",
+        };
+        help(code)
+    }
+
+    fn help<'a>(&'a self) -> Help<'a> {
+        match self {
+            RulesetError::HandleAccesses(HandleAccessesError::Fs(HandleAccessError::Compat(e))) => {
+                compat_help(e)
+            }
+            RulesetError::HandleAccesses(HandleAccessesError::Net(HandleAccessError::Compat(
+                e,
+            ))) => compat_help(e),
+            RulesetError::CreateRuleset(CreateRulesetError::MissingHandledAccess) => {
+                help("call RulesetAttr::handle_access() before Ruleset::create()")
+            }
+            RulesetError::CreateRuleset(CreateRulesetError::CreateRulesetCall { source }) => {
+                match source.raw_os_error() {
+                    Some(libc::ENOSYS) => help(
+                        "the kernel is built without Landlock (CONFIG_SECURITY_LANDLOCK); \
+                        see CompatLevel::BestEffort to run without sandbox",
+                    ),
+                    Some(libc::EOPNOTSUPP) => help(
+                        "Landlock is disabled at boot time, add \"landlock\" to the lsm= kernel \
+                        parameter; see CompatLevel::BestEffort to run without sandbox",
+                    ),
+                    _ => None,
+                }
+            }
+            RulesetError::AddRules(AddRulesError::Fs(e)) => add_rule_help(e),
+            RulesetError::AddRules(AddRulesError::Net(e)) => add_rule_help(e),
+            RulesetError::AddRules(AddRulesError::RawCall { source, .. }) => {
+                match source.raw_os_error() {
+                    Some(libc::EINVAL) => help(
+                        "check that the running kernel supports this rule type \
+                        and that its attribute is well-formed",
+                    ),
+                    _ => None,
+                }
+            }
+            RulesetError::RestrictSelf(e) => restrict_self_help(e),
+            RulesetError::RequireAbi(
+                RequireAbiError::Unsupported { required }
+                | RequireAbiError::Incompatible { required, .. },
+            ) => required.kernel_version().and_then(|(major, minor)| {
+                help(format!(
+                    "ABI::{required:?} needs Linux {major}.{minor} or a kernel with backported \
+                    Landlock features"
+                ))
+            }),
+        }
+    }
+}
+
+fn restrict_self_help<'a>(error: &RestrictSelfError) -> Help<'a> {
+    match error {
+        RestrictSelfError::RestrictSelfCall { source } => match source.raw_os_error() {
+            Some(libc::EPERM) => help(
+                "the thread needs no_new_privs (see RulesetCreatedAttr::set_no_new_privs()) \
+                or CAP_SYS_ADMIN",
+            ),
+            _ => None,
+        },
+        RestrictSelfError::DomainDepthExceeded { .. } => help(
+            "enforce a ruleset in a parent thread or process instead of stacking one \
+            per component, see landlock::domain_depth()",
+        ),
+        RestrictSelfError::CanaryAccessible { .. } => help(
+            "the canary file should be denied by the ruleset, \
+            see RulesetCreatedAttr::set_canary()",
+        ),
+        RestrictSelfError::OtherThreads { .. } => help(
+            "enforce the ruleset before spawning threads, \
+            or use landlock::reexec_and_restrict() to restrict the whole process",
+        ),
+        RestrictSelfError::SetNoNewPrivsCall { .. }
+        | RestrictSelfError::CanaryCheckCall { .. }
+        | RestrictSelfError::ThreadCountCall { .. } => None,
+    }
+}
+
+impl Diagnostic for PathFdError {
+    fn code<'a>(&'a self) -> Help<'a> {
+        help("landlock::path_fd")
+    }
+
+    fn help<'a>(&'a self) -> Help<'a> {
+        match self {
+            PathFdError::OpenCall { source, .. } => match source.raw_os_error() {
+                Some(libc::ENOENT) => help(
+                    "check that the path exists, or use path_beneath_rules() \
+                    to ignore missing paths",
+                ),
+                Some(libc::EACCES) => help("the path must be searchable by the calling process"),
+                _ => None,
+            },
+            PathFdError::Symlink { .. } => help(
+                "use the path of the symbolic link's target, \
+                or PathFd::new() to follow symbolic links",
+            ),
+        }
+    }
+}
+
+impl Diagnostic for PolicyError {
+    fn code<'a>(&'a self) -> Help<'a> {
+        match self {
+            PolicyError::Ruleset(e) => e.code(),
+            PolicyError::PathFd(e) => e.code(),
+            PolicyError::MissingVar { .. } | PolicyError::InvalidPort { .. } => {
+                help("landlock::policy_env")
+            }
+            PolicyError::UngrantedAccess { .. } => help("landlock::ungranted_access"),
+        }
+    }
+
+    fn help<'a>(&'a self) -> Help<'a> {
+        match self {
+            PolicyError::Ruleset(e) => e.help(),
+            PolicyError::PathFd(e) => e.help(),
+            PolicyError::MissingVar { name } => {
+                help(format!("set the {name} environment variable"))
+            }
+            PolicyError::InvalidPort { .. } => {
+                help("ports must be integers between 0 and 65535, separated by colons")
+            }
+            PolicyError::UngrantedAccess { .. } => help(
+                "grant these access rights with a rule, \
+                or confirm them with Policy::confirm_ungranted_fs() or confirm_ungranted_net()",
+            ),
+        }
+    }
+}
+
+impl Diagnostic for BulkRulesError {
+    fn code<'a>(&'a self) -> Help<'a> {
+        match self {
+            BulkRulesError::Ruleset(e) => e.code(),
+            BulkRulesError::PathFd(e) => e.code(),
+            BulkRulesError::GetRlimitCall { .. } | BulkRulesError::FileLimit { .. } => {
+                help("landlock::file_limit")
+            }
+        }
+    }
+
+    fn help<'a>(&'a self) -> Help<'a> {
+        match self {
+            BulkRulesError::Ruleset(e) => e.help(),
+            BulkRulesError::PathFd(e) => e.help(),
+            BulkRulesError::FileLimit { .. } => {
+                help("use a smaller chunk size, or raise RLIMIT_NOFILE (e.g. with ulimit -n)")
+            }
+            BulkRulesError::GetRlimitCall { .. } => None,
+        }
+    }
+}
+
+#[test]
+fn diagnostic_help() {
+    use crate::*;
+    use std::io;
+
+    let to_string = |help: Help| help.map(|h| h.to_string());
+
+    let error: RulesetError = HandleAccessesError::from(HandleAccessError::Compat(
+        CompatError::Access(AccessError::<AccessFs>::Incompatible {
+            access: AccessFs::Refer.into(),
+        }),
+    ))
+    .into();
+    assert_eq!(to_string(error.code()).unwrap(), "landlock::handle_access");
+    assert_eq!(
+        to_string(error.help()).unwrap(),
+        "AccessFs::Refer needs Linux 5.19 (ABI::V2); see CompatLevel::BestEffort \
+        to ignore access rights unsupported by the running kernel"
+    );
+    assert_eq!(
+        format_access(AccessFs::Execute | AccessFs::ReadFile),
+        "AccessFs::Execute | AccessFs::ReadFile"
+    );
+
+    let error: RulesetError = RestrictSelfError::RestrictSelfCall {
+        source: io::Error::from_raw_os_error(libc::EPERM),
+    }
+    .into();
+    assert!(to_string(error.help()).unwrap().contains("no_new_privs"));
+
+    let error: RulesetError = RequireAbiError::Incompatible {
+        required: ABI::V3,
+        actual: ABI::V2,
+    }
+    .into();
+    assert_eq!(to_string(error.code()).unwrap(), "landlock::require_abi");
+    assert!(to_string(error.help())
+        .unwrap()
+        .starts_with("ABI::V3 needs Linux 6.2"));
+
+    let error: RulesetError = RestrictSelfError::RestrictSelfCall {
+        source: io::Error::from_raw_os_error(libc::EINTR),
+    }
+    .into();
+    assert!(error.help().is_none());
+
+    let error = PolicyError::from(PathFdError::OpenCall {
+        source: io::Error::from_raw_os_error(libc::ENOENT),
+        path: "/foo".into(),
+    });
+    assert_eq!(to_string(error.code()).unwrap(), "landlock::path_fd");
+    assert!(to_string(error.help())
+        .unwrap()
+        .contains("path_beneath_rules()"));
+}
@@ -1,5 +1,16 @@
-use crate::{Access, AccessFs, BitFlags};
+#[cfg(feature = "caps")]
+use crate::caps::Capability;
+#[cfg(feature = "hardening")]
+use crate::hardening::HardeningStep;
+#[cfg(all(
+    feature = "namespaces",
+    any(target_os = "linux", target_os = "android")
+))]
+use crate::namespaces::Namespace;
+use crate::{Access, AccessFs, AccessNet, BitFlags, ABI};
+use std::fmt;
 use std::io;
+use std::os::unix::io::RawFd;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -15,6 +26,73 @@ pub enum RulesetError {
     AddRules(#[from] AddRulesError),
     #[error(transparent)]
     RestrictSelf(#[from] RestrictSelfError),
+    #[error(transparent)]
+    RequireAbi(#[from] RequireAbiError),
+}
+
+/// Category of a [`RulesetError`] or a [`PolicyError`], see [`RulesetError::kind()`].
+///
+/// This enables to handle errors according to their origin,
+/// e.g. to abort on programming errors but to only warn about environment limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The caller passed invalid or inconsistent arguments (e.g. empty access rights),
+    /// which should be fixed in the program.
+    InvalidInput,
+    /// The running kernel doesn't support a feature required by the caller
+    /// (i.e. with a strict [`CompatLevel`](crate::CompatLevel)).
+    Unsupported,
+    /// The runtime environment prevents the operation
+    /// (e.g. a missing file, denied permission, or too many open files).
+    Environment,
+}
+
+impl ErrorKind {
+    // Classifies the error of a system call.
+    fn from_io(error: &io::Error) -> Self {
+        match error.raw_os_error() {
+            Some(libc::EINVAL | libc::E2BIG | libc::EFAULT | libc::EBADF | libc::ENOMSG) => {
+                ErrorKind::InvalidInput
+            }
+            Some(libc::ENOSYS | libc::EOPNOTSUPP) => ErrorKind::Unsupported,
+            _ => ErrorKind::Environment,
+        }
+    }
+}
+
+impl RulesetError {
+    /// Gets the category of this error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{AccessFs, ErrorKind, Ruleset, RulesetAttr, BitFlags};
+    ///
+    /// match Ruleset::default().handle_access(BitFlags::<AccessFs>::EMPTY) {
+    ///     Err(error) => assert_eq!(error.kind(), ErrorKind::InvalidInput),
+    ///     Ok(_) => unreachable!(),
+    /// }
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            RulesetError::HandleAccesses(HandleAccessesError::Fs(e)) => e.kind(),
+            RulesetError::HandleAccesses(HandleAccessesError::Net(e)) => e.kind(),
+            RulesetError::CreateRuleset(CreateRulesetError::CreateRulesetCall { source }) => {
+                ErrorKind::from_io(source)
+            }
+            RulesetError::CreateRuleset(CreateRulesetError::MissingHandledAccess) => {
+                ErrorKind::InvalidInput
+            }
+            RulesetError::AddRules(AddRulesError::Fs(e)) => e.kind(),
+            RulesetError::AddRules(AddRulesError::Net(e)) => e.kind(),
+            RulesetError::AddRules(AddRulesError::RawCall { source, .. }) => {
+                ErrorKind::from_io(source)
+            }
+            RulesetError::RestrictSelf(e) => e.kind(),
+            RulesetError::RequireAbi(_) => ErrorKind::Unsupported,
+        }
+    }
 }
 
 #[test]
@@ -43,6 +121,19 @@ where
 pub enum HandleAccessesError {
     #[error(transparent)]
     Fs(HandleAccessError<AccessFs>),
+    #[error(transparent)]
+    Net(HandleAccessError<AccessNet>),
+}
+
+impl<T> HandleAccessError<T>
+where
+    T: Access,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            HandleAccessError::Compat(e) => e.kind(),
+        }
+    }
 }
 
 // Generically implement for all the access implementations rather than for the cases listed in
@@ -77,19 +168,44 @@ where
     T: Access,
 {
     /// The `landlock_add_rule()` system call failed.
-    #[error("failed to add a rule: {source}")]
+    /// `path` is the label of the rule, if any
+    /// (see [`PathBeneath::set_path_label()`](crate::PathBeneath::set_path_label)).
+    #[error("failed to add a rule{}: {}", PathContext(.path), OsErrorContext(.source))]
     #[non_exhaustive]
-    AddRuleCall { source: io::Error },
+    AddRuleCall {
+        source: io::Error,
+        path: Option<PathBuf>,
+    },
     /// The rule's access-rights are not all handled by the (requested) ruleset access-rights.
-    #[error("access-rights not handled by the ruleset: {incompatible:?}")]
+    /// This is checked before calling `landlock_add_rule()`,
+    /// which would otherwise only return `EINVAL`.
+    #[error("access-rights not handled by the ruleset: {incompatible:?} (handled: {handled:?})")]
+    #[non_exhaustive]
     UnhandledAccess {
+        /// Access-rights of the rule.
         access: BitFlags<T>,
+        /// Access-rights of the rule not handled by the ruleset.
         incompatible: BitFlags<T>,
+        /// Access-rights handled by the ruleset.
+        handled: BitFlags<T>,
     },
     #[error(transparent)]
     Compat(#[from] CompatError<T>),
 }
 
+impl<T> AddRuleError<T>
+where
+    T: Access,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            AddRuleError::AddRuleCall { source, .. } => ErrorKind::from_io(source),
+            AddRuleError::UnhandledAccess { .. } => ErrorKind::InvalidInput,
+            AddRuleError::Compat(e) => e.kind(),
+        }
+    }
+}
+
 // Generically implement for all the access implementations rather than for the cases listed in
 // AddRulesError (with #[from]).
 impl<A> From<AddRuleError<A>> for AddRulesError
@@ -108,6 +224,13 @@ where
 pub enum AddRulesError {
     #[error(transparent)]
     Fs(AddRuleError<AccessFs>),
+    #[error(transparent)]
+    Net(AddRuleError<AccessNet>),
+    /// The `landlock_add_rule()` system call failed for a rule added with
+    /// [`RulesetCreated::add_raw_rule()`](crate::RulesetCreated::add_raw_rule).
+    #[error("failed to add a raw rule of type {rule_type}: {}", OsErrorContext(.source))]
+    #[non_exhaustive]
+    RawCall { rule_type: u32, source: io::Error },
 }
 
 #[derive(Debug, Error)]
@@ -122,6 +245,28 @@ where
     Access(#[from] AccessError<T>),
 }
 
+impl<T> CompatError<T>
+where
+    T: Access,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            CompatError::PathBeneath(PathBeneathError::StatCall { source }) => {
+                ErrorKind::from_io(source)
+            }
+            CompatError::PathBeneath(PathBeneathError::DirectoryAccess { .. }) => {
+                ErrorKind::InvalidInput
+            }
+            CompatError::Access(AccessError::Empty | AccessError::Unknown { .. }) => {
+                ErrorKind::InvalidInput
+            }
+            CompatError::Access(
+                AccessError::Incompatible { .. } | AccessError::PartiallyCompatible { .. },
+            ) => ErrorKind::Unsupported,
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 #[non_exhaustive]
 pub enum PathBeneathError {
@@ -136,9 +281,17 @@ pub enum PathBeneathError {
     /// if the related PathBeneath object is not set to best-effort,
     /// and if its allowed access-rights contain directory-only ones
     /// whereas the file descriptor doesn't point to a directory.
-    #[error("incompatible directory-only access-rights: {incompatible:?}")]
+    /// With a best-effort compatibility level, these access-rights are instead removed
+    /// from the rule, which would otherwise be rejected by the kernel with `EINVAL`.
+    #[error(
+        "incompatible directory-only access-rights for a non-directory file descriptor: \
+         {incompatible:?}"
+    )]
+    #[non_exhaustive]
     DirectoryAccess {
+        /// Access-rights of the rule.
         access: BitFlags<AccessFs>,
+        /// Directory-only access-rights of the rule.
         incompatible: BitFlags<AccessFs>,
     },
 }
@@ -181,18 +334,466 @@ pub enum RestrictSelfError {
     #[non_exhaustive]
     SetNoNewPrivsCall { source: io::Error },
     /// The `landlock_restrict_self() `system call failed.
-    #[error("failed to restrict the calling thread: {source}")]
+    #[error("failed to restrict the calling thread: {}", RestrictSelfContext(.source))]
     #[non_exhaustive]
     RestrictSelfCall { source: io::Error },
+    /// The canary file is still readable after the enforcement,
+    /// see [`set_canary()`](crate::RulesetCreatedAttr::set_canary).
+    #[error("the sandbox is not enforced: \"{path}\" is readable")]
+    #[non_exhaustive]
+    CanaryAccessible { path: PathBuf },
+    /// The `open()` system call on the canary file failed with another error than `EACCES`,
+    /// see [`set_canary()`](crate::RulesetCreatedAttr::set_canary).
+    #[error("failed to check the canary \"{path}\": {source}")]
+    #[non_exhaustive]
+    CanaryCheckCall { source: io::Error, path: PathBuf },
+    /// The calling thread already has the maximum number of nested Landlock domains,
+    /// see [`domain_depth()`](crate::domain_depth).
+    #[error(
+        "the calling thread already has {depth} nested Landlock domains (maximum: {})",
+        crate::MAX_DOMAIN_DEPTH
+    )]
+    #[non_exhaustive]
+    DomainDepthExceeded { depth: usize },
+    /// Other threads of the process are running and would not be restricted,
+    /// see [`set_thread_check()`](crate::RulesetCreatedAttr::set_thread_check).
+    #[error("{threads} other thread(s) of the process would not be restricted")]
+    #[non_exhaustive]
+    OtherThreads { threads: usize },
+    /// Listing the `/proc/self/task` directory failed,
+    /// see [`set_thread_check()`](crate::RulesetCreatedAttr::set_thread_check).
+    #[error("failed to count the threads of the process: {source}")]
+    #[non_exhaustive]
+    ThreadCountCall { source: io::Error },
+}
+
+impl RestrictSelfError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            RestrictSelfError::SetNoNewPrivsCall { source }
+            | RestrictSelfError::RestrictSelfCall { source } => ErrorKind::from_io(source),
+            RestrictSelfError::DomainDepthExceeded { .. }
+            | RestrictSelfError::OtherThreads { .. } => ErrorKind::InvalidInput,
+            RestrictSelfError::ThreadCountCall { .. } => ErrorKind::Environment,
+            RestrictSelfError::CanaryAccessible { .. }
+            | RestrictSelfError::CanaryCheckCall { .. } => ErrorKind::Environment,
+        }
+    }
 }
 
 #[derive(Debug, Error)]
 #[non_exhaustive]
 pub enum PathFdError {
     /// The `open()` system call failed.
-    #[error("failed to open \"{path}\": {source}")]
+    #[error("failed to open \"{}\": {}", .path.display(), OsErrorContext(.source))]
     #[non_exhaustive]
     OpenCall { source: io::Error, path: PathBuf },
+    /// The path is a symbolic link, see [`PathFd::new_nofollow()`](crate::PathFd::new_nofollow).
+    #[error("\"{path}\" is a symbolic link")]
+    #[non_exhaustive]
+    Symlink { path: PathBuf },
+}
+
+/// Identifies errors when adding path rules with
+/// [`add_path_rules_bulk()`](crate::RulesetCreatedAttr::add_path_rules_bulk).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BulkRulesError {
+    #[error(transparent)]
+    Ruleset(#[from] RulesetError),
+    #[error(transparent)]
+    PathFd(#[from] PathFdError),
+    /// The `getrlimit(RLIMIT_NOFILE)` system call failed.
+    #[error("failed to get the file descriptor limit: {source}")]
+    #[non_exhaustive]
+    GetRlimitCall { source: io::Error },
+    /// Not enough file descriptors can be opened to process a chunk of paths.
+    #[error(
+        "not enough file descriptors: {needed} needed but {available} available \
+        (RLIMIT_NOFILE: {limit})"
+    )]
+    #[non_exhaustive]
+    FileLimit {
+        needed: usize,
+        available: usize,
+        limit: u64,
+    },
+}
+
+/// Identifies errors when loading or enforcing a [`Policy`](crate::Policy).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PolicyError {
+    #[error(transparent)]
+    Ruleset(#[from] RulesetError),
+    #[error(transparent)]
+    PathFd(#[from] PathFdError),
+    /// A required environment variable is not set.
+    #[error("missing environment variable {name}")]
+    #[non_exhaustive]
+    MissingVar { name: String },
+    /// An environment variable contains an element which is not a valid TCP port.
+    #[error("invalid port in {name}: \"{port}\"")]
+    #[non_exhaustive]
+    InvalidPort { name: String, port: String },
+    /// Some handled access rights are not granted by any rule, see
+    /// [`UngrantedAccessLint`](crate::UngrantedAccessLint).
+    #[error("handled access-rights not granted by any rule: {fs:?}, {net:?}")]
+    #[non_exhaustive]
+    UngrantedAccess {
+        fs: BitFlags<AccessFs>,
+        net: BitFlags<AccessNet>,
+    },
+}
+
+impl PolicyError {
+    /// Gets the category of this error, see [`RulesetError::kind()`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            PolicyError::Ruleset(e) => e.kind(),
+            PolicyError::PathFd(_) => ErrorKind::Environment,
+            PolicyError::MissingVar { .. }
+            | PolicyError::InvalidPort { .. }
+            | PolicyError::UngrantedAccess { .. } => ErrorKind::InvalidInput,
+        }
+    }
+}
+
+/// Identifies errors when creating a sandboxed child process with
+/// [`fork_and_restrict()`](crate::fork_and_restrict) or [`fork_and_exec()`](crate::fork_and_exec).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ForkError {
+    /// The child process failed to enforce the ruleset.
+    #[error(transparent)]
+    RestrictSelf(#[from] RestrictSelfError),
+    /// The `fork()` system call failed.
+    #[error("failed to fork: {source}")]
+    #[non_exhaustive]
+    ForkCall { source: io::Error },
+    /// The `pipe2()` system call failed or the child process didn't confirm the enforcement.
+    #[error("failed to communicate with the child process: {source}")]
+    #[non_exhaustive]
+    PipeCall { source: io::Error },
+    /// The program cannot be executed.
+    #[error("failed to execute the program: {source}")]
+    #[non_exhaustive]
+    ExecCall { source: io::Error },
+}
+
+/// Identifies errors when setting `no_new_privs`
+/// with [`set_no_new_privs()`](crate::set_no_new_privs).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum NoNewPrivsError {
+    /// The `prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0)` system call failed.
+    #[error("failed to set no_new_privs: {source}")]
+    #[non_exhaustive]
+    SetNoNewPrivsCall { source: io::Error },
+}
+
+/// Identifies errors when decoding a [`Policy`](crate::Policy) serialized with
+/// [`Policy::to_wire()`](crate::Policy::to_wire), possibly by another version of this crate.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum WireError {
+    /// The data doesn't start with the policy magic bytes.
+    #[error("not a serialized Landlock policy")]
+    BadMagic,
+    /// The policy was serialized with an incompatible version of the wire format.
+    #[error("unsupported policy format version {version} (supported: {supported})")]
+    #[non_exhaustive]
+    UnsupportedVersion { version: u16, supported: u16 },
+    /// The policy contains a critical record unknown to this version of the crate.
+    #[error("unsupported policy record {record:#06x}")]
+    #[non_exhaustive]
+    UnsupportedRecord { record: u16 },
+    /// The policy contains access rights unknown to this version of the crate.
+    #[error("unknown access-rights: {bits:#x}")]
+    #[non_exhaustive]
+    UnknownAccess { bits: u64 },
+    /// A record has an invalid content.
+    #[error("invalid policy record {record:#06x}")]
+    #[non_exhaustive]
+    InvalidRecord { record: u16 },
+    /// The data is shorter than announced.
+    #[error("truncated policy")]
+    Truncated,
+    /// The data is longer than announced.
+    #[error("trailing data after the policy")]
+    TrailingData,
+    /// The environment variable value is not a valid hexadecimal string.
+    #[error("invalid policy encoding")]
+    BadEncoding,
+    /// The inherited file descriptor is not a Landlock ruleset.
+    #[error("inherited file descriptor {fd} is not a Landlock ruleset")]
+    #[non_exhaustive]
+    InvalidFd { fd: i32 },
+    /// Reading the serialized policy failed.
+    #[error("failed to read the policy: {source}")]
+    #[non_exhaustive]
+    ReadCall { source: io::Error },
+}
+
+/// Identifies errors when the running kernel doesn't support a required Landlock ABI,
+/// see [`ABI::require()`](crate::ABI::require) and
+/// [`Ruleset::require_abi()`](crate::Ruleset::require_abi).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum RequireAbiError {
+    /// Landlock is not supported by the running kernel.
+    #[error("Landlock ABI {required} is required but Landlock is not supported")]
+    #[non_exhaustive]
+    Unsupported { required: ABI },
+    /// The running kernel only supports an older ABI.
+    #[error("Landlock ABI {required} is required but only {actual} is supported")]
+    #[non_exhaustive]
+    Incompatible { required: ABI, actual: ABI },
+}
+
+/// Identifies errors when parsing access rights from their names or raw bits,
+/// see [`Access::from_names()`](crate::Access::from_names) and
+/// [`Access::try_from_bits()`](crate::Access::try_from_bits).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ParseAccessError {
+    /// The name doesn't match any access right of this type known by this crate.
+    #[error("unknown access right \"{name}\"")]
+    #[non_exhaustive]
+    UnknownName { name: String },
+    /// The raw value contains bits that don't match any access right of this type
+    /// known by this crate.
+    #[error("unknown access-right bits {unknown:#x} in {bits:#x}")]
+    #[non_exhaustive]
+    UnknownBits { bits: u64, unknown: u64 },
+}
+
+/// Identifies errors when parsing an [`ABI`](crate::ABI) or a kernel version,
+/// see [`ABI::from_kernel_version()`](crate::ABI::from_kernel_version).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ParseAbiError {
+    /// The name doesn't match any ABI known by this crate.
+    #[error("unknown Landlock ABI \"{name}\"")]
+    #[non_exhaustive]
+    UnknownAbi { name: String },
+    /// The kernel version doesn't start with a major and a minor number (e.g. `6.7`).
+    #[error("invalid kernel version \"{version}\"")]
+    #[non_exhaustive]
+    InvalidKernelVersion { version: String },
+}
+
+/// Identifies errors when parsing an audit record,
+/// see [`Denial::parse()`](crate::audit::Denial::parse).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ParseAuditError {
+    /// The record doesn't have a valid type and `audit(<time>:<serial>)` stamp.
+    #[error("invalid audit record \"{record}\"")]
+    #[non_exhaustive]
+    InvalidRecord { record: String },
+    /// A field required to describe a Landlock denial is missing.
+    #[error("missing field \"{name}\" in the audit record")]
+    #[non_exhaustive]
+    MissingField { name: &'static str },
+    /// A field has an unexpected value.
+    #[error("invalid value \"{value}\" for the field \"{name}\" in the audit record")]
+    #[non_exhaustive]
+    InvalidField { name: &'static str, value: String },
+}
+
+/// Identifies errors when parsing a policy description,
+/// see [`Policy::from_canonical()`](crate::Policy::from_canonical).
+///
+/// Lines are numbered from 1.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ParsePolicyError {
+    /// The line is not a `key=value` element of a policy description.
+    #[error("line {line}: invalid policy element")]
+    #[non_exhaustive]
+    InvalidLine { line: usize },
+    /// The `abi` value is not a known ABI.
+    #[error("line {line}: {source}")]
+    #[non_exhaustive]
+    InvalidAbi { line: usize, source: ParseAbiError },
+    /// The access rights contain an unknown name.
+    #[error("line {line}: {source}")]
+    #[non_exhaustive]
+    InvalidAccess {
+        line: usize,
+        source: ParseAccessError,
+    },
+    /// The path is not properly quoted.
+    #[error("line {line}: invalid quoted path")]
+    #[non_exhaustive]
+    InvalidPath { line: usize },
+    /// The port is not a number between 0 and 65535.
+    #[error("line {line}: invalid port \"{port}\"")]
+    #[non_exhaustive]
+    InvalidPort { line: usize, port: String },
+}
+
+/// Identifies errors when running [`run_conformance()`](crate::run_conformance).
+#[cfg(feature = "conformance")]
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ConformanceError {
+    #[error(transparent)]
+    Ruleset(#[from] RulesetError),
+    #[error(transparent)]
+    PathFd(#[from] PathFdError),
+    /// Creating or removing the scratch directory tree failed.
+    #[error("failed to prepare the scratch directory: {source}")]
+    #[non_exhaustive]
+    ScratchCall { source: io::Error },
+    /// The `fork()` or `waitpid()` system call failed.
+    #[error("failed to run a child process: {source}")]
+    #[non_exhaustive]
+    ForkCall { source: io::Error },
+    /// A child process failed to enforce the ruleset or didn't exit normally.
+    #[error("failed to check {access:?} in a child process")]
+    #[non_exhaustive]
+    Child { access: AccessFs },
+}
+
+/// Identifies errors when dropping capabilities and enforcing a ruleset
+/// with [`CapsDrop::restrict_self()`](crate::caps::CapsDrop::restrict_self).
+#[cfg(feature = "caps")]
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CapsError {
+    #[error(transparent)]
+    Ruleset(#[from] RulesetError),
+    /// The `capget()` system call failed.
+    #[error("failed to get the capabilities: {source}")]
+    #[non_exhaustive]
+    CapgetCall { source: io::Error },
+    /// The `capset()` system call failed.
+    #[error("failed to drop the capabilities: {source}")]
+    #[non_exhaustive]
+    CapsetCall { source: io::Error },
+    /// The `prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_CLEAR_ALL, 0, 0, 0)` system call failed.
+    #[error("failed to clear the ambient capabilities: {source}")]
+    #[non_exhaustive]
+    AmbientCall { source: io::Error },
+    /// The `prctl(PR_CAPBSET_DROP, capability, 0, 0, 0)` system call failed.
+    #[error("failed to drop {capability:?} from the bounding set: {source}")]
+    #[non_exhaustive]
+    BoundingCall {
+        capability: Capability,
+        source: io::Error,
+    },
+}
+
+/// Identifies errors when getting the file descriptors passed by the service manager
+/// with [`ListenFds::from_env()`](crate::ListenFds::from_env).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ListenFdsError {
+    /// An environment variable doesn't match the passed file descriptors.
+    #[error("invalid value for {name}: \"{value}\"")]
+    #[non_exhaustive]
+    InvalidVar { name: String, value: String },
+    /// A passed file descriptor cannot be configured or inspected.
+    #[error("failed to inspect the passed file descriptor {fd}: {source}")]
+    #[non_exhaustive]
+    FdCall { fd: RawFd, source: io::Error },
+}
+
+/// Identifies errors when re-executing the current program to restrict it
+/// with [`reexec_and_restrict()`](crate::reexec_and_restrict).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ReexecError {
+    /// The ruleset cannot be created or enforced.
+    #[error(transparent)]
+    Policy(#[from] PolicyError),
+    /// The ruleset inherited from the calling program cannot be decoded.
+    #[error(transparent)]
+    Wire(#[from] WireError),
+    /// The current program cannot be executed again.
+    #[error("failed to re-execute the current program: {source}")]
+    #[non_exhaustive]
+    ExecCall { source: io::Error },
+}
+
+/// Identifies errors when hardening the process and enforcing a ruleset
+/// with [`Hardening::restrict_self()`](crate::hardening::Hardening::restrict_self).
+#[cfg(feature = "hardening")]
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum HardeningError {
+    #[error(transparent)]
+    Ruleset(#[from] RulesetError),
+    /// The system call of a hardening step failed,
+    /// in which case the next steps were not applied.
+    #[error("failed to apply the hardening step {step:?}: {source}")]
+    #[non_exhaustive]
+    StepCall {
+        step: HardeningStep,
+        source: io::Error,
+    },
+}
+
+/// Identifies errors when creating namespaces and enforcing a ruleset
+/// with [`Unshare::restrict_self()`](crate::namespaces::Unshare::restrict_self).
+#[cfg(all(
+    feature = "namespaces",
+    any(target_os = "linux", target_os = "android")
+))]
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum UnshareError {
+    #[error(transparent)]
+    Ruleset(#[from] RulesetError),
+    /// The `unshare()` system call failed
+    /// (e.g. because the process is multithreaded or user namespaces are disabled).
+    #[error("failed to create the {namespaces:?} namespaces: {source}")]
+    #[non_exhaustive]
+    UnshareCall {
+        namespaces: BitFlags<Namespace>,
+        source: io::Error,
+    },
+    /// Writing the user or group mapping of the new user namespace failed.
+    #[error("failed to map the user and group IDs: {source}")]
+    #[non_exhaustive]
+    IdMapCall { source: io::Error },
+    /// The `mount()` system call failed.
+    #[error("failed to mount \"{}\": {source}", target.display())]
+    #[non_exhaustive]
+    MountCall { target: PathBuf, source: io::Error },
+}
+
+/// Identifies errors when enforcing a ruleset with seccomp filters
+/// with [`SeccompRuleset`](crate::seccomp::SeccompRuleset).
+#[cfg(all(feature = "seccomp", any(target_os = "linux", target_os = "android")))]
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SeccompError {
+    #[error(transparent)]
+    Ruleset(#[from] RulesetError),
+    /// The seccomp filter cannot be built (e.g. for an unsupported architecture).
+    #[error("failed to build the seccomp filter: {source}")]
+    #[non_exhaustive]
+    BuildFilter { source: seccompiler::BackendError },
+    /// The `seccomp()` or `prctl()` system call failed.
+    #[error("failed to install the seccomp filter: {source}")]
+    #[non_exhaustive]
+    ApplyFilter { source: seccompiler::Error },
+}
+
+/// Identifies errors of the [`uniffi_api`](crate::uniffi_api) functions,
+/// exported to other languages with their message only.
+#[cfg(feature = "uniffi")]
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+#[non_exhaustive]
+pub enum BindingError {
+    #[error(transparent)]
+    ParseAccess(#[from] ParseAccessError),
+    #[error(transparent)]
+    Policy(#[from] PolicyError),
 }
 
 #[cfg(test)]
@@ -205,3 +806,562 @@ pub(crate) enum TestRulesetError {
     #[error(transparent)]
     File(#[from] std::io::Error),
 }
+
+// Formats the optional path of a rule, for error messages.
+struct PathContext<'a>(&'a Option<PathBuf>);
+
+impl fmt::Display for PathContext<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(path) => write!(f, " for \"{}\"", path.display()),
+            None => Ok(()),
+        }
+    }
+}
+
+// Explains why landlock_restrict_self() is not permitted.
+struct RestrictSelfContext<'a>(&'a io::Error);
+
+impl fmt::Display for RestrictSelfContext<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.raw_os_error() {
+            Some(libc::EPERM) => write!(
+                f,
+                "{} (the thread has neither no_new_privs nor CAP_SYS_ADMIN)",
+                self.0
+            ),
+            _ => write!(f, "{}", self.0),
+        }
+    }
+}
+
+// Prefixes the error with its errno name (e.g. "ENOENT"), if any.
+struct OsErrorContext<'a>(&'a io::Error);
+
+impl fmt::Display for OsErrorContext<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.raw_os_error().and_then(Errno::from_raw) {
+            Some(name) => write!(f, "{name}: {}", self.0),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+/// Error number of a failed system call, as documented for the Landlock system calls
+/// and for the related file operations.
+///
+/// See [`RulesetError::errno()`] and the same method on the other error types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[repr(i32)]
+#[non_exhaustive]
+pub enum Errno {
+    /// Operation not permitted (e.g. enforcing a ruleset without `no_new_privs`).
+    EPERM = libc::EPERM,
+    /// No such file or directory.
+    ENOENT = libc::ENOENT,
+    /// Input/output error.
+    EIO = libc::EIO,
+    /// Argument list too long (e.g. too many stacked Landlock domains).
+    E2BIG = libc::E2BIG,
+    /// Bad file descriptor.
+    EBADF = libc::EBADF,
+    /// Cannot allocate memory.
+    ENOMEM = libc::ENOMEM,
+    /// Permission denied.
+    EACCES = libc::EACCES,
+    /// Bad address.
+    EFAULT = libc::EFAULT,
+    /// Invalid cross-device link (e.g. a path resolved beyond its starting directory).
+    EXDEV = libc::EXDEV,
+    /// No such device.
+    ENODEV = libc::ENODEV,
+    /// Not a directory.
+    ENOTDIR = libc::ENOTDIR,
+    /// Invalid argument (e.g. unknown access rights or flags).
+    EINVAL = libc::EINVAL,
+    /// Too many open files in system.
+    ENFILE = libc::ENFILE,
+    /// Too many open files.
+    EMFILE = libc::EMFILE,
+    /// File name too long.
+    ENAMETOOLONG = libc::ENAMETOOLONG,
+    /// Function not implemented (e.g. a kernel built without Landlock).
+    ENOSYS = libc::ENOSYS,
+    /// Too many levels of symbolic links (e.g. a forbidden symbolic link).
+    ELOOP = libc::ELOOP,
+    /// No message of desired type (e.g. empty access rights).
+    ENOMSG = libc::ENOMSG,
+    /// File descriptor in bad state (e.g. not a ruleset file descriptor).
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    EBADFD = libc::EBADFD,
+    /// Operation not supported (e.g. Landlock disabled at boot time).
+    EOPNOTSUPP = libc::EOPNOTSUPP,
+    /// Stale file handle.
+    ESTALE = libc::ESTALE,
+}
+
+impl Errno {
+    /// Converts a raw error number, or returns `None` if it is not a known one.
+    pub fn from_raw(errno: i32) -> Option<Self> {
+        Some(match errno {
+            libc::EPERM => Errno::EPERM,
+            libc::ENOENT => Errno::ENOENT,
+            libc::EIO => Errno::EIO,
+            libc::E2BIG => Errno::E2BIG,
+            libc::EBADF => Errno::EBADF,
+            libc::ENOMEM => Errno::ENOMEM,
+            libc::EACCES => Errno::EACCES,
+            libc::EFAULT => Errno::EFAULT,
+            libc::EXDEV => Errno::EXDEV,
+            libc::ENODEV => Errno::ENODEV,
+            libc::ENOTDIR => Errno::ENOTDIR,
+            libc::EINVAL => Errno::EINVAL,
+            libc::ENFILE => Errno::ENFILE,
+            libc::EMFILE => Errno::EMFILE,
+            libc::ENAMETOOLONG => Errno::ENAMETOOLONG,
+            libc::ENOSYS => Errno::ENOSYS,
+            libc::ELOOP => Errno::ELOOP,
+            libc::ENOMSG => Errno::ENOMSG,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            libc::EBADFD => Errno::EBADFD,
+            libc::EOPNOTSUPP => Errno::EOPNOTSUPP,
+            libc::ESTALE => Errno::ESTALE,
+            _ => return None,
+        })
+    }
+
+    /// Gets the raw error number.
+    pub fn raw(self) -> i32 {
+        self as i32
+    }
+}
+
+/// Prints the name of the error number (e.g. `ENOENT`).
+impl fmt::Display for Errno {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+// Implements the errno accessors, relying on an io_error() method returning the wrapped
+// io::Error, if any.
+macro_rules! impl_errno {
+    ($name:ident $(<$t:ident>)?) => {
+        impl$(<$t: Access>)? $name$(<$t>)? {
+            /// Gets the error number of the failed system call, if any.
+            pub fn raw_os_error(&self) -> Option<i32> {
+                self.io_error().and_then(io::Error::raw_os_error)
+            }
+
+            /// Gets the error number of the failed system call, if it is a known one.
+            pub fn errno(&self) -> Option<Errno> {
+                self.raw_os_error().and_then(Errno::from_raw)
+            }
+        }
+    };
+}
+
+impl_errno!(RulesetError);
+impl_errno!(HandleAccessError<T>);
+impl_errno!(HandleAccessesError);
+impl_errno!(CreateRulesetError);
+impl_errno!(AddRuleError<T>);
+impl_errno!(AddRulesError);
+impl_errno!(CompatError<T>);
+impl_errno!(PathBeneathError);
+impl_errno!(RestrictSelfError);
+impl_errno!(PathFdError);
+impl_errno!(BulkRulesError);
+impl_errno!(PolicyError);
+impl_errno!(ForkError);
+impl_errno!(NoNewPrivsError);
+impl_errno!(WireError);
+impl_errno!(ListenFdsError);
+impl_errno!(ReexecError);
+#[cfg(feature = "conformance")]
+impl_errno!(ConformanceError);
+#[cfg(feature = "caps")]
+impl_errno!(CapsError);
+#[cfg(feature = "hardening")]
+impl_errno!(HardeningError);
+#[cfg(all(feature = "seccomp", any(target_os = "linux", target_os = "android")))]
+impl_errno!(SeccompError);
+#[cfg(all(
+    feature = "namespaces",
+    any(target_os = "linux", target_os = "android")
+))]
+impl_errno!(UnshareError);
+
+impl RulesetError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            RulesetError::HandleAccesses(e) => e.io_error(),
+            RulesetError::CreateRuleset(e) => e.io_error(),
+            RulesetError::AddRules(e) => e.io_error(),
+            RulesetError::RestrictSelf(e) => e.io_error(),
+            RulesetError::RequireAbi(_) => None,
+        }
+    }
+}
+
+impl<T> HandleAccessError<T>
+where
+    T: Access,
+{
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            HandleAccessError::Compat(e) => e.io_error(),
+        }
+    }
+}
+
+impl HandleAccessesError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            HandleAccessesError::Fs(e) => e.io_error(),
+            HandleAccessesError::Net(e) => e.io_error(),
+        }
+    }
+}
+
+impl CreateRulesetError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            CreateRulesetError::CreateRulesetCall { source } => Some(source),
+            CreateRulesetError::MissingHandledAccess => None,
+        }
+    }
+}
+
+impl<T> AddRuleError<T>
+where
+    T: Access,
+{
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            AddRuleError::AddRuleCall { source, .. } => Some(source),
+            AddRuleError::UnhandledAccess { .. } => None,
+            AddRuleError::Compat(e) => e.io_error(),
+        }
+    }
+}
+
+impl AddRulesError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            AddRulesError::Fs(e) => e.io_error(),
+            AddRulesError::Net(e) => e.io_error(),
+            AddRulesError::RawCall { source, .. } => Some(source),
+        }
+    }
+}
+
+impl<T> CompatError<T>
+where
+    T: Access,
+{
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            CompatError::PathBeneath(e) => e.io_error(),
+            CompatError::Access(_) => None,
+        }
+    }
+}
+
+impl PathBeneathError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            PathBeneathError::StatCall { source } => Some(source),
+            PathBeneathError::DirectoryAccess { .. } => None,
+        }
+    }
+}
+
+impl RestrictSelfError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            RestrictSelfError::SetNoNewPrivsCall { source }
+            | RestrictSelfError::RestrictSelfCall { source }
+            | RestrictSelfError::CanaryCheckCall { source, .. }
+            | RestrictSelfError::ThreadCountCall { source } => Some(source),
+            RestrictSelfError::CanaryAccessible { .. }
+            | RestrictSelfError::DomainDepthExceeded { .. }
+            | RestrictSelfError::OtherThreads { .. } => None,
+        }
+    }
+}
+
+impl PathFdError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            PathFdError::OpenCall { source, .. } => Some(source),
+            PathFdError::Symlink { .. } => None,
+        }
+    }
+}
+
+impl BulkRulesError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            BulkRulesError::Ruleset(e) => e.io_error(),
+            BulkRulesError::PathFd(e) => e.io_error(),
+            BulkRulesError::GetRlimitCall { source } => Some(source),
+            BulkRulesError::FileLimit { .. } => None,
+        }
+    }
+}
+
+impl PolicyError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            PolicyError::Ruleset(e) => e.io_error(),
+            PolicyError::PathFd(e) => e.io_error(),
+            PolicyError::MissingVar { .. }
+            | PolicyError::InvalidPort { .. }
+            | PolicyError::UngrantedAccess { .. } => None,
+        }
+    }
+}
+
+impl ForkError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            ForkError::RestrictSelf(e) => e.io_error(),
+            ForkError::ForkCall { source }
+            | ForkError::PipeCall { source }
+            | ForkError::ExecCall { source } => Some(source),
+        }
+    }
+}
+
+impl NoNewPrivsError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            NoNewPrivsError::SetNoNewPrivsCall { source } => Some(source),
+        }
+    }
+}
+
+#[cfg(feature = "caps")]
+impl CapsError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            CapsError::Ruleset(e) => e.io_error(),
+            CapsError::CapgetCall { source }
+            | CapsError::CapsetCall { source }
+            | CapsError::AmbientCall { source }
+            | CapsError::BoundingCall { source, .. } => Some(source),
+        }
+    }
+}
+
+#[cfg(feature = "hardening")]
+impl HardeningError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            HardeningError::Ruleset(e) => e.io_error(),
+            HardeningError::StepCall { source, .. } => Some(source),
+        }
+    }
+}
+
+#[cfg(all(feature = "seccomp", any(target_os = "linux", target_os = "android")))]
+impl SeccompError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            SeccompError::Ruleset(e) => e.io_error(),
+            SeccompError::ApplyFilter {
+                source: seccompiler::Error::Prctl(e) | seccompiler::Error::Seccomp(e),
+            } => Some(e),
+            SeccompError::BuildFilter { .. } | SeccompError::ApplyFilter { .. } => None,
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "namespaces",
+    any(target_os = "linux", target_os = "android")
+))]
+impl UnshareError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            UnshareError::Ruleset(e) => e.io_error(),
+            UnshareError::UnshareCall { source, .. }
+            | UnshareError::IdMapCall { source }
+            | UnshareError::MountCall { source, .. } => Some(source),
+        }
+    }
+}
+
+impl ListenFdsError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            ListenFdsError::InvalidVar { .. } => None,
+            ListenFdsError::FdCall { source, .. } => Some(source),
+        }
+    }
+}
+
+impl ReexecError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            ReexecError::Policy(e) => e.io_error(),
+            ReexecError::Wire(e) => e.io_error(),
+            ReexecError::ExecCall { source } => Some(source),
+        }
+    }
+}
+
+impl WireError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            WireError::ReadCall { source } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "conformance")]
+impl ConformanceError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            ConformanceError::Ruleset(e) => e.io_error(),
+            ConformanceError::PathFd(e) => e.io_error(),
+            ConformanceError::ScratchCall { source } | ConformanceError::ForkCall { source } => {
+                Some(source)
+            }
+            ConformanceError::Child { .. } => None,
+        }
+    }
+}
+
+#[test]
+fn error_errno() {
+    use crate::*;
+
+    for errno in [libc::EPERM, libc::EBADF, libc::E2BIG] {
+        assert_eq!(Errno::from_raw(errno).unwrap().raw(), errno);
+    }
+    assert_eq!(Errno::from_raw(4095), None);
+    assert_eq!(Errno::ENOMSG.to_string(), "ENOMSG");
+
+    let error = RulesetError::from(AddRulesError::from(AddRuleError::<AccessFs>::AddRuleCall {
+        source: io::Error::from_raw_os_error(libc::EBADF),
+        path: None,
+    }));
+    assert_eq!(error.raw_os_error(), Some(libc::EBADF));
+    assert_eq!(error.errno(), Some(Errno::EBADF));
+
+    let error = PolicyError::from(RulesetError::from(RestrictSelfError::RestrictSelfCall {
+        source: io::Error::from_raw_os_error(4095),
+    }));
+    assert_eq!(error.raw_os_error(), Some(4095));
+    assert_eq!(error.errno(), None);
+
+    let error = RulesetError::from(CreateRulesetError::MissingHandledAccess);
+    assert_eq!(error.raw_os_error(), None);
+    assert_eq!(error.errno(), None);
+    assert_eq!(
+        PathFdError::Symlink { path: "/".into() }.raw_os_error(),
+        None
+    );
+}
+
+#[test]
+fn error_path_context() {
+    let error = PathFdError::OpenCall {
+        source: io::Error::from_raw_os_error(libc::ENOENT),
+        path: "/var/lib/foo".into(),
+    };
+    assert!(error
+        .to_string()
+        .starts_with("failed to open \"/var/lib/foo\": ENOENT: "));
+
+    let error = AddRuleError::<AccessFs>::AddRuleCall {
+        source: io::Error::from_raw_os_error(libc::EBADF),
+        path: Some("/usr".into()),
+    };
+    assert!(error
+        .to_string()
+        .starts_with("failed to add a rule for \"/usr\": EBADF: "));
+
+    let error = AddRuleError::<AccessNet>::AddRuleCall {
+        source: io::Error::from_raw_os_error(4095),
+        path: None,
+    };
+    assert!(error.to_string().starts_with("failed to add a rule: "));
+}
+
+#[test]
+fn error_kind() {
+    use crate::*;
+
+    let io = |errno| io::Error::from_raw_os_error(errno);
+    let kind = |error: RulesetError| error.kind();
+    assert_eq!(
+        kind(CreateRulesetError::MissingHandledAccess.into()),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        kind(
+            CreateRulesetError::CreateRulesetCall {
+                source: io(libc::ENOSYS)
+            }
+            .into()
+        ),
+        ErrorKind::Unsupported
+    );
+    assert_eq!(
+        kind(
+            AddRulesError::from(AddRuleError::<AccessFs>::AddRuleCall {
+                source: io(libc::EINVAL),
+                path: None,
+            })
+            .into()
+        ),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        kind(
+            AddRulesError::from(AddRuleError::<AccessNet>::Compat(
+                AccessError::Incompatible {
+                    access: AccessNet::BindTcp.into()
+                }
+                .into()
+            ))
+            .into()
+        ),
+        ErrorKind::Unsupported
+    );
+    assert_eq!(
+        kind(
+            RestrictSelfError::RestrictSelfCall {
+                source: io(libc::EPERM)
+            }
+            .into()
+        ),
+        ErrorKind::Environment
+    );
+    assert_eq!(
+        kind(
+            RestrictSelfError::SetNoNewPrivsCall {
+                source: io(libc::EMFILE)
+            }
+            .into()
+        ),
+        ErrorKind::Environment
+    );
+    assert_eq!(
+        PolicyError::from(PathFdError::OpenCall {
+            source: io(libc::ENOENT),
+            path: "/foo".into(),
+        })
+        .kind(),
+        ErrorKind::Environment
+    );
+    assert_eq!(
+        PolicyError::MissingVar { name: "FOO".into() }.kind(),
+        ErrorKind::InvalidInput
+    );
+}
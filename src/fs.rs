@@ -1,17 +1,25 @@
+use crate::access::parse_name;
 use crate::compat::private::OptionCompatLevelMut;
 use crate::{
-    uapi, Access, AddRuleError, AddRulesError, CompatError, CompatLevel, CompatResult, CompatState,
-    Compatible, HandleAccessError, HandleAccessesError, PathBeneathError, PathFdError,
-    PrivateAccess, PrivateRule, Rule, Ruleset, RulesetCreated, RulesetError, TailoredCompatLevel,
-    TryCompat, ABI,
+    uapi, Access, AccessReport, AddRuleError, AddRulesError, CompatError, CompatLevel,
+    CompatResult, CompatState, Compatible, Downgrade, Downgrades, Fingerprint, HandleAccessError,
+    HandleAccessesError, HostFs, ParseAccessError, PathBeneathError, PathFdError, PolicyFs,
+    PrivateAccess, PrivateRule, Rule, RuleReport, Ruleset, RulesetCreated, RulesetError,
+    TailoredCompatLevel, TryCompat, ABI,
 };
 use enumflags2::{bitflags, make_bitflags, BitFlags};
-use std::fs::OpenOptions;
+use std::fmt;
+use std::fs::{File, OpenOptions};
 use std::io::Error;
 use std::mem::zeroed;
-use std::os::unix::fs::OpenOptionsExt;
+use std::os::raw::c_int;
 use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, OwnedFd};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use sys::{open_path, openat2_path, openat_path};
+
+#[cfg(feature = "broker")]
+pub(crate) use sys::openat2;
 
 #[cfg(test)]
 use crate::{RulesetAttr, RulesetCreatedAttr};
@@ -87,27 +95,114 @@ pub enum AccessFs {
 }
 
 impl Access for AccessFs {
+    /// Union of [`from_read()`](AccessFs::from_read) and [`from_write()`](AccessFs::from_write).
+    fn from_all(abi: ABI) -> BitFlags<Self> {
+        Self::from_read(abi) | Self::from_write(abi)
+    }
+}
+
+/// Access rights of each ABI, usable in `const` and `static` items.
+///
+/// These are the values returned by [`from_read()`](AccessFs::from_read),
+/// [`from_write()`](AccessFs::from_write) and [`from_all()`](Access::from_all),
+/// which enables to define a policy at compile time:
+///
+/// ```
+/// use landlock::{AccessFs, BitFlags};
+///
+/// static HANDLED: BitFlags<AccessFs> = AccessFs::ALL_V2;
+/// const READ_EXEC: BitFlags<AccessFs> = AccessFs::READ_V2;
+/// const CONFIG: BitFlags<AccessFs> = AccessFs::READ_V2.intersection_c(AccessFs::FILE);
+/// ```
+impl AccessFs {
+    /// Read access rights of [`ABI::V1`].
+    pub const READ_V1: BitFlags<Self> = make_bitflags!(AccessFs::{
+        Execute
+        | ReadFile
+        | ReadDir
+    });
+    /// Read access rights of [`ABI::V2`].
+    pub const READ_V2: BitFlags<Self> = Self::READ_V1;
+    /// Read access rights of [`ABI::V3`].
+    pub const READ_V3: BitFlags<Self> = Self::READ_V2;
+    /// Read access rights of [`ABI::V4`].
+    pub const READ_V4: BitFlags<Self> = Self::READ_V3;
+
+    /// Write access rights of [`ABI::V1`].
+    pub const WRITE_V1: BitFlags<Self> = make_bitflags!(AccessFs::{
+        WriteFile
+        | RemoveDir
+        | RemoveFile
+        | MakeChar
+        | MakeDir
+        | MakeReg
+        | MakeSock
+        | MakeFifo
+        | MakeBlock
+        | MakeSym
+    });
+    /// Write access rights of [`ABI::V2`].
+    pub const WRITE_V2: BitFlags<Self> = Self::WRITE_V1.union_c(make_bitflags!(AccessFs::{Refer}));
+    /// Write access rights of [`ABI::V3`].
+    pub const WRITE_V3: BitFlags<Self> =
+        Self::WRITE_V2.union_c(make_bitflags!(AccessFs::{Truncate}));
+    /// Write access rights of [`ABI::V4`].
+    // The fourth ABI only brings network access rights.
+    pub const WRITE_V4: BitFlags<Self> = Self::WRITE_V3;
+
+    /// All access rights of [`ABI::V1`].
+    pub const ALL_V1: BitFlags<Self> = Self::READ_V1.union_c(Self::WRITE_V1);
+    /// All access rights of [`ABI::V2`].
+    pub const ALL_V2: BitFlags<Self> = Self::READ_V2.union_c(Self::WRITE_V2);
+    /// All access rights of [`ABI::V3`].
+    pub const ALL_V3: BitFlags<Self> = Self::READ_V3.union_c(Self::WRITE_V3);
+    /// All access rights of [`ABI::V4`].
+    pub const ALL_V4: BitFlags<Self> = Self::READ_V4.union_c(Self::WRITE_V4);
+
+    /// Access rights legitimate for non-directory files, whatever the ABI.
+    /// See [`from_file()`](AccessFs::from_file) to only get the ones of a specific ABI.
+    pub const FILE: BitFlags<Self> = ACCESS_FILE;
+
     // Roughly read (i.e. not all FS actions are handled).
-    fn from_read(abi: ABI) -> BitFlags<Self> {
+    /// Gets the access rights identified as read-only according to a specific ABI.
+    /// Exclusive with [`from_write()`](AccessFs::from_write).
+    pub const fn from_read(abi: ABI) -> BitFlags<Self> {
         match abi {
             ABI::Unsupported => BitFlags::EMPTY,
-            ABI::V1 | ABI::V2 | ABI::V3 => make_bitflags!(AccessFs::{
-                Execute
-                | ReadFile
-                | ReadDir
-            }),
+            ABI::V1 => Self::READ_V1,
+            ABI::V2 => Self::READ_V2,
+            ABI::V3 => Self::READ_V3,
+            ABI::V4 => Self::READ_V4,
         }
     }
 
     // Roughly write (i.e. not all FS actions are handled).
-    fn from_write(abi: ABI) -> BitFlags<Self> {
+    /// Gets the access rights identified as write-only according to a specific ABI.
+    /// Exclusive with [`from_read()`](AccessFs::from_read).
+    pub const fn from_write(abi: ABI) -> BitFlags<Self> {
         match abi {
             ABI::Unsupported => BitFlags::EMPTY,
-            ABI::V1 => make_bitflags!(AccessFs::{
-                WriteFile
-                | RemoveDir
-                | RemoveFile
-                | MakeChar
+            ABI::V1 => Self::WRITE_V1,
+            ABI::V2 => Self::WRITE_V2,
+            ABI::V3 => Self::WRITE_V3,
+            ABI::V4 => Self::WRITE_V4,
+        }
+    }
+
+    /// Gets the access rights legitimate for non-directory files.
+    pub const fn from_file(abi: ABI) -> BitFlags<Self> {
+        Self::from_read(abi)
+            .union_c(Self::from_write(abi))
+            .intersection_c(ACCESS_FILE)
+    }
+
+    /// Gets the access rights to create any kind of file (i.e. the `Make*` ones)
+    /// according to a specific ABI.
+    pub const fn make_any(abi: ABI) -> BitFlags<Self> {
+        match abi {
+            ABI::Unsupported => BitFlags::EMPTY,
+            ABI::V1 | ABI::V2 | ABI::V3 | ABI::V4 => make_bitflags!(AccessFs::{
+                MakeChar
                 | MakeDir
                 | MakeReg
                 | MakeSock
@@ -115,12 +210,301 @@ impl Access for AccessFs {
                 | MakeBlock
                 | MakeSym
             }),
-            ABI::V2 => Self::from_write(ABI::V1) | AccessFs::Refer,
-            ABI::V3 => Self::from_write(ABI::V2) | AccessFs::Truncate,
+        }
+    }
+
+    /// Gets the access rights to remove any kind of file (i.e. the `Remove*` ones)
+    /// according to a specific ABI.
+    pub const fn remove_any(abi: ABI) -> BitFlags<Self> {
+        match abi {
+            ABI::Unsupported => BitFlags::EMPTY,
+            ABI::V1 | ABI::V2 | ABI::V3 | ABI::V4 => make_bitflags!(AccessFs::{
+                RemoveDir
+                | RemoveFile
+            }),
+        }
+    }
+
+    /// Gets the access rights which cannot change the file hierarchy
+    /// according to a specific ABI.
+    ///
+    /// These rights enable to read, execute, write and truncate existing files,
+    /// and to list directories,
+    /// but not to create, remove, rename or link files
+    /// (i.e. [`make_any()`](AccessFs::make_any), [`remove_any()`](AccessFs::remove_any)
+    /// and [`AccessFs::Refer`] are excluded).
+    pub const fn metadata_safe(abi: ABI) -> BitFlags<Self> {
+        match abi {
+            ABI::Unsupported => BitFlags::EMPTY,
+            ABI::V1 | ABI::V2 => Self::READ_V1.union_c(make_bitflags!(AccessFs::{WriteFile})),
+            // The fourth ABI only brings network access rights.
+            ABI::V3 | ABI::V4 => {
+                Self::metadata_safe(ABI::V2).union_c(make_bitflags!(AccessFs::{Truncate}))
+            }
         }
     }
 }
 
+impl AccessFs {
+    /// Gets the access rights required to open a file with the `open(2)` `flags`
+    /// (e.g. `libc::O_WRONLY | libc::O_CREAT`),
+    /// whatever the ABI.
+    ///
+    /// This enables policy generators and test suites to derive the minimal rules
+    /// from the code paths opening files:
+    /// * the access mode requires [`AccessFs::ReadFile`] and/or [`AccessFs::WriteFile`],
+    ///   or [`AccessFs::ReadDir`] instead of [`AccessFs::ReadFile`] with `O_DIRECTORY`;
+    /// * `O_TRUNC` requires [`AccessFs::Truncate`];
+    /// * `O_CREAT` requires [`AccessFs::MakeReg`] on the parent directory,
+    ///   if the file doesn't exist yet;
+    /// * `O_PATH` doesn't require any access right.
+    ///
+    /// Opening a directory without `O_DIRECTORY` requires [`AccessFs::ReadDir`]
+    /// instead of [`AccessFs::ReadFile`].
+    /// The returned access rights should be intersected with the ones supported by the ABI
+    /// of the policy, e.g. with [`from_all()`](Access::from_all).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{make_bitflags, AccessFs};
+    ///
+    /// assert_eq!(
+    ///     AccessFs::required_for_open_flags(libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC),
+    ///     make_bitflags!(AccessFs::{WriteFile | Truncate | MakeReg})
+    /// );
+    /// ```
+    pub fn required_for_open_flags(flags: c_int) -> BitFlags<Self> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        if flags & libc::O_PATH != 0 {
+            return BitFlags::EMPTY;
+        }
+
+        let read = if flags & libc::O_DIRECTORY != 0 {
+            AccessFs::ReadDir
+        } else {
+            AccessFs::ReadFile
+        };
+        let mut access = match flags & libc::O_ACCMODE {
+            libc::O_RDONLY => read.into(),
+            libc::O_WRONLY => AccessFs::WriteFile.into(),
+            _ => read | AccessFs::WriteFile,
+        };
+        if flags & libc::O_TRUNC != 0 {
+            access |= AccessFs::Truncate;
+        }
+        if flags & libc::O_CREAT != 0 {
+            access |= AccessFs::MakeReg;
+        }
+        access
+    }
+
+    /// Gets the access rights required to open a file with `options`,
+    /// as for [`required_for_open_flags()`](AccessFs::required_for_open_flags)
+    /// with the flags set by [`OpenAccess::flags()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{make_bitflags, AccessFs, OpenAccess};
+    ///
+    /// assert_eq!(
+    ///     AccessFs::required_for_open(&OpenAccess::new().append(true).create(true)),
+    ///     make_bitflags!(AccessFs::{WriteFile | MakeReg})
+    /// );
+    /// ```
+    pub fn required_for_open(options: &OpenAccess) -> BitFlags<Self> {
+        Self::required_for_open_flags(options.flags())
+    }
+}
+
+/// Options to open a file, mirroring [`OpenOptions`],
+/// to get the access rights required to open it with
+/// [`AccessFs::required_for_open()`].
+///
+/// The standard library doesn't expose the configuration of an [`OpenOptions`],
+/// which can then be created from an `OpenAccess` instead.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{AccessFs, OpenAccess};
+/// use std::fs::OpenOptions;
+///
+/// let options = OpenAccess::new().read(true);
+/// assert_eq!(AccessFs::required_for_open(&options), AccessFs::ReadFile);
+/// let file = OpenOptions::from(options).open("/dev/null");
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OpenAccess {
+    read: bool,
+    write: bool,
+    append: bool,
+    truncate: bool,
+    create: bool,
+    create_new: bool,
+    custom_flags: c_int,
+}
+
+impl OpenAccess {
+    /// Creates options without any access, as [`OpenOptions::new()`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// See [`OpenOptions::read()`].
+    pub fn read(mut self, read: bool) -> Self {
+        self.read = read;
+        self
+    }
+
+    /// See [`OpenOptions::write()`].
+    pub fn write(mut self, write: bool) -> Self {
+        self.write = write;
+        self
+    }
+
+    /// See [`OpenOptions::append()`].
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+
+    /// See [`OpenOptions::truncate()`].
+    pub fn truncate(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// See [`OpenOptions::create()`].
+    pub fn create(mut self, create: bool) -> Self {
+        self.create = create;
+        self
+    }
+
+    /// See [`OpenOptions::create_new()`].
+    pub fn create_new(mut self, create_new: bool) -> Self {
+        self.create_new = create_new;
+        self
+    }
+
+    /// See [`OpenOptionsExt::custom_flags()`](std::os::unix::fs::OpenOptionsExt::custom_flags).
+    /// The access mode bits (i.e. `O_ACCMODE`) are ignored.
+    pub fn custom_flags(mut self, flags: c_int) -> Self {
+        self.custom_flags = flags;
+        self
+    }
+
+    /// Returns the `open(2)` flags set by [`OpenOptions::open()`] with the same options.
+    pub fn flags(&self) -> c_int {
+        let mut flags = match (self.read, self.write || self.append) {
+            (true, true) => libc::O_RDWR,
+            (false, true) => libc::O_WRONLY,
+            // Opening without read nor write access fails with EINVAL.
+            (_, false) => libc::O_RDONLY,
+        };
+        if self.append {
+            flags |= libc::O_APPEND;
+        }
+        if self.truncate {
+            flags |= libc::O_TRUNC;
+        }
+        if self.create_new {
+            flags |= libc::O_CREAT | libc::O_EXCL;
+        } else if self.create {
+            flags |= libc::O_CREAT;
+        }
+        flags | (self.custom_flags & !libc::O_ACCMODE)
+    }
+}
+
+impl From<OpenAccess> for OpenOptions {
+    fn from(access: OpenAccess) -> Self {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut options = OpenOptions::new();
+        options
+            .read(access.read)
+            .write(access.write)
+            .append(access.append)
+            .truncate(access.truncate)
+            .create(access.create)
+            .create_new(access.create_new)
+            .custom_flags(access.custom_flags);
+        options
+    }
+}
+
+#[test]
+fn access_fs_required_for_open() {
+    assert_eq!(
+        AccessFs::required_for_open_flags(libc::O_RDONLY),
+        AccessFs::ReadFile
+    );
+    assert_eq!(
+        AccessFs::required_for_open_flags(libc::O_RDONLY | libc::O_DIRECTORY),
+        AccessFs::ReadDir
+    );
+    assert_eq!(
+        AccessFs::required_for_open_flags(libc::O_RDWR | libc::O_CREAT | libc::O_EXCL),
+        AccessFs::ReadFile | AccessFs::WriteFile | AccessFs::MakeReg
+    );
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    assert_eq!(
+        AccessFs::required_for_open_flags(libc::O_PATH | libc::O_CREAT),
+        BitFlags::EMPTY
+    );
+
+    let required = |options| AccessFs::required_for_open(&options);
+    assert_eq!(required(OpenAccess::new().read(true)), AccessFs::ReadFile);
+    assert_eq!(
+        required(OpenAccess::new().write(true).truncate(true)),
+        AccessFs::WriteFile | AccessFs::Truncate
+    );
+    assert_eq!(
+        required(OpenAccess::new().read(true).append(true).create_new(true)),
+        AccessFs::ReadFile | AccessFs::WriteFile | AccessFs::MakeReg
+    );
+    assert_eq!(
+        required(
+            OpenAccess::new()
+                .read(true)
+                .custom_flags(libc::O_DIRECTORY | libc::O_WRONLY)
+        ),
+        AccessFs::ReadDir
+    );
+
+    // The options are kept when converted.
+    let options = OpenAccess::new().read(true).custom_flags(libc::O_DIRECTORY);
+    assert!(OpenOptions::from(options).open("/").is_ok());
+    assert!(OpenOptions::from(options).open("/dev/null").is_err());
+}
+
+#[test]
+fn access_fs_groups() {
+    for abi in ABI::iter() {
+        let all = AccessFs::from_all(abi);
+        let make = AccessFs::make_any(abi);
+        let remove = AccessFs::remove_any(abi);
+        let safe = AccessFs::metadata_safe(abi);
+        assert!(all.contains(make | remove | safe));
+        assert!(AccessFs::from_write(abi).contains(make | remove));
+        // The groups partition all the access rights, except Refer.
+        assert_eq!(make & remove, BitFlags::EMPTY);
+        assert_eq!(safe & (make | remove), BitFlags::EMPTY);
+        assert_eq!(
+            all & !(make | remove | safe),
+            if abi >= ABI::V2 {
+                AccessFs::Refer.into()
+            } else {
+                BitFlags::EMPTY
+            }
+        );
+        // All the file-compatible access rights keep the hierarchy as is.
+        assert!(safe.contains(AccessFs::from_file(abi)));
+    }
+}
+
 #[test]
 fn consistent_access_fs_rw() {
     for abi in ABI::iter() {
@@ -132,11 +516,24 @@ fn consistent_access_fs_rw() {
     }
 }
 
-impl AccessFs {
-    /// Gets the access rights legitimate for non-directory files.
-    pub fn from_file(abi: ABI) -> BitFlags<Self> {
-        Self::from_all(abi) & ACCESS_FILE
+#[test]
+fn access_fs_const() {
+    const FILE_V1: BitFlags<AccessFs> = AccessFs::from_file(ABI::V1);
+
+    for (abi, all) in [
+        (ABI::V1, AccessFs::ALL_V1),
+        (ABI::V2, AccessFs::ALL_V2),
+        (ABI::V3, AccessFs::ALL_V3),
+        (ABI::V4, AccessFs::ALL_V4),
+    ] {
+        assert_eq!(AccessFs::from_all(abi), all);
     }
+    assert_eq!(AccessFs::ALL_V4, BitFlags::<AccessFs>::all());
+    assert_eq!(
+        FILE_V1,
+        AccessFs::Execute | AccessFs::ReadFile | AccessFs::WriteFile
+    );
+    assert_eq!(AccessFs::from_file(ABI::V3), AccessFs::FILE);
 }
 
 impl PrivateAccess for AccessFs {
@@ -146,11 +543,13 @@ impl PrivateAccess for AccessFs {
     ) -> Result<(), HandleAccessesError> {
         // We need to record the requested accesses for PrivateRule::check_consistency().
         ruleset.requested_handled_fs |= access;
+        let abi = ruleset.compat.probe();
         ruleset.actual_handled_fs |= match access
-            .try_compat(
-                ruleset.compat.abi(),
+            .try_compat_observed(
+                abi,
                 ruleset.compat.level,
                 &mut ruleset.compat.state,
+                &mut ruleset.compat.downgrades,
             )
             .map_err(HandleAccessError::Compat)?
         {
@@ -167,16 +566,56 @@ impl PrivateAccess for AccessFs {
     fn into_handle_accesses_error(error: HandleAccessError<Self>) -> HandleAccessesError {
         HandleAccessesError::Fs(error)
     }
+
+    fn into_downgrade(error: CompatError<Self>) -> Downgrade {
+        Downgrade::Fs(error)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            AccessFs::Execute => "execute",
+            AccessFs::WriteFile => "write_file",
+            AccessFs::ReadFile => "read_file",
+            AccessFs::ReadDir => "read_dir",
+            AccessFs::RemoveDir => "remove_dir",
+            AccessFs::RemoveFile => "remove_file",
+            AccessFs::MakeChar => "make_char",
+            AccessFs::MakeDir => "make_dir",
+            AccessFs::MakeReg => "make_reg",
+            AccessFs::MakeSock => "make_sock",
+            AccessFs::MakeFifo => "make_fifo",
+            AccessFs::MakeBlock => "make_block",
+            AccessFs::MakeSym => "make_sym",
+            AccessFs::Refer => "refer",
+            AccessFs::Truncate => "truncate",
+        }
+    }
 }
 
-// TODO: Make ACCESS_FILE a property of AccessFs.
-// TODO: Add tests for ACCESS_FILE.
-const ACCESS_FILE: BitFlags<AccessFs> = make_bitflags!(AccessFs::{
+/// Prints the stable name of the access right (e.g. `read_file`),
+/// see [`Access::to_names()`] for a set of access rights.
+impl fmt::Display for AccessFs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the stable name of an access right (e.g. `read_file`),
+/// see [`Access::from_names()`] for a set of access rights.
+impl FromStr for AccessFs {
+    type Err = ParseAccessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_name(s)
+    }
+}
+
+pub(crate) const ACCESS_FILE: BitFlags<AccessFs> = make_bitflags!(AccessFs::{
     ReadFile | WriteFile | Execute | Truncate
 });
 
 // XXX: What should we do when a stat call failed?
-fn is_file<F>(fd: F) -> Result<bool, Error>
+pub(crate) fn is_file<F>(fd: F) -> Result<bool, Error>
 where
     F: AsFd,
 {
@@ -189,6 +628,17 @@ where
     }
 }
 
+fn is_symlink<F>(fd: F) -> bool
+where
+    F: AsFd,
+{
+    unsafe {
+        let mut stat = zeroed();
+        libc::fstat(fd.as_fd().as_raw_fd(), &mut stat) == 0
+            && (stat.st_mode & libc::S_IFMT) == libc::S_IFLNK
+    }
+}
+
 /// Landlock rule for a file hierarchy.
 ///
 /// # Example
@@ -200,13 +650,24 @@ where
 ///     Ok(PathBeneath::new(PathFd::new("/home")?, AccessFs::ReadDir))
 /// }
 /// ```
-#[cfg_attr(test, derive(Debug))]
 pub struct PathBeneath<F> {
     attr: uapi::landlock_path_beneath_attr,
     // Ties the lifetime of a file descriptor to this object.
     parent_fd: F,
     allowed_access: BitFlags<AccessFs>,
     compat_level: Option<CompatLevel>,
+    path_label: Option<PathBuf>,
+}
+
+// Doesn't show the file descriptor number, which is meaningless once the ruleset is dumped.
+impl<F> fmt::Debug for PathBeneath<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PathBeneath")
+            .field("path", &self.path_label)
+            .field("allowed_access", &self.allowed_access)
+            .field("compat_level", &self.compat_level)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<F> PathBeneath<F>
@@ -229,9 +690,21 @@ where
             parent_fd: parent,
             allowed_access: access.into(),
             compat_level: None,
+            path_label: None,
         }
     }
 
+    /// Labels this rule with the `path` identified by its file descriptor,
+    /// which is then available in the [`CompatReport`](crate::CompatReport).
+    /// The label is only informative and doesn't change the rule.
+    pub fn set_path_label<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.path_label = Some(path.into());
+        self
+    }
+
     fn sync_attr(mut self) -> Self {
         // Synchronizes rule attributes.
         self.attr.allowed_access = self.allowed_access.bits();
@@ -248,15 +721,17 @@ where
         abi: ABI,
         parent_level: L,
         compat_state: &mut CompatState,
+        downgrades: &mut Downgrades,
     ) -> Result<Option<Self>, CompatError<AccessFs>>
     where
         L: Into<CompatLevel>,
     {
         // Checks with our own compatibility level, if any.
-        self.allowed_access = match self.allowed_access.try_compat(
+        self.allowed_access = match self.allowed_access.try_compat_observed(
             abi,
             self.tailored_compat_level(parent_level),
             compat_state,
+            downgrades,
         )? {
             Some(a) => a,
             None => return Ok(None),
@@ -312,6 +787,17 @@ fn path_beneath_try_compat() {
                 if access == ro_access && incompatible == AccessFs::ReadDir
         ));
 
+        // Directory-only access-rights are removed from best-effort rules.
+        let mut compat_state = CompatState::Init;
+        let raw_access = PathBeneath::new(PathFd::new(file).unwrap(), AccessFs::from_all(abi))
+            .try_compat(abi, CompatLevel::BestEffort, &mut compat_state)
+            .unwrap()
+            .unwrap()
+            .attr
+            .allowed_access;
+        assert_eq!(raw_access, AccessFs::from_file(abi).bits());
+        assert_eq!(compat_state, CompatState::Partial);
+
         let mut compat_state = CompatState::Init;
         assert!(matches!(
             PathBeneath::new(PathFd::new(file).unwrap(), BitFlags::EMPTY)
@@ -396,6 +882,22 @@ where
         0
     }
 
+    fn allowed_access(&self) -> BitFlags<AccessFs> {
+        self.allowed_access
+    }
+
+    fn report(&self, index: usize, requested: BitFlags<AccessFs>) -> RuleReport {
+        RuleReport::Fs {
+            index,
+            path: self.path_label.clone(),
+            access: AccessReport::new(requested, self.allowed_access),
+        }
+    }
+
+    fn path_label(&self) -> Option<&Path> {
+        self.path_label.as_deref()
+    }
+
     fn check_consistency(&self, ruleset: &RulesetCreated) -> Result<(), AddRulesError> {
         // Checks that this rule doesn't contain a superset of the access-rights handled by the
         // ruleset.  This check is about requested access-rights but not actual access-rights.
@@ -407,10 +909,26 @@ where
             Err(AddRuleError::UnhandledAccess {
                 access: self.allowed_access,
                 incompatible: self.allowed_access & !ruleset.requested_handled_fs,
+                handled: ruleset.requested_handled_fs,
             }
             .into())
         }
     }
+
+    fn update_fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.update_u64(self.get_type_id().into());
+        fingerprint.update_u64(self.allowed_access.bits());
+        // Identifies the file hierarchy with its inode, if possible.
+        // The types of st_dev and st_ino depend on the operating system.
+        #[allow(clippy::unnecessary_cast)]
+        unsafe {
+            let mut stat = zeroed::<libc::stat>();
+            if libc::fstat(self.parent_fd.as_fd().as_raw_fd(), &mut stat) == 0 {
+                fingerprint.update_u64(stat.st_dev as u64);
+                fingerprint.update_u64(stat.st_ino as u64);
+            }
+        }
+    }
 }
 
 #[test]
@@ -427,8 +945,8 @@ fn path_beneath_check_consistency() {
             .unwrap()
             .add_rule(PathBeneath::new(PathFd::new("/").unwrap(), rx_access))
             .unwrap_err(),
-        RulesetError::AddRules(AddRulesError::Fs(AddRuleError::UnhandledAccess { access, incompatible }))
-            if access == rx_access && incompatible == AccessFs::Execute
+        RulesetError::AddRules(AddRulesError::Fs(AddRuleError::UnhandledAccess { access, incompatible, handled }))
+            if access == rx_access && incompatible == AccessFs::Execute && handled == ro_access
     ));
 }
 
@@ -438,6 +956,8 @@ fn path_beneath_check_consistency() {
 /// and manage the lifetime of the underlying opened file descriptor.
 /// Indeed, using other [`AsFd`] implementations such as [`File`] brings more complexity
 /// and may lead to unexpected errors (e.g., denied access).
+/// Files or directories already opened by the application can still be converted
+/// to a `PathFd` (e.g. with `PathFd::from(file)`) instead of being opened again.
 ///
 /// [`File`]: std::fs::File
 ///
@@ -461,19 +981,116 @@ impl PathFd {
     where
         T: AsRef<Path>,
     {
+        PathFd::open(path.as_ref(), None, false)
+    }
+
+    /// Opens `path` without following it if it is a symbolic link (i.e. with `O_NOFOLLOW`),
+    /// and returns [`PathFdError::Symlink`] in this case.
+    ///
+    /// This ensures that a rule identifies the real file hierarchy,
+    /// and not a symbolic link which may be swapped by another process.
+    /// Symbolic links in the parent directories are still followed,
+    /// see [`PathFd::with_resolve()`] to forbid them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{PathFd, PathFdError};
+    ///
+    /// // /proc/self is a symbolic link.
+    /// assert!(matches!(
+    ///     PathFd::new_nofollow("/proc/self"),
+    ///     Err(PathFdError::Symlink { .. })
+    /// ));
+    /// ```
+    pub fn new_nofollow<T>(path: T) -> Result<Self, PathFdError>
+    where
+        T: AsRef<Path>,
+    {
+        PathFd::open(path.as_ref(), None, true)
+    }
+
+    /// Opens `path` relative to the directory `dirfd`, with the `openat(2)` system call.
+    ///
+    /// This enables to identify file hierarchies beneath an already opened directory
+    /// (e.g. received from another process) without building absolute paths.
+    /// As for `openat(2)`, an absolute `path` ignores `dirfd`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{PathFd, PathFdError};
+    ///
+    /// fn open_bin(usr: &PathFd) -> Result<PathFd, PathFdError> {
+    ///     PathFd::new_at(usr, "bin")
+    /// }
+    /// ```
+    pub fn new_at<D, T>(dirfd: D, path: T) -> Result<Self, PathFdError>
+    where
+        D: AsFd,
+        T: AsRef<Path>,
+    {
+        let path = path.as_ref();
         Ok(PathFd {
-            fd: OpenOptions::new()
-                .read(true)
-                // If the O_PATH is not supported, it is automatically ignored (Linux < 2.6.39).
-                .custom_flags(libc::O_PATH | libc::O_CLOEXEC)
-                .open(path.as_ref())
-                .map_err(|e| PathFdError::OpenCall {
-                    source: e,
-                    path: path.as_ref().into(),
-                })?
-                .into(),
+            fd: openat_path(dirfd.as_fd(), path).map_err(|e| PathFdError::OpenCall {
+                source: e,
+                path: path.into(),
+            })?,
         })
     }
+
+    /// Opens `path` with the `openat2(2)` system call,
+    /// restricting its resolution according to `resolve`.
+    ///
+    /// This protects the identification of the file hierarchies themselves,
+    /// e.g. against symbolic links planted by a less trusted process.
+    /// A relative path is resolved from the current directory,
+    /// which is the boundary of [`Resolve::Beneath`].
+    ///
+    /// On kernels without `openat2(2)` (i.e. Linux < 5.6),
+    /// this fails with a [`PathFdError::OpenCall`] wrapping `ENOSYS`
+    /// instead of ignoring `resolve`.
+    /// Falling back to a plain open is deliberately not the default,
+    /// because it would silently drop the protection requested by the caller
+    /// (see [`ResolveFs::set_fallback()`](crate::ResolveFs::set_fallback) to opt in).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{PathFd, PathFdError, Resolve};
+    ///
+    /// fn open_config() -> Result<PathFd, PathFdError> {
+    ///     PathFd::with_resolve("/etc/app", Resolve::NoSymlinks | Resolve::NoMagiclinks)
+    /// }
+    /// ```
+    pub fn with_resolve<T, R>(path: T, resolve: R) -> Result<Self, PathFdError>
+    where
+        T: AsRef<Path>,
+        R: Into<BitFlags<Resolve>>,
+    {
+        PathFd::open(path.as_ref(), Some(resolve.into()), false)
+    }
+
+    pub(crate) fn open(
+        path: &Path,
+        resolve: Option<BitFlags<Resolve>>,
+        no_follow: bool,
+    ) -> Result<Self, PathFdError> {
+        let flags = if no_follow { libc::O_NOFOLLOW } else { 0 };
+        let fd = match resolve {
+            None => open_path(path, flags),
+            Some(resolve) => openat2_path(path, resolve, flags),
+        }
+        .map_err(|e| PathFdError::OpenCall {
+            source: e,
+            path: path.into(),
+        })?;
+        // With O_PATH and O_NOFOLLOW, a symbolic link is opened instead of being rejected.
+        if no_follow && is_symlink(&fd) {
+            return Err(PathFdError::Symlink { path: path.into() });
+        }
+        Ok(PathFd { fd })
+    }
 }
 
 impl AsFd for PathFd {
@@ -482,9 +1099,298 @@ impl AsFd for PathFd {
     }
 }
 
+/// Takes ownership of an already opened file descriptor,
+/// e.g. converted from a [`cap_std::fs::Dir`](https://docs.rs/cap-std/latest/cap_std/fs/struct.Dir.html)
+/// with `OwnedFd::from(dir)`.
+///
+/// The file descriptor is not required to be opened with `O_PATH`.
+impl From<OwnedFd> for PathFd {
+    fn from(fd: OwnedFd) -> Self {
+        PathFd { fd }
+    }
+}
+
+/// Takes ownership of an already opened file or directory.
+impl From<File> for PathFd {
+    fn from(file: File) -> Self {
+        PathFd { fd: file.into() }
+    }
+}
+
+impl From<PathFd> for OwnedFd {
+    fn from(path: PathFd) -> Self {
+        path.fd
+    }
+}
+
+/// Restriction of the path resolution for [`PathFd::with_resolve()`],
+/// see the `RESOLVE_*` flags of `openat2(2)`.
+#[bitflags]
+#[repr(u64)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Resolve {
+    /// Forbids crossing mount points, including bind mounts.
+    NoXdev = 0x01,
+    /// Forbids following magic links (e.g. `/proc/self/exe` or `/proc/<pid>/fd/*`).
+    NoMagiclinks = 0x02,
+    /// Forbids following any symbolic link, including magic links.
+    NoSymlinks = 0x04,
+    /// Forbids resolving paths outside of the starting directory,
+    /// e.g. with absolute paths, `..` components or symbolic links.
+    Beneath = 0x08,
+}
+
+// Opening system calls, done with libc by default.
+#[cfg(not(all(feature = "rustix", any(target_os = "linux", target_os = "android"))))]
+mod sys {
+    use super::Resolve;
+    use enumflags2::BitFlags;
+    use std::ffi::CString;
+    use std::fs::OpenOptions;
+    use std::io::Error;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+    use std::path::Path;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    use {crate::uapi, std::mem::size_of, std::os::unix::io::RawFd};
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const O_PATH: i32 = libc::O_PATH;
+    // Without O_PATH, at least avoids blocking on FIFOs.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    const O_PATH: i32 = libc::O_NONBLOCK;
+
+    // Opens a file or a directory with the O_PATH flag.
+    pub(super) fn open_path(path: &Path, flags: i32) -> Result<OwnedFd, Error> {
+        Ok(OpenOptions::new()
+            .read(true)
+            // If the O_PATH is not supported, it is automatically ignored (Linux < 2.6.39).
+            .custom_flags(O_PATH | libc::O_CLOEXEC | flags)
+            .open(path)?
+            .into())
+    }
+
+    pub(super) fn openat_path(dirfd: BorrowedFd, path: &Path) -> Result<OwnedFd, Error> {
+        let path = c_path(path)?;
+        match unsafe { libc::openat(dirfd.as_raw_fd(), path.as_ptr(), O_PATH | libc::O_CLOEXEC) } {
+            fd if fd >= 0 => Ok(unsafe { OwnedFd::from_raw_fd(fd) }),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+
+    fn c_path(path: &Path) -> Result<CString, Error> {
+        CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| Error::from_raw_os_error(libc::EINVAL))
+    }
+
+    // Argument of openat2(2), not defined by old versions of the libc crate.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[repr(C)]
+    struct OpenHow {
+        flags: u64,
+        mode: u64,
+        resolve: u64,
+    }
+
+    pub(super) fn openat2_path(
+        path: &Path,
+        resolve: BitFlags<Resolve>,
+        flags: i32,
+    ) -> Result<OwnedFd, Error> {
+        openat2(None, path, O_PATH | libc::O_CLOEXEC | flags, resolve)
+    }
+
+    // Opens path relative to dirfd, or to the current directory if None.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(crate) fn openat2(
+        dirfd: Option<BorrowedFd>,
+        path: &Path,
+        flags: i32,
+        resolve: BitFlags<Resolve>,
+    ) -> Result<OwnedFd, Error> {
+        let path = c_path(path)?;
+        let how = OpenHow {
+            flags: flags as u64,
+            mode: 0,
+            resolve: resolve.bits(),
+        };
+        match unsafe {
+            libc::syscall(
+                uapi::linux::SYS_openat2,
+                dirfd.map_or(libc::AT_FDCWD, |fd| fd.as_raw_fd()),
+                path.as_ptr(),
+                &how as *const OpenHow,
+                size_of::<OpenHow>(),
+            )
+        } {
+            fd if fd >= 0 => Ok(unsafe { OwnedFd::from_raw_fd(fd as RawFd) }),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+
+    // Same as a Linux kernel without openat2(2).
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub(crate) fn openat2(
+        _dirfd: Option<BorrowedFd>,
+        _path: &Path,
+        _flags: i32,
+        _resolve: BitFlags<Resolve>,
+    ) -> Result<OwnedFd, Error> {
+        Err(Error::from_raw_os_error(libc::ENOSYS))
+    }
+}
+
+// Opening system calls, done with rustix with the rustix feature.
+#[cfg(all(feature = "rustix", any(target_os = "linux", target_os = "android")))]
+mod sys {
+    use super::Resolve;
+    use enumflags2::BitFlags;
+    use rustix::fs::{Mode, OFlags, ResolveFlags, CWD};
+    use std::io::Error;
+    use std::os::unix::io::{BorrowedFd, OwnedFd};
+    use std::path::Path;
+
+    fn path_flags(flags: i32) -> OFlags {
+        OFlags::PATH | OFlags::CLOEXEC | OFlags::from_bits_retain(flags as _)
+    }
+
+    // Opens a file or a directory with the O_PATH flag.
+    pub(super) fn open_path(path: &Path, flags: i32) -> Result<OwnedFd, Error> {
+        Ok(rustix::fs::open(path, path_flags(flags), Mode::empty())?)
+    }
+
+    pub(super) fn openat_path(dirfd: BorrowedFd, path: &Path) -> Result<OwnedFd, Error> {
+        Ok(rustix::fs::openat(
+            dirfd,
+            path,
+            path_flags(0),
+            Mode::empty(),
+        )?)
+    }
+
+    pub(super) fn openat2_path(
+        path: &Path,
+        resolve: BitFlags<Resolve>,
+        flags: i32,
+    ) -> Result<OwnedFd, Error> {
+        openat2(None, path, path_flags(flags).bits() as _, resolve)
+    }
+
+    // Opens path relative to dirfd, or to the current directory if None.
+    pub(crate) fn openat2(
+        dirfd: Option<BorrowedFd>,
+        path: &Path,
+        flags: i32,
+        resolve: BitFlags<Resolve>,
+    ) -> Result<OwnedFd, Error> {
+        Ok(rustix::fs::openat2(
+            dirfd.unwrap_or(CWD),
+            path,
+            OFlags::from_bits_retain(flags as _),
+            Mode::empty(),
+            ResolveFlags::from_bits_retain(resolve.bits()),
+        )?)
+    }
+}
+
+#[test]
+fn path_fd_with_resolve() {
+    let errno = |ret: Result<PathFd, PathFdError>| match ret {
+        Ok(_) => None,
+        Err(PathFdError::OpenCall { source, .. }) => source.raw_os_error(),
+        Err(e) => panic!("unexpected error: {e}"),
+    };
+
+    // Without openat2(2), the resolve flags are not silently ignored.
+    if errno(PathFd::with_resolve("/", BitFlags::EMPTY)) == Some(libc::ENOSYS) {
+        let fs = crate::ResolveFs::new(Resolve::Beneath);
+        assert!(fs.open_path(Path::new("/")).is_err());
+        fs.set_fallback(true).open_path(Path::new("/")).unwrap();
+        return;
+    }
+    assert_eq!(
+        errno(PathFd::with_resolve("/", Resolve::Beneath)),
+        Some(libc::EXDEV)
+    );
+    assert_eq!(
+        errno(PathFd::with_resolve("/proc/self", Resolve::NoSymlinks)),
+        Some(libc::ELOOP)
+    );
+    assert_eq!(
+        errno(PathFd::with_resolve("/proc/self", Resolve::NoMagiclinks)),
+        None
+    );
+    assert_eq!(
+        errno(PathFd::with_resolve(
+            "/proc/self/exe",
+            Resolve::NoMagiclinks
+        )),
+        Some(libc::ELOOP)
+    );
+    assert_eq!(
+        errno(PathFd::with_resolve("/does-not-exist", BitFlags::EMPTY)),
+        Some(libc::ENOENT)
+    );
+}
+
+#[test]
+fn path_fd_new_at() {
+    let etc = PathFd::new("/etc").unwrap();
+    PathFd::new_at(&etc, "passwd").unwrap();
+    PathFd::new_at(&etc, "/usr").unwrap();
+    assert!(matches!(
+        PathFd::new_at(&etc, "does-not-exist"),
+        Err(PathFdError::OpenCall { source, path })
+            if source.raw_os_error() == Some(libc::ENOENT) && path == Path::new("does-not-exist")
+    ));
+    assert!(matches!(
+        PathFd::new_at(&etc, "a\0b"),
+        Err(PathFdError::OpenCall { source, .. }) if source.raw_os_error() == Some(libc::EINVAL)
+    ));
+    // An O_PATH file descriptor of a file is not a directory.
+    let passwd = PathFd::new("/etc/passwd").unwrap();
+    assert!(matches!(
+        PathFd::new_at(&passwd, "foo"),
+        Err(PathFdError::OpenCall { source, .. }) if source.raw_os_error() == Some(libc::ENOTDIR)
+    ));
+}
+
+#[test]
+fn path_fd_new_nofollow() {
+    PathFd::new_nofollow("/").unwrap();
+    PathFd::new_nofollow("/proc/self/").unwrap();
+    assert!(matches!(
+        PathFd::new_nofollow("/proc/self"),
+        Err(PathFdError::Symlink { path }) if path == Path::new("/proc/self")
+    ));
+    assert!(matches!(
+        PathFd::open(Path::new("/proc/self"), Some(BitFlags::EMPTY), true),
+        Err(PathFdError::Symlink { .. })
+    ));
+    assert!(matches!(
+        PathFd::new_nofollow("/does-not-exist"),
+        Err(PathFdError::OpenCall { .. })
+    ));
+}
+
+#[test]
+fn path_fd_from() {
+    let file = File::open("/etc/passwd").unwrap();
+    let raw = file.as_raw_fd();
+    let path = PathFd::from(file);
+    assert_eq!(path.as_fd().as_raw_fd(), raw);
+    let fd = OwnedFd::from(path);
+    assert_eq!(fd.as_raw_fd(), raw);
+    let path = PathFd::from(fd);
+    assert_eq!(path.as_fd().as_raw_fd(), raw);
+    assert!(is_file(&path).unwrap());
+}
+
 #[test]
 fn path_fd() {
-    use std::fs::File;
     use std::io::Read;
 
     PathBeneath::new(PathFd::new("/").unwrap(), AccessFs::Execute);
@@ -538,19 +1444,43 @@ where
     I: IntoIterator<Item = P>,
     P: AsRef<Path>,
     A: Into<BitFlags<AccessFs>>,
+{
+    path_beneath_rules_in(HostFs, paths, access)
+}
+
+/// Same as [`path_beneath_rules()`] but opens `paths` with `fs`.
+pub fn path_beneath_rules_in<F, I, P, A>(
+    fs: F,
+    paths: I,
+    access: A,
+) -> impl Iterator<Item = Result<PathBeneath<PathFd>, RulesetError>>
+where
+    F: PolicyFs,
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+    A: Into<BitFlags<AccessFs>>,
 {
     let access = access.into();
-    paths.into_iter().filter_map(move |p| match PathFd::new(p) {
-        Ok(f) => {
-            let valid_access = match is_file(&f) {
-                Ok(true) => access & ACCESS_FILE,
-                // If the stat call failed, let's blindly rely on the requested access rights.
-                Err(_) | Ok(false) => access,
-            };
-            Some(Ok(PathBeneath::new(f, valid_access)))
-        }
-        Err(_) => None,
-    })
+    paths
+        .into_iter()
+        .filter_map(move |p| match fs.open_path(p.as_ref()) {
+            Ok(f) => Some(Ok(path_beneath_rule(f, access, p.as_ref()))),
+            Err(_) => None,
+        })
+}
+
+// Automatically adjusts access rights according to the file type when possible.
+pub(crate) fn path_beneath_rule(
+    fd: PathFd,
+    access: BitFlags<AccessFs>,
+    path: &Path,
+) -> PathBeneath<PathFd> {
+    let valid_access = match is_file(&fd) {
+        Ok(true) => access & ACCESS_FILE,
+        // If the stat call failed, let's blindly rely on the requested access rights.
+        Err(_) | Ok(false) => access,
+    };
+    PathBeneath::new(fd, valid_access).set_path_label(path)
 }
 
 #[test]
@@ -0,0 +1,292 @@
+//! Process hardening along with the ruleset enforcement.
+//!
+//! Daemons commonly complement their sandbox with a few process attributes
+//! limiting the impact of a compromise:
+//! resource limits (e.g. no core dump),
+//! a restrictive file mode creation mask,
+//! and no ptrace nor core dump of the process (i.e. not "dumpable").
+//! [`Hardening`] sets them in a documented order, right before enforcing a ruleset,
+//! and reports the step that failed, if any.
+//!
+//! Contrary to Landlock restrictions,
+//! these attributes apply to the whole process (i.e. all its threads) and are not inherited
+//! by its children in the same way (see [`Hardening::restrict_self()`]).
+//!
+//! This is only available with the `hardening` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use landlock::hardening::{Hardening, Resource};
+//! use landlock::{Access, AccessFs, HardeningError, Ruleset, RulesetAttr, ABI};
+//!
+//! fn lock_down() -> Result<(), HardeningError> {
+//!     let ruleset = Ruleset::default()
+//!         .handle_access(AccessFs::from_all(ABI::V1))?
+//!         .create()?;
+//!     let status = Hardening::default()
+//!         .set_rlimit(Resource::Nproc, 0)
+//!         .restrict_self(ruleset)?;
+//!     println!("applied: {:?}", status.steps);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{HardeningError, RestrictionStatus, RulesetCreated};
+use std::io::Error;
+use std::os::raw::c_int;
+
+/// Resource limited with `setrlimit(2)`,
+/// see [`Hardening::set_rlimit()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Resource {
+    /// `RLIMIT_AS`: maximum size of the virtual memory, in bytes.
+    As,
+    /// `RLIMIT_CORE`: maximum size of a core dump, in bytes.
+    Core,
+    /// `RLIMIT_CPU`: maximum CPU time, in seconds.
+    Cpu,
+    /// `RLIMIT_DATA`: maximum size of the data segment, in bytes.
+    Data,
+    /// `RLIMIT_FSIZE`: maximum size of the created files, in bytes.
+    Fsize,
+    /// `RLIMIT_MEMLOCK`: maximum size of locked memory, in bytes.
+    Memlock,
+    /// `RLIMIT_NOFILE`: maximum number of file descriptors, plus one.
+    Nofile,
+    /// `RLIMIT_NPROC`: maximum number of processes (or threads) of the real user ID.
+    Nproc,
+    /// `RLIMIT_STACK`: maximum size of the stack, in bytes.
+    Stack,
+}
+
+impl Resource {
+    fn raw(self) -> c_int {
+        (match self {
+            Resource::As => libc::RLIMIT_AS,
+            Resource::Core => libc::RLIMIT_CORE,
+            Resource::Cpu => libc::RLIMIT_CPU,
+            Resource::Data => libc::RLIMIT_DATA,
+            Resource::Fsize => libc::RLIMIT_FSIZE,
+            Resource::Memlock => libc::RLIMIT_MEMLOCK,
+            Resource::Nofile => libc::RLIMIT_NOFILE,
+            Resource::Nproc => libc::RLIMIT_NPROC,
+            Resource::Stack => libc::RLIMIT_STACK,
+        }) as c_int
+    }
+}
+
+/// Hardening step, see [`Hardening::restrict_self()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HardeningStep {
+    /// Lowers the soft and hard limits of a resource with `setrlimit(2)`.
+    #[non_exhaustive]
+    Rlimit { resource: Resource, limit: u64 },
+    /// Sets the file mode creation mask with `umask(2)`.
+    #[non_exhaustive]
+    Umask { mask: u32 },
+    /// Calls `prctl(2)` with `PR_SET_DUMPABLE` set to 0.
+    NotDumpable,
+}
+
+/// Process hardening steps to apply before enforcing a ruleset
+/// with [`restrict_self()`](Hardening::restrict_self).
+///
+/// The default configuration disables core dumps (i.e. [`Resource::Core`] set to 0),
+/// sets the file mode creation mask to 0o077,
+/// and makes the process not dumpable.
+#[derive(Debug, Clone)]
+pub struct Hardening {
+    rlimits: Vec<(Resource, u64)>,
+    umask: Option<u32>,
+    dumpable: bool,
+}
+
+impl Default for Hardening {
+    fn default() -> Self {
+        Hardening {
+            rlimits: vec![(Resource::Core, 0)],
+            umask: Some(0o077),
+            dumpable: false,
+        }
+    }
+}
+
+impl Hardening {
+    /// Creates a configuration without any hardening step,
+    /// contrary to [`Hardening::default()`].
+    pub fn new() -> Self {
+        Hardening {
+            rlimits: Vec::new(),
+            umask: None,
+            dumpable: true,
+        }
+    }
+
+    /// Lowers the soft and hard limits of `resource` to `limit`,
+    /// or keeps them if they are already lower.
+    ///
+    /// A limit can then only be raised again by a privileged process.
+    pub fn set_rlimit(mut self, resource: Resource, limit: u64) -> Self {
+        self.rlimits.retain(|(r, _)| *r != resource);
+        self.rlimits.push((resource, limit));
+        self
+    }
+
+    /// Sets the file mode creation mask of the process (e.g. 0o077),
+    /// or keeps it with `None`.
+    pub fn set_umask(mut self, mask: Option<u32>) -> Self {
+        self.umask = mask;
+        self
+    }
+
+    /// Configures whether the process stays "dumpable" (see `PR_SET_DUMPABLE` in `prctl(2)`),
+    /// i.e. whether it can be traced by other processes of the same user
+    /// and produce core dumps.
+    pub fn set_dumpable(mut self, dumpable: bool) -> Self {
+        self.dumpable = dumpable;
+        self
+    }
+
+    /// Applies the hardening steps to the process and then enforces `ruleset`
+    /// with [`RulesetCreated::restrict_self()`].
+    ///
+    /// The steps are applied in this order:
+    /// 1. the resource limits, in the order they were set;
+    /// 2. the file mode creation mask;
+    /// 3. the non-dumpable state;
+    /// 4. the ruleset enforcement.
+    ///
+    /// On error, the next steps (including the ruleset enforcement) are not applied,
+    /// but the previous ones are not reverted.
+    /// The resource limits and the file mode creation mask are inherited by child processes,
+    /// whereas the dumpable state is reset by `execve(2)`
+    /// (except for set-user-ID or file capability binaries).
+    pub fn restrict_self(
+        &self,
+        ruleset: RulesetCreated,
+    ) -> Result<HardeningStatus, HardeningError> {
+        let mut steps = Vec::new();
+        for (resource, limit) in &self.rlimits {
+            let step = HardeningStep::Rlimit {
+                resource: *resource,
+                limit: *limit,
+            };
+            lower_rlimit(*resource, *limit)
+                .map_err(|source| HardeningError::StepCall { step, source })?;
+            steps.push(step);
+        }
+
+        let previous_umask = self.umask.map(|mask| {
+            steps.push(HardeningStep::Umask { mask });
+            set_umask(mask)
+        });
+
+        if !self.dumpable {
+            let step = HardeningStep::NotDumpable;
+            set_not_dumpable().map_err(|source| HardeningError::StepCall { step, source })?;
+            steps.push(step);
+        }
+
+        Ok(HardeningStatus {
+            steps,
+            previous_umask,
+            ruleset: ruleset.restrict_self()?,
+        })
+    }
+}
+
+/// Status of the process hardening and of the ruleset enforcement,
+/// returned by [`Hardening::restrict_self()`].
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HardeningStatus {
+    /// Applied hardening steps, in order.
+    pub steps: Vec<HardeningStep>,
+    /// File mode creation mask replaced by [`HardeningStep::Umask`], if any.
+    pub previous_umask: Option<u32>,
+    /// Status of the ruleset enforcement.
+    pub ruleset: RestrictionStatus,
+}
+
+// The mode_t type is u16 on macOS.
+#[allow(clippy::useless_conversion)]
+fn set_umask(mask: u32) -> u32 {
+    unsafe { libc::umask(mask as libc::mode_t) }.into()
+}
+
+fn lower_rlimit(resource: Resource, limit: u64) -> Result<(), Error> {
+    let mut rlimit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // The resource type depends on the C library.
+    if unsafe { libc::getrlimit(resource.raw() as _, &mut rlimit) } != 0 {
+        return Err(Error::last_os_error());
+    }
+    // Saturates on 32-bit architectures, where RLIM_INFINITY is the maximum value.
+    let limit = libc::rlim_t::try_from(limit).unwrap_or(libc::RLIM_INFINITY);
+    rlimit.rlim_cur = rlimit.rlim_cur.min(limit);
+    rlimit.rlim_max = rlimit.rlim_max.min(limit);
+    if unsafe { libc::setrlimit(resource.raw() as _, &rlimit) } != 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_not_dumpable() -> Result<(), Error> {
+    match unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0, 0, 0, 0) } {
+        0 => Ok(()),
+        _ => Err(Error::last_os_error()),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn set_not_dumpable() -> Result<(), Error> {
+    Err(Error::from_raw_os_error(libc::ENOSYS))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn hardening_restrict_self() {
+    use crate::{Access, AccessFs, Ruleset, RulesetAttr, RulesetStatus, ABI};
+
+    let ruleset = Ruleset::from(ABI::Unsupported)
+        .handle_access(AccessFs::from_all(ABI::V1))
+        .unwrap()
+        .create()
+        .unwrap();
+    // Keeps the usual umask to not interfere with the other tests of this process.
+    let status = Hardening::default()
+        .set_umask(Some(0o022))
+        .set_rlimit(Resource::Core, 0)
+        .restrict_self(ruleset)
+        .unwrap();
+    assert_eq!(
+        status.steps,
+        [
+            HardeningStep::Rlimit {
+                resource: Resource::Core,
+                limit: 0
+            },
+            HardeningStep::Umask { mask: 0o022 },
+            HardeningStep::NotDumpable,
+        ]
+    );
+    assert!(status.previous_umask.is_some());
+    assert_eq!(status.ruleset.ruleset, RulesetStatus::NotEnforced);
+
+    let mut rlimit = libc::rlimit {
+        rlim_cur: 1,
+        rlim_max: 1,
+    };
+    assert_eq!(
+        unsafe { libc::getrlimit(libc::RLIMIT_CORE, &mut rlimit) },
+        0
+    );
+    assert_eq!((rlimit.rlim_cur, rlimit.rlim_max), (0, 0));
+    assert_eq!(unsafe { libc::prctl(libc::PR_GET_DUMPABLE, 0, 0, 0, 0) }, 0);
+}
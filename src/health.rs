@@ -0,0 +1,579 @@
+use crate::{AccessFs, AccessNet, BitFlags, RulesetStatus, ABI};
+use std::cell::Cell;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::panic;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[cfg(test)]
+use crate::*;
+
+// Records the last enforcement done through this crate, which is then queried by
+// SandboxHealth::current().  Mutex::new() is const since Rust 1.63.
+static LAST_HEALTH: Mutex<Option<SandboxHealth>> = Mutex::new(None);
+
+thread_local! {
+    // Number of Landlock domains enforced through this crate by the current thread, queried by
+    // current_restrictions().
+    static THREAD_LAYERS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Summary of the Landlock enforcement of the running process.
+///
+/// `SandboxHealth` is designed to be embedded as is in a service health endpoint
+/// (e.g., serialized to JSON with the `serde` feature)
+/// or printed by a CLI `--status` option,
+/// to let operators check that a live service is really sandboxed.
+///
+/// Each successful call to [`RulesetCreated::restrict_self()`](crate::RulesetCreated::restrict_self)
+/// records its result, which can then be retrieved from any thread with
+/// [`SandboxHealth::current()`].
+///
+/// # Example
+///
+/// ```
+/// use landlock::SandboxHealth;
+///
+/// fn status_endpoint() -> String {
+///     SandboxHealth::current().to_string()
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct SandboxHealth {
+    /// Status of the last enforced ruleset.
+    pub status: RulesetStatus,
+    /// Landlock ABI supported by the running kernel.
+    pub abi: ABI,
+    /// Fingerprint of the last enforced ruleset (i.e., its handled access rights and rules),
+    /// if any.
+    pub fingerprint: Option<String>,
+    /// Number of Landlock domains enforced through this crate by the running process.
+    pub layers: usize,
+    /// Handled file system access rights that were requested by the last ruleset
+    /// but that are not enforced because they are not supported by the running kernel.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_names::serialize")
+    )]
+    pub downgrades_fs: BitFlags<AccessFs>,
+    /// Handled network access rights that were requested by the last ruleset
+    /// but that are not enforced because they are not supported by the running kernel.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_names::serialize")
+    )]
+    pub downgrades_net: BitFlags<AccessNet>,
+    /// Status of `prctl(2)`'s `PR_SET_NO_NEW_PRIVS` enforcement.
+    pub no_new_privs: bool,
+}
+
+impl SandboxHealth {
+    /// Returns the health of the last enforcement done by the running process,
+    /// or a [`RulesetStatus::NotEnforced`] status if there was none.
+    pub fn current() -> Self {
+        match LAST_HEALTH.lock() {
+            Ok(last) => last.clone(),
+            // A panic while recording cannot leave an inconsistent SandboxHealth.
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+        .unwrap_or_else(|| SandboxHealth {
+            status: RulesetStatus::NotEnforced,
+            abi: ABI::new_current(),
+            fingerprint: None,
+            layers: 0,
+            downgrades_fs: BitFlags::EMPTY,
+            downgrades_net: BitFlags::EMPTY,
+            no_new_privs: false,
+        })
+    }
+
+    pub(crate) fn record(
+        status: RulesetStatus,
+        abi: ABI,
+        fingerprint: &Fingerprint,
+        new_layer: bool,
+        downgrades_fs: BitFlags<AccessFs>,
+        downgrades_net: BitFlags<AccessNet>,
+        no_new_privs: bool,
+    ) {
+        let mut last = LAST_HEALTH
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let layers = last.as_ref().map(|h| h.layers).unwrap_or(0) + usize::from(new_layer);
+        *last = Some(SandboxHealth {
+            status,
+            abi,
+            fingerprint: Some(fingerprint.to_string()),
+            layers,
+            downgrades_fs,
+            downgrades_net,
+            no_new_privs,
+        });
+    }
+
+    // Sets the domain depth of a new thread to the one of its parent thread.
+    pub(crate) fn inherit_layers(depth: usize) {
+        THREAD_LAYERS.with(|l| l.set(depth));
+    }
+
+    // Counts a new domain enforced by the calling thread, even if the following checks fail
+    // (e.g. the canary one).
+    pub(crate) fn record_layer() {
+        THREAD_LAYERS.with(|l| l.set(l.get() + 1));
+    }
+}
+
+impl fmt::Display for SandboxHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "landlock: {} (ABI {}, {} layer(s), no_new_privs: {}",
+            self.status, self.abi as i32, self.layers, self.no_new_privs
+        )?;
+        if let Some(ref fingerprint) = self.fingerprint {
+            write!(f, ", fingerprint: {fingerprint}")?;
+        }
+        if !self.downgrades_fs.is_empty() {
+            write!(f, ", downgraded fs: {:?}", self.downgrades_fs)?;
+        }
+        if !self.downgrades_net.is_empty() {
+            write!(f, ", downgraded net: {:?}", self.downgrades_net)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Maximum number of nested Landlock domains (i.e. layers) of a thread,
+/// as limited by the kernel.
+pub const MAX_DOMAIN_DEPTH: usize = 16;
+
+/// Returns the number of Landlock domains enforced through this crate by the calling thread
+/// (with [`RulesetCreated::restrict_self()`](crate::RulesetCreated::restrict_self)
+/// or [`ThreadRestrictor`](crate::thread::ThreadRestrictor)).
+///
+/// Domains are nested per thread, which is why this depth is not shared with the other threads,
+/// contrary to [`SandboxHealth::layers`].
+/// A thread spawned with [`thread::Builder`](crate::thread::Builder) or
+/// [`spawn_restricted()`](crate::thread::spawn_restricted) starts from the depth of its
+/// parent thread.
+/// However, this depth doesn't include the domains inherited by other threads
+/// (e.g. spawned with [`std::thread::spawn()`]) nor by the process,
+/// and then underestimates their actual depth.
+/// Neither does it include the domains enforced with
+/// [`restrict_self_async_signal_safe()`](crate::RulesetCreated::restrict_self_async_signal_safe).
+///
+/// Enforcing a ruleset fails with [`RestrictSelfError::DomainDepthExceeded`](crate::RestrictSelfError::DomainDepthExceeded)
+/// once this depth reaches [`MAX_DOMAIN_DEPTH`].
+pub fn domain_depth() -> usize {
+    THREAD_LAYERS.with(|l| l.get())
+}
+
+/// Landlock restrictions of the calling thread, see [`current_restrictions()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CurrentRestrictions {
+    /// The running kernel doesn't support Landlock (or it is disabled),
+    /// so the thread cannot be restricted.
+    Unsupported,
+    /// The calling thread enforced `layers` Landlock domains through this crate.
+    #[non_exhaustive]
+    Enforced { layers: usize },
+    /// The calling thread is restricted by a Landlock domain enforced before
+    /// (e.g. by a launcher before executing this program, or by the thread which created it),
+    /// according to heuristics.
+    Inherited,
+    /// No restriction was detected,
+    /// which doesn't guarantee that the calling thread is not restricted.
+    NotDetected,
+}
+
+impl CurrentRestrictions {
+    /// Returns true if the calling thread is known to be restricted.
+    pub fn is_restricted(self) -> bool {
+        matches!(
+            self,
+            CurrentRestrictions::Enforced { .. } | CurrentRestrictions::Inherited
+        )
+    }
+}
+
+/// Reports on a best-effort basis whether the calling thread is already restricted
+/// by a Landlock domain, e.g. for a library to not stack a redundant sandbox,
+/// or to adapt its behavior to a sandboxed environment.
+///
+/// The kernel doesn't expose the Landlock domain of a thread yet,
+/// so this function checks, in this order:
+/// 1. the Landlock support of the running kernel;
+/// 2. the rulesets enforced through this crate by the calling thread,
+///    with [`RulesetCreated::restrict_self()`](crate::RulesetCreated::restrict_self)
+///    or [`ThreadRestrictor`](crate::thread::ThreadRestrictor);
+/// 3. whether the thread is denied to inspect its parent process through `/proc`
+///    (i.e. to read its `exe` link) whereas it would be allowed without Landlock,
+///    because a Landlock domain denies ptrace-like access to processes outside of it.
+///
+/// The last heuristic is inconclusive if the parent process runs with other credentials,
+/// if it is restricted by the same domain (e.g. a sandboxed shell),
+/// or if `/proc` is not mounted.
+/// Another security module denying such access may also lead to a false positive.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{current_restrictions, Policy, PolicyError};
+///
+/// fn sandbox_once(policy: &Policy) -> Result<(), PolicyError> {
+///     if !current_restrictions().is_restricted() {
+///         policy.restrict_self()?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn current_restrictions() -> CurrentRestrictions {
+    if ABI::new_current() == ABI::Unsupported {
+        return CurrentRestrictions::Unsupported;
+    }
+    let layers = domain_depth();
+    if layers > 0 {
+        return CurrentRestrictions::Enforced { layers };
+    }
+    if is_parent_access_denied() {
+        CurrentRestrictions::Inherited
+    } else {
+        CurrentRestrictions::NotDetected
+    }
+}
+
+// Returns true if the calling thread is denied to read the parent's /proc/<pid>/exe link,
+// whereas the parent has the same credentials and is dumpable.
+fn is_parent_access_denied() -> bool {
+    let ppid = unsafe { libc::getppid() };
+    let (uid, gid) = unsafe { (libc::geteuid(), libc::getegid()) };
+    let proc_dir = PathBuf::from(format!("/proc/{ppid}"));
+
+    // The files of a non-dumpable process are owned by root.
+    let owner = match fs::metadata(&proc_dir) {
+        Ok(metadata) => std::os::unix::fs::MetadataExt::uid(&metadata),
+        Err(_) => return false,
+    };
+    let status = match fs::read_to_string(proc_dir.join("status")) {
+        Ok(status) => status,
+        Err(_) => return false,
+    };
+    // Format: "Uid:\t1000\t1000\t1000\t1000"
+    let same_ids = |name: &str, id: u32| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(|ids| ids.split_whitespace().all(|i| i.parse() == Ok(id)))
+            .unwrap_or(false)
+    };
+    if owner != uid || !same_ids("Uid:", uid) || !same_ids("Gid:", gid) {
+        return false;
+    }
+    match fs::read_link(proc_dir.join("exe")) {
+        Ok(_) => false,
+        Err(e) => matches!(e.raw_os_error(), Some(libc::EACCES) | Some(libc::EPERM)),
+    }
+}
+
+/// Panic hook appending the [`SandboxHealth`] to panic messages.
+///
+/// Post-mortem debugging of a sandboxed crash requires to know
+/// which sandbox was enforced when the program panicked
+/// (e.g., an access denied by a forgotten rule may lead to an unexpected panic).
+/// This hook is installed with
+/// [`RulesetCreatedAttr::set_panic_hook()`](crate::RulesetCreatedAttr::set_panic_hook)
+/// when the ruleset is enforced,
+/// or explicitly with [`PanicHook::install()`].
+/// The previous panic hook (e.g., the default one printing the panic message) is still called.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{
+///     Access, AccessFs, PanicHook, PathBeneath, PathFd, PolicyError, RestrictionStatus,
+///     Ruleset, RulesetAttr, RulesetCreatedAttr, ABI,
+/// };
+///
+/// fn restrict_thread() -> Result<RestrictionStatus, PolicyError> {
+///     let abi = ABI::V1;
+///     Ok(Ruleset::default()
+///         .handle_access(AccessFs::from_all(abi))?
+///         .create()?
+///         .add_rule(PathBeneath::new(PathFd::new("/var/log/app")?, AccessFs::from_all(abi)))?
+///         .set_panic_hook(Some(PanicHook::new().set_crash_log("/var/log/app/crash.log")))
+///         .restrict_self()?)
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct PanicHook {
+    crash_log: Option<PathBuf>,
+}
+
+impl PanicHook {
+    /// Creates a panic hook printing the sandbox health to the standard error.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the panic message and the sandbox health to the `path` file
+    /// instead of printing the sandbox health to the standard error.
+    ///
+    /// The file is opened when a panic occurs,
+    /// so `path` should be in a directory writable from the sandbox.
+    /// If the file cannot be written, the sandbox health is printed to the standard error.
+    pub fn set_crash_log<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.crash_log = Some(path.into());
+        self
+    }
+
+    /// Installs this panic hook for the running process,
+    /// chained with the previous one (see [`std::panic::set_hook()`]).
+    pub fn install(self) {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            previous(info);
+            self.report(info);
+        }));
+    }
+
+    // Takes a Display to be compatible with std::panic::PanicHookInfo (Rust >= 1.81) and
+    // std::panic::PanicInfo (previous Rust versions).
+    fn report(&self, info: &dyn fmt::Display) {
+        let health = SandboxHealth::current();
+        if let Some(ref path) = self.crash_log {
+            let written = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{info}\n{health}"));
+            if written.is_ok() {
+                return;
+            }
+        }
+        eprintln!("{health}");
+    }
+}
+
+// Fingerprint is not public outside this crate.
+//
+// Stable hash of a ruleset content: the same requests lead to the same fingerprint, whatever the
+// version of the Rust toolchain (contrary to std::collections::hash_map::DefaultHasher).
+// This is the 64-bit FNV-1a hash function.
+#[cfg_attr(test, derive(Debug))]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Fingerprint(u64);
+
+impl Default for Fingerprint {
+    fn default() -> Self {
+        Fingerprint(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fingerprint {
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    pub(crate) fn update_u64(&mut self, value: u64) {
+        self.update(&value.to_le_bytes());
+    }
+
+    pub(crate) fn from_u64(value: u64) -> Self {
+        Fingerprint(value)
+    }
+
+    pub(crate) fn to_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+#[test]
+fn fingerprint_fnv1a() {
+    let mut fingerprint = Fingerprint::default();
+    assert_eq!(fingerprint.to_string(), "cbf29ce484222325");
+    fingerprint.update(b"a");
+    assert_eq!(fingerprint.to_string(), "af63dc4c8601ec8c");
+
+    let mut other = Fingerprint::default();
+    other.update_u64(1);
+    assert_ne!(fingerprint, other);
+}
+
+#[test]
+fn sandbox_health_layers() {
+    // Don't explicitly call create() on a CI that doesn't support Landlock.
+    if !compat::can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+        return;
+    }
+
+    let layers = SandboxHealth::current().layers;
+    // Restricts a dedicated thread to not impact other tests.
+    let status = std::thread::spawn(|| {
+        Ruleset::from(ABI::V1)
+            .handle_access(AccessFs::Execute)
+            .unwrap()
+            .create()
+            .unwrap()
+            .restrict_self()
+            .unwrap()
+    })
+    .join()
+    .unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::FullyEnforced);
+
+    // Other tests may concurrently record their own enforcement, so only check the fields that
+    // are the same for all of them.
+    let health = SandboxHealth::current();
+    assert!(health.layers > layers);
+    assert!(health.fingerprint.is_some());
+    assert!(health.to_string().starts_with("landlock: "));
+}
+
+#[test]
+fn current_restrictions_detection() {
+    let current = current_restrictions();
+    if current == CurrentRestrictions::Unsupported {
+        return;
+    }
+    // The test process is not sandboxed.
+    assert_eq!(current, CurrentRestrictions::NotDetected);
+    if !compat::can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+        return;
+    }
+
+    // Restricts a dedicated thread to not impact other tests.
+    let create = || {
+        Ruleset::from(ABI::V1)
+            .handle_access(AccessFs::Execute)
+            .unwrap()
+            .create()
+            .unwrap()
+    };
+    let layers = std::thread::spawn(move || {
+        create().restrict_self().unwrap();
+        let first = current_restrictions();
+        create().restrict_self().unwrap();
+        (first, current_restrictions())
+    })
+    .join()
+    .unwrap();
+    assert_eq!(
+        layers,
+        (
+            CurrentRestrictions::Enforced { layers: 1 },
+            CurrentRestrictions::Enforced { layers: 2 }
+        )
+    );
+
+    // A process doesn't know about the domain it enforced without recording it, but still
+    // detects it.  This is checked in a re-executed test process to not restrict the test runner,
+    // nor to fork it while other tests are running.
+    const CHILD_ENV: &str = "LANDLOCK_TEST_CURRENT_RESTRICTIONS";
+    if std::env::var_os(CHILD_ENV).is_some() {
+        create().restrict_self_async_signal_safe().unwrap();
+        assert_eq!(current_restrictions(), CurrentRestrictions::Inherited);
+        return;
+    }
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "health::current_restrictions_detection",
+            "--exact",
+            "--nocapture",
+            "--test-threads=1",
+        ])
+        .env(CHILD_ENV, "1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success() && stdout.contains(" 1 passed;"),
+        "{stdout}{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn domain_depth_limit() {
+    if !compat::can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+        return;
+    }
+
+    // Restricts a dedicated thread to not impact other tests.
+    let (depth, error) = std::thread::spawn(|| {
+        let restrict = || {
+            Ruleset::from(ABI::V1)
+                .handle_access(AccessFs::Execute)
+                .unwrap()
+                .create()
+                .unwrap()
+                .restrict_self()
+        };
+        for _ in 0..MAX_DOMAIN_DEPTH {
+            restrict().unwrap();
+        }
+        (domain_depth(), restrict().unwrap_err())
+    })
+    .join()
+    .unwrap();
+    assert_eq!(depth, MAX_DOMAIN_DEPTH);
+    assert!(matches!(
+        error,
+        RulesetError::RestrictSelf(RestrictSelfError::DomainDepthExceeded { depth })
+            if depth == MAX_DOMAIN_DEPTH
+    ));
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    assert_eq!(domain_depth(), 0);
+}
+
+#[test]
+fn panic_hook_crash_log() {
+    let crash_log =
+        std::env::temp_dir().join(format!("landlock-panic-hook-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&crash_log);
+
+    // Restores the previous (i.e. libtest's) panic hook at the end of this test, even if it fails.
+    struct OnDrop<F: FnMut()>(F);
+    impl<F: FnMut()> Drop for OnDrop<F> {
+        fn drop(&mut self) {
+            (self.0)();
+        }
+    }
+    let previous = std::sync::Arc::new(panic::take_hook());
+    let set_previous = move || {
+        let previous = previous.clone();
+        panic::set_hook(Box::new(move |info| previous(info)));
+    };
+    set_previous();
+    let _restore = OnDrop(move || {
+        let _ = panic::take_hook();
+        set_previous();
+    });
+
+    PanicHook::new().set_crash_log(&crash_log).install();
+    assert!(std::thread::spawn(|| panic!("sandboxed crash"))
+        .join()
+        .is_err());
+
+    let log = std::fs::read_to_string(&crash_log).unwrap();
+    std::fs::remove_file(&crash_log).unwrap();
+    // Other tests may concurrently panic, so only check that this panic is logged.
+    assert!(log.contains("sandboxed crash\nlandlock: "));
+}
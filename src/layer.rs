@@ -0,0 +1,263 @@
+use crate::{
+    AccessFs, AccessNet, BitFlags, PathRule, Policy, PolicyError, PortRule, RestrictionStatus,
+    RulesetStatus,
+};
+use std::path::Path;
+
+/// Stack of policies enforced on the calling thread, one Landlock domain per policy.
+///
+/// Landlock restrictions can only be stacked:
+/// enforcing a new policy adds a new layer which can only further restrict the thread,
+/// whatever the access rights it grants.
+/// A common workflow is to enforce a broad policy at startup
+/// (e.g., to be able to load libraries and configuration files),
+/// and a stricter one once the initialization is done.
+/// `Layer` keeps track of the enforced policies to compute, with [`delta()`](Layer::delta),
+/// what a new policy would change,
+/// and to identify policies that cannot further restrict the thread
+/// or grants that would have no effect.
+///
+/// Paths are compared lexically: rule paths should then be canonical.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{Access, AccessFs, Layer, Policy, PolicyError, ABI};
+///
+/// fn two_phases() -> Result<(), PolicyError> {
+///     let abi = ABI::V1;
+///     let mut layer = Layer::new();
+///     layer.restrict_self(
+///         Policy::default()
+///             .handle_fs(AccessFs::from_all(abi))
+///             .allow_paths(["/usr", "/etc"], AccessFs::from_read(abi)),
+///     )?;
+///
+///     // Initialization...
+///
+///     let strict = Policy::default()
+///         .handle_fs(AccessFs::from_all(abi))
+///         .allow_path("/usr", AccessFs::from_read(abi));
+///     let delta = layer.delta(&strict);
+///     if !delta.restricts_further() {
+///         eprintln!("warning: the strict policy doesn't restrict more than the initial one");
+///     }
+///     layer.restrict_self(strict)?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Layer {
+    applied: Vec<Policy>,
+}
+
+/// Changes brought by a new policy on top of a [`Layer`], see [`Layer::delta()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LayerDelta {
+    /// File system access rights not handled by the previous layers,
+    /// which are then newly restricted.
+    pub newly_handled_fs: BitFlags<AccessFs>,
+    /// Network access rights not handled by the previous layers,
+    /// which are then newly restricted.
+    pub newly_handled_net: BitFlags<AccessNet>,
+    /// File system access rights already handled by the previous layers
+    /// but now denied for some paths which were allowed.
+    pub narrowed_fs: BitFlags<AccessFs>,
+    /// Network access rights already handled by the previous layers
+    /// but now denied for some ports which were allowed.
+    pub narrowed_net: BitFlags<AccessNet>,
+    /// Path rules of the new policy granting access rights denied by the previous layers,
+    /// which then have no effect (restricted to these access rights).
+    pub ineffective_paths: Vec<PathRule>,
+    /// Port rules of the new policy granting access rights denied by the previous layers,
+    /// which then have no effect (restricted to these access rights).
+    pub ineffective_ports: Vec<PortRule>,
+}
+
+impl LayerDelta {
+    /// Returns true if the new policy denies some accesses allowed by the previous layers.
+    pub fn restricts_further(&self) -> bool {
+        !self.newly_handled_fs.is_empty()
+            || !self.newly_handled_net.is_empty()
+            || !self.narrowed_fs.is_empty()
+            || !self.narrowed_net.is_empty()
+    }
+}
+
+// Returns true if policy allows access to path (with the same access rights as a parent path rule).
+fn allows_path(policy: &Policy, path: &Path, access: AccessFs) -> bool {
+    !policy.handled_fs().contains(access)
+        || policy
+            .path_rules()
+            .iter()
+            .any(|rule| rule.access.contains(access) && path.starts_with(&rule.path))
+}
+
+fn allows_port(policy: &Policy, port: u16, access: AccessNet) -> bool {
+    !policy.handled_net().contains(access)
+        || policy
+            .port_rules()
+            .iter()
+            .any(|rule| rule.access.contains(access) && rule.port == port)
+}
+
+impl Layer {
+    /// Creates an empty layer, which doesn't know about domains enforced by other means.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the policies enforced with [`restrict_self()`](Layer::restrict_self),
+    /// in the enforcement order.
+    pub fn applied(&self) -> &[Policy] {
+        &self.applied
+    }
+
+    fn allows_path(&self, path: &Path, access: AccessFs) -> bool {
+        self.applied.iter().all(|p| allows_path(p, path, access))
+    }
+
+    fn allows_port(&self, port: u16, access: AccessNet) -> bool {
+        self.applied.iter().all(|p| allows_port(p, port, access))
+    }
+
+    /// Computes the changes `policy` would bring on top of the enforced policies.
+    pub fn delta(&self, policy: &Policy) -> LayerDelta {
+        let handled_fs = self
+            .applied
+            .iter()
+            .fold(BitFlags::EMPTY, |acc, p| acc | p.handled_fs());
+        let handled_net = self
+            .applied
+            .iter()
+            .fold(BitFlags::EMPTY, |acc, p| acc | p.handled_net());
+
+        // Paths and ports allowed by the previous layers, but maybe not by the new policy.
+        let mut narrowed_fs = BitFlags::EMPTY;
+        for rule in self.applied.iter().flat_map(|p| p.path_rules()) {
+            for access in rule.access & handled_fs & policy.handled_fs() {
+                if self.allows_path(&rule.path, access) && !allows_path(policy, &rule.path, access)
+                {
+                    narrowed_fs |= access;
+                }
+            }
+        }
+        let mut narrowed_net = BitFlags::EMPTY;
+        for rule in self.applied.iter().flat_map(|p| p.port_rules()) {
+            for access in rule.access & handled_net & policy.handled_net() {
+                if self.allows_port(rule.port, access) && !allows_port(policy, rule.port, access) {
+                    narrowed_net |= access;
+                }
+            }
+        }
+
+        let ineffective_paths = policy
+            .path_rules()
+            .iter()
+            .filter_map(|rule| {
+                let access = rule
+                    .access
+                    .iter()
+                    .filter(|a| !self.allows_path(&rule.path, *a))
+                    .collect::<BitFlags<_>>();
+                if access.is_empty() {
+                    None
+                } else {
+                    Some(PathRule {
+                        path: rule.path.clone(),
+                        access,
+                    })
+                }
+            })
+            .collect();
+        let ineffective_ports = policy
+            .port_rules()
+            .iter()
+            .filter_map(|rule| {
+                let access = rule
+                    .access
+                    .iter()
+                    .filter(|a| !self.allows_port(rule.port, *a))
+                    .collect::<BitFlags<_>>();
+                if access.is_empty() {
+                    None
+                } else {
+                    Some(PortRule {
+                        port: rule.port,
+                        access,
+                    })
+                }
+            })
+            .collect();
+
+        LayerDelta {
+            newly_handled_fs: policy.handled_fs() & !handled_fs,
+            newly_handled_net: policy.handled_net() & !handled_net,
+            narrowed_fs,
+            narrowed_net,
+            ineffective_paths,
+            ineffective_ports,
+        }
+    }
+
+    /// Enforces `policy` on the calling thread as a new layer (see [`Policy::restrict_self()`]).
+    ///
+    /// The policy is only tracked if it is (at least partially) enforced.
+    pub fn restrict_self(&mut self, policy: Policy) -> Result<RestrictionStatus, PolicyError> {
+        let status = policy.restrict_self()?;
+        if status.ruleset != RulesetStatus::NotEnforced {
+            self.applied.push(policy);
+        }
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn layer_delta() {
+        let abi = ABI::V4;
+        let mut layer = Layer::new();
+        let broad = Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .allow_paths(["/usr", "/etc"], AccessFs::from_read(abi))
+            .allow_path("/tmp", AccessFs::from_all(abi));
+        let delta = layer.delta(&broad);
+        assert_eq!(delta.newly_handled_fs, AccessFs::from_all(abi));
+        assert!(delta.restricts_further());
+        assert!(delta.ineffective_paths.is_empty());
+        // Simulates the enforcement.
+        layer.applied.push(broad.clone());
+
+        // Same policy.
+        let delta = layer.delta(&broad);
+        assert!(!delta.restricts_further());
+        assert!(delta.ineffective_paths.is_empty());
+
+        // Stricter policy, which also tries to grant new accesses.
+        let strict = Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .handle_net(AccessNet::from_all(abi))
+            .allow_path("/usr", AccessFs::from_read(abi))
+            .allow_path("/tmp/foo", AccessFs::from_all(abi))
+            .allow_path("/etc", AccessFs::from_all(abi))
+            .allow_port(443, AccessNet::ConnectTcp);
+        let delta = layer.delta(&strict);
+        assert!(delta.restricts_further());
+        assert_eq!(delta.newly_handled_net, AccessNet::from_all(abi));
+        // /tmp and /etc are not fully allowed anymore.
+        assert_eq!(delta.narrowed_fs, AccessFs::from_all(abi));
+        assert_eq!(
+            delta.ineffective_paths,
+            [PathRule {
+                path: "/etc".into(),
+                access: AccessFs::from_write(abi),
+            }]
+        );
+        assert!(delta.ineffective_ports.is_empty());
+    }
+}
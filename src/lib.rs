@@ -27,7 +27,7 @@
 //!
 //! # Current limitations
 //!
-//! This crate exposes the Landlock features available as of Linux 5.19
+//! This crate exposes the Landlock features available as of Linux 6.7
 //! and then inherits some [kernel limitations](https://www.kernel.org/doc/html/latest/userspace-api/landlock.html#current-limitations)
 //! that will be addressed with future kernel releases
 //! (e.g., arbitrary mounts are always denied).
@@ -47,6 +47,9 @@
 //! (e.g., empty or inconsistent access rights).
 //! [`RulesetError`] identifies such kind of errors.
 //!
+//! Programs which must fail closed can create their ruleset with [`Ruleset::strict()`] instead,
+//! which sets [`CompatLevel::HardRequirement`].
+//!
 //! With [`set_compatibility(CompatLevel::BestEffort)`](Compatible::set_compatibility),
 //! users of the crate may mark Landlock features that are deemed required
 //! and other features that may be downgraded to use lower security on systems
@@ -79,21 +82,71 @@
 #[macro_use]
 extern crate lazy_static;
 
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
 pub use access::Access;
-pub use compat::{CompatLevel, Compatible, ABI};
+pub use access_set::AccessSet;
+pub use activation::{ListenFd, ListenFds};
+pub use backend::{LandlockBackend, SandboxBackend};
+#[cfg(feature = "clap")]
+pub use cli::SandboxArgs;
+pub use compat::{CompatLevel, Compatible, Downgrade, ABI};
+#[cfg(feature = "conformance")]
+pub use conformance::{run_conformance, ConformanceOutcome, ConformanceReport};
 pub use enumflags2::{make_bitflags, BitFlags};
+#[cfg(feature = "uniffi")]
+pub use errors::BindingError;
+#[cfg(feature = "caps")]
+pub use errors::CapsError;
+#[cfg(feature = "conformance")]
+pub use errors::ConformanceError;
+#[cfg(feature = "hardening")]
+pub use errors::HardeningError;
+#[cfg(all(feature = "seccomp", any(target_os = "linux", target_os = "android")))]
+pub use errors::SeccompError;
+#[cfg(all(
+    feature = "namespaces",
+    any(target_os = "linux", target_os = "android")
+))]
+pub use errors::UnshareError;
 pub use errors::{
-    AccessError, AddRuleError, AddRulesError, CompatError, CreateRulesetError, HandleAccessError,
-    HandleAccessesError, PathBeneathError, PathFdError, RestrictSelfError, RulesetError,
+    AccessError, AddRuleError, AddRulesError, BulkRulesError, CompatError, CreateRulesetError,
+    Errno, ErrorKind, ForkError, HandleAccessError, HandleAccessesError, ListenFdsError,
+    NoNewPrivsError, ParseAbiError, ParseAccessError, ParseAuditError, ParsePolicyError,
+    PathBeneathError, PathFdError, PolicyError, ReexecError, RequireAbiError, RestrictSelfError,
+    RulesetError, WireError,
+};
+pub use fs::{
+    path_beneath_rules, path_beneath_rules_in, AccessFs, OpenAccess, PathBeneath, PathFd, Resolve,
+};
+pub use health::{
+    current_restrictions, domain_depth, CurrentRestrictions, PanicHook, SandboxHealth,
+    MAX_DOMAIN_DEPTH,
+};
+pub use layer::{Layer, LayerDelta};
+pub use mount::{MountInfo, MountWarning};
+pub use net::{AccessNet, NetPort};
+pub use policy::{PathRule, Policy, PolicyFinding, PortRule, UngrantedAccessLint};
+pub use policy_fs::{HostFs, MockFs, PolicyFs, ResolveFs, RootedFs};
+pub use privs::{no_new_privs_enabled, set_no_new_privs};
+pub use process::{
+    fork_and_exec, fork_and_restrict, reexec_and_restrict, CommandExt, RestrictedChild,
 };
-pub use fs::{path_beneath_rules, AccessFs, PathBeneath, PathFd};
+pub use report::{AccessReport, CompatOutcome, CompatReport, RuleReport, Telemetry};
 pub use ruleset::{
     RestrictionStatus, Rule, Ruleset, RulesetAttr, RulesetCreated, RulesetCreatedAttr,
-    RulesetStatus,
+    RulesetMetadata, RulesetStatus, ThreadCheck,
 };
+pub use systemd::{SystemdDirective, SystemdReport};
 
 use access::PrivateAccess;
-use compat::{CompatResult, CompatState, Compatibility, TailoredCompatLevel, TryCompat};
+use compat::{
+    CompatResult, CompatState, Compatibility, DowngradeObserver, Downgrades, TailoredCompatLevel,
+    TryCompat,
+};
+use fs::{is_file, ACCESS_FILE};
+use health::Fingerprint;
 use ruleset::PrivateRule;
 
 #[cfg(test)]
@@ -104,11 +157,61 @@ use errors::TestRulesetError;
 use strum::IntoEnumIterator;
 
 mod access;
+mod access_set;
+mod activation;
+pub mod audit;
+mod backend;
+#[cfg(feature = "broker")]
+pub mod broker;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "caps")]
+pub mod caps;
+#[cfg(feature = "clap")]
+mod cli;
 mod compat;
+#[cfg(feature = "conformance")]
+mod conformance;
+#[cfg(feature = "miette")]
+mod diagnostic;
 mod errors;
 mod fs;
+#[cfg(feature = "hardening")]
+pub mod hardening;
+mod health;
+mod layer;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod mount;
+#[cfg(all(
+    feature = "namespaces",
+    any(target_os = "linux", target_os = "android")
+))]
+pub mod namespaces;
+mod net;
+#[cfg(feature = "oci")]
+pub mod oci;
+mod policy;
+mod policy_fs;
+mod privs;
+mod process;
+#[cfg(feature = "unstable-uapi")]
+pub mod raw;
+mod report;
 mod ruleset;
+mod scm;
+#[cfg(all(feature = "seccomp", any(target_os = "linux", target_os = "android")))]
+pub mod seccomp;
+#[cfg(feature = "serde")]
+pub mod serde_names;
+mod systemd;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+pub mod thread;
 mod uapi;
+#[cfg(feature = "uniffi")]
+pub mod uniffi_api;
+mod wire;
 
 #[cfg(test)]
 mod tests {
@@ -156,6 +259,7 @@ mod tests {
                         Ok(RestrictionStatus {
                             ruleset,
                             no_new_privs: true,
+                            ..
                         }) if ruleset == ruleset_status
                     ))
                 }
@@ -343,4 +447,21 @@ mod tests {
             false,
         );
     }
+
+    #[test]
+    fn abi_v4_tcp() {
+        check_ruleset_support(
+            ABI::V3,
+            Some(ABI::V4),
+            move |ruleset: Ruleset| -> _ {
+                Ok(ruleset
+                    .handle_access(AccessFs::Truncate)?
+                    .handle_access(AccessNet::BindTcp | AccessNet::ConnectTcp)?
+                    .create()?
+                    .add_rule(NetPort::new(1, AccessNet::ConnectTcp))?
+                    .restrict_self()?)
+            },
+            false,
+        );
+    }
 }
@@ -0,0 +1,193 @@
+// Metrics emitted with the metrics feature when a ruleset is enforced, see
+// RulesetCreated::restrict_self().  The crate name is prefixed with :: to not refer to this
+// module.
+
+use crate::{Access, AccessFs, AccessNet, BitFlags, CompatOutcome, RuleReport, RulesetStatus, ABI};
+
+const ENFORCEMENTS: &str = "landlock_enforcements_total";
+const ABI_VERSION: &str = "landlock_abi_version";
+const RULES: &str = "landlock_rules_total";
+const DOWNGRADED_ACCESSES: &str = "landlock_downgraded_accesses_total";
+
+fn status_label(status: RulesetStatus) -> &'static str {
+    match status {
+        RulesetStatus::FullyEnforced => "fully_enforced",
+        RulesetStatus::PartiallyEnforced => "partially_enforced",
+        RulesetStatus::NotEnforced => "not_enforced",
+    }
+}
+
+fn record_downgrades<A>(kind: &'static str, access: BitFlags<A>)
+where
+    A: Access,
+{
+    for a in access.iter() {
+        ::metrics::counter!(DOWNGRADED_ACCESSES, "type" => kind, "access" => a.name()).increment(1);
+    }
+}
+
+pub(crate) fn record_enforcement(
+    status: RulesetStatus,
+    abi: ABI,
+    rules: &[RuleReport],
+    downgrades_fs: BitFlags<AccessFs>,
+    downgrades_net: BitFlags<AccessNet>,
+) {
+    ::metrics::counter!(ENFORCEMENTS, "status" => status_label(status)).increment(1);
+    ::metrics::gauge!(ABI_VERSION).set(abi as i32);
+    for rule in rules {
+        let kind = match rule {
+            RuleReport::Fs { .. } => "fs",
+            RuleReport::Net { .. } => "net",
+        };
+        let outcome = match rule.outcome() {
+            CompatOutcome::Applied => "applied",
+            CompatOutcome::Degraded => "degraded",
+            CompatOutcome::Dropped => "dropped",
+        };
+        ::metrics::counter!(RULES, "type" => kind, "outcome" => outcome).increment(1);
+    }
+    record_downgrades("fs", downgrades_fs);
+    record_downgrades("net", downgrades_net);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::metrics::{
+        Counter, CounterFn, Gauge, GaugeFn, Histogram, Key, KeyName, Metadata, Recorder,
+        SharedString, Unit,
+    };
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    type Values = Arc<Mutex<BTreeMap<String, f64>>>;
+
+    // Minimal recorder keeping the last value of each counter and gauge, identified by its
+    // name and labels.
+    #[derive(Default)]
+    struct TestRecorder {
+        values: Values,
+    }
+
+    struct TestHandle {
+        id: String,
+        values: Values,
+    }
+
+    impl TestHandle {
+        fn update<F>(&self, f: F)
+        where
+            F: FnOnce(&mut f64),
+        {
+            f(self
+                .values
+                .lock()
+                .unwrap()
+                .entry(self.id.clone())
+                .or_default());
+        }
+    }
+
+    impl CounterFn for TestHandle {
+        fn increment(&self, value: u64) {
+            self.update(|v| *v += value as f64);
+        }
+
+        fn absolute(&self, value: u64) {
+            self.update(|v| *v = value as f64);
+        }
+    }
+
+    impl GaugeFn for TestHandle {
+        fn increment(&self, value: f64) {
+            self.update(|v| *v += value);
+        }
+
+        fn decrement(&self, value: f64) {
+            self.update(|v| *v -= value);
+        }
+
+        fn set(&self, value: f64) {
+            self.update(|v| *v = value);
+        }
+    }
+
+    impl TestRecorder {
+        fn handle(&self, key: &Key) -> Arc<TestHandle> {
+            let labels = key
+                .labels()
+                .map(|l| format!("{}={}", l.key(), l.value()))
+                .collect::<Vec<_>>()
+                .join(",");
+            Arc::new(TestHandle {
+                id: format!("{}{{{labels}}}", key.name()),
+                values: self.values.clone(),
+            })
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.handle(key))
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::from_arc(self.handle(key))
+        }
+
+        fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    #[test]
+    fn metrics_enforcement() {
+        use crate::{uapi, NetPort, Ruleset, RulesetAttr, RulesetCreatedAttr};
+
+        let recorder = TestRecorder::default();
+        ::metrics::with_local_recorder(&recorder, || {
+            let _kernel = uapi::MockKernel::new(ABI::V3).install();
+            Ruleset::default()
+                .handle_access(AccessFs::Execute | AccessFs::Truncate)
+                .unwrap()
+                .handle_access(AccessNet::BindTcp)
+                .unwrap()
+                .create()
+                .unwrap()
+                .add_rule(NetPort::new(80, AccessNet::BindTcp))
+                .unwrap()
+                .restrict_self()
+                .unwrap();
+        });
+
+        let metrics = recorder
+            .values
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(k, v)| (k.clone(), *v))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            metrics,
+            [
+                ("landlock_abi_version{}".into(), 3.0),
+                (
+                    "landlock_downgraded_accesses_total{type=net,access=bind_tcp}".into(),
+                    1.0
+                ),
+                (
+                    "landlock_enforcements_total{status=partially_enforced}".into(),
+                    1.0
+                ),
+                ("landlock_rules_total{type=net,outcome=dropped}".into(), 1.0),
+            ]
+        );
+    }
+}
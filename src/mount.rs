@@ -0,0 +1,320 @@
+use crate::Policy;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
+
+/// Mount point of the current mount namespace, as listed in `/proc/self/mountinfo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MountInfo {
+    /// Unique identifier of the mount.
+    pub id: u32,
+    /// Identifier of the parent mount (or of itself for the root of the namespace).
+    pub parent_id: u32,
+    /// Mount point relative to the process's root directory.
+    pub mount_point: PathBuf,
+    /// File system type (e.g. `ext4` or `overlay`).
+    pub fs_type: String,
+}
+
+impl MountInfo {
+    /// Reads the mount points of the calling process's mount namespace.
+    pub fn read_current() -> io::Result<Vec<Self>> {
+        Ok(Self::parse(&fs::read("/proc/self/mountinfo")?))
+    }
+
+    /// Parses the content of a `/proc/<pid>/mountinfo` file, ignoring malformed lines.
+    pub fn parse(content: &[u8]) -> Vec<Self> {
+        content
+            .split(|b| *b == b'\n')
+            .filter_map(Self::parse_line)
+            .collect()
+    }
+
+    // Format: "36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw"
+    fn parse_line(line: &[u8]) -> Option<Self> {
+        let mut fields = line.split(|b| *b == b' ');
+        let id = parse_u32(fields.next()?)?;
+        let parent_id = parse_u32(fields.next()?)?;
+        let mount_point = unescape(fields.nth(2)?);
+        // Skips the mount options and the optional fields.
+        let mut fields = fields.skip_while(|f| *f != b"-").skip(1);
+        let fs_type = String::from_utf8_lossy(fields.next()?).into_owned();
+        Some(MountInfo {
+            id,
+            parent_id,
+            mount_point: OsString::from_vec(mount_point).into(),
+            fs_type,
+        })
+    }
+}
+
+fn parse_u32(field: &[u8]) -> Option<u32> {
+    std::str::from_utf8(field).ok()?.parse().ok()
+}
+
+// Decodes the octal escapes (e.g. "\040" for a space) of the mountinfo fields.
+fn unescape(field: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(field.len());
+    let mut i = 0;
+    while i < field.len() {
+        let octal = field
+            .get(i + 1..i + 4)
+            .filter(|d| field[i] == b'\\' && d.iter().all(|b| (b'0'..=b'7').contains(b)));
+        match octal {
+            Some(d) => {
+                out.push(
+                    d.iter()
+                        .fold(0u8, |acc, b| acc.wrapping_mul(8) + (b - b'0')),
+                );
+                i += 4;
+            }
+            None => {
+                out.push(field[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Issue found by [`Policy::check_mounts()`], which may lead to unexpected denials or accesses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MountWarning {
+    /// The path rule `index` identifies a file hierarchy of a mount
+    /// which is hidden by the later mount on `mount_point`.
+    /// The rule then applies to the file hierarchy of this later mount, if the path still exists.
+    Shadowed { index: usize, mount_point: PathBuf },
+    /// The path rule `index` identifies a file hierarchy of a `fs_type` file system
+    /// on which Landlock rules may not behave as expected:
+    /// * `overlay`: rules on the merged hierarchy don't apply to the underlying layers,
+    ///   and the other way around;
+    /// * `proc`, `sysfs`...: files may be created or replaced by the kernel at any time,
+    ///   which then loses the rules tied to them;
+    /// * `fuse`, `nfs`, `cifs`...: the identified files may change on the remote side.
+    UnusualFs { index: usize, fs_type: String },
+}
+
+impl fmt::Display for MountWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MountWarning::Shadowed { index, mount_point } => write!(
+                f,
+                "path rule #{index} is shadowed by a later mount on {}",
+                mount_point.display()
+            ),
+            MountWarning::UnusualFs { index, fs_type } => write!(
+                f,
+                "path rule #{index} is on a {fs_type} file system, \
+                whose Landlock semantics may be surprising"
+            ),
+        }
+    }
+}
+
+const UNUSUAL_FS: &[&str] = &[
+    "overlay",
+    "proc",
+    "sysfs",
+    "debugfs",
+    "tracefs",
+    "securityfs",
+    "cgroup",
+    "cgroup2",
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "9p",
+];
+
+fn is_unusual_fs(fs_type: &str) -> bool {
+    UNUSUAL_FS.contains(&fs_type) || fs_type == "fuse" || fs_type.starts_with("fuse.")
+}
+
+// Returns true if `ancestor` is a parent (or a grand-parent...) of `mount`.
+fn is_ancestor(mounts: &[MountInfo], ancestor: &MountInfo, mount: &MountInfo) -> bool {
+    let mut current = mount;
+    // Bounds the walk in case of a cycle in malformed data.
+    for _ in 0..mounts.len() {
+        if current.parent_id == ancestor.id {
+            return true;
+        }
+        match mounts
+            .iter()
+            .find(|m| m.id == current.parent_id && m.id != current.id)
+        {
+            Some(parent) => current = parent,
+            None => return false,
+        }
+    }
+    false
+}
+
+// Gets the later mount hiding the mount at `index`, if any.
+fn shadowing(mounts: &[MountInfo], index: usize) -> Option<&MountInfo> {
+    let mount = &mounts[index];
+    mounts[index + 1..].iter().rev().find(|later| {
+        mount.mount_point.starts_with(&later.mount_point) && !is_ancestor(mounts, later, mount)
+    })
+}
+
+impl Policy {
+    /// Compares the path rules of this policy with the mount points of the calling process,
+    /// see [`check_mounts_with()`](Policy::check_mounts_with).
+    pub fn check_mounts(&self) -> io::Result<Vec<MountWarning>> {
+        Ok(self.check_mounts_with(&MountInfo::read_current()?))
+    }
+
+    /// Compares the path rules of this policy with `mounts`,
+    /// in the order of a `mountinfo` file (see [`MountInfo::parse()`]).
+    ///
+    /// This warns about rules whose paths are shadowed by a later mount,
+    /// or are on a file system with surprising Landlock semantics.
+    /// The paths are compared as they are written:
+    /// see [`normalize_paths()`](Policy::normalize_paths) to resolve them first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Access, AccessFs, MountInfo, MountWarning, Policy, ABI};
+    ///
+    /// let mounts = MountInfo::parse(
+    ///     b"1 1 8:1 / / rw - ext4 /dev/sda1 rw\n\
+    ///       2 1 0:5 / /proc rw - proc proc rw\n",
+    /// );
+    /// let warnings = Policy::default()
+    ///     .handle_fs(AccessFs::from_all(ABI::V1))
+    ///     .allow_path("/usr", AccessFs::from_read(ABI::V1))
+    ///     .allow_path("/proc/self", AccessFs::from_read(ABI::V1))
+    ///     .check_mounts_with(&mounts);
+    /// assert!(matches!(
+    ///     warnings[..],
+    ///     [MountWarning::UnusualFs { index: 1, .. }]
+    /// ));
+    /// ```
+    pub fn check_mounts_with(&self, mounts: &[MountInfo]) -> Vec<MountWarning> {
+        let mut warnings = Vec::new();
+        for (index, rule) in self.path_rules().iter().enumerate() {
+            let path: &Path = &rule.path;
+            // The innermost mount containing the path, as listed.
+            let containing = mounts
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| path.starts_with(&m.mount_point))
+                .max_by_key(|(i, m)| (m.mount_point.components().count(), *i));
+            let (i, mount) = match containing {
+                Some(containing) => containing,
+                None => continue,
+            };
+            let mount = match shadowing(mounts, i) {
+                Some(later) => {
+                    warnings.push(MountWarning::Shadowed {
+                        index,
+                        mount_point: later.mount_point.clone(),
+                    });
+                    later
+                }
+                None => mount,
+            };
+            if is_unusual_fs(&mount.fs_type) {
+                warnings.push(MountWarning::UnusualFs {
+                    index,
+                    fs_type: mount.fs_type.clone(),
+                });
+            }
+        }
+        warnings
+    }
+}
+
+#[test]
+fn mount_info_parse() {
+    let mounts = MountInfo::parse(
+        b"22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw\n\
+          23 22 0:21 / /proc rw,nosuid - proc proc rw\n\
+          24 22 0:22 / /mnt/with\\040space rw shared:2 master:3 - fuse.sshfs host: rw\n\
+          malformed line\n",
+    );
+    assert_eq!(
+        mounts,
+        [
+            MountInfo {
+                id: 22,
+                parent_id: 1,
+                mount_point: "/".into(),
+                fs_type: "ext4".into(),
+            },
+            MountInfo {
+                id: 23,
+                parent_id: 22,
+                mount_point: "/proc".into(),
+                fs_type: "proc".into(),
+            },
+            MountInfo {
+                id: 24,
+                parent_id: 22,
+                mount_point: "/mnt/with space".into(),
+                fs_type: "fuse.sshfs".into(),
+            },
+        ]
+    );
+
+    // The running system's mount table is parsable.
+    if let Ok(current) = MountInfo::read_current() {
+        assert!(!current.is_empty());
+    }
+}
+
+#[test]
+fn policy_check_mounts() {
+    use crate::{Access, AccessFs, ABI};
+
+    let mounts = MountInfo::parse(
+        b"1 1 8:1 / / rw - ext4 /dev/sda1 rw\n\
+          2 1 0:5 / /proc rw - proc proc rw\n\
+          3 1 8:2 / /srv/data rw - ext4 /dev/sda2 rw\n\
+          4 3 8:3 / /srv/data/sub rw - ext4 /dev/sda3 rw\n\
+          5 1 0:30 / /srv rw - overlay overlay rw\n\
+          6 1 0:31 / /home rw - tmpfs tmpfs rw\n\
+          7 6 8:4 / /home/user rw - nfs4 server:/user rw\n",
+    );
+    let warnings = Policy::default()
+        .handle_fs(AccessFs::from_all(ABI::V1))
+        .allow_path("/usr", AccessFs::from_read(ABI::V1))
+        .allow_path("/proc/self", AccessFs::from_read(ABI::V1))
+        .allow_path("/srv/data/sub/x", AccessFs::from_read(ABI::V1))
+        // A child mount is not shadowed by its parent.
+        .allow_path("/home/user/doc", AccessFs::from_read(ABI::V1))
+        .allow_path("/home/other", AccessFs::from_read(ABI::V1))
+        .check_mounts_with(&mounts);
+    assert_eq!(
+        warnings,
+        [
+            MountWarning::UnusualFs {
+                index: 1,
+                fs_type: "proc".into(),
+            },
+            MountWarning::Shadowed {
+                index: 2,
+                mount_point: "/srv".into(),
+            },
+            MountWarning::UnusualFs {
+                index: 2,
+                fs_type: "overlay".into(),
+            },
+            MountWarning::UnusualFs {
+                index: 3,
+                fs_type: "nfs4".into(),
+            },
+        ]
+    );
+    assert_eq!(
+        warnings[1].to_string(),
+        "path rule #2 is shadowed by a later mount on /srv"
+    );
+}
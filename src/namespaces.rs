@@ -0,0 +1,279 @@
+//! Namespaces created along with the ruleset enforcement.
+//!
+//! Landlock restricts access to existing resources,
+//! whereas namespaces give a process its own view of some of them.
+//! For instance, a new network namespace only contains a loopback interface (down),
+//! which denies any network access,
+//! and a new mount namespace can get a private and empty `/tmp` directory.
+//! [`Unshare`] creates such namespaces with `unshare(2)` right before enforcing a ruleset,
+//! because a sandboxed process cannot change its mount points anymore.
+//!
+//! This is only available on Linux with the `namespaces` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use landlock::namespaces::Unshare;
+//! use landlock::{Access, AccessFs, Ruleset, RulesetAttr, UnshareError, ABI};
+//!
+//! fn isolate() -> Result<(), UnshareError> {
+//!     let ruleset = Ruleset::default()
+//!         .handle_access(AccessFs::from_all(ABI::V1))?
+//!         .create()?;
+//!     let status = Unshare::new()
+//!         .user(true)
+//!         .network(true)
+//!         .private_tmp(true)
+//!         .restrict_self(ruleset)?;
+//!     println!("new namespaces: {:?}", status.namespaces);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{BitFlags, RestrictionStatus, RulesetCreated, UnshareError};
+use enumflags2::bitflags;
+use std::ffi::CString;
+use std::io::Error;
+use std::os::raw::{c_int, c_ulong};
+use std::ptr;
+
+/// Namespace type, see `namespaces(7)`.
+#[bitflags]
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Namespace {
+    /// User namespace (`CLONE_NEWUSER`),
+    /// which enables unprivileged processes to create the other namespaces.
+    User = 1 << 0,
+    /// Mount namespace (`CLONE_NEWNS`).
+    Mount = 1 << 1,
+    /// Network namespace (`CLONE_NEWNET`).
+    Network = 1 << 2,
+}
+
+impl Namespace {
+    fn clone_flag(self) -> c_int {
+        match self {
+            Namespace::User => libc::CLONE_NEWUSER,
+            Namespace::Mount => libc::CLONE_NEWNS,
+            Namespace::Network => libc::CLONE_NEWNET,
+        }
+    }
+}
+
+/// Namespaces to create before enforcing a ruleset
+/// with [`restrict_self()`](Unshare::restrict_self).
+///
+/// The calling process must be single-threaded,
+/// otherwise `unshare(2)` fails with `EINVAL` for user and mount namespaces.
+#[derive(Debug, Clone)]
+pub struct Unshare {
+    namespaces: BitFlags<Namespace>,
+    private_tmp: bool,
+}
+
+impl Default for Unshare {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Unshare {
+    /// Creates a configuration without any new namespace.
+    pub fn new() -> Self {
+        Unshare {
+            namespaces: BitFlags::EMPTY,
+            private_tmp: false,
+        }
+    }
+
+    /// Configures whether to create a user namespace,
+    /// in which the current user and group are mapped to themselves.
+    ///
+    /// This is required to create the other namespaces without `CAP_SYS_ADMIN`.
+    pub fn user(mut self, user: bool) -> Self {
+        self.namespaces.set(Namespace::User, user);
+        self
+    }
+
+    /// Configures whether to create a mount namespace.
+    ///
+    /// The propagation of all the mount points is then set to private,
+    /// so that the new mount points are not visible outside of this namespace.
+    pub fn mount(mut self, mount: bool) -> Self {
+        self.namespaces.set(Namespace::Mount, mount);
+        self
+    }
+
+    /// Configures whether to create a network namespace,
+    /// which isolates the process from the network interfaces and the abstract Unix sockets.
+    pub fn network(mut self, network: bool) -> Self {
+        self.namespaces.set(Namespace::Network, network);
+        self
+    }
+
+    /// Configures whether to mount an empty tmpfs on `/tmp`,
+    /// which implies a mount namespace.
+    pub fn private_tmp(mut self, private_tmp: bool) -> Self {
+        self.private_tmp = private_tmp;
+        if private_tmp {
+            self.namespaces |= Namespace::Mount;
+        }
+        self
+    }
+
+    /// Creates the namespaces and then enforces `ruleset`
+    /// with [`RulesetCreated::restrict_self()`].
+    ///
+    /// The steps are applied in this order:
+    /// 1. `unshare(2)` with all the namespaces at once;
+    /// 2. the user and group mappings, for a user namespace;
+    /// 3. the private mount propagation and then `/tmp`, for a mount namespace;
+    /// 4. the ruleset enforcement.
+    ///
+    /// If a step fails, the next ones (including the ruleset enforcement) are not applied.
+    pub fn restrict_self(&self, ruleset: RulesetCreated) -> Result<UnshareStatus, UnshareError> {
+        // Must be read before creating the user namespace, in which they are not mapped yet.
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+
+        if !self.namespaces.is_empty() {
+            let flags = self
+                .namespaces
+                .iter()
+                .fold(0, |flags, ns| flags | ns.clone_flag());
+            if unsafe { libc::unshare(flags) } != 0 {
+                return Err(UnshareError::UnshareCall {
+                    namespaces: self.namespaces,
+                    source: Error::last_os_error(),
+                });
+            }
+        }
+
+        if self.namespaces.contains(Namespace::User) {
+            map_ids(uid, gid).map_err(|source| UnshareError::IdMapCall { source })?;
+        }
+
+        if self.namespaces.contains(Namespace::Mount) {
+            mount(None, "/", None, libc::MS_REC | libc::MS_PRIVATE, None)?;
+            if self.private_tmp {
+                mount(
+                    Some("tmpfs"),
+                    "/tmp",
+                    Some("tmpfs"),
+                    libc::MS_NOSUID | libc::MS_NODEV,
+                    Some("mode=1777"),
+                )?;
+            }
+        }
+
+        Ok(UnshareStatus {
+            namespaces: self.namespaces,
+            private_tmp: self.private_tmp,
+            ruleset: ruleset.restrict_self()?,
+        })
+    }
+}
+
+/// Status of the namespace creation and of the ruleset enforcement,
+/// returned by [`Unshare::restrict_self()`].
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UnshareStatus {
+    /// Created namespaces.
+    pub namespaces: BitFlags<Namespace>,
+    /// True if an empty tmpfs is mounted on `/tmp`.
+    pub private_tmp: bool,
+    /// Status of the ruleset enforcement.
+    pub ruleset: RestrictionStatus,
+}
+
+fn map_ids(uid: libc::uid_t, gid: libc::gid_t) -> Result<(), Error> {
+    // Writing the group mapping requires to deny setgroups(2) without CAP_SETGID.
+    std::fs::write("/proc/self/setgroups", "deny")?;
+    std::fs::write("/proc/self/gid_map", format!("{gid} {gid} 1"))?;
+    std::fs::write("/proc/self/uid_map", format!("{uid} {uid} 1"))
+}
+
+fn mount(
+    source: Option<&str>,
+    target: &str,
+    fstype: Option<&str>,
+    flags: c_ulong,
+    data: Option<&str>,
+) -> Result<(), UnshareError> {
+    // These constant strings don't contain any nul byte.
+    let cstring = |s: Option<&str>| s.map(|s| CString::new(s).unwrap());
+    let as_ptr = |s: &Option<CString>| s.as_ref().map(|s| s.as_ptr()).unwrap_or(ptr::null());
+    let (source, fstype, data) = (cstring(source), cstring(fstype), cstring(data));
+    let target_c = CString::new(target).unwrap();
+    let ret = unsafe {
+        libc::mount(
+            as_ptr(&source),
+            target_c.as_ptr(),
+            as_ptr(&fstype),
+            flags,
+            as_ptr(&data).cast(),
+        )
+    };
+    match ret {
+        0 => Ok(()),
+        _ => Err(UnshareError::MountCall {
+            target: target.into(),
+            source: Error::last_os_error(),
+        }),
+    }
+}
+
+#[test]
+fn unshare_restrict_self() {
+    use crate::{Access, AccessFs, Ruleset, RulesetAttr, RulesetStatus, ABI};
+
+    // Exit codes of the forked child.
+    const SKIP: i32 = 2;
+    const FAILED: i32 = 3;
+
+    let ruleset = Ruleset::from(ABI::Unsupported)
+        .handle_access(AccessFs::from_all(ABI::V1))
+        .unwrap()
+        .create()
+        .unwrap();
+    let net_ns = || std::fs::read_link("/proc/self/ns/net").ok();
+    let parent_net_ns = net_ns();
+    // The test process is multithreaded, whereas unshare(2) requires a single thread.
+    let pid = unsafe { libc::fork() };
+    assert!(pid >= 0);
+    if pid == 0 {
+        let code = match Unshare::new()
+            .user(true)
+            .network(true)
+            .private_tmp(true)
+            .restrict_self(ruleset)
+        {
+            // User namespaces may be disabled or denied by the system configuration.
+            Err(UnshareError::UnshareCall { .. }) => SKIP,
+            Err(_) => FAILED,
+            Ok(status) => {
+                let is_tmp_empty = std::fs::read_dir("/tmp")
+                    .map(|mut d| d.next().is_none())
+                    .unwrap_or(false);
+                let is_net_isolated = net_ns() != parent_net_ns;
+                if status.namespaces == Namespace::User | Namespace::Mount | Namespace::Network
+                    && status.private_tmp
+                    && status.ruleset.ruleset == RulesetStatus::NotEnforced
+                    && is_tmp_empty
+                    && is_net_isolated
+                {
+                    0
+                } else {
+                    FAILED
+                }
+            }
+        };
+        unsafe { libc::_exit(code) };
+    }
+    let mut status = 0;
+    assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+    assert!(libc::WIFEXITED(status));
+    assert_ne!(libc::WEXITSTATUS(status), FAILED);
+}
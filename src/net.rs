@@ -0,0 +1,334 @@
+use crate::access::parse_name;
+use crate::compat::private::OptionCompatLevelMut;
+use crate::{
+    uapi, Access, AccessReport, AddRuleError, AddRulesError, CompatError, CompatLevel,
+    CompatResult, CompatState, Compatible, Downgrade, Downgrades, Fingerprint, HandleAccessError,
+    HandleAccessesError, ParseAccessError, PrivateAccess, PrivateRule, Rule, RuleReport, Ruleset,
+    RulesetCreated, TailoredCompatLevel, TryCompat, ABI,
+};
+use enumflags2::{bitflags, make_bitflags, BitFlags};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(test)]
+use crate::{RulesetAttr, RulesetCreatedAttr, RulesetError};
+#[cfg(test)]
+use strum::IntoEnumIterator;
+
+/// Network access right.
+///
+/// Each variant of `AccessNet` is an [access right](https://www.kernel.org/doc/html/latest/userspace-api/landlock.html#access-rights)
+/// for the network.
+/// A set of access rights can be created with [`BitFlags<AccessNet>`](BitFlags).
+///
+/// # Example
+///
+/// ```
+/// use landlock::{ABI, Access, AccessNet, BitFlags, make_bitflags};
+///
+/// let bind = AccessNet::BindTcp;
+///
+/// let bind_set: BitFlags<AccessNet> = bind.into();
+///
+/// let bind_connect = make_bitflags!(AccessNet::{BindTcp | ConnectTcp});
+///
+/// let net_v4 = AccessNet::from_all(ABI::V4);
+///
+/// assert_eq!(bind_connect, net_v4);
+/// ```
+///
+/// # Warning
+///
+/// To avoid unknown restrictions **don't use `BitFlags::<AccessNet>::all()` nor `BitFlags::ALL`**,
+/// but use a version you tested and vetted instead,
+/// for instance [`AccessNet::from_all(ABI::V4)`](Access::from_all).
+/// Direct use of **the [`BitFlags`] API is deprecated**.
+/// See [`ABI`] for the rationale and help to test it.
+#[bitflags]
+#[repr(u64)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AccessNet {
+    /// Bind a TCP socket to a local port.
+    BindTcp = uapi::LANDLOCK_ACCESS_NET_BIND_TCP as u64,
+    /// Connect a TCP socket to a remote port.
+    ConnectTcp = uapi::LANDLOCK_ACCESS_NET_CONNECT_TCP as u64,
+}
+
+impl Access for AccessNet {
+    fn from_all(abi: ABI) -> BitFlags<Self> {
+        match abi {
+            ABI::Unsupported | ABI::V1 | ABI::V2 | ABI::V3 => BitFlags::EMPTY,
+            ABI::V4 => Self::ALL_V4,
+        }
+    }
+}
+
+/// Access rights of each ABI, usable in `const` and `static` items
+/// (see [`AccessFs::ALL_V1`](crate::AccessFs::ALL_V1)).
+impl AccessNet {
+    /// All access rights of [`ABI::V4`], the first ABI supporting network restrictions.
+    pub const ALL_V4: BitFlags<Self> = make_bitflags!(AccessNet::{BindTcp | ConnectTcp});
+}
+
+#[test]
+fn access_net_from_all() {
+    for abi in ABI::iter() {
+        // Network access rights are only supported since the fourth ABI.
+        assert_eq!(AccessNet::from_all(abi).is_empty(), abi < ABI::V4);
+    }
+}
+
+impl PrivateAccess for AccessNet {
+    fn ruleset_handle_access(
+        ruleset: &mut Ruleset,
+        access: BitFlags<Self>,
+    ) -> Result<(), HandleAccessesError> {
+        // We need to record the requested accesses for PrivateRule::check_consistency().
+        ruleset.requested_handled_net |= access;
+        let abi = ruleset.compat.probe();
+        ruleset.actual_handled_net |= match access
+            .try_compat_observed(
+                abi,
+                ruleset.compat.level,
+                &mut ruleset.compat.state,
+                &mut ruleset.compat.downgrades,
+            )
+            .map_err(HandleAccessError::Compat)?
+        {
+            Some(a) => a,
+            None => return Ok(()),
+        };
+        Ok(())
+    }
+
+    fn into_add_rules_error(error: AddRuleError<Self>) -> AddRulesError {
+        AddRulesError::Net(error)
+    }
+
+    fn into_handle_accesses_error(error: HandleAccessError<Self>) -> HandleAccessesError {
+        HandleAccessesError::Net(error)
+    }
+
+    fn into_downgrade(error: CompatError<Self>) -> Downgrade {
+        Downgrade::Net(error)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            AccessNet::BindTcp => "bind_tcp",
+            AccessNet::ConnectTcp => "connect_tcp",
+        }
+    }
+}
+
+/// Prints the stable name of the access right (e.g. `bind_tcp`),
+/// see [`Access::to_names()`] for a set of access rights.
+impl fmt::Display for AccessNet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the stable name of an access right (e.g. `bind_tcp`),
+/// see [`Access::from_names()`] for a set of access rights.
+impl FromStr for AccessNet {
+    type Err = ParseAccessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_name(s)
+    }
+}
+
+/// Landlock rule for a network port.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{AccessNet, NetPort};
+///
+/// fn bind_http() -> NetPort {
+///     NetPort::new(80, AccessNet::BindTcp)
+/// }
+/// ```
+pub struct NetPort {
+    attr: uapi::landlock_net_port_attr,
+    // Only 16-bit port make sense for now.
+    port: u16,
+    allowed_access: BitFlags<AccessNet>,
+    compat_level: Option<CompatLevel>,
+}
+
+impl fmt::Debug for NetPort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NetPort")
+            .field("port", &self.port)
+            .field("allowed_access", &self.allowed_access)
+            .field("compat_level", &self.compat_level)
+            .finish()
+    }
+}
+
+// If we need support for 32 or 64 ports, we'll add a new_32() or a new_64() method returning a
+// Result with a potential overflow error.
+impl NetPort {
+    /// Creates a new TCP port rule.
+    ///
+    /// As defined by the Linux ABI, `port` with a value of `0` means that TCP bindings will be
+    /// allowed for a port range defined by `/proc/sys/net/ipv4/ip_local_port_range`.
+    pub fn new<A>(port: u16, access: A) -> Self
+    where
+        A: Into<BitFlags<AccessNet>>,
+    {
+        NetPort {
+            // Invalid access-rights until try_compat() is called.
+            attr: uapi::landlock_net_port_attr {
+                allowed_access: 0,
+                port: port as u64,
+            },
+            port,
+            allowed_access: access.into(),
+            compat_level: None,
+        }
+    }
+}
+
+impl TryCompat<AccessNet> for NetPort {
+    fn try_compat_children<L>(
+        mut self,
+        abi: ABI,
+        parent_level: L,
+        compat_state: &mut CompatState,
+        downgrades: &mut Downgrades,
+    ) -> Result<Option<Self>, CompatError<AccessNet>>
+    where
+        L: Into<CompatLevel>,
+    {
+        // Checks with our own compatibility level, if any.
+        self.allowed_access = match self.allowed_access.try_compat_observed(
+            abi,
+            self.tailored_compat_level(parent_level),
+            compat_state,
+            downgrades,
+        )? {
+            Some(a) => a,
+            None => return Ok(None),
+        };
+        Ok(Some(self))
+    }
+
+    fn try_compat_inner(
+        mut self,
+        _abi: ABI,
+    ) -> Result<CompatResult<Self, AccessNet>, CompatError<AccessNet>> {
+        // Synchronizes rule attributes with the allowed_access updated by try_compat_children().
+        self.attr.allowed_access = self.allowed_access.bits();
+        Ok(CompatResult::Full(self))
+    }
+}
+
+impl OptionCompatLevelMut for NetPort {
+    fn as_option_compat_level_mut(&mut self) -> &mut Option<CompatLevel> {
+        &mut self.compat_level
+    }
+}
+
+impl OptionCompatLevelMut for &mut NetPort {
+    fn as_option_compat_level_mut(&mut self) -> &mut Option<CompatLevel> {
+        &mut self.compat_level
+    }
+}
+
+impl Compatible for NetPort {}
+
+impl Compatible for &mut NetPort {}
+
+impl Rule<AccessNet> for NetPort {}
+
+impl PrivateRule<AccessNet> for NetPort {
+    fn as_ptr(&self) -> *const libc::c_void {
+        &self.attr as *const _ as _
+    }
+
+    fn get_type_id(&self) -> uapi::landlock_rule_type {
+        uapi::landlock_rule_type_LANDLOCK_RULE_NET_PORT
+    }
+
+    fn get_flags(&self) -> u32 {
+        0
+    }
+
+    fn allowed_access(&self) -> BitFlags<AccessNet> {
+        self.allowed_access
+    }
+
+    fn report(&self, index: usize, requested: BitFlags<AccessNet>) -> RuleReport {
+        RuleReport::Net {
+            index,
+            port: self.port,
+            access: AccessReport::new(requested, self.allowed_access),
+        }
+    }
+
+    fn path_label(&self) -> Option<&Path> {
+        None
+    }
+
+    fn check_consistency(&self, ruleset: &RulesetCreated) -> Result<(), AddRulesError> {
+        // Checks that this rule doesn't contain a superset of the access-rights handled by the
+        // ruleset.  This check is about requested access-rights but not actual access-rights.
+        // Indeed, we want to get a deterministic behavior, i.e. not based on the running kernel
+        // (which is handled by Ruleset and RulesetCreated).
+        if ruleset.requested_handled_net.contains(self.allowed_access) {
+            Ok(())
+        } else {
+            Err(AddRuleError::UnhandledAccess {
+                access: self.allowed_access,
+                incompatible: self.allowed_access & !ruleset.requested_handled_net,
+                handled: ruleset.requested_handled_net,
+            }
+            .into())
+        }
+    }
+
+    fn update_fingerprint(&self, fingerprint: &mut Fingerprint) {
+        fingerprint.update_u64(self.get_type_id().into());
+        fingerprint.update_u64(self.allowed_access.bits());
+        fingerprint.update_u64(self.port.into());
+    }
+}
+
+#[test]
+fn net_port_check_consistency() {
+    assert!(matches!(
+        Ruleset::from(ABI::Unsupported)
+            .handle_access(AccessNet::BindTcp)
+            .unwrap()
+            .create()
+            .unwrap()
+            .add_rule(NetPort::new(443, AccessNet::ConnectTcp))
+            .unwrap_err(),
+        RulesetError::AddRules(AddRulesError::Net(AddRuleError::UnhandledAccess { access, incompatible, handled }))
+            if access == AccessNet::ConnectTcp && incompatible == AccessNet::ConnectTcp && handled == AccessNet::BindTcp
+    ));
+}
+
+#[test]
+fn net_port_try_compat() {
+    let mut compat_state = CompatState::Init;
+    assert!(NetPort::new(80, AccessNet::BindTcp)
+        .try_compat(ABI::V3, CompatLevel::BestEffort, &mut compat_state)
+        .unwrap()
+        .is_none());
+    assert_eq!(compat_state, CompatState::No);
+
+    let mut compat_state = CompatState::Init;
+    let rule = NetPort::new(80, AccessNet::BindTcp)
+        .try_compat(ABI::V4, CompatLevel::HardRequirement, &mut compat_state)
+        .unwrap()
+        .unwrap();
+    assert_eq!(rule.attr.allowed_access, AccessNet::BindTcp as u64);
+    assert_eq!(rule.attr.port, 80);
+    assert_eq!(compat_state, CompatState::Full);
+}
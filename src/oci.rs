@@ -0,0 +1,382 @@
+//! Interoperability with the [OCI runtime specification](https://github.com/opencontainers/runtime-spec).
+//!
+//! A container configuration (i.e. `config.json`) can describe a Landlock ruleset with the
+//! proposed `process.landlock` object:
+//!
+//! ```json
+//! "landlock": {
+//!     "ruleset": {
+//!         "handledAccessFS": ["execute", "write_file", "read_file", "read_dir"],
+//!         "handledAccessNetwork": ["bind_tcp", "connect_tcp"]
+//!     },
+//!     "rules": {
+//!         "pathBeneath": [
+//!             { "allowedAccess": ["execute", "read_file", "read_dir"], "paths": ["/usr"] }
+//!         ],
+//!         "netPort": [{ "allowedAccess": ["connect_tcp"], "ports": [443] }]
+//!     },
+//!     "disableBestEffort": false
+//! }
+//! ```
+//!
+//! [`Config`] deserializes such configuration, converts it to a [`Policy`] with
+//! [`policy()`](Config::policy) or [`mount_policy()`](Config::mount_policy),
+//! and updates it from a [`Policy`] with [`set_policy()`](Config::set_policy).
+//! The other fields are kept as is, which enables a container runtime to write back the
+//! configuration.
+//! The access rights are identified by their names (see [`serde_names`](crate::serde_names)).
+//!
+//! This is only available with the `oci` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use landlock::oci::Config;
+//!
+//! fn enforce_config(json: &str) -> Result<(), Box<dyn std::error::Error>> {
+//!     let config: Config = serde_json::from_str(json)?;
+//!     if let Some(policy) = config.policy() {
+//!         let status = policy.restrict_self()?;
+//!         println!("Landlock: {:?}", status.ruleset);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{Access, AccessFs, AccessNet, BitFlags, CompatLevel, Compatible, Policy, ABI};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+/// Container configuration (i.e. `config.json`), limited to the fields used by this crate.
+///
+/// The other fields are kept in `other`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Config {
+    /// Root file system of the container.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<Root>,
+    /// Additional mount points of the container.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mounts: Vec<Mount>,
+    /// Container process.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process: Option<Process>,
+    /// Other fields of the configuration.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Root file system (i.e. `root` object) of a [`Config`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Root {
+    /// Path to the root file system, from the runtime's point of view.
+    pub path: PathBuf,
+    /// True if the root file system is read-only.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub readonly: bool,
+    /// Other fields of the root object.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Mount point (i.e. item of the `mounts` array) of a [`Config`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Mount {
+    /// Mount point, from the container's point of view.
+    pub destination: PathBuf,
+    /// Mount options (e.g. `ro` or `nosuid`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+    /// Other fields of the mount object (e.g. `type` or `source`).
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl Mount {
+    fn is_read_only(&self) -> bool {
+        self.options.iter().any(|o| o == "ro")
+    }
+}
+
+/// Container process (i.e. `process` object) of a [`Config`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Process {
+    /// Landlock configuration of the process.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub landlock: Option<Landlock>,
+    /// Other fields of the process object (e.g. `args` or `env`).
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Landlock configuration (i.e. `process.landlock` object) of a [`Config`].
+///
+/// It can be converted from and into a [`Policy`],
+/// which groups the rules with the same access rights.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Landlock {
+    /// Handled access rights.
+    #[serde(default)]
+    pub ruleset: LandlockRuleset,
+    /// Rules allowing some of the handled access rights.
+    #[serde(default)]
+    pub rules: LandlockRules,
+    /// True to require a full enforcement of the ruleset
+    /// (see [`CompatLevel::HardRequirement`]).
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub disable_best_effort: bool,
+}
+
+/// Handled access rights (i.e. `ruleset` object) of a [`Landlock`] configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct LandlockRuleset {
+    /// Handled file system access rights.
+    #[serde(
+        rename = "handledAccessFS",
+        default,
+        with = "crate::serde_names",
+        skip_serializing_if = "is_empty"
+    )]
+    pub handled_access_fs: BitFlags<AccessFs>,
+    /// Handled network access rights.
+    #[serde(
+        rename = "handledAccessNetwork",
+        default,
+        with = "crate::serde_names",
+        skip_serializing_if = "is_empty"
+    )]
+    pub handled_access_net: BitFlags<AccessNet>,
+}
+
+/// Rules (i.e. `rules` object) of a [`Landlock`] configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LandlockRules {
+    /// File system rules.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path_beneath: Vec<LandlockPathBeneath>,
+    /// Network rules.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub net_port: Vec<LandlockNetPort>,
+}
+
+/// File system rule (i.e. item of the `pathBeneath` array) of a [`Landlock`] configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LandlockPathBeneath {
+    /// Access rights allowed beneath each path.
+    #[serde(with = "crate::serde_names")]
+    pub allowed_access: BitFlags<AccessFs>,
+    /// Paths, from the container's point of view.
+    pub paths: Vec<PathBuf>,
+}
+
+/// Network rule (i.e. item of the `netPort` array) of a [`Landlock`] configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LandlockNetPort {
+    /// Access rights allowed for each port.
+    #[serde(with = "crate::serde_names")]
+    pub allowed_access: BitFlags<AccessNet>,
+    /// TCP ports.
+    pub ports: Vec<u16>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn is_empty<A>(access: &BitFlags<A>) -> bool
+where
+    A: Access,
+{
+    access.is_empty()
+}
+
+impl From<&Landlock> for Policy {
+    fn from(landlock: &Landlock) -> Self {
+        let mut policy = Policy::default()
+            .handle_fs(landlock.ruleset.handled_access_fs)
+            .handle_net(landlock.ruleset.handled_access_net);
+        for rule in &landlock.rules.path_beneath {
+            policy = policy.allow_paths(rule.paths.iter().cloned(), rule.allowed_access);
+        }
+        for rule in &landlock.rules.net_port {
+            for port in &rule.ports {
+                policy = policy.allow_port(*port, rule.allowed_access);
+            }
+        }
+        if landlock.disable_best_effort {
+            policy = policy.set_compatibility(CompatLevel::HardRequirement);
+        }
+        policy
+    }
+}
+
+impl From<&Policy> for Landlock {
+    fn from(policy: &Policy) -> Self {
+        let mut rules = LandlockRules::default();
+        for rule in policy.path_rules() {
+            match rules
+                .path_beneath
+                .iter_mut()
+                .find(|r| r.allowed_access == rule.access)
+            {
+                Some(r) => r.paths.push(rule.path.clone()),
+                None => rules.path_beneath.push(LandlockPathBeneath {
+                    allowed_access: rule.access,
+                    paths: vec![rule.path.clone()],
+                }),
+            }
+        }
+        for rule in policy.port_rules() {
+            match rules
+                .net_port
+                .iter_mut()
+                .find(|r| r.allowed_access == rule.access)
+            {
+                Some(r) => r.ports.push(rule.port),
+                None => rules.net_port.push(LandlockNetPort {
+                    allowed_access: rule.access,
+                    ports: vec![rule.port],
+                }),
+            }
+        }
+        Landlock {
+            ruleset: LandlockRuleset {
+                handled_access_fs: policy.handled_fs(),
+                handled_access_net: policy.handled_net(),
+            },
+            rules,
+            disable_best_effort: policy.compat_level() == Some(CompatLevel::HardRequirement),
+        }
+    }
+}
+
+impl Config {
+    /// Converts the `process.landlock` object to a policy, if any.
+    pub fn policy(&self) -> Option<Policy> {
+        self.process
+            .as_ref()
+            .and_then(|p| p.landlock.as_ref())
+            .map(Policy::from)
+    }
+
+    /// Replaces the `process.landlock` object with the conversion of `policy`.
+    ///
+    /// The policy's configurations which cannot be described by the OCI specification
+    /// (e.g. [`Policy::setup_deadline()`]) are ignored.
+    pub fn set_policy(&mut self, policy: &Policy) {
+        self.process.get_or_insert_with(Process::default).landlock = Some(policy.into());
+    }
+
+    /// Creates a policy mirroring the mount points of the container:
+    /// it handles all the file system access rights of `abi`,
+    /// allows read access (see [`AccessFs::from_read()`]) beneath the root directory,
+    /// and allows all the access rights beneath the mount points without the `ro` option.
+    ///
+    /// Contrary to [`policy()`](Config::policy), this ignores the `process.landlock` object,
+    /// and write access to the root directory is always denied.
+    /// Both policies can be combined with [`Policy::merge()`].
+    pub fn mount_policy(&self, abi: ABI) -> Policy {
+        let mut policy = Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .allow_path("/", AccessFs::from_read(abi));
+        for mount in self.mounts.iter().filter(|m| !m.is_read_only()) {
+            policy = policy.allow_path(mount.destination.clone(), AccessFs::from_all(abi));
+        }
+        policy
+    }
+}
+
+#[test]
+fn oci_config() {
+    let json = r#"{
+        "ociVersion": "1.0.2",
+        "root": { "path": "rootfs", "readonly": true },
+        "mounts": [
+            { "destination": "/proc", "type": "proc", "source": "proc" },
+            { "destination": "/data", "options": ["rbind", "ro"] },
+            { "destination": "/tmp", "type": "tmpfs", "options": ["nosuid"] }
+        ],
+        "process": {
+            "args": ["sh"],
+            "landlock": {
+                "ruleset": {
+                    "handledAccessFS": ["execute", "write_file", "read_file", "read_dir"],
+                    "handledAccessNetwork": ["connect_tcp"]
+                },
+                "rules": {
+                    "pathBeneath": [
+                        { "allowedAccess": ["execute", "read_file", "read_dir"], "paths": ["/usr", "/etc"] },
+                        { "allowedAccess": ["write_file", "read_file"], "paths": ["/tmp"] }
+                    ],
+                    "netPort": [{ "allowedAccess": ["connect_tcp"], "ports": [443, 80] }]
+                },
+                "disableBestEffort": true
+            }
+        }
+    }"#;
+    let config: Config = serde_json::from_str(json).unwrap();
+    assert_eq!(config.other["ociVersion"], "1.0.2");
+    assert_eq!(config.mounts[0].other["type"], "proc");
+
+    let policy = config.policy().unwrap();
+    assert_eq!(
+        policy.handled_fs(),
+        AccessFs::Execute | AccessFs::WriteFile | AccessFs::ReadFile | AccessFs::ReadDir
+    );
+    assert_eq!(policy.handled_net(), AccessNet::ConnectTcp);
+    assert_eq!(policy.path_rules().len(), 3);
+    assert_eq!(policy.path_rules()[1].path, PathBuf::from("/etc"));
+    assert_eq!(
+        policy.path_rules()[2].access,
+        AccessFs::WriteFile | AccessFs::ReadFile
+    );
+    assert_eq!(policy.port_rules().len(), 2);
+    assert_eq!(policy.port_rules()[1].port, 80);
+    assert_eq!(policy.compat_level(), Some(CompatLevel::HardRequirement));
+
+    // Converting back the policy gives the same configuration.
+    let mut updated = config.clone();
+    updated.set_policy(&policy);
+    assert_eq!(updated, config);
+    let value: Value = serde_json::from_str(json).unwrap();
+    assert_eq!(serde_json::to_value(&updated).unwrap(), value);
+
+    let mut empty = Config::default();
+    assert!(empty.policy().is_none());
+    empty.set_policy(&Policy::default());
+    assert_eq!(
+        serde_json::to_string(&empty).unwrap(),
+        r#"{"process":{"landlock":{"ruleset":{},"rules":{}}}}"#
+    );
+
+    let abi = ABI::V1;
+    let mount_policy = config.mount_policy(abi);
+    assert_eq!(mount_policy.handled_fs(), AccessFs::from_all(abi));
+    let rules: Vec<_> = mount_policy
+        .path_rules()
+        .iter()
+        .map(|r| (r.path.to_str().unwrap(), r.access))
+        .collect();
+    assert_eq!(
+        rules,
+        [
+            ("/", AccessFs::from_read(abi)),
+            ("/proc", AccessFs::from_all(abi)),
+            ("/tmp", AccessFs::from_all(abi)),
+        ]
+    );
+}
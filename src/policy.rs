@@ -0,0 +1,1368 @@
+use crate::compat::private::OptionCompatLevelMut;
+use crate::{
+    is_file, Access, AccessFs, AccessNet, BitFlags, CompatLevel, Compatible, Downgrade,
+    DowngradeObserver, Fingerprint, HostFs, NetPort, ParsePolicyError, PathBeneath, PolicyError,
+    PolicyFs, RestrictionStatus, Ruleset, RulesetAttr, RulesetCreated, RulesetCreatedAttr, ABI,
+    ACCESS_FILE,
+};
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[cfg(test)]
+use crate::{MockFs, RulesetError, RulesetStatus};
+
+/// Access rights allowed beneath a path, as described by a [`Policy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PathRule {
+    /// File hierarchy (or file) identified by this rule.
+    pub path: PathBuf,
+    /// Access rights allowed beneath `path`.
+    pub access: BitFlags<AccessFs>,
+}
+
+/// Access rights allowed for a TCP port, as described by a [`Policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PortRule {
+    /// TCP port identified by this rule.
+    pub port: u16,
+    /// Access rights allowed for `port`.
+    pub access: BitFlags<AccessNet>,
+}
+
+/// Behavior of [`Policy::create()`] when some handled access rights are not granted by any rule.
+///
+/// Handling an access right without granting it anywhere denies it for the whole system,
+/// which may be intended (e.g., to forbid any execution)
+/// or may be a bug in the policy (e.g., a forgotten writable directory).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UngrantedAccessLint {
+    /// Silently accepts handled access rights not granted by any rule.
+    #[default]
+    Allow,
+    /// Returns a [`PolicyError::UngrantedAccess`] error for handled access rights not granted
+    /// by any rule, unless they are explicitly confirmed with
+    /// [`Policy::confirm_ungranted_fs()`] or [`Policy::confirm_ungranted_net()`].
+    Deny,
+}
+
+/// Issue found in a [`Policy`] by [`Policy::analyze()`].
+///
+/// Rules are identified by their index in [`Policy::path_rules()`] or [`Policy::port_rules()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PolicyFinding {
+    /// The path rule `index` is redundant with the path rule `ancestor`,
+    /// which identifies the same path or a parent one, and allows a superset of its access rights.
+    RedundantPath { index: usize, ancestor: usize },
+    /// The port rule `index` is redundant with the previous port rule `first`,
+    /// which identifies the same port and allows a superset of its access rights.
+    RedundantPort { index: usize, first: usize },
+    /// The path rule `index` allows access rights which are not handled by the policy,
+    /// and are then not restricted anyway.
+    UnhandledFs {
+        index: usize,
+        access: BitFlags<AccessFs>,
+    },
+    /// The port rule `index` allows access rights which are not handled by the policy,
+    /// and are then not restricted anyway.
+    UnhandledNet {
+        index: usize,
+        access: BitFlags<AccessNet>,
+    },
+    /// The path rule `index` allows access rights confirmed to be denied everywhere with
+    /// [`Policy::confirm_ungranted_fs()`].
+    ConfirmedUngrantedFs {
+        index: usize,
+        access: BitFlags<AccessFs>,
+    },
+    /// The port rule `index` allows access rights confirmed to be denied everywhere with
+    /// [`Policy::confirm_ungranted_net()`].
+    ConfirmedUngrantedNet {
+        index: usize,
+        access: BitFlags<AccessNet>,
+    },
+}
+
+impl fmt::Display for PolicyFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyFinding::RedundantPath { index, ancestor } => write!(
+                f,
+                "path rule #{index} is redundant with path rule #{ancestor}"
+            ),
+            PolicyFinding::RedundantPort { index, first } => {
+                write!(f, "port rule #{index} is redundant with port rule #{first}")
+            }
+            PolicyFinding::UnhandledFs { index, access } => write!(
+                f,
+                "path rule #{index} allows unhandled access rights: {access:?}"
+            ),
+            PolicyFinding::UnhandledNet { index, access } => write!(
+                f,
+                "port rule #{index} allows unhandled access rights: {access:?}"
+            ),
+            PolicyFinding::ConfirmedUngrantedFs { index, access } => write!(
+                f,
+                "path rule #{index} allows access rights confirmed as ungranted: {access:?}"
+            ),
+            PolicyFinding::ConfirmedUngrantedNet { index, access } => write!(
+                f,
+                "port rule #{index} allows access rights confirmed as ungranted: {access:?}"
+            ),
+        }
+    }
+}
+
+/// Declarative description of a Landlock sandbox.
+///
+/// Contrary to [`Ruleset`], which is tied to the running kernel and to opened file descriptors,
+/// a `Policy` only describes the requested restrictions with paths and ports.
+/// It can then be built by different front-ends (e.g., the environment variables
+/// of the kernel's sandboxer with [`Policy::from_sandboxer_env()`])
+/// and enforced at the right time with [`Policy::restrict_self()`].
+///
+/// # Example
+///
+/// ```
+/// use landlock::{ABI, Access, AccessFs, AccessNet, Policy, PolicyError, RestrictionStatus};
+///
+/// fn restrict_thread() -> Result<RestrictionStatus, PolicyError> {
+///     let abi = ABI::V4;
+///     Policy::default()
+///         .handle_fs(AccessFs::from_all(abi))
+///         .handle_net(AccessNet::from_all(abi))
+///         .allow_paths(["/usr", "/etc"], AccessFs::from_read(abi))
+///         .allow_path("/tmp", AccessFs::from_all(abi))
+///         .allow_port(443, AccessNet::ConnectTcp)
+///         .restrict_self()
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    handled_fs: BitFlags<AccessFs>,
+    handled_net: BitFlags<AccessNet>,
+    path_rules: Vec<PathRule>,
+    port_rules: Vec<PortRule>,
+    compat_level: Option<CompatLevel>,
+    ungranted_lint: UngrantedAccessLint,
+    confirmed_ungranted_fs: BitFlags<AccessFs>,
+    confirmed_ungranted_net: BitFlags<AccessNet>,
+    setup_deadline: Option<Duration>,
+    downgrade_observer: Option<DowngradeObserver>,
+}
+
+impl Policy {
+    /// Name of the environment variable listing read-only paths,
+    /// as used by the kernel's sandboxer.
+    pub const ENV_FS_RO_NAME: &'static str = "LL_FS_RO";
+    /// Name of the environment variable listing read-write paths,
+    /// as used by the kernel's sandboxer.
+    pub const ENV_FS_RW_NAME: &'static str = "LL_FS_RW";
+    /// Name of the environment variable listing bindable TCP ports,
+    /// as used by the kernel's sandboxer.
+    pub const ENV_TCP_BIND_NAME: &'static str = "LL_TCP_BIND";
+    /// Name of the environment variable listing connectable TCP ports,
+    /// as used by the kernel's sandboxer.
+    pub const ENV_TCP_CONNECT_NAME: &'static str = "LL_TCP_CONNECT";
+
+    /// Adds a set of file system access rights that will be handled (i.e. denied by default).
+    pub fn handle_fs<A>(mut self, access: A) -> Self
+    where
+        A: Into<BitFlags<AccessFs>>,
+    {
+        self.handled_fs |= access.into();
+        self
+    }
+
+    /// Adds a set of network access rights that will be handled (i.e. denied by default).
+    pub fn handle_net<A>(mut self, access: A) -> Self
+    where
+        A: Into<BitFlags<AccessNet>>,
+    {
+        self.handled_net |= access.into();
+        self
+    }
+
+    /// Allows `access` beneath `path`.
+    ///
+    /// Directory-only access rights are automatically dropped when `path` is not a directory,
+    /// similarly to [`path_beneath_rules()`](crate::path_beneath_rules).
+    pub fn allow_path<P, A>(mut self, path: P, access: A) -> Self
+    where
+        P: Into<PathBuf>,
+        A: Into<BitFlags<AccessFs>>,
+    {
+        self.path_rules.push(PathRule {
+            path: path.into(),
+            access: access.into(),
+        });
+        self
+    }
+
+    /// Allows `access` beneath each path of `paths`.
+    pub fn allow_paths<I, P, A>(self, paths: I, access: A) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+        A: Into<BitFlags<AccessFs>>,
+    {
+        let access = access.into();
+        paths
+            .into_iter()
+            .fold(self, |policy, path| policy.allow_path(path, access))
+    }
+
+    /// Normalizes the path rules to reduce their number,
+    /// and then the number of file descriptors opened by [`create()`](Policy::create):
+    /// * paths are canonicalized (i.e. symbolic links and `..` components are resolved);
+    /// * rules identifying the same file (i.e. same device and inode) are merged,
+    ///   with the union of their access rights;
+    /// * rules beneath another rule allowing a superset of their access rights are removed.
+    ///
+    /// Paths that cannot be canonicalized (e.g. because they don't exist) are kept as is.
+    /// The remaining rules keep their order.
+    /// Because paths are resolved when this method is called,
+    /// it should not be called long before the enforcement.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Access, AccessFs, Policy, ABI};
+    ///
+    /// let policy = Policy::default()
+    ///     .handle_fs(AccessFs::from_all(ABI::V1))
+    ///     .allow_path("/usr", AccessFs::from_read(ABI::V1))
+    ///     .allow_path("/usr/share", AccessFs::ReadFile)
+    ///     .normalize_paths();
+    /// assert_eq!(policy.path_rules().len(), 1);
+    /// ```
+    pub fn normalize_paths(self) -> Self {
+        self.normalize_paths_in(HostFs)
+    }
+
+    /// Same as [`normalize_paths()`](Policy::normalize_paths) but resolves the paths with `fs`.
+    pub fn normalize_paths_in<F>(mut self, fs: F) -> Self
+    where
+        F: PolicyFs,
+    {
+        let mut rules: Vec<(PathRule, Option<(u64, u64)>)> = Vec::new();
+        for rule in self.path_rules.drain(..) {
+            let path = fs.canonicalize(&rule.path).unwrap_or(rule.path);
+            let id = fs.metadata(&path).ok().map(|m| (m.dev(), m.ino()));
+            match rules
+                .iter_mut()
+                .find(|(r, i)| r.path == path || (id.is_some() && *i == id))
+            {
+                Some((same, _)) => same.access |= rule.access,
+                None => rules.push((
+                    PathRule {
+                        path,
+                        access: rule.access,
+                    },
+                    id,
+                )),
+            }
+        }
+        self.path_rules = rules
+            .iter()
+            .filter(|(rule, _)| {
+                !rules.iter().any(|(parent, _)| {
+                    parent.path != rule.path
+                        && rule.path.starts_with(&parent.path)
+                        && parent.access.contains(rule.access)
+                })
+            })
+            .map(|(rule, _)| rule.clone())
+            .collect();
+        self
+    }
+
+    /// Allows `access` for the TCP `port`.
+    pub fn allow_port<A>(mut self, port: u16, access: A) -> Self
+    where
+        A: Into<BitFlags<AccessNet>>,
+    {
+        self.port_rules.push(PortRule {
+            port,
+            access: access.into(),
+        });
+        self
+    }
+
+    /// Adds the restrictions and the rules of `other` to this policy,
+    /// e.g. to extend a base profile with application-specific rules.
+    ///
+    /// The result is the most permissive combination of the rules but the strictest
+    /// combination of the configurations:
+    /// * the handled access rights are the union of both policies' ones;
+    /// * a rule of `other` identifying the same path (as written) or the same port
+    ///   as a rule of this policy is merged into it, with the union of their access rights,
+    ///   and the other rules are appended in order;
+    /// * the compatibility level is the strictest one (see [`CompatLevel`]),
+    ///   the ungranted access lint is [`UngrantedAccessLint::Deny`] if set by any policy,
+    ///   the confirmed ungranted access rights are the union of both policies' ones,
+    ///   and the setup deadline is the shortest one;
+    /// * the downgrade observer of this policy is kept if any, otherwise the one of `other`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Access, AccessFs, AccessNet, Policy, ABI};
+    ///
+    /// let abi = ABI::V4;
+    /// let base = Policy::default()
+    ///     .handle_fs(AccessFs::from_all(abi))
+    ///     .allow_path("/usr", AccessFs::from_read(abi));
+    /// let app = Policy::default()
+    ///     .handle_net(AccessNet::from_all(abi))
+    ///     .allow_path("/usr", AccessFs::Execute)
+    ///     .allow_port(443, AccessNet::ConnectTcp);
+    /// let policy = base.merge(app);
+    /// assert_eq!(policy.path_rules().len(), 1);
+    /// assert_eq!(
+    ///     policy.path_rules()[0].access,
+    ///     AccessFs::from_read(abi) | AccessFs::Execute
+    /// );
+    /// assert_eq!(policy.port_rules().len(), 1);
+    /// ```
+    pub fn merge(mut self, other: Policy) -> Self {
+        self.handled_fs |= other.handled_fs;
+        self.handled_net |= other.handled_net;
+        for rule in other.path_rules {
+            match self.path_rules.iter_mut().find(|r| r.path == rule.path) {
+                Some(same) => same.access |= rule.access,
+                None => self.path_rules.push(rule),
+            }
+        }
+        for rule in other.port_rules {
+            match self.port_rules.iter_mut().find(|r| r.port == rule.port) {
+                Some(same) => same.access |= rule.access,
+                None => self.port_rules.push(rule),
+            }
+        }
+        self.compat_level = self.compat_level.max(other.compat_level);
+        if other.ungranted_lint == UngrantedAccessLint::Deny {
+            self.ungranted_lint = UngrantedAccessLint::Deny;
+        }
+        self.confirmed_ungranted_fs |= other.confirmed_ungranted_fs;
+        self.confirmed_ungranted_net |= other.confirmed_ungranted_net;
+        self.setup_deadline = match (self.setup_deadline, other.setup_deadline) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        if self.downgrade_observer.is_none() {
+            self.downgrade_observer = other.downgrade_observer;
+        }
+        self
+    }
+
+    /// Bounds the time spent by [`create()`](Policy::create) to open the paths and add the rules
+    /// (see [`RulesetCreatedAttr::setup_deadline()`]).
+    ///
+    /// Once exceeded, the remaining rules are skipped (without opening their paths)
+    /// unless the policy's compatibility level is stricter than [`CompatLevel::BestEffort`].
+    pub fn setup_deadline(mut self, timeout: Duration) -> Self {
+        self.setup_deadline = Some(timeout);
+        self
+    }
+
+    /// Registers a callback called for each access right or rule ignored
+    /// because of the compatibility level (see [`Ruleset::on_downgrade()`]).
+    ///
+    /// The callback is not encoded by [`to_env_value()`](Policy::to_env_value).
+    pub fn on_downgrade<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Downgrade) + Send + Sync + 'static,
+    {
+        self.downgrade_observer = Some(DowngradeObserver::new(f));
+        self
+    }
+
+    /// Gets the handled file system access rights.
+    pub fn handled_fs(&self) -> BitFlags<AccessFs> {
+        self.handled_fs
+    }
+
+    /// Gets the handled network access rights.
+    pub fn handled_net(&self) -> BitFlags<AccessNet> {
+        self.handled_net
+    }
+
+    /// Gets the path rules, in the order they were added.
+    pub fn path_rules(&self) -> &[PathRule] {
+        &self.path_rules
+    }
+
+    /// Gets the port rules, in the order they were added.
+    pub fn port_rules(&self) -> &[PortRule] {
+        &self.port_rules
+    }
+
+    /// Gets the compatibility level set with [`set_compatibility()`](Compatible::set_compatibility),
+    /// if any.
+    pub fn compat_level(&self) -> Option<CompatLevel> {
+        self.compat_level
+    }
+
+    /// Configures the check of handled access rights not granted by any rule,
+    /// done by [`create()`](Policy::create).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{
+    ///     ABI, Access, AccessFs, Policy, PolicyError, RulesetCreated, UngrantedAccessLint,
+    /// };
+    ///
+    /// fn create_read_only() -> Result<RulesetCreated, PolicyError> {
+    ///     let abi = ABI::V1;
+    ///     Policy::default()
+    ///         .set_ungranted_lint(UngrantedAccessLint::Deny)
+    ///         .handle_fs(AccessFs::from_all(abi))
+    ///         .allow_path("/", AccessFs::from_read(abi))
+    ///         // Without this confirmation, create() would return an error.
+    ///         .confirm_ungranted_fs(AccessFs::from_write(abi))
+    ///         .create()
+    /// }
+    /// ```
+    pub fn set_ungranted_lint(mut self, lint: UngrantedAccessLint) -> Self {
+        self.ungranted_lint = lint;
+        self
+    }
+
+    /// Confirms that the file system access rights `access` are intended to be denied everywhere,
+    /// which excludes them from the [`UngrantedAccessLint`] check.
+    pub fn confirm_ungranted_fs<A>(mut self, access: A) -> Self
+    where
+        A: Into<BitFlags<AccessFs>>,
+    {
+        self.confirmed_ungranted_fs |= access.into();
+        self
+    }
+
+    /// Confirms that the network access rights `access` are intended to be denied everywhere,
+    /// which excludes them from the [`UngrantedAccessLint`] check.
+    pub fn confirm_ungranted_net<A>(mut self, access: A) -> Self
+    where
+        A: Into<BitFlags<AccessNet>>,
+    {
+        self.confirmed_ungranted_net |= access.into();
+        self
+    }
+
+    /// Gets the handled file system access rights which are not granted by any rule
+    /// nor confirmed with [`confirm_ungranted_fs()`](Policy::confirm_ungranted_fs).
+    pub fn ungranted_fs(&self) -> BitFlags<AccessFs> {
+        self.path_rules
+            .iter()
+            .fold(self.handled_fs & !self.confirmed_ungranted_fs, |acc, r| {
+                acc & !r.access
+            })
+    }
+
+    /// Gets the handled network access rights which are not granted by any rule
+    /// nor confirmed with [`confirm_ungranted_net()`](Policy::confirm_ungranted_net).
+    pub fn ungranted_net(&self) -> BitFlags<AccessNet> {
+        self.port_rules.iter().fold(
+            self.handled_net & !self.confirmed_ungranted_net,
+            |acc, r| acc & !r.access,
+        )
+    }
+
+    /// Looks for redundant rules and inconsistencies in this policy,
+    /// to help keep large policies maintainable.
+    ///
+    /// Paths are compared as they are written, without accessing the file system:
+    /// see [`normalize_paths()`](Policy::normalize_paths) to resolve them first.
+    /// The findings are sorted by kind, then by rule index.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Access, AccessFs, Policy, PolicyFinding, ABI};
+    ///
+    /// let policy = Policy::default()
+    ///     .handle_fs(AccessFs::from_all(ABI::V1))
+    ///     .allow_path("/usr", AccessFs::from_read(ABI::V1))
+    ///     .allow_path("/usr/share", AccessFs::ReadFile);
+    /// assert_eq!(
+    ///     policy.analyze(),
+    ///     [PolicyFinding::RedundantPath {
+    ///         index: 1,
+    ///         ancestor: 0
+    ///     }]
+    /// );
+    /// ```
+    pub fn analyze(&self) -> Vec<PolicyFinding> {
+        let mut findings = Vec::new();
+        for (index, rule) in self.path_rules.iter().enumerate() {
+            // For identical rules, only the later ones are redundant.
+            if let Some(ancestor) = self.path_rules.iter().enumerate().position(|(i, other)| {
+                i != index
+                    && rule.path.starts_with(&other.path)
+                    && other.access.contains(rule.access)
+                    && (other.path != rule.path || other.access != rule.access || i < index)
+            }) {
+                findings.push(PolicyFinding::RedundantPath { index, ancestor });
+            }
+        }
+        for (index, rule) in self.port_rules.iter().enumerate() {
+            if let Some(first) = self.port_rules[..index]
+                .iter()
+                .position(|other| other.port == rule.port && other.access.contains(rule.access))
+            {
+                findings.push(PolicyFinding::RedundantPort { index, first });
+            }
+        }
+        for (index, rule) in self.path_rules.iter().enumerate() {
+            let access = rule.access & !self.handled_fs;
+            if !access.is_empty() {
+                findings.push(PolicyFinding::UnhandledFs { index, access });
+            }
+        }
+        for (index, rule) in self.port_rules.iter().enumerate() {
+            let access = rule.access & !self.handled_net;
+            if !access.is_empty() {
+                findings.push(PolicyFinding::UnhandledNet { index, access });
+            }
+        }
+        for (index, rule) in self.path_rules.iter().enumerate() {
+            let access = rule.access & self.confirmed_ungranted_fs;
+            if !access.is_empty() {
+                findings.push(PolicyFinding::ConfirmedUngrantedFs { index, access });
+            }
+        }
+        for (index, rule) in self.port_rules.iter().enumerate() {
+            let access = rule.access & self.confirmed_ungranted_net;
+            if !access.is_empty() {
+                findings.push(PolicyFinding::ConfirmedUngrantedNet { index, access });
+            }
+        }
+        findings
+    }
+
+    /// Gets a canonical description of the restrictions this policy enforces
+    /// with the `abi` Landlock version.
+    ///
+    /// Access rights not supported by `abi` are removed (as with a best-effort compatibility),
+    /// rules without any remaining handled access right are dropped,
+    /// and rules for the same path or port are merged and sorted.
+    /// Two policies building the same sandbox then get the same description,
+    /// whatever the order of their rules.
+    /// Paths are not resolved (see [`normalize_paths()`](Policy::normalize_paths)),
+    /// and the compatibility level is not part of the description.
+    ///
+    /// The format is one `key=value` line per element,
+    /// with access rights identified by their names (see [`Access::to_names()`]).
+    /// Paths are quoted, and their bytes which are not printable ASCII characters,
+    /// or which are `"` or `%`, are percent-encoded (e.g. `%0A` for a new line).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Access, AccessFs, Policy, ABI};
+    ///
+    /// let policy = Policy::default()
+    ///     .handle_fs(AccessFs::from_all(ABI::V2))
+    ///     .allow_path("/usr", AccessFs::Execute | AccessFs::Refer);
+    /// assert_eq!(
+    ///     policy.canonical(ABI::V1),
+    ///     "abi=v1\n\
+    ///      handled_fs=execute|write_file|read_file|read_dir|remove_dir|remove_file|make_char|\
+    ///      make_dir|make_reg|make_sock|make_fifo|make_block|make_sym\n\
+    ///      handled_net=\n\
+    ///      path \"/usr\"=execute\n"
+    /// );
+    /// ```
+    pub fn canonical(&self, abi: ABI) -> String {
+        let handled_fs = self.handled_fs & AccessFs::from_all(abi);
+        let handled_net = self.handled_net & AccessNet::from_all(abi);
+        let mut paths = BTreeMap::new();
+        for rule in &self.path_rules {
+            *paths.entry(&rule.path).or_insert(BitFlags::EMPTY) |= rule.access & handled_fs;
+        }
+        let mut ports = BTreeMap::new();
+        for rule in &self.port_rules {
+            *ports.entry(rule.port).or_insert(BitFlags::EMPTY) |= rule.access & handled_net;
+        }
+
+        let mut out = format!(
+            "abi={abi}\nhandled_fs={}\nhandled_net={}\n",
+            AccessFs::to_names(handled_fs),
+            AccessNet::to_names(handled_net)
+        );
+        for (path, access) in paths.into_iter().filter(|(_, a)| !a.is_empty()) {
+            out += &format!("path {}={}\n", quote_path(path), AccessFs::to_names(access));
+        }
+        for (port, access) in ports.into_iter().filter(|(_, a)| !a.is_empty()) {
+            out += &format!("port {port}={}\n", AccessNet::to_names(access));
+        }
+        out
+    }
+
+    /// Gets a stable hash of the [`canonical()`](Policy::canonical) description of this policy
+    /// for the running kernel, as 16 hexadecimal digits.
+    ///
+    /// This identifies the sandbox configuration a process started with (e.g. to log it
+    /// or to compare it with an expected value), and doesn't change with the version of this
+    /// crate nor of the Rust toolchain.
+    /// However, it changes with the supported Landlock ABI.
+    pub fn fingerprint(&self) -> String {
+        let mut fingerprint = Fingerprint::default();
+        fingerprint.update(self.canonical(ABI::new_current()).as_bytes());
+        fingerprint.to_string()
+    }
+
+    /// Builds a policy from a description in the [`canonical()`](Policy::canonical) format,
+    /// e.g. stored in a policy file.
+    ///
+    /// The elements may be in any order and repeated (in which case they are merged),
+    /// and blank lines and lines starting with `#` are ignored.
+    /// The `abi` element is optional and only checked to be a known [`ABI`]:
+    /// the returned policy handles the described access rights whatever the ABI.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{AccessFs, AccessNet, Policy};
+    ///
+    /// let policy = Policy::from_canonical(
+    ///     "# Web server\n\
+    ///      handled_fs=execute|read_file|read_dir\n\
+    ///      handled_net=bind_tcp\n\
+    ///      path \"/usr\"=execute|read_file|read_dir\n\
+    ///      port 443=bind_tcp\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(policy.path_rules()[0].path.to_str(), Some("/usr"));
+    /// assert_eq!(policy.port_rules()[0].access, AccessNet::BindTcp);
+    /// ```
+    pub fn from_canonical(description: &str) -> Result<Self, ParsePolicyError> {
+        let mut policy = Policy::default();
+        for (index, raw) in description.lines().enumerate() {
+            let line = index + 1;
+            let element = raw.trim();
+            if element.is_empty() || element.starts_with('#') {
+                continue;
+            }
+            let access_fs = |names: &str| {
+                AccessFs::from_names(names)
+                    .map_err(|source| ParsePolicyError::InvalidAccess { line, source })
+            };
+            let access_net = |names: &str| {
+                AccessNet::from_names(names)
+                    .map_err(|source| ParsePolicyError::InvalidAccess { line, source })
+            };
+            if let Some(quoted) = element.strip_prefix("path ") {
+                let (path, rest) =
+                    unquote_path(quoted).ok_or(ParsePolicyError::InvalidPath { line })?;
+                let names = rest
+                    .strip_prefix('=')
+                    .ok_or(ParsePolicyError::InvalidLine { line })?;
+                policy = policy.allow_path(path, access_fs(names)?);
+                continue;
+            }
+            let (key, value) = element
+                .split_once('=')
+                .ok_or(ParsePolicyError::InvalidLine { line })?;
+            policy = match key.trim() {
+                "abi" => {
+                    value
+                        .trim()
+                        .parse::<ABI>()
+                        .map_err(|source| ParsePolicyError::InvalidAbi { line, source })?;
+                    policy
+                }
+                "handled_fs" => policy.handle_fs(access_fs(value)?),
+                "handled_net" => policy.handle_net(access_net(value)?),
+                key => match key.strip_prefix("port ") {
+                    Some(port) => {
+                        let port = port.trim();
+                        let port =
+                            port.parse::<u16>()
+                                .map_err(|_| ParsePolicyError::InvalidPort {
+                                    line,
+                                    port: port.to_string(),
+                                })?;
+                        policy.allow_port(port, access_net(value)?)
+                    }
+                    None => return Err(ParsePolicyError::InvalidLine { line }),
+                },
+            };
+        }
+        Ok(policy)
+    }
+
+    /// Builds a policy from the environment variables used by the
+    /// [kernel's sandboxer](https://git.kernel.org/pub/scm/linux/kernel/git/stable/linux.git/tree/samples/landlock/sandboxer.c):
+    /// * `LL_FS_RO`: paths allowed to be used in a read-only way;
+    /// * `LL_FS_RW`: paths allowed to be used in a read-write way;
+    /// * `LL_TCP_BIND` (optional): ports allowed to bind;
+    /// * `LL_TCP_CONNECT` (optional): ports allowed to connect.
+    ///
+    /// Each variable contains a list of elements separated by a colon,
+    /// e.g. `LL_FS_RO="/bin:/lib:/usr"` or `LL_TCP_CONNECT="80:443"`.
+    /// An empty variable doesn't allow anything.
+    /// As for the sandboxer, all file system access rights defined by `abi` are handled,
+    /// whereas a network access right is only handled if its related variable is set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Policy, PolicyError, RestrictionStatus, ABI};
+    ///
+    /// fn restrict_env() -> Result<RestrictionStatus, PolicyError> {
+    ///     // In the shell: export LL_FS_RO="/usr:/etc" LL_FS_RW="/tmp"
+    ///     Policy::from_sandboxer_env(ABI::V4)?.restrict_self()
+    /// }
+    /// ```
+    pub fn from_sandboxer_env(abi: ABI) -> Result<Self, PolicyError> {
+        Self::from_sandboxer_vars(abi, |name| std::env::var_os(name))
+    }
+
+    fn from_sandboxer_vars<F>(abi: ABI, var: F) -> Result<Self, PolicyError>
+    where
+        F: Fn(&str) -> Option<OsString>,
+    {
+        let required = |name: &str| {
+            var(name).ok_or_else(|| PolicyError::MissingVar {
+                name: name.to_string(),
+            })
+        };
+        let mut policy = Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .allow_paths(
+                split_var(&required(Self::ENV_FS_RO_NAME)?).map(PathBuf::from),
+                AccessFs::from_read(abi),
+            )
+            .allow_paths(
+                split_var(&required(Self::ENV_FS_RW_NAME)?).map(PathBuf::from),
+                AccessFs::from_all(abi),
+            );
+        for (name, access) in [
+            (Self::ENV_TCP_BIND_NAME, AccessNet::BindTcp),
+            (Self::ENV_TCP_CONNECT_NAME, AccessNet::ConnectTcp),
+        ] {
+            if let Some(ports) = var(name) {
+                policy = policy.handle_net(access);
+                for port in split_var(&ports) {
+                    let port = port
+                        .to_str()
+                        .and_then(|p| p.parse::<u16>().ok())
+                        .ok_or_else(|| PolicyError::InvalidPort {
+                            name: name.to_string(),
+                            port: port.to_string_lossy().into_owned(),
+                        })?;
+                    policy = policy.allow_port(port, access);
+                }
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Creates a [`RulesetCreated`] matching this policy,
+    /// which can then be enforced later or extended with other rules.
+    ///
+    /// On error, returns a wrapped [`RulesetError`](crate::RulesetError),
+    /// or a wrapped [`PathFdError`](crate::PathFdError) if a path cannot be opened,
+    /// or [`PolicyError::UngrantedAccess`] according to
+    /// [`set_ungranted_lint()`](Policy::set_ungranted_lint).
+    pub fn create(&self) -> Result<RulesetCreated, PolicyError> {
+        self.create_in(HostFs)
+    }
+
+    /// Same as [`create()`](Policy::create) but opens the paths with `fs`.
+    pub fn create_in<F>(&self, fs: F) -> Result<RulesetCreated, PolicyError>
+    where
+        F: PolicyFs,
+    {
+        self.create_from(Ruleset::default(), fs)
+    }
+
+    fn create_from<F>(&self, mut ruleset: Ruleset, fs: F) -> Result<RulesetCreated, PolicyError>
+    where
+        F: PolicyFs,
+    {
+        if self.ungranted_lint == UngrantedAccessLint::Deny {
+            let (fs, net) = (self.ungranted_fs(), self.ungranted_net());
+            if !fs.is_empty() || !net.is_empty() {
+                return Err(PolicyError::UngrantedAccess { fs, net });
+            }
+        }
+        if let Some(level) = self.compat_level {
+            ruleset = ruleset.set_compatibility(level);
+        }
+        if let Some(observer) = &self.downgrade_observer {
+            ruleset.compat.downgrades.observer = Some(observer.clone());
+        }
+        if !self.handled_fs.is_empty() {
+            ruleset = ruleset.handle_access(self.handled_fs)?;
+        }
+        if !self.handled_net.is_empty() {
+            ruleset = ruleset.handle_access(self.handled_net)?;
+        }
+        let mut ruleset = ruleset.create()?;
+        if let Some(timeout) = self.setup_deadline {
+            ruleset = ruleset.setup_deadline(timeout);
+        }
+        for rule in &self.path_rules {
+            // Opening a path may be slow (e.g. on a network file system).
+            if ruleset.skip_inherited_rule() {
+                continue;
+            }
+            let fd = fs.open_path(&rule.path)?;
+            let access = match is_file(&fd) {
+                Ok(true) => rule.access & ACCESS_FILE,
+                // If the stat call failed, let's blindly rely on the requested access rights.
+                Err(_) | Ok(false) => rule.access,
+            };
+            ruleset = ruleset.add_rule(PathBeneath::new(fd, access).set_path_label(&rule.path))?;
+        }
+        for rule in &self.port_rules {
+            ruleset = ruleset.add_rule(NetPort::new(rule.port, rule.access))?;
+        }
+        Ok(ruleset)
+    }
+
+    /// Creates and enforces this policy on the calling thread.
+    ///
+    /// This is a shortcut for `policy.create()?.restrict_self()`,
+    /// see [`RulesetCreated::restrict_self()`].
+    pub fn restrict_self(&self) -> Result<RestrictionStatus, PolicyError> {
+        Ok(self.create()?.restrict_self()?)
+    }
+}
+
+// Splits a colon-separated list, ignoring empty elements (e.g. for an empty variable).
+fn split_var(value: &OsStr) -> impl Iterator<Item = &OsStr> {
+    value
+        .as_bytes()
+        .split(|b| *b == b':')
+        .filter(|e| !e.is_empty())
+        .map(OsStr::from_bytes)
+}
+
+// Quotes a path for Policy::canonical(), percent-encoding the bytes which are not printable ASCII
+// characters (or are '"' or '%') to not depend on the Unicode tables of the Rust toolchain.
+fn quote_path(path: &Path) -> String {
+    let mut quoted = String::with_capacity(path.as_os_str().len() + 2);
+    quoted.push('"');
+    for b in path.as_os_str().as_bytes() {
+        match b {
+            b'"' | b'%' => quoted += &format!("%{b:02X}"),
+            b' '..=b'~' => quoted.push(char::from(*b)),
+            _ => quoted += &format!("%{b:02X}"),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Parses a path quoted with quote_path() and returns it with the remaining text.
+fn unquote_path(quoted: &str) -> Option<(PathBuf, &str)> {
+    let (encoded, rest) = quoted.strip_prefix('"')?.split_once('"')?;
+    let encoded = encoded.as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        if encoded[i] == b'%' {
+            let hex = std::str::from_utf8(encoded.get(i + 1..i + 3)?).ok()?;
+            if !hex.bytes().all(|h| h.is_ascii_hexdigit()) {
+                return None;
+            }
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            bytes.push(encoded[i]);
+            i += 1;
+        }
+    }
+    Some((OsStr::from_bytes(&bytes).into(), rest))
+}
+
+impl OptionCompatLevelMut for Policy {
+    fn as_option_compat_level_mut(&mut self) -> &mut Option<CompatLevel> {
+        &mut self.compat_level
+    }
+}
+
+impl OptionCompatLevelMut for &mut Policy {
+    fn as_option_compat_level_mut(&mut self) -> &mut Option<CompatLevel> {
+        &mut self.compat_level
+    }
+}
+
+impl Compatible for Policy {}
+
+impl Compatible for &mut Policy {}
+
+#[cfg(test)]
+fn sandboxer_vars<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+    move |name| {
+        vars.iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| OsString::from(v))
+    }
+}
+
+#[test]
+fn policy_from_sandboxer_vars() {
+    let abi = ABI::V4;
+    let policy = Policy::from_sandboxer_vars(
+        abi,
+        sandboxer_vars(&[
+            ("LL_FS_RO", "/usr:/etc"),
+            ("LL_FS_RW", "/tmp::"),
+            ("LL_TCP_CONNECT", "80:443"),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(policy.handled_fs(), AccessFs::from_all(abi));
+    assert_eq!(policy.handled_net(), AccessNet::ConnectTcp);
+    assert_eq!(
+        policy.path_rules(),
+        [
+            PathRule {
+                path: "/usr".into(),
+                access: AccessFs::from_read(abi),
+            },
+            PathRule {
+                path: "/etc".into(),
+                access: AccessFs::from_read(abi),
+            },
+            PathRule {
+                path: "/tmp".into(),
+                access: AccessFs::from_all(abi),
+            },
+        ]
+    );
+    assert_eq!(
+        policy.port_rules(),
+        [
+            PortRule {
+                port: 80,
+                access: AccessNet::ConnectTcp.into(),
+            },
+            PortRule {
+                port: 443,
+                access: AccessNet::ConnectTcp.into(),
+            },
+        ]
+    );
+
+    // Empty variables are legitimate.
+    let policy =
+        Policy::from_sandboxer_vars(abi, sandboxer_vars(&[("LL_FS_RO", ""), ("LL_FS_RW", "")]))
+            .unwrap();
+    assert!(policy.path_rules().is_empty());
+    assert!(policy.handled_net().is_empty());
+}
+
+#[test]
+fn policy_from_sandboxer_vars_errors() {
+    assert!(matches!(
+        Policy::from_sandboxer_vars(ABI::V1, sandboxer_vars(&[("LL_FS_RO", "/usr")])).unwrap_err(),
+        PolicyError::MissingVar { name } if name == "LL_FS_RW"
+    ));
+
+    assert!(matches!(
+        Policy::from_sandboxer_vars(
+            ABI::V4,
+            sandboxer_vars(&[("LL_FS_RO", ""), ("LL_FS_RW", ""), ("LL_TCP_BIND", "80:http")])
+        )
+        .unwrap_err(),
+        PolicyError::InvalidPort { name, port } if name == "LL_TCP_BIND" && port == "http"
+    ));
+}
+
+#[test]
+fn policy_create() {
+    let abi = ABI::V1;
+    let status = Policy::default()
+        .handle_fs(AccessFs::from_all(abi))
+        // Directory-only access rights are dropped for /etc/passwd.
+        .allow_paths(["/usr", "/etc/passwd"], AccessFs::from_all(abi))
+        .create_from(Ruleset::from(ABI::Unsupported), HostFs)
+        .unwrap()
+        .set_no_new_privs(false)
+        .restrict_self()
+        .unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::NotEnforced);
+
+    assert!(matches!(
+        Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .allow_path("/does-not-exist", AccessFs::ReadFile)
+            .create_from(Ruleset::from(ABI::Unsupported), HostFs)
+            .unwrap_err(),
+        PolicyError::PathFd(_)
+    ));
+
+    assert!(matches!(
+        Policy::default()
+            .allow_port(80, AccessNet::BindTcp)
+            .create_from(Ruleset::from(ABI::Unsupported), HostFs)
+            .unwrap_err(),
+        PolicyError::Ruleset(RulesetError::CreateRuleset(_))
+    ));
+}
+
+#[test]
+fn policy_ungranted_lint() {
+    let abi = ABI::V4;
+    let policy = Policy::default()
+        .set_ungranted_lint(UngrantedAccessLint::Deny)
+        .handle_fs(AccessFs::from_all(abi))
+        .handle_net(AccessNet::from_all(abi))
+        .allow_path("/usr", AccessFs::from_read(abi))
+        .allow_path("/tmp", AccessFs::WriteFile | AccessFs::ReadFile)
+        .allow_port(443, AccessNet::ConnectTcp);
+    let ungranted_fs = AccessFs::from_write(abi) & !AccessFs::WriteFile;
+    assert_eq!(policy.ungranted_fs(), ungranted_fs);
+    assert_eq!(policy.ungranted_net(), AccessNet::BindTcp);
+    assert!(matches!(
+        policy
+            .create_from(Ruleset::from(ABI::Unsupported), HostFs)
+            .unwrap_err(),
+        PolicyError::UngrantedAccess { fs, net } if fs == ungranted_fs && net == AccessNet::BindTcp
+    ));
+
+    // Explicit confirmations make the policy valid.
+    let policy = policy
+        .confirm_ungranted_fs(ungranted_fs)
+        .confirm_ungranted_net(AccessNet::BindTcp);
+    assert!(policy.ungranted_fs().is_empty());
+    assert!(policy.ungranted_net().is_empty());
+    policy
+        .create_from(Ruleset::from(ABI::Unsupported), HostFs)
+        .unwrap();
+
+    // The lint is disabled by default.
+    Policy::default()
+        .handle_fs(AccessFs::Execute)
+        .create_from(Ruleset::from(ABI::Unsupported), HostFs)
+        .unwrap();
+}
+
+#[test]
+fn policy_setup_deadline() {
+    let abi = ABI::V4;
+    // Paths of skipped rules are not opened.
+    let ruleset = Policy::default()
+        .handle_fs(AccessFs::from_all(abi))
+        .handle_net(AccessNet::from_all(abi))
+        .allow_path("/does-not-exist", AccessFs::ReadFile)
+        .allow_port(443, AccessNet::ConnectTcp)
+        .setup_deadline(Duration::ZERO)
+        .create_from(Ruleset::from(ABI::Unsupported), MockFs::default())
+        .unwrap();
+    assert_eq!(ruleset.skipped_rules(), 2);
+}
+
+#[test]
+fn policy_normalize_paths() {
+    let abi = ABI::V1;
+    let policy = Policy::default()
+        .handle_fs(AccessFs::from_all(abi))
+        .allow_path("/usr", AccessFs::from_read(abi))
+        .allow_path("/usr/share", AccessFs::ReadFile)
+        .allow_path("/etc/../usr/.", AccessFs::WriteFile)
+        .allow_path("/etc", AccessFs::ReadFile)
+        .allow_path("/etc/passwd", AccessFs::ReadFile | AccessFs::WriteFile)
+        .allow_path("/does-not-exist", AccessFs::ReadFile)
+        .allow_path("/does-not-exist", AccessFs::Execute)
+        .normalize_paths();
+    assert_eq!(
+        policy.path_rules(),
+        [
+            PathRule {
+                path: "/usr".into(),
+                access: AccessFs::from_read(abi) | AccessFs::WriteFile,
+            },
+            PathRule {
+                path: "/etc".into(),
+                access: AccessFs::ReadFile.into(),
+            },
+            // Not covered by /etc.
+            PathRule {
+                path: "/etc/passwd".into(),
+                access: AccessFs::ReadFile | AccessFs::WriteFile,
+            },
+            PathRule {
+                path: "/does-not-exist".into(),
+                access: AccessFs::ReadFile | AccessFs::Execute,
+            },
+        ]
+    );
+}
+
+#[test]
+fn policy_normalize_paths_in() {
+    let mut fs = MockFs::default();
+    fs.insert("/data", std::fs::File::open("/usr").unwrap());
+    fs.insert("/alias", std::fs::File::open("/usr").unwrap());
+    fs.insert("/data/cache", std::fs::File::open("/etc").unwrap());
+    let policy = Policy::default()
+        .allow_path("/data", AccessFs::ReadFile)
+        .allow_path("/alias", AccessFs::ReadDir)
+        .allow_path("/data/cache", AccessFs::ReadDir)
+        // Not resolved by the host file system.
+        .allow_path("/usr", AccessFs::ReadFile)
+        .normalize_paths_in(&fs);
+    assert_eq!(
+        policy.path_rules(),
+        [
+            PathRule {
+                path: "/data".into(),
+                access: AccessFs::ReadFile | AccessFs::ReadDir,
+            },
+            PathRule {
+                path: "/usr".into(),
+                access: AccessFs::ReadFile.into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn policy_analyze() {
+    let abi = ABI::V1;
+    let policy = Policy::default()
+        .handle_fs(AccessFs::from_all(abi))
+        .handle_net(AccessNet::ConnectTcp)
+        .allow_path("/usr", AccessFs::from_read(abi))
+        .allow_path("/usr/share", AccessFs::ReadFile)
+        // Not a child of /usr.
+        .allow_path("/usrx", AccessFs::ReadFile)
+        // Not a subset of /usr.
+        .allow_path("/usr/local", AccessFs::from_all(abi))
+        .allow_path("/usr", AccessFs::from_read(abi))
+        .allow_path("/tmp", AccessFs::from_all(ABI::V2))
+        .allow_port(443, AccessNet::ConnectTcp)
+        .allow_port(443, AccessNet::ConnectTcp)
+        .allow_port(80, AccessNet::BindTcp)
+        .confirm_ungranted_fs(AccessFs::Execute | AccessFs::MakeSock);
+    assert_eq!(
+        policy.analyze(),
+        [
+            PolicyFinding::RedundantPath {
+                index: 1,
+                ancestor: 0
+            },
+            PolicyFinding::RedundantPath {
+                index: 4,
+                ancestor: 0
+            },
+            PolicyFinding::RedundantPort { index: 1, first: 0 },
+            PolicyFinding::UnhandledFs {
+                index: 5,
+                access: AccessFs::Refer.into()
+            },
+            PolicyFinding::UnhandledNet {
+                index: 2,
+                access: AccessNet::BindTcp.into()
+            },
+            PolicyFinding::ConfirmedUngrantedFs {
+                index: 0,
+                access: AccessFs::Execute.into()
+            },
+            PolicyFinding::ConfirmedUngrantedFs {
+                index: 3,
+                access: AccessFs::Execute | AccessFs::MakeSock
+            },
+            PolicyFinding::ConfirmedUngrantedFs {
+                index: 4,
+                access: AccessFs::Execute.into()
+            },
+            PolicyFinding::ConfirmedUngrantedFs {
+                index: 5,
+                access: AccessFs::Execute | AccessFs::MakeSock
+            },
+        ]
+    );
+    assert_eq!(
+        PolicyFinding::RedundantPath {
+            index: 1,
+            ancestor: 0
+        }
+        .to_string(),
+        "path rule #1 is redundant with path rule #0"
+    );
+    assert!(Policy::default().analyze().is_empty());
+}
+
+#[test]
+fn policy_fingerprint() {
+    use crate::uapi;
+
+    let abi = ABI::V4;
+    let policy = Policy::default()
+        .handle_fs(AccessFs::from_all(abi))
+        .handle_net(AccessNet::from_all(abi))
+        .allow_path("/usr", AccessFs::Execute)
+        .allow_path("/tmp", AccessFs::Truncate)
+        .allow_path("/usr", AccessFs::ReadFile)
+        .allow_port(443, AccessNet::ConnectTcp);
+    let reordered = Policy::default()
+        .handle_net(AccessNet::from_all(abi))
+        .handle_fs(AccessFs::from_all(abi))
+        .allow_port(443, AccessNet::ConnectTcp)
+        .allow_path("/usr", AccessFs::ReadFile | AccessFs::Execute)
+        .allow_path("/tmp", AccessFs::Truncate)
+        .set_compatibility(CompatLevel::HardRequirement);
+    assert_eq!(policy.canonical(abi), reordered.canonical(abi));
+    assert_eq!(
+        policy.canonical(ABI::V2),
+        "abi=v2\n\
+         handled_fs=execute|write_file|read_file|read_dir|remove_dir|remove_file|make_char|\
+         make_dir|make_reg|make_sock|make_fifo|make_block|make_sym|refer\n\
+         handled_net=\n\
+         path \"/usr\"=execute|read_file\n"
+    );
+
+    std::thread::spawn(move || {
+        let _kernel = uapi::MockKernel::new(ABI::V2).install();
+        let fingerprint = policy.fingerprint();
+        assert_eq!(fingerprint.len(), 16);
+        assert_eq!(fingerprint, reordered.fingerprint());
+        assert_ne!(
+            fingerprint,
+            reordered
+                .allow_path("/etc", AccessFs::ReadFile)
+                .fingerprint()
+        );
+        let _kernel = uapi::MockKernel::new(ABI::V4).install();
+        assert_ne!(fingerprint, policy.fingerprint());
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn policy_from_canonical() {
+    let abi = ABI::V4;
+    let policy = Policy::default()
+        .handle_fs(AccessFs::from_all(abi))
+        .handle_net(AccessNet::from_all(abi))
+        .allow_path("/usr", AccessFs::Execute | AccessFs::ReadFile)
+        .allow_path("/tmp/a \"b\"=\\c\té", AccessFs::MakeReg)
+        .allow_path(OsStr::from_bytes(b"/tmp/\xff\n"), AccessFs::ReadDir)
+        .allow_port(443, AccessNet::ConnectTcp);
+    let canonical = policy.canonical(abi);
+    assert!(canonical.contains("path \"/tmp/a %22b%22=\\c%09%C3%A9\"=make_reg\n"));
+    assert!(canonical.contains("path \"/tmp/%FF%0A\"=read_dir\n"));
+    let parsed = Policy::from_canonical(&canonical).unwrap();
+    assert_eq!(parsed.canonical(abi), canonical);
+    assert_eq!(parsed.path_rules().len(), 3);
+
+    let parsed = Policy::from_canonical(
+        "\n# Comment\n  handled_fs = read_file | execute\n\
+         path \"/usr\"=execute\npath \"/usr\"=read_file\nport 80=\n",
+    )
+    .unwrap();
+    assert_eq!(parsed.handled_fs(), AccessFs::ReadFile | AccessFs::Execute);
+    assert!(parsed.handled_net().is_empty());
+    assert_eq!(parsed.path_rules().len(), 2);
+    assert_eq!(parsed.port_rules()[0].access, BitFlags::EMPTY);
+
+    let err = |description: &str| Policy::from_canonical(description).unwrap_err();
+    assert_eq!(
+        err("abi=v1\nhandled_fs"),
+        ParsePolicyError::InvalidLine { line: 2 }
+    );
+    assert_eq!(err("paths=/usr"), ParsePolicyError::InvalidLine { line: 1 });
+    assert!(matches!(
+        err("abi=v42"),
+        ParsePolicyError::InvalidAbi { line: 1, .. }
+    ));
+    assert!(matches!(
+        err("handled_net=bind_udp"),
+        ParsePolicyError::InvalidAccess { line: 1, .. }
+    ));
+    assert_eq!(
+        err("path \"/usr=execute"),
+        ParsePolicyError::InvalidPath { line: 1 }
+    );
+    assert_eq!(
+        err("path /usr=execute"),
+        ParsePolicyError::InvalidPath { line: 1 }
+    );
+    assert_eq!(
+        err("path \"/usr%2\"=execute"),
+        ParsePolicyError::InvalidPath { line: 1 }
+    );
+    assert_eq!(
+        err("path \"/usr\" execute"),
+        ParsePolicyError::InvalidLine { line: 1 }
+    );
+    assert_eq!(
+        err("port 65536=bind_tcp"),
+        ParsePolicyError::InvalidPort {
+            line: 1,
+            port: "65536".into()
+        }
+    );
+}
+
+#[test]
+fn policy_merge() {
+    let abi = ABI::V4;
+    let base = Policy::default()
+        .handle_fs(AccessFs::from_read(abi))
+        .allow_path("/usr", AccessFs::ReadFile)
+        .allow_path("/etc", AccessFs::ReadFile)
+        .set_compatibility(CompatLevel::SoftRequirement)
+        .setup_deadline(Duration::from_secs(2));
+    let app = Policy::default()
+        .handle_fs(AccessFs::Execute)
+        .handle_net(AccessNet::ConnectTcp)
+        .allow_path("/usr", AccessFs::Execute | AccessFs::ReadFile)
+        .allow_path("/opt/app", AccessFs::Execute)
+        .allow_port(443, AccessNet::ConnectTcp)
+        .set_ungranted_lint(UngrantedAccessLint::Deny)
+        .confirm_ungranted_net(AccessNet::BindTcp)
+        .set_compatibility(CompatLevel::BestEffort)
+        .setup_deadline(Duration::from_secs(1));
+
+    let policy = base.merge(app);
+    assert_eq!(
+        policy.handled_fs(),
+        AccessFs::from_read(abi) | AccessFs::Execute
+    );
+    assert_eq!(policy.handled_net(), AccessNet::ConnectTcp);
+    assert_eq!(
+        policy.path_rules(),
+        [
+            PathRule {
+                path: "/usr".into(),
+                access: AccessFs::Execute | AccessFs::ReadFile,
+            },
+            PathRule {
+                path: "/etc".into(),
+                access: AccessFs::ReadFile.into(),
+            },
+            PathRule {
+                path: "/opt/app".into(),
+                access: AccessFs::Execute.into(),
+            },
+        ]
+    );
+    assert_eq!(policy.port_rules().len(), 1);
+    assert_eq!(policy.compat_level(), Some(CompatLevel::SoftRequirement));
+    assert_eq!(policy.ungranted_lint, UngrantedAccessLint::Deny);
+    assert_eq!(policy.confirmed_ungranted_net, AccessNet::BindTcp);
+    assert_eq!(policy.setup_deadline, Some(Duration::from_secs(1)));
+
+    // Merging is idempotent.
+    let merged = policy.clone().merge(policy.clone());
+    assert_eq!(merged.path_rules(), policy.path_rules());
+    assert_eq!(merged.port_rules(), policy.port_rules());
+}
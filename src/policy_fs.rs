@@ -0,0 +1,344 @@
+use crate::{BitFlags, PathFd, PathFdError, Resolve};
+use std::collections::HashMap;
+use std::fs::{self, File, Metadata};
+use std::io::Error;
+use std::os::unix::io::{AsFd, AsRawFd, OwnedFd};
+use std::path::{Component, Path, PathBuf};
+
+#[cfg(test)]
+use crate::{path_beneath_rules_in, AccessFs};
+
+/// File system used to resolve and open the paths of rules.
+///
+/// `PolicyFs` is used by [`Policy::create_in()`](crate::Policy::create_in)
+/// and [`path_beneath_rules_in()`](crate::path_beneath_rules_in)
+/// to open the file descriptors identifying the file hierarchies of rules.
+/// The default implementation is [`HostFs`], but other implementations enable
+/// to reuse the same policies with a different root directory ([`RootedFs`]),
+/// with pre-opened file descriptors ([`MockFs`]),
+/// or to delegate the opening to another process.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{ABI, Access, AccessFs, Policy, PolicyError, RootedFs, RulesetCreated};
+///
+/// fn create_for_container(root: &str) -> Result<RulesetCreated, PolicyError> {
+///     let abi = ABI::V1;
+///     Policy::default()
+///         .handle_fs(AccessFs::from_all(abi))
+///         .allow_path("/usr", AccessFs::from_read(abi))
+///         .create_in(RootedFs::new(root))
+/// }
+/// ```
+pub trait PolicyFs {
+    /// Opens `path` to identify a file hierarchy (e.g., with `O_PATH`).
+    fn open_path(&self, path: &Path) -> Result<PathFd, PathFdError>;
+
+    /// Returns the canonical form of `path` (i.e. with symbolic links and `..` components
+    /// resolved), used by [`Policy::normalize_paths_in()`](crate::Policy::normalize_paths_in).
+    ///
+    /// The default implementation calls [`std::fs::canonicalize()`].
+    fn canonicalize(&self, path: &Path) -> Result<PathBuf, Error> {
+        fs::canonicalize(path)
+    }
+
+    /// Returns the metadata of the file identified by `path` (e.g. its device and inode numbers),
+    /// used by [`Policy::normalize_paths_in()`](crate::Policy::normalize_paths_in).
+    ///
+    /// The default implementation calls [`std::fs::metadata()`].
+    fn metadata(&self, path: &Path) -> Result<Metadata, Error> {
+        fs::metadata(path)
+    }
+}
+
+impl<T> PolicyFs for &T
+where
+    T: PolicyFs + ?Sized,
+{
+    fn open_path(&self, path: &Path) -> Result<PathFd, PathFdError> {
+        (**self).open_path(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> Result<PathBuf, Error> {
+        (**self).canonicalize(path)
+    }
+
+    fn metadata(&self, path: &Path) -> Result<Metadata, Error> {
+        (**self).metadata(path)
+    }
+}
+
+// Returns the metadata of the file opened as fd, which may be an O_PATH file descriptor.
+fn fd_metadata(fd: &OwnedFd) -> Result<Metadata, Error> {
+    File::from(fd.try_clone()?).metadata()
+}
+
+fn path_fd_io_error(error: PathFdError) -> Error {
+    match error {
+        PathFdError::OpenCall { source, .. } => source,
+        PathFdError::Symlink { .. } => Error::from_raw_os_error(libc::ELOOP),
+    }
+}
+
+/// File system of the running process, opening paths with [`PathFd::new()`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HostFs;
+
+impl PolicyFs for HostFs {
+    fn open_path(&self, path: &Path) -> Result<PathFd, PathFdError> {
+        PathFd::new(path)
+    }
+}
+
+/// File system of the running process, opening paths with [`PathFd::with_resolve()`].
+///
+/// # Example
+///
+/// ```
+/// use landlock::{ABI, Access, AccessFs, Policy, PolicyError, ResolveFs, Resolve, RulesetCreated};
+///
+/// fn create_without_magic_links() -> Result<RulesetCreated, PolicyError> {
+///     let abi = ABI::V1;
+///     Policy::default()
+///         .handle_fs(AccessFs::from_all(abi))
+///         .allow_path("/usr", AccessFs::from_read(abi))
+///         .create_in(ResolveFs::new(Resolve::NoMagiclinks))
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ResolveFs {
+    resolve: BitFlags<Resolve>,
+    no_follow: bool,
+    fallback: bool,
+}
+
+impl ResolveFs {
+    /// Creates a file system restricting the path resolution according to `resolve`.
+    pub fn new<R>(resolve: R) -> Self
+    where
+        R: Into<BitFlags<Resolve>>,
+    {
+        ResolveFs {
+            resolve: resolve.into(),
+            no_follow: false,
+            fallback: false,
+        }
+    }
+
+    /// Rejects paths which are symbolic links, with [`PathFdError::Symlink`]
+    /// (see [`PathFd::new_nofollow()`]).
+    pub fn set_no_follow(mut self, no_follow: bool) -> Self {
+        self.no_follow = no_follow;
+        self
+    }
+
+    /// Opens paths without restricting their resolution
+    /// on kernels without `openat2(2)` (i.e. Linux < 5.6),
+    /// instead of failing with `ENOSYS`.
+    pub fn set_fallback(mut self, fallback: bool) -> Self {
+        self.fallback = fallback;
+        self
+    }
+}
+
+impl PolicyFs for ResolveFs {
+    fn open_path(&self, path: &Path) -> Result<PathFd, PathFdError> {
+        match PathFd::open(path, Some(self.resolve), self.no_follow) {
+            Err(PathFdError::OpenCall { ref source, .. })
+                if self.fallback && source.raw_os_error() == Some(libc::ENOSYS) =>
+            {
+                PathFd::open(path, None, self.no_follow)
+            }
+            ret => ret,
+        }
+    }
+
+    // Resolves the path with the same restrictions as open_path().
+    fn canonicalize(&self, path: &Path) -> Result<PathBuf, Error> {
+        let fd = OwnedFd::from(self.open_path(path).map_err(path_fd_io_error)?);
+        fs::read_link(format!("/proc/self/fd/{}", fd.as_raw_fd()))
+    }
+
+    fn metadata(&self, path: &Path) -> Result<Metadata, Error> {
+        fd_metadata(&self.open_path(path).map_err(path_fd_io_error)?.into())
+    }
+}
+
+/// File system resolving absolute paths relative to a root directory,
+/// e.g. the root of a container or of a chroot not entered yet.
+///
+/// Paths are lexically joined to the root directory, without resolving `..` components
+/// beyond it.  However, symbolic links are still resolved by the kernel
+/// and may then point outside of the root directory.
+#[derive(Debug, Clone)]
+pub struct RootedFs {
+    root: PathBuf,
+}
+
+impl RootedFs {
+    /// Creates a file system rooted at the `root` directory.
+    pub fn new<P>(root: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        RootedFs { root: root.into() }
+    }
+
+    fn resolve(&self, path: &Path) -> PathBuf {
+        let mut resolved = self.root.clone();
+        let mut depth = 0;
+        for component in path.components() {
+            match component {
+                Component::Normal(name) => {
+                    resolved.push(name);
+                    depth += 1;
+                }
+                Component::ParentDir if depth > 0 => {
+                    resolved.pop();
+                    depth -= 1;
+                }
+                // Can't escape the root directory.
+                Component::ParentDir | Component::RootDir | Component::CurDir => {}
+                Component::Prefix(_) => {}
+            }
+        }
+        resolved
+    }
+}
+
+impl PolicyFs for RootedFs {
+    fn open_path(&self, path: &Path) -> Result<PathFd, PathFdError> {
+        PathFd::new(self.resolve(path)).map_err(|e| match e {
+            // Reports the requested path instead of the resolved one.
+            PathFdError::OpenCall { source, .. } => PathFdError::OpenCall {
+                source,
+                path: path.into(),
+            },
+            PathFdError::Symlink { .. } => PathFdError::Symlink { path: path.into() },
+        })
+    }
+
+    // Returns the canonical path relative to the root directory.
+    fn canonicalize(&self, path: &Path) -> Result<PathBuf, Error> {
+        let root = fs::canonicalize(&self.root)?;
+        let resolved = fs::canonicalize(self.resolve(path))?;
+        match resolved.strip_prefix(&root) {
+            Ok(relative) => Ok(Path::new("/").join(relative)),
+            // A symbolic link pointed outside of the root directory.
+            Err(_) => Err(Error::from_raw_os_error(libc::EXDEV)),
+        }
+    }
+
+    fn metadata(&self, path: &Path) -> Result<Metadata, Error> {
+        fs::metadata(self.resolve(path))
+    }
+}
+
+/// In-memory file system mapping paths to pre-opened file descriptors.
+///
+/// `MockFs` is useful to test policies without depending on the host's file hierarchy,
+/// or to use file descriptors received from another process.
+/// Opening a path duplicates its mapped file descriptor,
+/// and opening an unknown path fails with `ENOENT`.
+#[derive(Debug, Default)]
+pub struct MockFs {
+    paths: HashMap<PathBuf, OwnedFd>,
+}
+
+impl MockFs {
+    /// Maps `path` to `fd`, replacing any previous mapping.
+    pub fn insert<P, F>(&mut self, path: P, fd: F)
+    where
+        P: Into<PathBuf>,
+        F: Into<OwnedFd>,
+    {
+        self.paths.insert(path.into(), fd.into());
+    }
+}
+
+impl PolicyFs for MockFs {
+    fn open_path(&self, path: &Path) -> Result<PathFd, PathFdError> {
+        let open_error = |source| PathFdError::OpenCall {
+            source,
+            path: path.into(),
+        };
+        let fd = self
+            .paths
+            .get(path)
+            .ok_or_else(|| open_error(Error::from_raw_os_error(libc::ENOENT)))?;
+        Ok(fd.as_fd().try_clone_to_owned().map_err(open_error)?.into())
+    }
+
+    // Only the mapped paths exist, as is.
+    fn canonicalize(&self, path: &Path) -> Result<PathBuf, Error> {
+        match self.paths.contains_key(path) {
+            true => Ok(path.into()),
+            false => Err(Error::from_raw_os_error(libc::ENOENT)),
+        }
+    }
+
+    fn metadata(&self, path: &Path) -> Result<Metadata, Error> {
+        match self.paths.get(path) {
+            Some(fd) => fd_metadata(fd),
+            None => Err(Error::from_raw_os_error(libc::ENOENT)),
+        }
+    }
+}
+
+#[test]
+fn rooted_fs_resolve() {
+    let fs = RootedFs::new("/srv/root");
+    for (path, resolved) in [
+        ("/usr/lib", "/srv/root/usr/lib"),
+        ("usr/./lib", "/srv/root/usr/lib"),
+        ("/usr/../etc", "/srv/root/etc"),
+        ("/../../etc", "/srv/root/etc"),
+        ("/", "/srv/root"),
+    ] {
+        assert_eq!(fs.resolve(Path::new(path)), Path::new(resolved));
+    }
+
+    assert!(matches!(
+        RootedFs::new("/does-not-exist").open_path(Path::new("/etc")).unwrap_err(),
+        PathFdError::OpenCall { path, .. } if path == Path::new("/etc")
+    ));
+    RootedFs::new("/").open_path(Path::new("/etc")).unwrap();
+    assert_eq!(
+        RootedFs::new("/usr")
+            .canonicalize(Path::new("/lib/../bin"))
+            .unwrap(),
+        Path::new("/bin")
+    );
+}
+
+#[test]
+fn resolve_fs_no_follow() {
+    let fs = ResolveFs::default();
+    fs.open_path(Path::new("/proc/self")).unwrap();
+    assert_eq!(
+        fs.canonicalize(Path::new("/usr/../etc")).unwrap(),
+        Path::new("/etc")
+    );
+    assert!(matches!(
+        fs.set_no_follow(true).open_path(Path::new("/proc/self")),
+        Err(PathFdError::Symlink { .. })
+    ));
+}
+
+#[test]
+fn mock_fs_open() {
+    let mut fs = MockFs::default();
+    fs.insert("/data", std::fs::File::open("/").unwrap());
+    fs.open_path(Path::new("/data")).unwrap();
+    assert!(matches!(
+        fs.open_path(Path::new("/other")).unwrap_err(),
+        PathFdError::OpenCall { source, .. } if source.raw_os_error() == Some(libc::ENOENT)
+    ));
+
+    // Unknown paths are silently ignored by path_beneath_rules_in().
+    assert_eq!(
+        path_beneath_rules_in(&fs, ["/data", "/other"], AccessFs::ReadDir).count(),
+        1
+    );
+}
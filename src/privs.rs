@@ -0,0 +1,64 @@
+use crate::{uapi, NoNewPrivsError};
+use std::io::Error;
+
+// Doesn't allocate.
+pub(crate) fn prctl_set_no_new_privs() -> Result<(), Error> {
+    match unsafe { uapi::prctl_set_no_new_privs() } {
+        0 => Ok(()),
+        _ => Err(Error::last_os_error()),
+    }
+}
+
+pub(crate) fn support_no_new_privs() -> bool {
+    // Only Linux < 3.5 or kernel with seccomp filters should return an error.
+    matches!(unsafe { uapi::prctl_get_no_new_privs() }, 0 | 1)
+}
+
+// Returns true if the calling thread can enforce a ruleset without no_new_privs.
+pub(crate) fn has_cap_sys_admin() -> bool {
+    unsafe { uapi::has_cap_sys_admin() == 1 }
+}
+
+/// Calls `prctl(2)` with the `PR_SET_NO_NEW_PRIVS` command for the calling thread,
+/// independently of any ruleset.
+///
+/// Once set, `no_new_privs` is inherited by the child threads and processes, and it cannot be
+/// unset: `execve(2)` no longer grants privileges (e.g. set-user-ID binaries).
+/// It is required to enforce a Landlock ruleset (without `CAP_SYS_ADMIN`)
+/// but also to load a seccomp filter,
+/// which may need to be done before building the ruleset.
+/// [`RulesetCreated::restrict_self()`](crate::RulesetCreated::restrict_self) sets it anyway,
+/// unless configured otherwise with
+/// [`set_no_new_privs()`](crate::RulesetCreatedAttr::set_no_new_privs).
+///
+/// # Example
+///
+/// ```
+/// use landlock::{no_new_privs_enabled, set_no_new_privs, NoNewPrivsError};
+///
+/// fn drop_privileges() -> Result<(), NoNewPrivsError> {
+///     set_no_new_privs()?;
+///     assert!(no_new_privs_enabled());
+///     Ok(())
+/// }
+/// ```
+pub fn set_no_new_privs() -> Result<(), NoNewPrivsError> {
+    prctl_set_no_new_privs().map_err(|source| NoNewPrivsError::SetNoNewPrivsCall { source })
+}
+
+/// Returns true if `no_new_privs` is set for the calling thread,
+/// according to `prctl(2)` with the `PR_GET_NO_NEW_PRIVS` command.
+///
+/// This returns false if the running kernel doesn't support it (i.e. Linux < 3.5).
+pub fn no_new_privs_enabled() -> bool {
+    unsafe { uapi::prctl_get_no_new_privs() == 1 }
+}
+
+#[test]
+fn no_new_privs() {
+    let kernel = uapi::MockKernel::new(crate::ABI::V1).install();
+    assert!(!no_new_privs_enabled());
+    set_no_new_privs().unwrap();
+    assert!(no_new_privs_enabled());
+    assert!(kernel.no_new_privs());
+}
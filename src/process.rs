@@ -0,0 +1,672 @@
+use crate::{
+    BitFlags, CompatReport, ForkError, Policy, PolicyError, ReexecError, RestrictSelfError,
+    RestrictionStatus, RulesetCreated, RulesetStatus, ABI,
+};
+use std::env;
+use std::ffi::{CString, OsStr};
+use std::io;
+use std::mem;
+use std::os::raw::c_int;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::process::{CommandExt as _, ExitStatusExt};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use std::ptr;
+
+/// Extension trait to sandbox child processes spawned with [`Command`].
+///
+/// The ruleset is created by the parent process,
+/// which can then report any policy error before spawning the child,
+/// and enforced by the child right before calling `execve(2)`.
+/// The parent process is not restricted.
+///
+/// The ruleset file descriptor is close-on-exec and is then not leaked to the executed program.
+/// It is closed by the parent when the [`Command`] is dropped.
+/// The same [`Command`] can be spawned several times with the same ruleset.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{Access, AccessFs, CommandExt, Policy, ABI};
+/// use std::process::Command;
+///
+/// fn spawn_sandboxed() -> Result<(), Box<dyn std::error::Error>> {
+///     let abi = ABI::V1;
+///     let policy = Policy::default()
+///         .handle_fs(AccessFs::from_all(abi))
+///         .allow_paths(["/usr", "/etc"], AccessFs::from_read(abi));
+///     let status = Command::new("/usr/bin/true").landlock(&policy)?.status()?;
+///     println!("child exited with {status}");
+///     Ok(())
+/// }
+/// ```
+pub trait CommandExt {
+    /// Creates a ruleset from `policy` and enforces it on the child process
+    /// (see [`Policy::create()`]).
+    ///
+    /// On error, returns a [`PolicyError`] without modifying the command.
+    fn landlock(&mut self, policy: &Policy) -> Result<&mut Self, PolicyError>;
+
+    /// Enforces `ruleset` on the child process, as would [`RulesetCreated::restrict_self()`].
+    ///
+    /// If the enforcement fails, spawning the child returns an [`io::Error`]
+    /// with the related errno.
+    /// Unlike [`RulesetCreated::restrict_self()`],
+    /// this does not update [`SandboxHealth::current()`](crate::SandboxHealth::current).
+    fn landlock_ruleset(&mut self, ruleset: RulesetCreated) -> &mut Self;
+}
+
+impl CommandExt for Command {
+    fn landlock(&mut self, policy: &Policy) -> Result<&mut Self, PolicyError> {
+        Ok(self.landlock_ruleset(policy.create()?))
+    }
+
+    fn landlock_ruleset(&mut self, mut ruleset: RulesetCreated) -> &mut Self {
+        // The closure only runs in the forked child, in which the ruleset is a copy of the
+        // parent's one.
+        let restrict = move || match ruleset.restrict_self_async_signal_safe() {
+            Ok(_) => Ok(()),
+            Err(RestrictSelfError::SetNoNewPrivsCall { source })
+            | Err(RestrictSelfError::RestrictSelfCall { source }) => Err(
+                io::Error::from_raw_os_error(source.raw_os_error().unwrap_or(libc::EPERM)),
+            ),
+            Err(RestrictSelfError::DomainDepthExceeded { .. }) => {
+                Err(io::Error::from_raw_os_error(libc::E2BIG))
+            }
+            // The canary and the threads are not checked by restrict_self_async_signal_safe().
+            Err(RestrictSelfError::CanaryAccessible { .. })
+            | Err(RestrictSelfError::CanaryCheckCall { .. })
+            | Err(RestrictSelfError::OtherThreads { .. })
+            | Err(RestrictSelfError::ThreadCountCall { .. }) => {
+                Err(io::Error::from_raw_os_error(libc::EPERM))
+            }
+        };
+        unsafe { self.pre_exec(restrict) }
+    }
+}
+
+/// Child process created by [`fork_and_restrict()`] or [`fork_and_exec()`].
+///
+/// Contrary to [`std::process::Child`], dropping a `RestrictedChild` doesn't wait for it,
+/// which should then be done with [`wait()`](RestrictedChild::wait).
+#[derive(Debug)]
+pub struct RestrictedChild {
+    pid: libc::pid_t,
+    status: RestrictionStatus,
+}
+
+impl RestrictedChild {
+    /// Returns the process identifier of the child.
+    pub fn id(&self) -> u32 {
+        self.pid as u32
+    }
+
+    /// Returns the status of the ruleset enforcement, as confirmed by the child
+    /// before it ran the closure or executed the program.
+    pub fn restriction_status(&self) -> &RestrictionStatus {
+        &self.status
+    }
+
+    /// Waits for the child to exit and returns its exit status.
+    pub fn wait(self) -> io::Result<ExitStatus> {
+        wait_pid(self.pid)
+    }
+}
+
+fn wait_pid(pid: libc::pid_t) -> io::Result<ExitStatus> {
+    let mut status = 0;
+    loop {
+        if unsafe { libc::waitpid(pid, &mut status, 0) } >= 0 {
+            return Ok(ExitStatus::from_raw(status));
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+// Messages sent by the child to the parent through a close-on-exec pipe: a tag, the restriction
+// status, and an errno.
+const MSG_ENFORCED: u8 = 0;
+const MSG_NO_NEW_PRIVS: u8 = 1;
+const MSG_RESTRICT: u8 = 2;
+const MSG_EXEC: u8 = 3;
+const MSG_LEN: usize = 8;
+
+fn encode_msg(tag: u8, status: Option<&RestrictionStatus>, errno: c_int) -> [u8; MSG_LEN] {
+    let mut msg = [0; MSG_LEN];
+    msg[0] = tag;
+    if let Some(status) = status {
+        msg[1] = match status.ruleset {
+            RulesetStatus::FullyEnforced => 0,
+            RulesetStatus::PartiallyEnforced => 1,
+            RulesetStatus::NotEnforced => 2,
+        };
+        msg[2] = status.no_new_privs.into();
+    }
+    msg[4..].copy_from_slice(&errno.to_le_bytes());
+    msg
+}
+
+// Async-signal-safe.
+fn write_msg(fd: c_int, msg: &[u8; MSG_LEN]) {
+    // The message is smaller than PIPE_BUF and then written atomically.
+    unsafe { libc::write(fd, msg.as_ptr().cast(), MSG_LEN) };
+}
+
+// Returns None on EOF.
+fn read_msg(fd: c_int) -> io::Result<Option<[u8; MSG_LEN]>> {
+    let mut msg = [0; MSG_LEN];
+    loop {
+        match unsafe { libc::read(fd, msg.as_mut_ptr().cast(), MSG_LEN) } {
+            0 => return Ok(None),
+            n if n as usize == MSG_LEN => return Ok(Some(msg)),
+            n if n > 0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            _ => {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+fn decode_errno(msg: &[u8; MSG_LEN]) -> io::Error {
+    io::Error::from_raw_os_error(c_int::from_le_bytes([msg[4], msg[5], msg[6], msg[7]]))
+}
+
+// Async-signal-safe, to be called in the child: enforces the ruleset, reports it to the parent,
+// and returns true on success.
+fn child_restrict(ruleset: &mut RulesetCreated, fd: c_int) -> bool {
+    let msg = match ruleset.restrict_self_async_signal_safe() {
+        Ok(status) => {
+            write_msg(fd, &encode_msg(MSG_ENFORCED, Some(&status), 0));
+            return true;
+        }
+        Err(RestrictSelfError::SetNoNewPrivsCall { source }) => {
+            encode_msg(MSG_NO_NEW_PRIVS, None, source.raw_os_error().unwrap_or(0))
+        }
+        Err(RestrictSelfError::RestrictSelfCall { source }) => {
+            encode_msg(MSG_RESTRICT, None, source.raw_os_error().unwrap_or(0))
+        }
+        Err(RestrictSelfError::DomainDepthExceeded { .. }) => {
+            encode_msg(MSG_RESTRICT, None, libc::E2BIG)
+        }
+        // The canary and the threads are not checked by restrict_self_async_signal_safe().
+        Err(RestrictSelfError::CanaryAccessible { .. })
+        | Err(RestrictSelfError::CanaryCheckCall { .. })
+        | Err(RestrictSelfError::OtherThreads { .. })
+        | Err(RestrictSelfError::ThreadCountCall { .. }) => {
+            encode_msg(MSG_RESTRICT, None, libc::EPERM)
+        }
+    };
+    write_msg(fd, &msg);
+    false
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn pipe_cloexec() -> io::Result<[c_int; 2]> {
+    let mut fds = [-1; 2];
+    match unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } {
+        0 => Ok(fds),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+// Without pipe2(2), the file descriptors may leak to a concurrent fork.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn pipe_cloexec() -> io::Result<[c_int; 2]> {
+    use std::os::unix::io::{AsFd, FromRawFd, IntoRawFd, OwnedFd};
+
+    let mut fds = [-1; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let [read_fd, write_fd] = fds.map(|fd| unsafe { OwnedFd::from_raw_fd(fd) });
+    for fd in [&read_fd, &write_fd] {
+        crate::ruleset::set_cloexec(fd.as_fd(), true)?;
+    }
+    Ok([read_fd.into_raw_fd(), write_fd.into_raw_fd()])
+}
+
+// Forks and runs child in the child process with the write end of a close-on-exec pipe, then
+// waits for the enforcement confirmation, and for the exec result if wait_exec is true.
+//
+// Safety: child must be async-signal-safe and must not return.
+unsafe fn fork_with<F>(wait_exec: bool, child: F) -> Result<RestrictedChild, ForkError>
+where
+    F: FnOnce(c_int),
+{
+    let [read_fd, write_fd] = pipe_cloexec().map_err(|source| ForkError::PipeCall { source })?;
+    let pid = libc::fork();
+    if pid == 0 {
+        libc::close(read_fd);
+        child(write_fd);
+        libc::_exit(127);
+    }
+    libc::close(write_fd);
+    let ret = if pid < 0 {
+        Err(ForkError::ForkCall {
+            source: io::Error::last_os_error(),
+        })
+    } else {
+        wait_confirmation(read_fd, pid, wait_exec)
+    };
+    libc::close(read_fd);
+    ret
+}
+
+fn wait_confirmation(
+    fd: c_int,
+    pid: libc::pid_t,
+    wait_exec: bool,
+) -> Result<RestrictedChild, ForkError> {
+    let failed = |err: ForkError| {
+        // Reaps the child, which exits on failure.
+        let _ = wait_pid(pid);
+        Err(err)
+    };
+    let msg = match read_msg(fd) {
+        Ok(Some(msg)) => msg,
+        Ok(None) => {
+            return failed(ForkError::PipeCall {
+                source: io::ErrorKind::UnexpectedEof.into(),
+            })
+        }
+        Err(source) => return failed(ForkError::PipeCall { source }),
+    };
+    let status = match msg[0] {
+        MSG_ENFORCED => RestrictionStatus {
+            ruleset: match msg[1] {
+                0 => RulesetStatus::FullyEnforced,
+                1 => RulesetStatus::PartiallyEnforced,
+                _ => RulesetStatus::NotEnforced,
+            },
+            no_new_privs: msg[2] != 0,
+            // Completed by the caller.
+            abi: ABI::Unsupported,
+            dropped_fs: BitFlags::EMPTY,
+            dropped_net: BitFlags::EMPTY,
+            warnings: Vec::new(),
+            compat_report: None,
+        },
+        MSG_NO_NEW_PRIVS => {
+            return failed(
+                RestrictSelfError::SetNoNewPrivsCall {
+                    source: decode_errno(&msg),
+                }
+                .into(),
+            )
+        }
+        _ => {
+            return failed(
+                RestrictSelfError::RestrictSelfCall {
+                    source: decode_errno(&msg),
+                }
+                .into(),
+            )
+        }
+    };
+    if wait_exec {
+        // The pipe is closed on a successful exec.
+        match read_msg(fd) {
+            Ok(None) => {}
+            Ok(Some(msg)) if msg[0] == MSG_EXEC => {
+                return failed(ForkError::ExecCall {
+                    source: decode_errno(&msg),
+                })
+            }
+            Ok(Some(_)) => {
+                return failed(ForkError::PipeCall {
+                    source: io::ErrorKind::InvalidData.into(),
+                })
+            }
+            Err(source) => return failed(ForkError::PipeCall { source }),
+        }
+    }
+    Ok(RestrictedChild { pid, status })
+}
+
+/// Forks the calling process, enforces `ruleset` in the child, and runs `child` in it.
+///
+/// The child exits with the value returned by `child`, or 101 if it panics.
+/// This function returns once the child confirmed the enforcement of the ruleset,
+/// which is then available with [`RestrictedChild::restriction_status()`].
+/// If the enforcement fails, the child exits without running `child`
+/// and a wrapped [`RestrictSelfError`] is returned.
+///
+/// The parent process is not restricted,
+/// and the ruleset file descriptor is closed in both processes.
+/// As for [`RulesetCreated::restrict_self_async_signal_safe()`],
+/// the enforcement is not recorded for [`SandboxHealth::current()`](crate::SandboxHealth::current).
+///
+/// # Safety
+///
+/// If the calling process is multithreaded,
+/// `child` must only call [async-signal-safe](https://man7.org/linux/man-pages/man7/signal-safety.7.html)
+/// functions (e.g., no memory allocation),
+/// as for [`pre_exec()`](std::os::unix::process::CommandExt::pre_exec) closures.
+/// Use [`fork_and_exec()`] to execute a program instead.
+pub unsafe fn fork_and_restrict<F>(
+    mut ruleset: RulesetCreated,
+    child: F,
+) -> Result<RestrictedChild, ForkError>
+where
+    F: FnOnce() -> i32,
+{
+    let (warnings, report) = ruleset.take_report();
+    let restricted = fork_with(false, |fd| {
+        if child_restrict(&mut ruleset, fd) {
+            libc::close(fd);
+            // Dropping the ruleset would free its heap allocations (e.g. the canary path),
+            // which is not async-signal-safe.
+            if let Some(ruleset_fd) = ruleset.as_fd() {
+                libc::close(ruleset_fd.as_raw_fd());
+            }
+            mem::forget(ruleset);
+            let code = panic::catch_unwind(AssertUnwindSafe(child)).unwrap_or(101);
+            libc::_exit(code);
+        }
+    })?;
+    Ok(complete_status(restricted, warnings, report))
+}
+
+// Completes the status received from the child with the parent's view of the ruleset.
+fn complete_status(
+    mut restricted: RestrictedChild,
+    warnings: Vec<String>,
+    report: CompatReport,
+) -> RestrictedChild {
+    let mut status = RestrictionStatus::new(
+        restricted.status.ruleset,
+        restricted.status.no_new_privs,
+        report.abi,
+        report.handled_fs,
+        report.handled_net,
+    );
+    status.warnings = warnings;
+    status.compat_report = Some(report);
+    restricted.status = status;
+    restricted
+}
+
+// Returns the path of program as execvp(3) would, but without allocating in the child.
+fn find_program(program: &Path) -> PathBuf {
+    if program.as_os_str().as_bytes().contains(&b'/') {
+        return program.into();
+    }
+    env::var_os("PATH")
+        .and_then(|paths| {
+            env::split_paths(&paths)
+                .map(|dir| dir.join(program))
+                .find(|path| {
+                    path.metadata()
+                        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+                        .unwrap_or(false)
+                })
+        })
+        .unwrap_or_else(|| program.into())
+}
+
+fn to_cstring(value: &OsStr) -> Result<CString, ForkError> {
+    CString::new(value.as_bytes()).map_err(|_| ForkError::ExecCall {
+        source: io::ErrorKind::InvalidInput.into(),
+    })
+}
+
+/// Forks the calling process, enforces `ruleset` in the child, and executes `program` in it
+/// with `args` (not including the program name) and the current environment.
+///
+/// If `program` doesn't contain a slash, it is searched in the `PATH` directories.
+/// This function returns once the child confirmed the enforcement of the ruleset
+/// and executed the program.
+/// If the enforcement fails, a wrapped [`RestrictSelfError`] is returned,
+/// and if the execution fails, [`ForkError::ExecCall`] is returned.
+///
+/// Contrary to [`fork_and_restrict()`], this function is safe
+/// because the child only calls async-signal-safe functions.
+/// See [`CommandExt`] for a higher-level interface.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{fork_and_exec, Access, AccessFs, Policy, ABI};
+///
+/// fn run_sandboxed() -> Result<(), Box<dyn std::error::Error>> {
+///     let abi = ABI::V1;
+///     let ruleset = Policy::default()
+///         .handle_fs(AccessFs::from_all(abi))
+///         .allow_paths(["/usr", "/etc"], AccessFs::from_read(abi))
+///         .create()?;
+///     let child = fork_and_exec(ruleset, "true", Vec::<String>::new())?;
+///     println!("sandbox: {:?}", child.restriction_status());
+///     println!("child exited with {}", child.wait()?);
+///     Ok(())
+/// }
+/// ```
+pub fn fork_and_exec<P, I, S>(
+    mut ruleset: RulesetCreated,
+    program: P,
+    args: I,
+) -> Result<RestrictedChild, ForkError>
+where
+    P: AsRef<OsStr>,
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    // Prepares everything before forking to not allocate in the child.
+    let path = to_cstring(find_program(Path::new(program.as_ref())).as_os_str())?;
+    let mut argv = vec![to_cstring(program.as_ref())?];
+    for arg in args {
+        argv.push(to_cstring(arg.as_ref())?);
+    }
+    let mut argv_ptrs: Vec<_> = argv.iter().map(|a| a.as_ptr()).collect();
+    argv_ptrs.push(ptr::null());
+    let (warnings, report) = ruleset.take_report();
+
+    let restricted = unsafe {
+        fork_with(true, |fd| {
+            if child_restrict(&mut ruleset, fd) {
+                // The ruleset and the pipe file descriptors are close-on-exec.
+                libc::execv(path.as_ptr(), argv_ptrs.as_ptr());
+                let errno = io::Error::last_os_error().raw_os_error().unwrap_or(0);
+                write_msg(fd, &encode_msg(MSG_EXEC, None, errno));
+            }
+        })
+    }?;
+    Ok(complete_status(restricted, warnings, report))
+}
+
+/// Restricts the whole current process with `policy`
+/// by executing the current program again, with the same arguments and environment,
+/// and enforcing the ruleset at its start.
+///
+/// A thread can only restrict itself and its future children,
+/// which is why a ruleset should be enforced before any thread is spawned
+/// (e.g. by an async runtime or a library).
+/// This function must then be called at the very start of `main()`:
+/// * When called by the initial program,
+///   it creates a ruleset from `policy` (reporting any policy error),
+///   passes it through an inherited file descriptor and the
+///   [`RulesetCreated::ENV_FD_NAME`] environment variable,
+///   and replaces the process with the same program, keeping the same process ID.
+///   It only returns on error.
+/// * When called by the re-executed program,
+///   it takes the inherited ruleset (ignoring `policy`),
+///   enforces it with [`RulesetCreated::restrict_self()`],
+///   and returns the enforcement status.
+///
+/// The program must be built so that the re-executed instance calls this function
+/// before spawning any thread, e.g. with a plain `main()` building the async runtime afterwards.
+///
+/// # Example
+///
+/// ```no_run
+/// use landlock::{reexec_and_restrict, Access, AccessFs, Policy, ABI};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let abi = ABI::V1;
+///     let policy = Policy::default()
+///         .handle_fs(AccessFs::from_all(abi))
+///         .allow_paths(["/usr", "/etc"], AccessFs::from_read(abi));
+///     let status = reexec_and_restrict(&policy)?;
+///     println!("sandbox: {:?}", status.ruleset);
+///     // The whole process is now restricted, including the threads spawned from here.
+///     Ok(())
+/// }
+/// ```
+pub fn reexec_and_restrict(policy: &Policy) -> Result<RestrictionStatus, ReexecError> {
+    if let Some(ruleset) = RulesetCreated::from_inherited_env()? {
+        return Ok(ruleset.restrict_self().map_err(PolicyError::from)?);
+    }
+
+    let ruleset = policy.create()?;
+    let exe = env::current_exe().map_err(|source| ReexecError::ExecCall { source })?;
+    ruleset
+        .set_inheritable(true)
+        .map_err(|source| ReexecError::ExecCall { source })?;
+    let mut args = env::args_os();
+    let mut command = Command::new(exe);
+    if let Some(arg0) = args.next() {
+        command.arg0(arg0);
+    }
+    let source = command
+        .args(args)
+        .env(RulesetCreated::ENV_FD_NAME, ruleset.to_env_value())
+        .exec();
+    // The ruleset file descriptor is closed when dropped.
+    Err(ReexecError::ExecCall { source })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn command_landlock() {
+        if !can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+            return;
+        }
+        let abi = ABI::V1;
+        // Only allows to execute a shell, without any read access to /etc .
+        let policy = Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .allow_paths(
+                ["/bin", "/lib", "/lib64", "/usr"]
+                    .into_iter()
+                    .filter(|p| std::path::Path::new(p).exists()),
+                AccessFs::from_read(abi),
+            )
+            .set_compatibility(CompatLevel::HardRequirement);
+
+        let mut command = Command::new("/bin/sh");
+        command.args(["-c", "exec cat /etc/passwd >/dev/null 2>&1"]);
+        command.landlock(&policy).unwrap();
+        assert!(!command.status().unwrap().success());
+        // Spawning the same command again enforces the ruleset again.
+        assert!(!command.status().unwrap().success());
+
+        // The parent is not restricted.
+        assert!(std::fs::read("/etc/passwd").is_ok());
+        let status = Command::new("/bin/sh")
+            .args(["-c", "exec cat /etc/passwd >/dev/null 2>&1"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn fork_restrict_and_exec() {
+        if !can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+            return;
+        }
+        let abi = ABI::V1;
+        let policy = Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .allow_paths(
+                ["/bin", "/lib", "/lib64", "/usr"]
+                    .into_iter()
+                    .filter(|p| std::path::Path::new(p).exists()),
+                AccessFs::from_read(abi),
+            );
+
+        // The child cannot read /etc/passwd.
+        let child = fork_and_exec(policy.create().unwrap(), "cat", ["/etc/passwd"]).unwrap();
+        assert_eq!(
+            child.restriction_status().ruleset,
+            RulesetStatus::FullyEnforced
+        );
+        assert!(!child.wait().unwrap().success());
+
+        assert!(matches!(
+            fork_and_exec(policy.create().unwrap(), "/does-not-exist", ["foo"]).unwrap_err(),
+            ForkError::ExecCall { source } if source.raw_os_error() == Some(libc::ENOENT)
+        ));
+
+        // The closure runs in the restricted child.  Opening a file doesn't allocate.
+        let child = unsafe {
+            fork_and_restrict(policy.create().unwrap(), || {
+                match libc::open(b"/etc/passwd\0".as_ptr().cast(), libc::O_RDONLY) {
+                    -1 => 42,
+                    _ => 0,
+                }
+            })
+        }
+        .unwrap();
+        assert_eq!(child.wait().unwrap().code(), Some(42));
+    }
+
+    #[test]
+    fn reexec_restrict() {
+        // Set for the child test process, which is then executed again by reexec_and_restrict().
+        const CHILD_ENV: &str = "LANDLOCK_TEST_REEXEC";
+
+        if !can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+            return;
+        }
+        let abi = ABI::V1;
+        let policy = Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .allow_paths(
+                ["/bin", "/lib", "/lib64", "/usr"]
+                    .into_iter()
+                    .filter(|p| std::path::Path::new(p).exists()),
+                AccessFs::from_read(abi),
+            );
+
+        if env::var_os(CHILD_ENV).is_some() {
+            // Only returns in the re-executed test process.
+            let status = reexec_and_restrict(&policy).unwrap();
+            assert_eq!(status.ruleset, RulesetStatus::FullyEnforced);
+            assert!(env::var_os(RulesetCreated::ENV_FD_NAME).is_none());
+            assert_eq!(domain_depth(), 1);
+            assert!(std::fs::read("/etc/passwd").is_err());
+            return;
+        }
+
+        let output = Command::new(env::current_exe().unwrap())
+            .args([
+                "process::tests::reexec_restrict",
+                "--exact",
+                "--nocapture",
+                "--test-threads=1",
+            ])
+            .env(CHILD_ENV, "1")
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            output.status.success() && stdout.contains(" 1 passed;"),
+            "{stdout}{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        // The parent is not restricted.
+        assert!(std::fs::read("/etc/passwd").is_ok());
+    }
+}
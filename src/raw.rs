@@ -0,0 +1,35 @@
+//! Raw Landlock UAPI bindings, as defined by `<linux/landlock.h>`.
+//!
+//! This module enables to experiment with kernel features not covered yet by the safe API,
+//! e.g. new access rights when built with the `regen-uapi` feature and recent kernel headers.
+//! These are the kernel types and constants (generated with bindgen),
+//! and the system call numbers for the target architecture (only on Linux).
+//!
+//! This is only available with the `unstable-uapi` feature,
+//! and **its content may change with any release of this crate**, without semver guarantee.
+//!
+//! # Example
+//!
+//! ```
+//! use landlock::raw;
+//!
+//! let attr = raw::landlock_ruleset_attr {
+//!     handled_access_fs: raw::LANDLOCK_ACCESS_FS_EXECUTE.into(),
+//!     handled_access_net: 0,
+//! };
+//! let abi = unsafe {
+//!     libc::syscall(
+//!         raw::SYS_landlock_create_ruleset,
+//!         std::ptr::null::<raw::landlock_ruleset_attr>(),
+//!         0,
+//!         raw::LANDLOCK_CREATE_RULESET_VERSION,
+//!     )
+//! };
+//! # let _ = (attr, abi);
+//! ```
+
+pub use crate::uapi::landlock::*;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use crate::uapi::linux::{
+    SYS_landlock_add_rule, SYS_landlock_create_ruleset, SYS_landlock_restrict_self,
+};
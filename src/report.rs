@@ -0,0 +1,257 @@
+use crate::{Access, AccessFs, AccessNet, BitFlags, Errno, RulesetStatus, ABI};
+use std::path::PathBuf;
+
+/// Outcome of a requested feature according to the running kernel and the compatibility level,
+/// see [`CompatReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum CompatOutcome {
+    /// The feature is fully applied.
+    Applied,
+    /// Only a subset of the requested access rights is applied.
+    Degraded,
+    /// None of the requested access rights is applied.
+    Dropped,
+}
+
+/// Requested access rights and the subset of them which is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(bound = ""))]
+#[non_exhaustive]
+pub struct AccessReport<A>
+where
+    A: Access,
+{
+    /// Access rights requested by the caller.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_names::serialize")
+    )]
+    pub requested: BitFlags<A>,
+    /// Access rights taken into account, according to the running kernel
+    /// and the compatibility level.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_names::serialize")
+    )]
+    pub applied: BitFlags<A>,
+}
+
+impl<A> AccessReport<A>
+where
+    A: Access,
+{
+    pub(crate) fn new(requested: BitFlags<A>, applied: BitFlags<A>) -> Self {
+        AccessReport { requested, applied }
+    }
+
+    /// Gets the requested access rights which are not applied.
+    pub fn ignored(&self) -> BitFlags<A> {
+        self.requested & !self.applied
+    }
+
+    /// Compares the applied access rights with the requested ones.
+    pub fn outcome(&self) -> CompatOutcome {
+        if self.applied == self.requested {
+            CompatOutcome::Applied
+        } else if self.applied.is_empty() {
+            CompatOutcome::Dropped
+        } else {
+            CompatOutcome::Degraded
+        }
+    }
+}
+
+/// Report of a rule added with [`add_rule()`](crate::RulesetCreatedAttr::add_rule),
+/// see [`CompatReport::rules`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+#[non_exhaustive]
+pub enum RuleReport {
+    /// File system rule (i.e. [`PathBeneath`](crate::PathBeneath)).
+    #[non_exhaustive]
+    Fs {
+        /// Index of the rule among the rules added to the ruleset.
+        index: usize,
+        /// Path identified by the rule, if known
+        /// (see [`PathBeneath::set_path_label()`](crate::PathBeneath::set_path_label)).
+        path: Option<PathBuf>,
+        /// Access rights allowed by the rule.
+        access: AccessReport<AccessFs>,
+    },
+    /// Network rule (i.e. [`NetPort`](crate::NetPort)).
+    #[non_exhaustive]
+    Net {
+        /// Index of the rule among the rules added to the ruleset.
+        index: usize,
+        /// TCP port identified by the rule.
+        port: u16,
+        /// Access rights allowed by the rule.
+        access: AccessReport<AccessNet>,
+    },
+}
+
+impl RuleReport {
+    /// Gets the index of the rule among the rules added to the ruleset.
+    pub fn index(&self) -> usize {
+        match self {
+            RuleReport::Fs { index, .. } | RuleReport::Net { index, .. } => *index,
+        }
+    }
+
+    /// Compares the applied access rights of the rule with the requested ones.
+    pub fn outcome(&self) -> CompatOutcome {
+        match self {
+            RuleReport::Fs { access, .. } => access.outcome(),
+            RuleReport::Net { access, .. } => access.outcome(),
+        }
+    }
+
+    // Marks all the access rights as not applied.
+    pub(crate) fn into_dropped(mut self) -> Self {
+        match &mut self {
+            RuleReport::Fs { access, .. } => access.applied = BitFlags::EMPTY,
+            RuleReport::Net { access, .. } => access.applied = BitFlags::EMPTY,
+        }
+        self
+    }
+}
+
+/// Details how the requested handled access rights and rules of a ruleset
+/// are taken into account, according to the running kernel and the compatibility levels.
+///
+/// The report is available before enforcement with [`RulesetCreated::compat_report()`],
+/// and after with [`RestrictionStatus::compat_report`].
+/// Whether the ruleset is actually enforced is given by [`RestrictionStatus::ruleset`]
+/// (e.g., a dropped rule with [`CompatLevel::SoftRequirement`](crate::CompatLevel::SoftRequirement)
+/// makes the whole ruleset not enforced).
+///
+/// [`RulesetCreated::compat_report()`]: crate::RulesetCreated::compat_report
+/// [`RestrictionStatus::compat_report`]: crate::RestrictionStatus::compat_report
+/// [`RestrictionStatus::ruleset`]: crate::RestrictionStatus::ruleset
+///
+/// # Example
+///
+/// ```
+/// use landlock::{
+///     Access, AccessFs, CompatOutcome, Ruleset, RulesetAttr, RulesetCreatedAttr, RulesetError,
+///     ABI,
+/// };
+///
+/// fn check_refer() -> Result<(), RulesetError> {
+///     let ruleset = Ruleset::default()
+///         .handle_access(AccessFs::from_all(ABI::V2))?
+///         .create()?;
+///     let report = ruleset.compat_report();
+///     if report.handled_fs.outcome() != CompatOutcome::Applied {
+///         eprintln!(
+///             "warning: {:?} not supported by the running kernel ({:?})",
+///             report.handled_fs.ignored(),
+///             report.abi,
+///         );
+///     }
+///     ruleset.restrict_self()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct CompatReport {
+    /// Landlock ABI supported by the running kernel.
+    pub abi: ABI,
+    /// File system access rights handled by the ruleset.
+    pub handled_fs: AccessReport<AccessFs>,
+    /// Network access rights handled by the ruleset.
+    pub handled_net: AccessReport<AccessNet>,
+    /// Rules successfully added to the ruleset (including dropped ones), in the same order.
+    /// This is empty for a ruleset received from another process.
+    pub rules: Vec<RuleReport>,
+}
+
+impl CompatReport {
+    /// Gets the rules which are not fully applied.
+    pub fn degraded_rules(&self) -> impl Iterator<Item = &RuleReport> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.outcome() != CompatOutcome::Applied)
+    }
+
+    /// Returns true if all the handled access rights and rules are fully applied.
+    pub fn is_fully_applied(&self) -> bool {
+        self.handled_fs.outcome() == CompatOutcome::Applied
+            && self.handled_net.outcome() == CompatOutcome::Applied
+            && self.degraded_rules().next().is_none()
+    }
+}
+
+/// Compact summary of a [`RestrictionStatus`](crate::RestrictionStatus),
+/// see [`RestrictionStatus::telemetry()`](crate::RestrictionStatus::telemetry).
+///
+/// With the `serde` feature, this can be serialized (e.g. to JSON) and shipped as is to a
+/// telemetry backend, to monitor the sandboxing of a fleet of services.
+/// Contrary to the full [`CompatReport`], its size doesn't depend on the number of rules,
+/// and it doesn't contain any path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct Telemetry {
+    /// Landlock ABI supported by the running kernel.
+    pub abi: ABI,
+    /// Status of the Landlock ruleset enforcement.
+    pub status: RulesetStatus,
+    /// Status of `prctl(2)`'s `PR_SET_NO_NEW_PRIVS` enforcement.
+    pub no_new_privs: bool,
+    /// File system access rights requested to be handled but not restricted.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_names::serialize")
+    )]
+    pub dropped_fs: BitFlags<AccessFs>,
+    /// Network access rights requested to be handled but not restricted.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_names::serialize")
+    )]
+    pub dropped_net: BitFlags<AccessNet>,
+    /// Number of rules which are not fully applied, if known.
+    pub degraded_rules: Option<usize>,
+    /// Number of features ignored with the [`CompatLevel::WarnOnly`](crate::CompatLevel::WarnOnly)
+    /// compatibility level.
+    pub warnings: usize,
+    /// Error returned by the running kernel when probing Landlock, if it is not supported:
+    /// [`Errno::ENOSYS`] if the kernel is built without Landlock,
+    /// or [`Errno::EOPNOTSUPP`] if Landlock is disabled at boot time.
+    pub landlock_errno: Option<Errno>,
+}
+
+#[test]
+fn access_report_outcome() {
+    use crate::make_bitflags;
+
+    let requested = make_bitflags!(AccessFs::{Execute | Refer});
+    assert_eq!(
+        AccessReport::new(requested, requested).outcome(),
+        CompatOutcome::Applied
+    );
+    let degraded = AccessReport::new(requested, AccessFs::Execute.into());
+    assert_eq!(degraded.outcome(), CompatOutcome::Degraded);
+    assert_eq!(degraded.ignored(), AccessFs::Refer);
+    assert_eq!(
+        AccessReport::new(requested, BitFlags::EMPTY).outcome(),
+        CompatOutcome::Dropped
+    );
+    // Nothing requested.
+    assert_eq!(
+        AccessReport::<AccessNet>::new(BitFlags::EMPTY, BitFlags::EMPTY).outcome(),
+        CompatOutcome::Applied
+    );
+}
@@ -1,12 +1,27 @@
-use crate::compat::private::OptionCompatLevelMut;
+use crate::compat::{self, private::OptionCompatLevelMut};
+use crate::fs::path_beneath_rule;
+use crate::privs::{has_cap_sys_admin, prctl_set_no_new_privs, support_no_new_privs};
+use crate::scm::{recv_msg, send_msg};
+use crate::wire::{from_hex, to_hex};
 use crate::{
-    uapi, Access, AccessFs, AddRuleError, AddRulesError, BitFlags, CompatLevel, CompatState,
-    Compatibility, Compatible, CreateRulesetError, RestrictSelfError, RulesetError, TryCompat,
+    uapi, Access, AccessFs, AccessNet, AccessReport, AddRuleError, AddRulesError, BitFlags,
+    BulkRulesError, CompatLevel, CompatOutcome, CompatReport, CompatState, Compatibility,
+    Compatible, CreateRulesetError, Downgrade, DowngradeObserver, Errno, Fingerprint, PanicHook,
+    PathFd, PathFdError, RestrictSelfError, RuleReport, RulesetError, SandboxHealth, Telemetry,
+    TryCompat, WireError, ABI,
 };
-use libc::close;
-use std::io::Error;
-use std::mem::size_of_val;
-use std::os::unix::io::RawFd;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs;
+use std::io::{self, Error};
+use std::mem::{self, size_of_val};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 #[cfg(test)]
 use crate::*;
@@ -27,11 +42,21 @@ where
     fn as_ptr(&self) -> *const libc::c_void;
     fn get_type_id(&self) -> uapi::landlock_rule_type;
     fn get_flags(&self) -> u32;
+    // Access rights of the rule, updated by try_compat().
+    fn allowed_access(&self) -> BitFlags<T>;
+    // Reports the current access rights of the rule as the applied ones.
+    fn report(&self, index: usize, requested: BitFlags<T>) -> RuleReport;
+    // Identifies the rule in error messages, if it is tied to a path.
+    fn path_label(&self) -> Option<&Path>;
     fn check_consistency(&self, ruleset: &RulesetCreated) -> Result<(), AddRulesError>;
+    // Identifies the requested rule, independently of the running kernel.
+    fn update_fingerprint(&self, fingerprint: &mut Fingerprint);
 }
 
 /// Enforcement status of a ruleset.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 pub enum RulesetStatus {
     /// All requested restrictions are enforced.
     FullyEnforced,
@@ -43,6 +68,17 @@ pub enum RulesetStatus {
     NotEnforced,
 }
 
+/// Prints "fully enforced", "partially enforced", or "not enforced".
+impl fmt::Display for RulesetStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RulesetStatus::FullyEnforced => "fully enforced",
+            RulesetStatus::PartiallyEnforced => "partially enforced",
+            RulesetStatus::NotEnforced => "not enforced",
+        })
+    }
+}
+
 impl From<CompatState> for RulesetStatus {
     fn from(state: CompatState) -> Self {
         match state {
@@ -58,27 +94,157 @@ impl From<CompatState> for RulesetStatus {
 /// Status of a [`RulesetCreated`]
 /// after calling [`restrict_self()`](RulesetCreated::restrict_self).
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[non_exhaustive]
 pub struct RestrictionStatus {
     /// Status of the Landlock ruleset enforcement.
     pub ruleset: RulesetStatus,
     /// Status of `prctl(2)`'s `PR_SET_NO_NEW_PRIVS` enforcement.
     pub no_new_privs: bool,
+    /// Landlock ABI supported by the running kernel,
+    /// against which the ruleset was evaluated.
+    pub abi: ABI,
+    /// File system access rights requested to be handled but not restricted,
+    /// either because they are not supported by the running kernel
+    /// or because the ruleset is not enforced at all.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_names::serialize")
+    )]
+    pub dropped_fs: BitFlags<AccessFs>,
+    /// Network access rights requested to be handled but not restricted,
+    /// either because they are not supported by the running kernel
+    /// or because the ruleset is not enforced at all.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_names::serialize")
+    )]
+    pub dropped_net: BitFlags<AccessNet>,
+    /// Descriptions of the features ignored with the [`CompatLevel::WarnOnly`]
+    /// compatibility level, and of the threads left unrestricted with [`ThreadCheck::Warn`].
+    pub warnings: Vec<String>,
+    /// Details of the enforced features (see [`RulesetCreated::compat_report()`]),
+    /// or `None` if returned by
+    /// [`restrict_self_async_signal_safe()`](RulesetCreated::restrict_self_async_signal_safe).
+    pub compat_report: Option<CompatReport>,
 }
 
-fn prctl_set_no_new_privs() -> Result<(), Error> {
-    match unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) } {
-        0 => Ok(()),
-        _ => Err(Error::last_os_error()),
+impl RestrictionStatus {
+    // Doesn't allocate.
+    pub(crate) fn new(
+        ruleset: RulesetStatus,
+        no_new_privs: bool,
+        abi: ABI,
+        handled_fs: AccessReport<AccessFs>,
+        handled_net: AccessReport<AccessNet>,
+    ) -> Self {
+        let (dropped_fs, dropped_net) = match ruleset {
+            RulesetStatus::NotEnforced => (handled_fs.requested, handled_net.requested),
+            RulesetStatus::FullyEnforced | RulesetStatus::PartiallyEnforced => {
+                (handled_fs.ignored(), handled_net.ignored())
+            }
+        };
+        RestrictionStatus {
+            ruleset,
+            no_new_privs,
+            abi,
+            dropped_fs,
+            dropped_net,
+            warnings: Vec::new(),
+            compat_report: None,
+        }
+    }
+
+    /// Gets a compact summary of this status, meant to be sent to a telemetry backend.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{
+    ///     Access, AccessFs, Ruleset, RulesetAttr, RulesetCreatedAttr, RulesetError, ABI,
+    /// };
+    ///
+    /// fn restrict() -> Result<(), RulesetError> {
+    ///     let status = Ruleset::default()
+    ///         .handle_access(AccessFs::from_all(ABI::V4))?
+    ///         .create()?
+    ///         .restrict_self()?;
+    ///     let telemetry = status.telemetry();
+    ///     if let Some(errno) = telemetry.landlock_errno {
+    ///         eprintln!("Landlock is not supported by the running kernel: {errno}");
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn telemetry(&self) -> Telemetry {
+        Telemetry {
+            abi: self.abi,
+            status: self.ruleset,
+            no_new_privs: self.no_new_privs,
+            dropped_fs: self.dropped_fs,
+            dropped_net: self.dropped_net,
+            degraded_rules: self
+                .compat_report
+                .as_ref()
+                .map(|report| report.degraded_rules().count()),
+            warnings: self.warnings.len(),
+            // An emulated ABI (see Ruleset::from()) doesn't come from the kernel probe.
+            landlock_errno: match self.abi {
+                ABI::Unsupported => ABI::current_errno().and_then(Errno::from_raw),
+                _ => None,
+            },
+        }
     }
 }
 
-fn support_no_new_privs() -> bool {
-    // Only Linux < 3.5 or kernel with seccomp filters should return an error.
-    matches!(
-        unsafe { libc::prctl(libc::PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0) },
-        0 | 1
-    )
+/// Prints a summary for logs, e.g.
+/// "fully enforced with ABI v3, no_new_privs set; 2 of 14 requested access rights unsupported".
+impl fmt::Display for RestrictionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.abi {
+            ABI::Unsupported => write!(f, "{} without Landlock support", self.ruleset)?,
+            abi => write!(f, "{} with ABI v{}", self.ruleset, abi as i32)?,
+        }
+        let nnp = if self.no_new_privs { "set" } else { "not set" };
+        write!(f, ", no_new_privs {nnp}")?;
+        let dropped = self.dropped_fs.len() + self.dropped_net.len();
+        if dropped > 0 {
+            match &self.compat_report {
+                Some(report) => write!(
+                    f,
+                    "; {dropped} of {} requested access rights unsupported",
+                    report.handled_fs.requested.len() + report.handled_net.requested.len()
+                )?,
+                None => write!(f, "; {dropped} requested access rights unsupported")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// Configuration of prctl(2)'s PR_SET_NO_NEW_PRIVS enforcement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NoNewPrivs {
+    Disabled,
+    Enabled,
+    // Only if the thread doesn't have CAP_SYS_ADMIN and the ruleset is enforced.
+    IfNeeded,
+}
+
+/// Check of the other threads of the process before enforcing a ruleset,
+/// see [`RulesetCreatedAttr::set_thread_check()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ThreadCheck {
+    /// Adds a description of the unrestricted threads to [`RestrictionStatus::warnings`].
+    Warn,
+    /// Returns [`RestrictSelfError::OtherThreads`] without enforcing the ruleset.
+    Deny,
+}
+
+// Returns the number of threads of the calling process.
+fn thread_count() -> io::Result<usize> {
+    Ok(fs::read_dir("/proc/self/task")?.count())
 }
 
 /// Landlock ruleset builder.
@@ -92,6 +258,10 @@ fn support_no_new_privs() -> bool {
 /// (e.g., a first set of generic restrictions before reading any file,
 /// then a second set of tailored restrictions after reading the configuration).
 ///
+/// A `Ruleset` only describes the handled access rights and the compatibility configuration,
+/// and can then be cloned to derive several variants from a common base
+/// (e.g., one per worker) before [`create()`](Ruleset::create) is called for each of them.
+///
 /// # Simple example
 ///
 /// Simple helper handling only Landlock-related errors.
@@ -164,9 +334,12 @@ fn support_no_new_privs() -> bool {
 /// let status = restrict_paths(&["/usr", "/home"]).expect("failed to build the ruleset");
 /// ```
 #[cfg_attr(test, derive(Debug))]
+#[derive(Clone)]
 pub struct Ruleset {
     pub(crate) requested_handled_fs: BitFlags<AccessFs>,
+    pub(crate) requested_handled_net: BitFlags<AccessNet>,
     pub(crate) actual_handled_fs: BitFlags<AccessFs>,
+    pub(crate) actual_handled_net: BitFlags<AccessNet>,
     pub(crate) compat: Compatibility,
 }
 
@@ -175,7 +348,9 @@ impl From<Compatibility> for Ruleset {
         Ruleset {
             // Non-working default handled FS accesses to force users to set them explicitely.
             requested_handled_fs: Default::default(),
+            requested_handled_net: Default::default(),
             actual_handled_fs: Default::default(),
+            actual_handled_net: Default::default(),
             compat,
         }
     }
@@ -188,6 +363,166 @@ impl From<ABI> for Ruleset {
     }
 }
 
+#[test]
+fn ruleset_lazy_probe() {
+    // The mocked kernel is installed after the ruleset creation.
+    let ruleset = Ruleset::default();
+    assert!(ruleset.compat.abi.is_none());
+    let _kernel = uapi::MockKernel::new(ABI::V1).install();
+    let ruleset = ruleset
+        .with_max_abi(ABI::V4)
+        .handle_access(AccessFs::from_all(ABI::V2))
+        .unwrap();
+    assert_eq!(ruleset.compat.abi(), ABI::V1);
+    assert_eq!(ruleset.actual_handled_fs, AccessFs::from_all(ABI::V1));
+
+    // The maximum ABI is applied once probed.
+    let ruleset = Ruleset::default()
+        .with_max_abi(ABI::Unsupported)
+        .handle_access(AccessFs::Execute)
+        .unwrap();
+    assert_eq!(ruleset.compat.abi(), ABI::Unsupported);
+    assert_eq!(ruleset.compat.state, CompatState::No);
+}
+
+#[test]
+fn ruleset_with_max_abi() {
+    let ruleset = Ruleset::from(ABI::V2).with_max_abi(ABI::V1);
+    assert_eq!(ruleset.compat.abi(), ABI::V1);
+    // The ABI is never raised.
+    let ruleset = ruleset.with_max_abi(ABI::V4);
+    assert_eq!(ruleset.compat.abi(), ABI::V1);
+    let ruleset = ruleset.handle_access(AccessFs::from_all(ABI::V2)).unwrap();
+    assert_eq!(ruleset.actual_handled_fs, AccessFs::from_all(ABI::V1));
+
+    let ruleset = Ruleset::from(ABI::V2).with_max_abi(ABI::Unsupported);
+    assert_eq!(ruleset.compat.state, CompatState::No);
+    let status = ruleset
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .create()
+        .unwrap()
+        .restrict_self()
+        .unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::NotEnforced);
+    assert_eq!(status.abi, ABI::Unsupported);
+}
+
+#[test]
+fn ruleset_handle_access_with_levels() {
+    let _kernel = uapi::MockKernel::new(ABI::V1).install();
+    let ruleset = Ruleset::default()
+        .handle_access_with_levels([
+            (AccessFs::from_all(ABI::V1), CompatLevel::HardRequirement),
+            (AccessFs::Refer.into(), CompatLevel::BestEffort),
+        ])
+        .unwrap();
+    assert_eq!(ruleset.requested_handled_fs, AccessFs::from_all(ABI::V2));
+    assert_eq!(ruleset.actual_handled_fs, AccessFs::from_all(ABI::V1));
+    assert_eq!(ruleset.compat.level, None);
+
+    assert!(matches!(
+        Ruleset::default()
+            .handle_access_with_levels([(AccessFs::Refer, CompatLevel::HardRequirement)])
+            .unwrap_err(),
+        RulesetError::HandleAccesses(HandleAccessesError::Fs(HandleAccessError::Compat(
+            CompatError::Access(AccessError::Incompatible { .. })
+        )))
+    ));
+
+    // The ruleset's level is more constrained.
+    assert!(Ruleset::new_with_level(CompatLevel::HardRequirement)
+        .handle_access_with_levels([(AccessFs::Refer, CompatLevel::BestEffort)])
+        .is_err());
+}
+
+#[test]
+fn ruleset_new_with_level() {
+    let _kernel = uapi::MockKernel::new(ABI::V1).install();
+    let ruleset = Ruleset::new_with_level(CompatLevel::HardRequirement);
+    assert_eq!(ruleset.compat.level, Some(CompatLevel::HardRequirement));
+    assert!(matches!(
+        ruleset.handle_access(AccessFs::Refer).unwrap_err(),
+        RulesetError::HandleAccesses(HandleAccessesError::Fs(HandleAccessError::Compat(
+            CompatError::Access(AccessError::Incompatible { .. })
+        )))
+    ));
+
+    // The level is inherited by the created ruleset.
+    let created = Ruleset::new_with_level(CompatLevel::SoftRequirement)
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .create()
+        .unwrap();
+    assert_eq!(created.compat.level, Some(CompatLevel::SoftRequirement));
+
+    assert_eq!(
+        Ruleset::strict().compat.level,
+        Some(CompatLevel::HardRequirement)
+    );
+}
+
+#[test]
+fn ruleset_from_abi() {
+    let _kernel = uapi::MockKernel::new(ABI::V4).install();
+    for abi in [ABI::V1, ABI::V3, ABI::V4] {
+        let ruleset = Ruleset::from_abi(abi).unwrap();
+        assert_eq!(ruleset.requested_handled_fs, AccessFs::from_all(abi));
+        assert_eq!(ruleset.requested_handled_net, AccessNet::from_all(abi));
+    }
+    assert!(matches!(
+        Ruleset::from_abi(ABI::Unsupported).unwrap().create(),
+        Err(RulesetError::CreateRuleset(
+            CreateRulesetError::MissingHandledAccess
+        ))
+    ));
+}
+
+#[test]
+fn ruleset_clone() {
+    let _kernel = uapi::MockKernel::new(ABI::V4).install();
+    let base = Ruleset::default()
+        .set_compatibility(CompatLevel::HardRequirement)
+        .handle_access(AccessFs::Execute)
+        .unwrap();
+
+    let worker = base
+        .clone()
+        .handle_access(AccessNet::ConnectTcp)
+        .unwrap()
+        .create()
+        .unwrap()
+        .add_rule(NetPort::new(443, AccessNet::ConnectTcp))
+        .unwrap();
+    assert_eq!(worker.requested_handled_net, AccessNet::ConnectTcp);
+
+    // The base is not affected by its variants.
+    assert!(base.requested_handled_net.is_empty());
+    assert_eq!(base.compat.level, Some(CompatLevel::HardRequirement));
+    assert!(base
+        .create()
+        .unwrap()
+        .add_rule(NetPort::new(443, AccessNet::ConnectTcp))
+        .is_err());
+}
+
+#[test]
+fn ruleset_require_abi() {
+    let _kernel = uapi::MockKernel::new(ABI::V3).install();
+    assert!(Ruleset::default().require_abi(ABI::V3).is_ok());
+    assert!(matches!(
+        Ruleset::default().require_abi(ABI::V4).unwrap_err(),
+        RulesetError::RequireAbi(RequireAbiError::Incompatible {
+            required: ABI::V4,
+            actual: ABI::V3,
+        })
+    ));
+    assert!(Ruleset::default()
+        .with_max_abi(ABI::V1)
+        .require_abi(ABI::V2)
+        .is_err());
+}
+
 #[test]
 fn ruleset_add_rule_iter() {
     assert!(matches!(
@@ -207,7 +542,9 @@ fn ruleset_add_rule_iter() {
 
 impl Default for Ruleset {
     /// Returns a new `Ruleset`.
-    /// This call automatically probes the running kernel to know if it supports Landlock.
+    /// This call doesn't perform any system call:
+    /// the running kernel is automatically probed to know if it supports Landlock
+    /// by the first [`handle_access()`](Ruleset::handle_access) call.
     ///
     /// To be able to successfully call [`create()`](Ruleset::create),
     /// it is required to set the handled accesses with
@@ -228,6 +565,167 @@ impl Ruleset {
         Ruleset::default()
     }
 
+    /// Returns a new `Ruleset` with the `level` compatibility level,
+    /// which is the same as `Ruleset::default().set_compatibility(level)`.
+    ///
+    /// This level applies to all the following build steps,
+    /// including the rules added to the [`RulesetCreated`],
+    /// unless [`set_compatibility()`](Compatible::set_compatibility) is called again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{
+    ///     Access, AccessFs, CompatLevel, RestrictionStatus, Ruleset, RulesetAttr, RulesetError, ABI,
+    /// };
+    ///
+    /// fn restrict_strictly() -> Result<RestrictionStatus, RulesetError> {
+    ///     Ruleset::new_with_level(CompatLevel::HardRequirement)
+    ///         .handle_access(AccessFs::from_all(ABI::V1))?
+    ///         .create()?
+    ///         .restrict_self()
+    /// }
+    /// ```
+    pub fn new_with_level(level: CompatLevel) -> Self {
+        Ruleset::default().set_compatibility(level)
+    }
+
+    /// Returns a new `Ruleset` failing closed,
+    /// which is the same as `Ruleset::new_with_level(CompatLevel::HardRequirement)`.
+    ///
+    /// Any Landlock feature not supported by the running kernel then returns a compatibility error,
+    /// for all the following build steps and rules.
+    /// Contrary to a crate-wide default, this only applies to the rulesets created this way.
+    pub fn strict() -> Self {
+        Self::new_with_level(CompatLevel::HardRequirement)
+    }
+
+    /// Returns a new `Ruleset` handling all the access rights defined by `abi`
+    /// (i.e. [`AccessFs::from_all()`] and [`AccessNet::from_all()`]),
+    /// to build a sandbox denying everything which is not explicitly allowed by a rule.
+    ///
+    /// This is the same as calling [`handle_access()`](RulesetAttr::handle_access)
+    /// for each type of access right, and the same compatibility rules apply:
+    /// with the default [`CompatLevel::BestEffort`],
+    /// access rights not supported by the running kernel are ignored.
+    ///
+    /// On error, returns a wrapped [`HandleAccessesError`](crate::HandleAccessesError).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{RestrictionStatus, Ruleset, RulesetError, ABI};
+    ///
+    /// fn deny_all() -> Result<RestrictionStatus, RulesetError> {
+    ///     Ruleset::from_abi(ABI::V4)?.create()?.restrict_self()
+    /// }
+    /// ```
+    pub fn from_abi(abi: ABI) -> Result<Self, RulesetError> {
+        let mut ruleset = Ruleset::default();
+        let fs = AccessFs::from_all(abi);
+        if !fs.is_empty() {
+            ruleset = ruleset.handle_access(fs)?;
+        }
+        let net = AccessNet::from_all(abi);
+        if !net.is_empty() {
+            ruleset = ruleset.handle_access(net)?;
+        }
+        Ok(ruleset)
+    }
+
+    /// Registers a callback called for each access right or rule ignored
+    /// because of the compatibility level
+    /// (i.e. [`CompatLevel::BestEffort`] or [`CompatLevel::SoftRequirement`]),
+    /// which is otherwise silent.
+    ///
+    /// The callback is kept by the [`RulesetCreated`] returned by [`create()`](Ruleset::create),
+    /// and then also called for the ignored rules.
+    /// It should be registered before any [`handle_access()`](RulesetAttr::handle_access) call
+    /// to be notified of all the ignored features.
+    /// Registering a new callback replaces the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Access, AccessFs, RestrictionStatus, Ruleset, RulesetAttr, RulesetError, ABI};
+    ///
+    /// fn restrict_logged() -> Result<RestrictionStatus, RulesetError> {
+    ///     let abi = ABI::V2;
+    ///     Ruleset::default()
+    ///         .on_downgrade(|downgrade| eprintln!("warning: {downgrade}"))
+    ///         .handle_access(AccessFs::from_all(abi))?
+    ///         .create()?
+    ///         .restrict_self()
+    /// }
+    /// ```
+    pub fn on_downgrade<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Downgrade) + Send + Sync + 'static,
+    {
+        self.compat.downgrades.observer = Some(DowngradeObserver::new(f));
+        self
+    }
+
+    /// Caps the Landlock ABI used to make compatibility decisions to `max`,
+    /// as if the running kernel only supported it.
+    ///
+    /// This enables developers running a recent kernel to check how their ruleset degrades
+    /// on older kernels (e.g. with the [`RestrictionStatus`] or [`CompatReport`]),
+    /// while still enforcing the supported subset.
+    /// The ABI is never raised above the one supported by the running kernel,
+    /// and [`ABI::Unsupported`] makes the ruleset not enforced at all.
+    ///
+    /// This must be called before [`handle_access()`](RulesetAttr::handle_access)
+    /// to apply to all the requested access rights.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Access, AccessFs, Ruleset, RulesetAttr, RulesetError, ABI};
+    ///
+    /// fn check_on_v1() -> Result<(), RulesetError> {
+    ///     let report = Ruleset::default()
+    ///         .with_max_abi(ABI::V1)
+    ///         .handle_access(AccessFs::from_all(ABI::V2))?
+    ///         .create()?
+    ///         .compat_report();
+    ///     // Refer is only supported since the second ABI.
+    ///     assert!(!report.handled_fs.applied.contains(AccessFs::Refer));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_max_abi(mut self, max: ABI) -> Self {
+        self.compat.set_max_abi(max);
+        self
+    }
+
+    /// Checks that the running kernel supports at least the `required` ABI,
+    /// or returns a [`RequireAbiError`](crate::RequireAbiError) otherwise,
+    /// whatever the compatibility level.
+    ///
+    /// This states the minimum requirement of a sandbox explicitly,
+    /// instead of relying on the access rights requested with
+    /// [`CompatLevel::HardRequirement`].
+    /// The ABI is capped by [`with_max_abi()`](Ruleset::with_max_abi),
+    /// see also [`ABI::require()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Access, AccessFs, Ruleset, RulesetAttr, RulesetError, RulesetCreated, ABI};
+    ///
+    /// fn create_ruleset() -> Result<RulesetCreated, RulesetError> {
+    ///     Ruleset::default()
+    ///         .require_abi(ABI::V2)?
+    ///         .handle_access(AccessFs::from_all(ABI::V2))?
+    ///         .create()
+    /// }
+    /// ```
+    pub fn require_abi(mut self, required: ABI) -> Result<Self, RulesetError> {
+        compat::require_abi(required, self.compat.probe())?;
+        Ok(self)
+    }
+
     /// Attempts to create a real Landlock ruleset (if supported by the running kernel).
     /// The returned [`RulesetCreated`] is also a builder.
     ///
@@ -235,13 +733,13 @@ impl Ruleset {
     pub fn create(mut self) -> Result<RulesetCreated, RulesetError> {
         let body = || -> Result<RulesetCreated, CreateRulesetError> {
             // Checks that there is at least one requested access.
-            if self.requested_handled_fs.is_empty() {
+            if self.requested_handled_fs.is_empty() && self.requested_handled_net.is_empty() {
                 // No handle_access() call.
                 return Err(CreateRulesetError::MissingHandledAccess);
             }
 
             // The compatibility state is initialized by handle_access() and verified by the
-            // requested_handled_fs check.
+            // requested_handled_fs and requested_handled_net checks.
             #[cfg(test)]
             assert!(!matches!(self.compat.state, CompatState::Init));
             if self.compat.state == CompatState::Init {
@@ -249,9 +747,9 @@ impl Ruleset {
             }
 
             // Checks that the ruleset handles at least one access.
-            if self.actual_handled_fs.is_empty() {
+            if self.actual_handled_fs.is_empty() && self.actual_handled_net.is_empty() {
                 match self.compat.level.into() {
-                    CompatLevel::BestEffort => {
+                    CompatLevel::BestEffort | CompatLevel::WarnOnly => {
                         self.compat.update(CompatState::No);
                     }
                     CompatLevel::SoftRequirement => {
@@ -265,16 +763,19 @@ impl Ruleset {
 
             let attr = uapi::landlock_ruleset_attr {
                 handled_access_fs: self.actual_handled_fs.bits(),
-                handled_access_net: 0,
+                handled_access_net: self.actual_handled_net.bits(),
             };
 
             match self.compat.state {
                 CompatState::Init | CompatState::No | CompatState::Dummy => {
-                    Ok(RulesetCreated::new(self, -1))
+                    Ok(RulesetCreated::new(self, None))
                 }
                 CompatState::Full | CompatState::Partial => {
                     match unsafe { uapi::landlock_create_ruleset(&attr, size_of_val(&attr), 0) } {
-                        fd if fd >= 0 => Ok(RulesetCreated::new(self, fd)),
+                        fd if fd >= 0 => Ok(RulesetCreated::new(
+                            self,
+                            Some(unsafe { OwnedFd::from_raw_fd(fd) }),
+                        )),
                         _ => Err(CreateRulesetError::CreateRulesetCall {
                             source: Error::last_os_error(),
                         }),
@@ -338,6 +839,77 @@ pub trait RulesetAttr: Sized + AsMut<Ruleset> + Compatible {
         U::ruleset_handle_access(self.as_mut(), access.into())?;
         Ok(self)
     }
+
+    /// Same as [`handle_access()`](RulesetAttr::handle_access) but borrows the ruleset,
+    /// e.g. to handle access rights in a loop or according to a condition.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{AccessFs, AccessNet, Ruleset, RulesetAttr, RulesetError};
+    ///
+    /// fn build_ruleset(network: bool) -> Result<Ruleset, RulesetError> {
+    ///     let mut ruleset = Ruleset::default();
+    ///     ruleset.handle_access_mut(AccessFs::Execute)?;
+    ///     if network {
+    ///         ruleset.handle_access_mut(AccessNet::ConnectTcp)?;
+    ///     }
+    ///     Ok(ruleset)
+    /// }
+    /// ```
+    fn handle_access_mut<T, U>(&mut self, access: T) -> Result<&mut Self, RulesetError>
+    where
+        T: Into<BitFlags<U>>,
+        U: Access,
+    {
+        U::ruleset_handle_access(self.as_mut(), access.into())?;
+        Ok(self)
+    }
+
+    /// Same as [`handle_access()`](RulesetAttr::handle_access) but with a compatibility level
+    /// for each set of access rights,
+    /// e.g. to require the access rights of a specific ABI while handling the newer ones
+    /// in a best-effort way, without calling
+    /// [`set_compatibility()`](Compatible::set_compatibility) in between.
+    ///
+    /// As for rules, the level of each set is combined with the ruleset's one,
+    /// and the most constrained level is used.
+    /// The ruleset's compatibility level is not changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{
+    ///     Access, AccessFs, CompatLevel, RestrictionStatus, Ruleset, RulesetAttr, RulesetError,
+    ///     ABI,
+    /// };
+    ///
+    /// fn restrict() -> Result<RestrictionStatus, RulesetError> {
+    ///     Ruleset::default()
+    ///         .handle_access_with_levels([
+    ///             (AccessFs::from_all(ABI::V1), CompatLevel::HardRequirement),
+    ///             (AccessFs::Refer.into(), CompatLevel::BestEffort),
+    ///         ])?
+    ///         .create()?
+    ///         .restrict_self()
+    /// }
+    /// ```
+    fn handle_access_with_levels<I, T, U>(mut self, levels: I) -> Result<Self, RulesetError>
+    where
+        I: IntoIterator<Item = (T, CompatLevel)>,
+        T: Into<BitFlags<U>>,
+        U: Access,
+    {
+        for (access, level) in levels {
+            let ruleset = self.as_mut();
+            let parent = ruleset.compat.level;
+            ruleset.compat.level = Some(CompatLevel::from(parent).max(level));
+            let result = U::ruleset_handle_access(ruleset, access.into());
+            ruleset.compat.level = parent;
+            result?;
+        }
+        Ok(self)
+    }
 }
 
 impl RulesetAttr for Ruleset {}
@@ -414,7 +986,10 @@ pub trait RulesetCreatedAttr: Sized + AsMut<RulesetCreated> + Compatible {
     /// Attempts to add a new rule to the ruleset.
     ///
     /// On error, returns a wrapped [`AddRulesError`].
-    fn add_rule<T, U>(mut self, rule: T) -> Result<Self, RulesetError>
+    ///
+    /// Once the [`setup_deadline()`](RulesetCreatedAttr::setup_deadline) is exceeded,
+    /// the rule is skipped if its compatibility level is [`CompatLevel::BestEffort`].
+    fn add_rule<T, U>(mut self, mut rule: T) -> Result<Self, RulesetError>
     where
         T: Rule<U>,
         U: Access,
@@ -422,34 +997,54 @@ pub trait RulesetCreatedAttr: Sized + AsMut<RulesetCreated> + Compatible {
         let body = || -> Result<Self, AddRulesError> {
             let self_ref = self.as_mut();
             rule.check_consistency(self_ref)?;
+            let index = self_ref.rule_reports.len();
+            let requested = rule.allowed_access();
+            let dropped = rule.report(index, requested).into_dropped();
+            if self_ref.skip_rule(rule.tailored_compat_level(self_ref.compat.level)) {
+                self_ref.rule_reports.push(dropped);
+                return Ok(self);
+            }
+            rule.update_fingerprint(&mut self_ref.fingerprint);
             let compat_rule = match rule
-                .try_compat(
+                .try_compat_observed(
                     self_ref.compat.abi(),
                     self_ref.compat.level,
                     &mut self_ref.compat.state,
+                    &mut self_ref.compat.downgrades,
                 )
                 .map_err(AddRuleError::Compat)?
             {
                 Some(r) => r,
-                None => return Ok(self),
+                None => {
+                    self_ref.dropped_rules += 1;
+                    self_ref.rule_reports.push(dropped);
+                    return Ok(self);
+                }
             };
             match self_ref.compat.state {
-                CompatState::Init | CompatState::No | CompatState::Dummy => Ok(self),
-                CompatState::Full | CompatState::Partial => match unsafe {
-                    uapi::landlock_add_rule(
-                        self_ref.fd,
-                        compat_rule.get_type_id(),
-                        compat_rule.as_ptr(),
-                        compat_rule.get_flags(),
-                    )
-                } {
-                    0 => Ok(self),
-                    _ => Err(AddRuleError::<U>::AddRuleCall {
-                        source: Error::last_os_error(),
+                CompatState::Init | CompatState::No | CompatState::Dummy => {}
+                CompatState::Full | CompatState::Partial => {
+                    if unsafe {
+                        uapi::landlock_add_rule(
+                            self_ref.raw_fd(),
+                            compat_rule.get_type_id(),
+                            compat_rule.as_ptr(),
+                            compat_rule.get_flags(),
+                        )
+                    } != 0
+                    {
+                        return Err(AddRuleError::<U>::AddRuleCall {
+                            source: Error::last_os_error(),
+                            path: compat_rule.path_label().map(Into::into),
+                        }
+                        .into());
                     }
-                    .into()),
-                },
+                }
             }
+            self_ref
+                .rule_reports
+                .push(compat_rule.report(index, requested));
+            Ok(self)
         };
         Ok(body()?)
     }
@@ -541,37 +1136,391 @@ pub trait RulesetCreatedAttr: Sized + AsMut<RulesetCreated> + Compatible {
         Ok(self)
     }
 
-    /// Configures the ruleset to call `prctl(2)` with the `PR_SET_NO_NEW_PRIVS` command
-    /// in [`restrict_self()`](RulesetCreated::restrict_self).
+    /// Same as [`add_rule()`](RulesetCreatedAttr::add_rule) but borrows the ruleset,
+    /// e.g. to add rules in a loop or according to a condition.
     ///
-    /// This is ignored if an error was encountered to a [`Ruleset`] or [`RulesetCreated`] method
-    /// call while [`CompatLevel::SoftRequirement`] was set (with
-    /// [`set_compatibility()`](Compatible::set_compatibility)).
-    fn set_no_new_privs(mut self, no_new_privs: bool) -> Self {
-        <Self as AsMut<RulesetCreated>>::as_mut(&mut self).no_new_privs = no_new_privs;
-        self
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{
+    ///     AccessNet, NetPort, Ruleset, RulesetAttr, RulesetCreated, RulesetCreatedAttr,
+    ///     RulesetError,
+    /// };
+    ///
+    /// fn build_ruleset(ports: &[u16]) -> Result<RulesetCreated, RulesetError> {
+    ///     let mut ruleset = Ruleset::default()
+    ///         .handle_access(AccessNet::ConnectTcp)?
+    ///         .create()?;
+    ///     for port in ports {
+    ///         ruleset.add_rule_mut(NetPort::new(*port, AccessNet::ConnectTcp))?;
+    ///     }
+    ///     Ok(ruleset)
+    /// }
+    /// ```
+    fn add_rule_mut<T, U>(&mut self, rule: T) -> Result<&mut Self, RulesetError>
+    where
+        T: Rule<U>,
+        U: Access,
+    {
+        self.as_mut().add_rule(rule)?;
+        Ok(self)
     }
-}
 
-/// Ruleset created with [`Ruleset::create()`].
-#[cfg_attr(test, derive(Debug))]
-pub struct RulesetCreated {
-    fd: RawFd,
-    no_new_privs: bool,
-    pub(crate) requested_handled_fs: BitFlags<AccessFs>,
-    compat: Compatibility,
-}
+    /// Same as [`add_rules()`](RulesetCreatedAttr::add_rules) but borrows the ruleset.
+    fn add_rules_mut<I, T, U, E>(&mut self, rules: I) -> Result<&mut Self, E>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+        T: Rule<U>,
+        U: Access,
+        E: From<RulesetError>,
+    {
+        self.as_mut().add_rules(rules)?;
+        Ok(self)
+    }
 
-impl RulesetCreated {
-    fn new(ruleset: Ruleset, fd: RawFd) -> Self {
-        // The compatibility state is initialized by Ruleset::create().
-        #[cfg(test)]
-        assert!(!matches!(ruleset.compat.state, CompatState::Init));
+    /// Opens each of `paths` and immediately adds a [`PathBeneath`](crate::PathBeneath) rule
+    /// for it with `access`,
+    /// calling `on_path` with the outcome for each path.
+    ///
+    /// Contrary to collecting [`path_beneath_rules()`](crate::path_beneath_rules)
+    /// before adding them,
+    /// the file descriptor of each path is closed once its rule is added,
+    /// which bounds the number of open file descriptors whatever the number of paths
+    /// (see `RLIMIT_NOFILE`).
+    /// As for `path_beneath_rules()`, paths that cannot be opened are skipped
+    /// (their error is passed to `on_path`),
+    /// and access rights are adjusted to the file type when possible.
+    /// Adding a rule may still fail as for [`add_rule()`](RulesetCreatedAttr::add_rule),
+    /// in which case the remaining paths are not processed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{
+    ///     Access, AccessFs, RestrictionStatus, Ruleset, RulesetAttr, RulesetCreatedAttr,
+    ///     RulesetError, ABI,
+    /// };
+    /// use std::path::PathBuf;
+    ///
+    /// fn restrict_many(paths: &[PathBuf]) -> Result<RestrictionStatus, RulesetError> {
+    ///     let abi = ABI::V1;
+    ///     Ruleset::default()
+    ///         .handle_access(AccessFs::from_all(abi))?
+    ///         .create()?
+    ///         .add_path_rules(paths, AccessFs::from_read(abi), |path, result| {
+    ///             if let Err(e) = result {
+    ///                 eprintln!("skipping {}: {e}", path.display());
+    ///             }
+    ///         })?
+    ///         .restrict_self()
+    /// }
+    /// ```
+    fn add_path_rules<I, P, A, F>(
+        mut self,
+        paths: I,
+        access: A,
+        mut on_path: F,
+    ) -> Result<Self, RulesetError>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+        A: Into<BitFlags<AccessFs>>,
+        F: FnMut(&Path, Result<(), &PathFdError>),
+    {
+        let access = access.into();
+        for path in paths {
+            let path = path.as_ref();
+            match PathFd::new(path) {
+                Ok(fd) => {
+                    self = self.add_rule(path_beneath_rule(fd, access, path))?;
+                    on_path(path, Ok(()));
+                }
+                Err(e) => on_path(path, Err(&e)),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Adds a [`PathBeneath`](crate::PathBeneath) rule with `access` for each of `paths`,
+    /// opening at most `chunk_size` of them at the same time.
+    ///
+    /// Contrary to [`add_path_rules()`](RulesetCreatedAttr::add_path_rules),
+    /// each chunk of paths is fully opened before adding their rules,
+    /// and any path that cannot be opened is an error.
+    /// Before opening any path, the `RLIMIT_NOFILE` soft limit is compared
+    /// with the number of file descriptors already opened by the process,
+    /// and [`BulkRulesError::FileLimit`] is returned if a chunk cannot be opened,
+    /// instead of failing with `EMFILE` after adding some of the rules.
+    ///
+    /// The number of opened file descriptors is read from `/proc/self/fd`,
+    /// and is assumed to be zero if this directory is not available.
+    fn add_path_rules_bulk<I, P, A>(
+        mut self,
+        paths: I,
+        access: A,
+        chunk_size: usize,
+    ) -> Result<Self, BulkRulesError>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+        A: Into<BitFlags<AccessFs>>,
+    {
+        let access = access.into();
+        let paths = paths.into_iter().collect::<Vec<_>>();
+        let needed = chunk_size.max(1).min(paths.len());
+        let limit = nofile_limit().map_err(|source| BulkRulesError::GetRlimitCall { source })?;
+        let available = usize::try_from(limit)
+            .unwrap_or(usize::MAX)
+            .saturating_sub(open_fds());
+        if needed > available {
+            return Err(BulkRulesError::FileLimit {
+                needed,
+                available,
+                limit,
+            });
+        }
+        for chunk in paths.chunks(chunk_size.max(1)) {
+            let rules = chunk
+                .iter()
+                .map(|path| {
+                    let path = path.as_ref();
+                    Ok(path_beneath_rule(PathFd::new(path)?, access, path))
+                })
+                .collect::<Result<Vec<_>, PathFdError>>()?;
+            for rule in rules {
+                self = self.add_rule(rule)?;
+            }
+        }
+        Ok(self)
+    }
 
+    /// Configures the ruleset to call `prctl(2)` with the `PR_SET_NO_NEW_PRIVS` command
+    /// in [`restrict_self()`](RulesetCreated::restrict_self).
+    ///
+    /// This is ignored if an error was encountered to a [`Ruleset`] or [`RulesetCreated`] method
+    /// call while [`CompatLevel::SoftRequirement`] was set (with
+    /// [`set_compatibility()`](Compatible::set_compatibility)).
+    fn set_no_new_privs(mut self, no_new_privs: bool) -> Self {
+        <Self as AsMut<RulesetCreated>>::as_mut(&mut self).no_new_privs = if no_new_privs {
+            NoNewPrivs::Enabled
+        } else {
+            NoNewPrivs::Disabled
+        };
+        self
+    }
+
+    /// Configures the ruleset to call `prctl(2)` with the `PR_SET_NO_NEW_PRIVS` command
+    /// in [`restrict_self()`](RulesetCreated::restrict_self) only if it is required
+    /// to enforce the ruleset,
+    /// i.e. if the calling thread doesn't have `CAP_SYS_ADMIN` in its user namespace.
+    ///
+    /// This enables privileged programs (e.g. container runtimes) to still execute
+    /// set-user-ID or file capability binaries once sandboxed.
+    /// Whether `no_new_privs` was set is returned in [`RestrictionStatus::no_new_privs`].
+    /// As for [`set_no_new_privs()`](RulesetCreatedAttr::set_no_new_privs),
+    /// this is ignored if an error was encountered while
+    /// [`CompatLevel::SoftRequirement`] was set.
+    fn set_no_new_privs_if_needed(mut self) -> Self {
+        <Self as AsMut<RulesetCreated>>::as_mut(&mut self).no_new_privs = NoNewPrivs::IfNeeded;
+        self
+    }
+
+    /// Bounds the time spent to add the next rules to `timeout`, starting now.
+    ///
+    /// Once the deadline is exceeded, [`add_rule()`](RulesetCreatedAttr::add_rule)
+    /// and [`add_rules()`](RulesetCreatedAttr::add_rules) skip the optional rules,
+    /// i.e. the ones with the [`CompatLevel::BestEffort`] or [`CompatLevel::WarnOnly`]
+    /// compatibility level,
+    /// but still add the mandatory ones
+    /// (see [`set_compatibility()`](Compatible::set_compatibility)).
+    /// Skipping a rule doesn't weaken the sandbox but denies the accesses it would have granted.
+    /// The number of skipped rules is returned by [`RulesetCreated::skipped_rules()`].
+    ///
+    /// This is useful for latency-critical launchers that need a bounded setup time
+    /// (e.g., with slow network file systems).
+    fn setup_deadline(mut self, timeout: Duration) -> Self {
+        <Self as AsMut<RulesetCreated>>::as_mut(&mut self).deadline =
+            Instant::now().checked_add(timeout);
+        self
+    }
+
+    /// Configures the ruleset to install `hook` once enforced
+    /// by [`restrict_self()`](RulesetCreated::restrict_self),
+    /// to append the sandbox status and the ruleset fingerprint to panic messages.
+    ///
+    /// No panic hook is installed by default.
+    fn set_panic_hook(mut self, hook: Option<PanicHook>) -> Self {
+        <Self as AsMut<RulesetCreated>>::as_mut(&mut self).panic_hook = hook;
+        self
+    }
+
+    /// Configures [`restrict_self()`](RulesetCreated::restrict_self) to check,
+    /// once the ruleset is enforced, that reading `path` is denied with `EACCES`.
+    ///
+    /// This proves that the sandbox is actually in place,
+    /// whatever the returned [`RestrictionStatus`]:
+    /// if the canary can still be opened (e.g. because Landlock is not supported
+    /// by the running kernel), or if opening it fails for another reason,
+    /// [`restrict_self()`](RulesetCreated::restrict_self) returns
+    /// [`RestrictSelfError::CanaryAccessible`] or [`RestrictSelfError::CanaryCheckCall`].
+    ///
+    /// `path` must identify an existing file or directory which is not allowed to be read
+    /// by the ruleset, which must then handle [`AccessFs::ReadFile`] or [`AccessFs::ReadDir`]
+    /// accordingly.
+    /// The check is not performed by
+    /// [`restrict_self_async_signal_safe()`](RulesetCreated::restrict_self_async_signal_safe).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{
+    ///     path_beneath_rules, Access, AccessFs, RestrictionStatus, Ruleset, RulesetAttr,
+    ///     RulesetCreatedAttr, RulesetError, ABI,
+    /// };
+    ///
+    /// fn restrict_proven() -> Result<RestrictionStatus, RulesetError> {
+    ///     let abi = ABI::V1;
+    ///     Ruleset::default()
+    ///         .handle_access(AccessFs::from_all(abi))?
+    ///         .create()?
+    ///         .add_rules(path_beneath_rules(&["/usr"], AccessFs::from_read(abi)))?
+    ///         // Fails if /etc/passwd is still readable after enforcement.
+    ///         .set_canary("/etc/passwd")
+    ///         .restrict_self()
+    /// }
+    /// ```
+    fn set_canary<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        <Self as AsMut<RulesetCreated>>::as_mut(&mut self).canary = Some(path.into());
+        self
+    }
+
+    /// Configures [`restrict_self()`](RulesetCreated::restrict_self) to check,
+    /// before enforcing the ruleset, that the calling thread is the only thread of the process.
+    ///
+    /// A ruleset only restricts the calling thread and its future children,
+    /// so the threads already running (e.g. spawned by an async runtime or a library)
+    /// are silently left unrestricted.
+    /// The threads are counted with the `/proc/self/task` entries,
+    /// and if the other threads are found (or cannot be counted) the `check` is applied.
+    /// No check is performed by default,
+    /// nor by [`restrict_self_async_signal_safe()`](RulesetCreated::restrict_self_async_signal_safe).
+    ///
+    /// See [`reexec_and_restrict()`](crate::reexec_and_restrict) to restrict a whole process
+    /// which may already have several threads.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{
+    ///     Access, AccessFs, RestrictionStatus, Ruleset, RulesetAttr, RulesetCreatedAttr,
+    ///     RulesetError, ThreadCheck, ABI,
+    /// };
+    ///
+    /// fn restrict_process() -> Result<RestrictionStatus, RulesetError> {
+    ///     Ruleset::default()
+    ///         .handle_access(AccessFs::from_all(ABI::V1))?
+    ///         .create()?
+    ///         // Fails if other threads would not be restricted.
+    ///         .set_thread_check(Some(ThreadCheck::Deny))
+    ///         .restrict_self()
+    /// }
+    /// ```
+    fn set_thread_check(mut self, check: Option<ThreadCheck>) -> Self {
+        <Self as AsMut<RulesetCreated>>::as_mut(&mut self).thread_check = check;
+        self
+    }
+}
+
+// Returns the soft limit of the number of open file descriptors.
+// The rlim_t type is not u64 on some 32-bit architectures.
+#[allow(clippy::useless_conversion)]
+fn nofile_limit() -> io::Result<u64> {
+    let mut rlim = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    match unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut rlim) } {
+        0 => Ok(u64::from(rlim.rlim_cur)),
+        _ => Err(Error::last_os_error()),
+    }
+}
+
+// Returns the number of file descriptors opened by the process.
+fn open_fds() -> usize {
+    fs::read_dir("/proc/self/fd")
+        // Ignores the directory's own file descriptor.
+        .map(|dir| dir.count().saturating_sub(1))
+        .unwrap_or(0)
+}
+
+/// Ruleset created with [`Ruleset::create()`].
+///
+/// Its [`Debug`](fmt::Debug) output shows the handled access rights and the added rules
+/// (see [`compat_report()`](RulesetCreated::compat_report)),
+/// but not the ruleset file descriptor.
+pub struct RulesetCreated {
+    fd: Option<OwnedFd>,
+    no_new_privs: NoNewPrivs,
+    pub(crate) requested_handled_fs: BitFlags<AccessFs>,
+    pub(crate) requested_handled_net: BitFlags<AccessNet>,
+    actual_handled_fs: BitFlags<AccessFs>,
+    actual_handled_net: BitFlags<AccessNet>,
+    pub(crate) fingerprint: Fingerprint,
+    panic_hook: Option<PanicHook>,
+    canary: Option<PathBuf>,
+    thread_check: Option<ThreadCheck>,
+    deadline: Option<Instant>,
+    skipped_rules: usize,
+    dropped_rules: usize,
+    rule_reports: Vec<RuleReport>,
+    compat: Compatibility,
+}
+
+impl fmt::Debug for RulesetCreated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RulesetCreated")
+            .field("abi", &self.compat.abi())
+            .field(
+                "handled_fs",
+                &AccessReport::new(self.requested_handled_fs, self.actual_handled_fs),
+            )
+            .field(
+                "handled_net",
+                &AccessReport::new(self.requested_handled_net, self.actual_handled_net),
+            )
+            .field("rules", &self.rule_reports)
+            .field("no_new_privs", &self.no_new_privs)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RulesetCreated {
+    fn new(ruleset: Ruleset, fd: Option<OwnedFd>) -> Self {
+        // The compatibility state is initialized by Ruleset::create().
+        #[cfg(test)]
+        assert!(!matches!(ruleset.compat.state, CompatState::Init));
+
+        let mut fingerprint = Fingerprint::default();
+        fingerprint.update_u64(ruleset.requested_handled_fs.bits());
+        fingerprint.update_u64(ruleset.requested_handled_net.bits());
         RulesetCreated {
             fd,
-            no_new_privs: true,
+            no_new_privs: NoNewPrivs::Enabled,
             requested_handled_fs: ruleset.requested_handled_fs,
+            requested_handled_net: ruleset.requested_handled_net,
+            actual_handled_fs: ruleset.actual_handled_fs,
+            actual_handled_net: ruleset.actual_handled_net,
+            fingerprint,
+            panic_hook: None,
+            canary: None,
+            thread_check: None,
+            deadline: None,
+            skipped_rules: 0,
+            dropped_rules: 0,
+            rule_reports: Vec::new(),
             compat: ruleset.compat,
         }
     }
@@ -583,95 +1532,1213 @@ impl RulesetCreated {
     /// according to the ruleset configuration.
     ///
     /// On error, returns a wrapped [`RestrictSelfError`].
+    ///
+    /// The result is also recorded for [`SandboxHealth::current()`],
+    /// and the panic hook is installed if any
+    /// (see [`set_panic_hook()`](RulesetCreatedAttr::set_panic_hook)).
+    ///
+    /// With the `metrics` feature, the enforcement is also reported to the installed
+    /// [metrics](https://docs.rs/metrics) recorder:
+    /// * `landlock_enforcements_total` counter, labeled with the `status`
+    ///   (e.g. `fully_enforced`);
+    /// * `landlock_abi_version` gauge, set to the ABI version of the running kernel
+    ///   (0 if not supported);
+    /// * `landlock_rules_total` counter, labeled with the rule `type` (`fs` or `net`)
+    ///   and its compatibility `outcome` (`applied`, `degraded` or `dropped`);
+    /// * `landlock_downgraded_accesses_total` counter, labeled with the `type` and the name of
+    ///   each handled `access` right not supported by the running kernel.
     pub fn restrict_self(mut self) -> Result<RestrictionStatus, RulesetError> {
-        let mut body = || -> Result<RestrictionStatus, RestrictSelfError> {
-            // FIXME: Enforce no_new_privs even if something failed with SoftRequirement. The
-            // rationale is that no_new_privs should not be an issue on its own if it is not
-            // explicitly deactivated.
-            //
-            // Ignores prctl_set_no_new_privs() if an error was encountered with
-            // CompatLevel::SoftRequirement set.
-            let enforced_nnp = if self.compat.state != CompatState::Dummy && self.no_new_privs {
-                if let Err(e) = prctl_set_no_new_privs() {
-                    match self.compat.level.into() {
-                        CompatLevel::BestEffort => {}
-                        CompatLevel::SoftRequirement => {
-                            self.compat.update(CompatState::Dummy);
+        Ok(self.restrict(true)?)
+    }
+
+    /// Enforces the ruleset with [`restrict_self()`](RulesetCreated::restrict_self),
+    /// or terminates the process with the `code` exit status
+    /// unless the ruleset is [fully enforced](RulesetStatus::FullyEnforced).
+    ///
+    /// Before exiting, `message` is printed to the standard error
+    /// followed by the error or the actual ruleset status.
+    /// This is meant for security-critical programs which must never run unsandboxed,
+    /// whatever the compatibility configuration.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use landlock::{Access, AccessFs, Ruleset, RulesetAttr, ABI};
+    ///
+    /// let status = Ruleset::default()
+    ///     .handle_access(AccessFs::from_all(ABI::V1))
+    ///     .and_then(|ruleset| ruleset.create())
+    ///     .expect("failed to create the ruleset")
+    ///     .enforce_or_exit(1, "refusing to run unsandboxed");
+    /// // Never reached if the sandbox is not fully enforced.
+    /// ```
+    pub fn enforce_or_exit<M>(self, code: i32, message: M) -> RestrictionStatus
+    where
+        M: std::fmt::Display,
+    {
+        match self.restrict_self() {
+            Ok(status) if status.ruleset == RulesetStatus::FullyEnforced => status,
+            Ok(status) => {
+                eprintln!("{message}: ruleset {:?}", status.ruleset);
+                std::process::exit(code);
+            }
+            Err(e) => {
+                eprintln!("{message}: {e}");
+                std::process::exit(code);
+            }
+        }
+    }
+
+    /// Returns the number of optional rules skipped because the
+    /// [`setup_deadline()`](RulesetCreatedAttr::setup_deadline) was exceeded.
+    pub fn skipped_rules(&self) -> usize {
+        self.skipped_rules
+    }
+
+    /// Returns the number of rules dropped because none of their access rights
+    /// are supported by the running kernel
+    /// (according to the compatibility levels).
+    ///
+    /// Contrary to the skipped rules, the accesses these rules would have granted are allowed
+    /// if the related access rights are not handled by the ruleset either.
+    pub fn dropped_rules(&self) -> usize {
+        self.dropped_rules
+    }
+
+    /// Returns the number of file system rules (i.e. [`PathBeneath`](crate::PathBeneath))
+    /// added to this ruleset, not counting the dropped and skipped ones.
+    ///
+    /// This enables to check that a configuration produced the expected number of rules
+    /// before enforcing the ruleset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{
+    ///     path_beneath_rules, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreatedAttr,
+    ///     RulesetError, ABI,
+    /// };
+    ///
+    /// fn restrict(paths: &[&str]) -> Result<(), RulesetError> {
+    ///     let abi = ABI::V1;
+    ///     let ruleset = Ruleset::default()
+    ///         .handle_access(AccessFs::from_all(abi))?
+    ///         .create()?
+    ///         .add_rules(path_beneath_rules(paths, AccessFs::from_read(abi)))?;
+    ///     if ruleset.fs_rules() != paths.len() {
+    ///         eprintln!("Some rules are dropped or skipped");
+    ///     }
+    ///     ruleset.restrict_self()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn fs_rules(&self) -> usize {
+        self.count_rules(|report| matches!(report, RuleReport::Fs { .. }))
+    }
+
+    /// Returns the number of network rules (i.e. [`NetPort`](crate::NetPort))
+    /// added to this ruleset, not counting the dropped and skipped ones.
+    pub fn net_rules(&self) -> usize {
+        self.count_rules(|report| matches!(report, RuleReport::Net { .. }))
+    }
+
+    fn count_rules<P>(&self, predicate: P) -> usize
+    where
+        P: Fn(&RuleReport) -> bool,
+    {
+        self.rule_reports
+            .iter()
+            .filter(|report| predicate(report) && report.outcome() != CompatOutcome::Dropped)
+            .count()
+    }
+
+    /// Details how the requested handled access rights and the added rules
+    /// are taken into account, according to the running kernel and the compatibility levels.
+    pub fn compat_report(&self) -> CompatReport {
+        CompatReport {
+            abi: self.compat.abi(),
+            handled_fs: AccessReport::new(self.requested_handled_fs, self.actual_handled_fs),
+            handled_net: AccessReport::new(self.requested_handled_net, self.actual_handled_net),
+            rules: self.rule_reports.clone(),
+        }
+    }
+
+    /// Gets the file system access rights effectively handled by this ruleset,
+    /// i.e. the requested ones supported by the running kernel
+    /// (according to the compatibility levels).
+    ///
+    /// This enables the sandboxed program to adapt its behavior,
+    /// e.g. to copy files instead of moving them across directories
+    /// if [`AccessFs::Refer`] is not handled, because such moves are then always denied.
+    /// This is empty if the ruleset will not be enforced at all
+    /// (see [`RulesetStatus::NotEnforced`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Access, AccessFs, Ruleset, RulesetAttr, RulesetError, ABI};
+    ///
+    /// fn can_move_files() -> Result<bool, RulesetError> {
+    ///     let ruleset = Ruleset::default()
+    ///         .handle_access(AccessFs::from_all(ABI::V2))?
+    ///         .create()?;
+    ///     Ok(ruleset.handled_fs().is_empty() || ruleset.handled_fs().contains(AccessFs::Refer))
+    /// }
+    /// ```
+    pub fn handled_fs(&self) -> BitFlags<AccessFs> {
+        match self.compat.state {
+            CompatState::Full | CompatState::Partial => self.actual_handled_fs,
+            CompatState::Init | CompatState::No | CompatState::Dummy => BitFlags::EMPTY,
+        }
+    }
+
+    /// Gets the network access rights effectively handled by this ruleset,
+    /// see [`handled_fs()`](RulesetCreated::handled_fs).
+    pub fn handled_net(&self) -> BitFlags<AccessNet> {
+        match self.compat.state {
+            CompatState::Full | CompatState::Partial => self.actual_handled_net,
+            CompatState::Init | CompatState::No | CompatState::Dummy => BitFlags::EMPTY,
+        }
+    }
+
+    /// Returns the properties of this ruleset not held by its file descriptor,
+    /// which are required to rebuild it with [`from_fd()`](RulesetCreated::from_fd).
+    pub fn metadata(&self) -> RulesetMetadata {
+        RulesetMetadata {
+            no_new_privs: self.no_new_privs,
+            requested_handled_fs: self.requested_handled_fs,
+            requested_handled_net: self.requested_handled_net,
+            actual_handled_fs: self.actual_handled_fs,
+            actual_handled_net: self.actual_handled_net,
+            fingerprint: self.fingerprint,
+            compat: self.compat.clone(),
+        }
+    }
+
+    /// Converts this ruleset into its file descriptor,
+    /// or `None` if Landlock is not supported by the running kernel
+    /// (in which case there is no file descriptor).
+    ///
+    /// The [`metadata()`](RulesetCreated::metadata) should be retrieved before
+    /// to rebuild the ruleset with [`from_fd()`](RulesetCreated::from_fd).
+    pub fn into_owned_fd(mut self) -> Option<OwnedFd> {
+        self.fd.take()
+    }
+
+    /// Borrows the file descriptor of this ruleset,
+    /// or returns `None` if Landlock is not supported by the running kernel.
+    pub fn as_fd(&self) -> Option<BorrowedFd<'_>> {
+        self.fd.as_ref().map(AsFd::as_fd)
+    }
+
+    /// Adds a rule of a type not supported by this crate yet,
+    /// e.g. defined by a recent kernel (see the `unstable-uapi` feature).
+    ///
+    /// `attr` is passed as is to the `landlock_add_rule()` system call,
+    /// with `rule_type` and without any compatibility check:
+    /// the call fails if the running kernel doesn't support this rule type.
+    /// However, nothing is added if Landlock is not supported by the running kernel
+    /// (i.e. the ruleset doesn't have a file descriptor).
+    /// This rule is part of the ruleset's fingerprint
+    /// (see [`SandboxHealth::fingerprint`](crate::SandboxHealth::fingerprint))
+    /// but not of the rule reports.
+    ///
+    /// On error, returns a wrapped [`AddRulesError::RawCall`].
+    ///
+    /// # Safety
+    ///
+    /// `attr` must be a valid attribute for `rule_type`, as defined by the kernel:
+    /// its layout must match the kernel's one and it must not be shorter,
+    /// otherwise the kernel may read past the end of `attr`.
+    /// Any file descriptor it contains must remain valid for the duration of the call.
+    pub unsafe fn add_raw_rule(
+        mut self,
+        rule_type: u32,
+        attr: &[u8],
+    ) -> Result<Self, RulesetError> {
+        self.fingerprint.update_u64(rule_type.into());
+        self.fingerprint.update(attr);
+        match self.compat.state {
+            CompatState::Init | CompatState::No | CompatState::Dummy => {}
+            CompatState::Full | CompatState::Partial => {
+                if uapi::landlock_add_rule(self.raw_fd(), rule_type, attr.as_ptr().cast(), 0) != 0 {
+                    return Err(AddRulesError::RawCall {
+                        rule_type,
+                        source: Error::last_os_error(),
+                    }
+                    .into());
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    // Returns -1 if there is no file descriptor, which is then rejected by the kernel.
+    fn raw_fd(&self) -> RawFd {
+        self.fd.as_ref().map_or(-1, AsRawFd::as_raw_fd)
+    }
+
+    /// Rebuilds a ruleset from its file descriptor and its metadata,
+    /// e.g. received from another process (see [`recv()`](RulesetCreated::recv)).
+    ///
+    /// `fd` must be a Landlock ruleset file descriptor,
+    /// otherwise [`restrict_self()`](RulesetCreated::restrict_self) will fail.
+    pub fn from_fd(fd: OwnedFd, metadata: RulesetMetadata) -> Self {
+        Self::from_parts(Some(fd), metadata)
+    }
+
+    fn from_parts(fd: Option<OwnedFd>, metadata: RulesetMetadata) -> Self {
+        RulesetCreated {
+            fd,
+            no_new_privs: metadata.no_new_privs,
+            requested_handled_fs: metadata.requested_handled_fs,
+            requested_handled_net: metadata.requested_handled_net,
+            actual_handled_fs: metadata.actual_handled_fs,
+            actual_handled_net: metadata.actual_handled_net,
+            fingerprint: metadata.fingerprint,
+            panic_hook: None,
+            canary: None,
+            thread_check: None,
+            deadline: None,
+            skipped_rules: 0,
+            dropped_rules: 0,
+            rule_reports: Vec::new(),
+            compat: metadata.compat,
+        }
+    }
+
+    /// Sends this ruleset (i.e. a duplicate of its file descriptor and its metadata)
+    /// over a Unix socket, to be received with [`recv()`](RulesetCreated::recv).
+    ///
+    /// This enables a privileged helper to build a ruleset once
+    /// and to hand it to several processes which then restrict themselves.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{
+    ///     Access, AccessFs, Ruleset, RulesetAttr, RulesetCreated, RulesetError, ABI,
+    /// };
+    /// use std::os::unix::net::UnixStream;
+    ///
+    /// fn hand_over() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (helper, target) = UnixStream::pair()?;
+    ///     Ruleset::default()
+    ///         .handle_access(AccessFs::from_all(ABI::V1))?
+    ///         .create()?
+    ///         .send(&helper)?;
+    ///
+    ///     // In the target process:
+    ///     let status = RulesetCreated::recv(&target)?.restrict_self()?;
+    ///     println!("{status:?}");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn send(&self, stream: &UnixStream) -> io::Result<()> {
+        send_msg(stream, &self.metadata().to_bytes(), self.as_fd())
+    }
+
+    /// Receives a ruleset sent with [`send()`](RulesetCreated::send).
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the received data is not a ruleset
+    /// sent by this version of the crate.
+    pub fn recv(stream: &UnixStream) -> io::Result<Self> {
+        let mut data = [0; RulesetMetadata::LEN];
+        let fd = recv_msg(stream, &mut data)?;
+        let metadata = RulesetMetadata::from_bytes(&data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        match (metadata.compat.state, &fd) {
+            (CompatState::Full | CompatState::Partial, None) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "missing ruleset file descriptor",
+            )),
+            _ => Ok(Self::from_parts(fd, metadata)),
+        }
+    }
+
+    /// Name of the environment variable used to pass a ruleset to an executed program,
+    /// see [`to_env_value()`](RulesetCreated::to_env_value).
+    pub const ENV_FD_NAME: &'static str = "LANDLOCK_RULESET_FD";
+
+    /// Configures the ruleset file descriptor to be inherited (or not) by executed programs.
+    ///
+    /// The ruleset file descriptor is close-on-exec by default.
+    /// Making it inheritable enables a launcher to pass a prebuilt ruleset
+    /// to a cooperating program, see [`to_env_value()`](RulesetCreated::to_env_value).
+    /// This has no effect if Landlock is not supported by the running kernel.
+    pub fn set_inheritable(&self, inheritable: bool) -> io::Result<()> {
+        if let Some(fd) = self.as_fd() {
+            set_cloexec(fd, !inheritable)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes the ruleset file descriptor number and its metadata as an environment variable
+    /// value, to be set with the [`ENV_FD_NAME`](RulesetCreated::ENV_FD_NAME) name.
+    ///
+    /// The executed program can then restrict itself with
+    /// [`from_inherited_env()`](RulesetCreated::from_inherited_env).
+    /// The file descriptor must be made inheritable with
+    /// [`set_inheritable()`](RulesetCreated::set_inheritable).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Access, AccessFs, Ruleset, RulesetAttr, RulesetCreated, ABI};
+    /// use std::process::Command;
+    ///
+    /// fn launch() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let ruleset = Ruleset::default()
+    ///         .handle_access(AccessFs::from_all(ABI::V1))?
+    ///         .create()?;
+    ///     ruleset.set_inheritable(true)?;
+    ///     // The child calls RulesetCreated::from_inherited_env()?.restrict_self() at startup.
+    ///     Command::new("true")
+    ///         .env(RulesetCreated::ENV_FD_NAME, ruleset.to_env_value())
+    ///         .status()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_env_value(&self) -> OsString {
+        let mut value = OsString::from(format!("{}:", self.raw_fd()));
+        value.push(to_hex(&self.metadata().to_bytes()));
+        value
+    }
+
+    /// Takes ownership of the ruleset inherited from the parent process through the
+    /// [`ENV_FD_NAME`](RulesetCreated::ENV_FD_NAME) environment variable, if any.
+    ///
+    /// The environment variable is removed to not take ownership of the file descriptor twice,
+    /// and the file descriptor is made close-on-exec again.
+    /// This should then be called once, at startup (i.e. before spawning threads).
+    ///
+    /// Returns [`WireError::InvalidFd`] if the inherited file descriptor is not a Landlock
+    /// ruleset.
+    pub fn from_inherited_env() -> Result<Option<Self>, WireError> {
+        let value = match env::var_os(Self::ENV_FD_NAME) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        env::remove_var(Self::ENV_FD_NAME);
+        Self::from_env_value(&value).map(Some)
+    }
+
+    fn from_env_value(value: &OsStr) -> Result<Self, WireError> {
+        let value = value.as_bytes();
+        let sep = value
+            .iter()
+            .position(|b| *b == b':')
+            .ok_or(WireError::BadEncoding)?;
+        let fd: RawFd = std::str::from_utf8(&value[..sep])
+            .ok()
+            .and_then(|fd| fd.parse().ok())
+            .ok_or(WireError::BadEncoding)?;
+        let metadata =
+            RulesetMetadata::from_bytes(&from_hex(OsStr::from_bytes(&value[sep + 1..]))?)?;
+        match metadata.compat.state {
+            CompatState::Full | CompatState::Partial => {
+                // The file descriptor is only borrowed if it is a ruleset.
+                if !is_ruleset_fd(fd)
+                    || set_cloexec(unsafe { BorrowedFd::borrow_raw(fd) }, true).is_err()
+                {
+                    return Err(WireError::InvalidFd { fd });
+                }
+                Ok(Self::from_parts(
+                    Some(unsafe { OwnedFd::from_raw_fd(fd) }),
+                    metadata,
+                ))
+            }
+            _ => Ok(Self::from_parts(None, metadata)),
+        }
+    }
+
+    // Returns true, and records it, if a rule with the given compatibility level must be skipped
+    // because the setup deadline is exceeded.
+    pub(crate) fn skip_rule(&mut self, level: CompatLevel) -> bool {
+        let skip =
+            level <= CompatLevel::WarnOnly && self.deadline.map_or(false, |d| Instant::now() >= d);
+        if skip {
+            self.skipped_rules += 1;
+        }
+        skip
+    }
+
+    // Same as skip_rule() for a rule without its own compatibility level.
+    pub(crate) fn skip_inherited_rule(&mut self) -> bool {
+        self.skip_rule(self.compat.level.into())
+    }
+
+    /// Same as [`restrict_self()`](RulesetCreated::restrict_self)
+    /// but guaranteed to be [async-signal-safe](https://man7.org/linux/man-pages/man7/signal-safety.7.html):
+    /// it neither allocates memory nor takes any lock.
+    ///
+    /// This makes it safe to call in a child process between `fork(2)` and `execve(2)`,
+    /// e.g. in a [`pre_exec()`](std::os::unix::process::CommandExt::pre_exec) closure
+    /// (see also [`CommandExt`](crate::CommandExt)).
+    /// The ruleset is borrowed to not close its file descriptor in the child,
+    /// and the returned error is not wrapped in a [`RulesetError`].
+    ///
+    /// Unlike [`restrict_self()`](RulesetCreated::restrict_self),
+    /// the result is not recorded for [`SandboxHealth::current()`],
+    /// the panic hook is not installed,
+    /// the returned [`RestrictionStatus::warnings`] is empty,
+    /// and there is no [`RestrictionStatus::compat_report`].
+    pub fn restrict_self_async_signal_safe(
+        &mut self,
+    ) -> Result<RestrictionStatus, RestrictSelfError> {
+        self.restrict(false)
+    }
+
+    // Doesn't allocate nor lock if record is false.
+    pub(crate) fn restrict(
+        &mut self,
+        record: bool,
+    ) -> Result<RestrictionStatus, RestrictSelfError> {
+        if record {
+            self.check_threads()?;
+        }
+
+        // FIXME: Enforce no_new_privs even if something failed with SoftRequirement. The
+        // rationale is that no_new_privs should not be an issue on its own if it is not
+        // explicitly deactivated.
+        //
+        // Ignores prctl_set_no_new_privs() if an error was encountered with
+        // CompatLevel::SoftRequirement set.
+        let set_nnp = match self.no_new_privs {
+            NoNewPrivs::Disabled => false,
+            NoNewPrivs::Enabled => true,
+            NoNewPrivs::IfNeeded => {
+                matches!(self.compat.state, CompatState::Full | CompatState::Partial)
+                    && !has_cap_sys_admin()
+            }
+        };
+        let enforced_nnp = if self.compat.state != CompatState::Dummy && set_nnp {
+            if let Err(e) = prctl_set_no_new_privs() {
+                match self.compat.level.into() {
+                    CompatLevel::BestEffort => {}
+                    CompatLevel::WarnOnly => {
+                        // Recording a warning would allocate.
+                        if record {
+                            self.compat
+                                .downgrades
+                                .warnings
+                                .push(format!("failed to set no_new_privs: {e}"));
                         }
-                        CompatLevel::HardRequirement => {
+                    }
+                    CompatLevel::SoftRequirement => {
+                        self.compat.update(CompatState::Dummy);
+                    }
+                    CompatLevel::HardRequirement => {
+                        return Err(RestrictSelfError::SetNoNewPrivsCall { source: e });
+                    }
+                }
+                // To get a consistent behavior, calls this prctl whether or not
+                // Landlock is supported by the running kernel.
+                let support_nnp = support_no_new_privs();
+                match self.compat.state {
+                    // It should not be an error for kernel (older than 3.5) not supporting
+                    // no_new_privs.
+                    CompatState::Init | CompatState::No | CompatState::Dummy => {
+                        if support_nnp {
+                            // The kernel seems to be between 3.5 (included) and 5.13 (excluded),
+                            // or Landlock is not enabled; no_new_privs should be supported anyway.
                             return Err(RestrictSelfError::SetNoNewPrivsCall { source: e });
                         }
                     }
-                    // To get a consistent behavior, calls this prctl whether or not
-                    // Landlock is supported by the running kernel.
-                    let support_nnp = support_no_new_privs();
-                    match self.compat.state {
-                        // It should not be an error for kernel (older than 3.5) not supporting
-                        // no_new_privs.
-                        CompatState::Init | CompatState::No | CompatState::Dummy => {
-                            if support_nnp {
-                                // The kernel seems to be between 3.5 (included) and 5.13 (excluded),
-                                // or Landlock is not enabled; no_new_privs should be supported anyway.
-                                return Err(RestrictSelfError::SetNoNewPrivsCall { source: e });
-                            }
-                        }
-                        // A kernel supporting Landlock should also support no_new_privs (unless
-                        // filtered by seccomp).
-                        CompatState::Full | CompatState::Partial => {
-                            return Err(RestrictSelfError::SetNoNewPrivsCall { source: e })
-                        }
+                    // A kernel supporting Landlock should also support no_new_privs (unless
+                    // filtered by seccomp).
+                    CompatState::Full | CompatState::Partial => {
+                        return Err(RestrictSelfError::SetNoNewPrivsCall { source: e })
                     }
-                    false
-                } else {
-                    true
                 }
-            } else {
                 false
-            };
+            } else {
+                true
+            }
+        } else {
+            false
+        };
 
-            match self.compat.state {
-                CompatState::Init | CompatState::No | CompatState::Dummy => Ok(RestrictionStatus {
-                    ruleset: self.compat.state.into(),
-                    no_new_privs: enforced_nnp,
-                }),
-                CompatState::Full | CompatState::Partial => {
-                    match unsafe { uapi::landlock_restrict_self(self.fd, 0) } {
-                        0 => {
-                            self.compat.update(CompatState::Full);
-                            Ok(RestrictionStatus {
-                                ruleset: self.compat.state.into(),
-                                no_new_privs: enforced_nnp,
-                            })
+        match self.compat.state {
+            CompatState::Init | CompatState::No | CompatState::Dummy => {
+                if record {
+                    self.check_canary()?;
+                    self.record_health(false, enforced_nnp);
+                }
+                Ok(self.status(enforced_nnp, record))
+            }
+            CompatState::Full | CompatState::Partial => {
+                let depth = crate::domain_depth();
+                if depth >= crate::MAX_DOMAIN_DEPTH {
+                    return Err(RestrictSelfError::DomainDepthExceeded { depth });
+                }
+                match unsafe { uapi::landlock_restrict_self(self.raw_fd(), 0) } {
+                    0 => {
+                        self.compat.update(CompatState::Full);
+                        if record {
+                            SandboxHealth::record_layer();
+                            self.check_canary()?;
+                            self.record_health(true, enforced_nnp);
+                        }
+                        Ok(self.status(enforced_nnp, record))
+                    }
+                    // TODO: match other specific Landlock restrict self errors
+                    _ => {
+                        let source = Error::last_os_error();
+                        match source.raw_os_error() {
+                            // Some domains were inherited or enforced without this crate.
+                            Some(libc::E2BIG) => Err(RestrictSelfError::DomainDepthExceeded {
+                                depth: depth.max(crate::MAX_DOMAIN_DEPTH),
+                            }),
+                            _ => Err(RestrictSelfError::RestrictSelfCall { source }),
                         }
-                        // TODO: match specific Landlock restrict self errors
-                        _ => Err(RestrictSelfError::RestrictSelfCall {
-                            source: Error::last_os_error(),
-                        }),
                     }
                 }
             }
+        }
+    }
+
+    // Doesn't allocate if alloc is false, in which case the warnings and the compatibility report
+    // are not included.
+    fn status(&self, no_new_privs: bool, alloc: bool) -> RestrictionStatus {
+        let mut status = RestrictionStatus::new(
+            self.compat.state.into(),
+            no_new_privs,
+            self.compat.abi(),
+            AccessReport::new(self.requested_handled_fs, self.actual_handled_fs),
+            AccessReport::new(self.requested_handled_net, self.actual_handled_net),
+        );
+        if alloc {
+            status.warnings = self.compat.downgrades.warnings.clone();
+            status.compat_report = Some(self.compat_report());
+        }
+        status
+    }
+
+    // Moves the warnings and the compatibility report out of this ruleset, e.g. to not free them
+    // in a forked child.
+    pub(crate) fn take_report(&mut self) -> (Vec<String>, CompatReport) {
+        let rule_reports = mem::take(&mut self.rule_reports);
+        let report = CompatReport {
+            rules: rule_reports,
+            ..self.compat_report()
         };
-        Ok(body()?)
+        (mem::take(&mut self.compat.downgrades.warnings), report)
+    }
+
+    fn check_threads(&mut self) -> Result<(), RestrictSelfError> {
+        let check = match self.thread_check {
+            Some(check) => check,
+            None => return Ok(()),
+        };
+        let threads = match thread_count() {
+            Ok(count) => count.saturating_sub(1),
+            Err(source) if check == ThreadCheck::Deny => {
+                return Err(RestrictSelfError::ThreadCountCall { source })
+            }
+            Err(e) => {
+                self.compat
+                    .downgrades
+                    .warnings
+                    .push(format!("failed to count the threads of the process: {e}"));
+                return Ok(());
+            }
+        };
+        if threads == 0 {
+            return Ok(());
+        }
+        let error = RestrictSelfError::OtherThreads { threads };
+        match check {
+            ThreadCheck::Warn => {
+                self.compat.downgrades.warnings.push(error.to_string());
+                Ok(())
+            }
+            ThreadCheck::Deny => Err(error),
+        }
+    }
+
+    // The canary is kept to check it again at each enforcement (e.g. for each thread of a
+    // ThreadRestrictor).
+    fn check_canary(&self) -> Result<(), RestrictSelfError> {
+        let path = match self.canary {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+        match fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK | libc::O_NOCTTY)
+            .open(path)
+        {
+            Ok(_) => Err(RestrictSelfError::CanaryAccessible { path: path.clone() }),
+            Err(e) if e.raw_os_error() == Some(libc::EACCES) => Ok(()),
+            Err(e) => Err(RestrictSelfError::CanaryCheckCall {
+                source: e,
+                path: path.clone(),
+            }),
+        }
+    }
+
+    fn record_health(&mut self, new_layer: bool, no_new_privs: bool) {
+        SandboxHealth::record(
+            self.compat.state.into(),
+            self.compat.abi(),
+            &self.fingerprint,
+            new_layer,
+            self.requested_handled_fs & !self.actual_handled_fs,
+            self.requested_handled_net & !self.actual_handled_net,
+            no_new_privs,
+        );
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_enforcement(
+            self.compat.state.into(),
+            self.compat.abi(),
+            &self.rule_reports,
+            self.requested_handled_fs & !self.actual_handled_fs,
+            self.requested_handled_net & !self.actual_handled_net,
+        );
+        if let Some(hook) = self.panic_hook.take() {
+            hook.install();
+        }
+    }
+}
+
+/// Properties of a [`RulesetCreated`] not held by its file descriptor,
+/// e.g. the handled access rights and the compatibility configuration.
+///
+/// See [`RulesetCreated::metadata()`] and [`RulesetCreated::from_fd()`].
+#[derive(Clone)]
+pub struct RulesetMetadata {
+    no_new_privs: NoNewPrivs,
+    requested_handled_fs: BitFlags<AccessFs>,
+    requested_handled_net: BitFlags<AccessNet>,
+    actual_handled_fs: BitFlags<AccessFs>,
+    actual_handled_net: BitFlags<AccessNet>,
+    fingerprint: Fingerprint,
+    compat: Compatibility,
+}
+
+impl RulesetMetadata {
+    /// Size of the serialized metadata.
+    pub const LEN: usize = 48;
+
+    // Version of the serialization format.
+    const VERSION: u8 = 1;
+
+    /// Serializes the metadata,
+    /// e.g. to pass it to another process along with the ruleset file descriptor.
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut data = [0; Self::LEN];
+        data[0] = Self::VERSION;
+        data[1] = self.compat.abi() as u8;
+        data[2] = match self.compat.level {
+            None => 0,
+            Some(CompatLevel::BestEffort) => 1,
+            Some(CompatLevel::SoftRequirement) => 2,
+            Some(CompatLevel::HardRequirement) => 3,
+            Some(CompatLevel::WarnOnly) => 4,
+        };
+        data[3] = match self.compat.state {
+            CompatState::Init => 0,
+            CompatState::Full => 1,
+            CompatState::Partial => 2,
+            CompatState::No => 3,
+            CompatState::Dummy => 4,
+        };
+        data[4] = match self.no_new_privs {
+            NoNewPrivs::Disabled => 0,
+            NoNewPrivs::Enabled => 1,
+            NoNewPrivs::IfNeeded => 2,
+        };
+        for (i, value) in [
+            self.requested_handled_fs.bits(),
+            self.requested_handled_net.bits(),
+            self.actual_handled_fs.bits(),
+            self.actual_handled_net.bits(),
+            self.fingerprint.to_u64(),
+        ]
+        .iter()
+        .enumerate()
+        {
+            data[8 + i * 8..16 + i * 8].copy_from_slice(&value.to_le_bytes());
+        }
+        data
+    }
+
+    /// Deserializes metadata serialized with [`to_bytes()`](RulesetMetadata::to_bytes)
+    /// by the same version of this crate.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, WireError> {
+        if data.len() < Self::LEN {
+            return Err(WireError::Truncated);
+        }
+        if data.len() > Self::LEN {
+            return Err(WireError::TrailingData);
+        }
+        if data[0] != Self::VERSION {
+            return Err(WireError::UnsupportedVersion {
+                version: data[0].into(),
+                supported: Self::VERSION.into(),
+            });
+        }
+        let value = |i: usize| {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&data[8 + i * 8..16 + i * 8]);
+            u64::from_le_bytes(bytes)
+        };
+        let fs = |bits| BitFlags::from_bits(bits).map_err(|_| WireError::UnknownAccess { bits });
+        let net = |bits| BitFlags::from_bits(bits).map_err(|_| WireError::UnknownAccess { bits });
+        let level = match data[2] {
+            0 => None,
+            1 => Some(CompatLevel::BestEffort),
+            2 => Some(CompatLevel::SoftRequirement),
+            3 => Some(CompatLevel::HardRequirement),
+            4 => Some(CompatLevel::WarnOnly),
+            _ => return Err(WireError::BadEncoding),
+        };
+        let state = match data[3] {
+            0 => CompatState::Init,
+            1 => CompatState::Full,
+            2 => CompatState::Partial,
+            3 => CompatState::No,
+            4 => CompatState::Dummy,
+            _ => return Err(WireError::BadEncoding),
+        };
+        let no_new_privs = match data[4] {
+            0 => NoNewPrivs::Disabled,
+            1 => NoNewPrivs::Enabled,
+            2 => NoNewPrivs::IfNeeded,
+            _ => return Err(WireError::BadEncoding),
+        };
+        Ok(RulesetMetadata {
+            no_new_privs,
+            requested_handled_fs: fs(value(0))?,
+            requested_handled_net: net(value(1))?,
+            actual_handled_fs: fs(value(2))?,
+            actual_handled_net: net(value(3))?,
+            fingerprint: Fingerprint::from_u64(value(4)),
+            compat: Compatibility::from_parts(ABI::from(i32::from(data[1])), level, state),
+        })
+    }
+}
+
+pub(crate) fn set_cloexec(fd: BorrowedFd, cloexec: bool) -> io::Result<()> {
+    let fd = fd.as_raw_fd();
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+    if flags < 0 {
+        return Err(Error::last_os_error());
+    }
+    let flags = if cloexec {
+        flags | libc::FD_CLOEXEC
+    } else {
+        flags & !libc::FD_CLOEXEC
+    };
+    if unsafe { libc::fcntl(fd, libc::F_SETFD, flags) } < 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(())
+}
+
+// Checks that fd is an open Landlock ruleset file descriptor.
+fn is_ruleset_fd(fd: RawFd) -> bool {
+    fd >= 0
+        && fs::read_link(format!("/proc/self/fd/{fd}"))
+            .map(|target| target.as_os_str() == "anon_inode:[landlock-ruleset]")
+            .unwrap_or(false)
+}
+
+impl AsMut<RulesetCreated> for RulesetCreated {
+    fn as_mut(&mut self) -> &mut RulesetCreated {
+        self
     }
 }
 
-impl Drop for RulesetCreated {
-    fn drop(&mut self) {
-        if self.fd >= 0 {
-            unsafe { close(self.fd) };
-        }
-    }
+impl RulesetCreatedAttr for RulesetCreated {}
+
+impl RulesetCreatedAttr for &mut RulesetCreated {}
+
+#[test]
+fn ruleset_add_raw_rule() {
+    let kernel = uapi::MockKernel::new(ABI::V4).install();
+    let create = || {
+        Ruleset::default()
+            .handle_access(AccessNet::BindTcp)
+            .unwrap()
+            .create()
+            .unwrap()
+    };
+    let port = uapi::landlock_net_port_attr {
+        allowed_access: AccessNet::BindTcp as u64,
+        port: 80,
+    };
+    let attr = unsafe {
+        std::slice::from_raw_parts(&port as *const _ as *const u8, std::mem::size_of_val(&port))
+    };
+    let ruleset =
+        unsafe { create().add_raw_rule(uapi::landlock_rule_type_LANDLOCK_RULE_NET_PORT, attr) }
+            .unwrap();
+    assert_eq!(kernel.rules(), 1);
+    // The fingerprint depends on the raw rules.
+    let other =
+        unsafe { create().add_raw_rule(uapi::landlock_rule_type_LANDLOCK_RULE_NET_PORT, attr) }
+            .unwrap();
+    assert_eq!(ruleset.fingerprint, other.fingerprint);
+    assert_ne!(ruleset.fingerprint, create().fingerprint);
+
+    // Unknown rule types are rejected by the kernel.
+    assert!(matches!(
+        unsafe { create().add_raw_rule(99, attr) },
+        Err(RulesetError::AddRules(AddRulesError::RawCall { rule_type: 99, source }))
+            if source.raw_os_error() == Some(libc::EINVAL)
+    ));
+    assert_eq!(kernel.rules(), 2);
+
+    // Nothing is added without Landlock support.
+    drop(kernel);
+    let _kernel = uapi::MockKernel::new(ABI::Unsupported).install();
+    assert!(unsafe { create().add_raw_rule(99, attr) }.is_ok());
+}
+
+#[test]
+fn restriction_status_telemetry() {
+    let restrict = || {
+        Ruleset::default()
+            .handle_access(AccessFs::from_all(ABI::V4))
+            .unwrap()
+            .handle_access(AccessNet::BindTcp)
+            .unwrap()
+            .create()
+            .unwrap()
+            .add_rule(NetPort::new(80, AccessNet::BindTcp))
+            .unwrap()
+            .restrict_self()
+            .unwrap()
+    };
+
+    let kernel = uapi::MockKernel::new(ABI::V2).install();
+    let telemetry = restrict().telemetry();
+    assert_eq!(
+        telemetry,
+        Telemetry {
+            abi: ABI::V2,
+            status: RulesetStatus::PartiallyEnforced,
+            no_new_privs: true,
+            dropped_fs: AccessFs::Truncate.into(),
+            dropped_net: AccessNet::BindTcp.into(),
+            degraded_rules: Some(1),
+            warnings: 0,
+            landlock_errno: None,
+        }
+    );
+    #[cfg(feature = "serde")]
+    assert_eq!(
+        serde_json::to_string(&telemetry).unwrap(),
+        r#"{"abi":"v2","status":"PartiallyEnforced","no_new_privs":true,"dropped_fs":["truncate"],"dropped_net":["bind_tcp"],"degraded_rules":1,"warnings":0,"landlock_errno":null}"#
+    );
+
+    drop(kernel);
+    let kernel = uapi::MockKernel::new(ABI::Unsupported).install();
+    let telemetry = restrict().telemetry();
+    assert_eq!(telemetry.status, RulesetStatus::NotEnforced);
+    assert_eq!(telemetry.landlock_errno, Some(Errno::EOPNOTSUPP));
+
+    // An emulated ABI doesn't come from the running kernel.
+    drop(kernel);
+    let _kernel = uapi::MockKernel::new(ABI::V4).install();
+    let status = Ruleset::from(ABI::Unsupported)
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .create()
+        .unwrap()
+        .restrict_self()
+        .unwrap();
+    assert_eq!(status.telemetry().landlock_errno, None);
+}
+
+#[test]
+fn ruleset_metadata_bytes() {
+    let ruleset = Ruleset::from(ABI::Unsupported)
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .create()
+        .unwrap()
+        .set_no_new_privs(false);
+    let data = ruleset.metadata().to_bytes();
+    let metadata = RulesetMetadata::from_bytes(&data).unwrap();
+    assert_eq!(metadata.to_bytes(), data);
+    assert_eq!(metadata.no_new_privs, NoNewPrivs::Disabled);
+    assert_eq!(metadata.requested_handled_fs, AccessFs::Execute);
+    assert_eq!(metadata.fingerprint, ruleset.fingerprint);
+    assert!(ruleset.into_owned_fd().is_none());
+
+    assert!(matches!(
+        RulesetMetadata::from_bytes(&data[1..]),
+        Err(WireError::Truncated)
+    ));
+    let mut bad = data;
+    bad[0] = 2;
+    assert!(matches!(
+        RulesetMetadata::from_bytes(&bad),
+        Err(WireError::UnsupportedVersion { version: 2, .. })
+    ));
+    let mut bad = data;
+    bad[15] = 0x80;
+    assert!(matches!(
+        RulesetMetadata::from_bytes(&bad),
+        Err(WireError::UnknownAccess { .. })
+    ));
+}
+
+#[test]
+fn ruleset_send_recv() {
+    let abi = ABI::V1;
+    if !compat::can_emulate(abi, abi, Some(abi)) {
+        return;
+    }
+
+    let (sender, receiver) = UnixStream::pair().unwrap();
+    let ruleset = Ruleset::from(abi)
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .create()
+        .unwrap();
+    // The same ruleset can be sent several times.
+    ruleset.send(&sender).unwrap();
+    ruleset.send(&sender).unwrap();
+    drop(ruleset);
+
+    for _ in 0..2 {
+        let ruleset = RulesetCreated::recv(&receiver).unwrap();
+        let status = std::thread::spawn(move || ruleset.restrict_self().unwrap())
+            .join()
+            .unwrap();
+        assert_eq!(status.ruleset, RulesetStatus::FullyEnforced);
+    }
+
+    // Rebuilds a ruleset from its file descriptor.
+    let ruleset = Ruleset::from(abi)
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .create()
+        .unwrap();
+    let metadata = ruleset.metadata();
+    let ruleset = RulesetCreated::from_fd(ruleset.into_owned_fd().unwrap(), metadata);
+    let status = std::thread::spawn(move || ruleset.restrict_self().unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::FullyEnforced);
+}
+
+#[test]
+fn ruleset_env_value() {
+    use std::os::unix::io::IntoRawFd;
+
+    let abi = ABI::V1;
+    if !compat::can_emulate(abi, abi, Some(abi)) {
+        return;
+    }
+
+    let ruleset = Ruleset::from(abi)
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .create()
+        .unwrap();
+    let cloexec = |ruleset: &RulesetCreated| {
+        let fd = ruleset.as_fd().unwrap().as_raw_fd();
+        (unsafe { libc::fcntl(fd, libc::F_GETFD) } & libc::FD_CLOEXEC) != 0
+    };
+    assert!(cloexec(&ruleset));
+    ruleset.set_inheritable(true).unwrap();
+    assert!(!cloexec(&ruleset));
+
+    // Simulates the executed program, which owns the file descriptor.
+    let value = ruleset.to_env_value();
+    let _ = IntoRawFd::into_raw_fd(ruleset.into_owned_fd().unwrap());
+    let ruleset = RulesetCreated::from_env_value(&value).unwrap();
+    assert!(cloexec(&ruleset));
+    let status = std::thread::spawn(move || ruleset.restrict_self().unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::FullyEnforced);
+
+    // Standard input is not a ruleset.
+    let sep = value.as_bytes().iter().position(|b| *b == b':').unwrap();
+    let mut stdin_value = OsString::from("0");
+    stdin_value.push(OsStr::from_bytes(&value.as_bytes()[sep..]));
+    assert!(matches!(
+        RulesetCreated::from_env_value(&stdin_value),
+        Err(WireError::InvalidFd { fd: 0 })
+    ));
+    assert!(matches!(
+        RulesetCreated::from_env_value(OsStr::new("3")),
+        Err(WireError::BadEncoding)
+    ));
+}
+
+#[test]
+fn no_new_privs_if_needed() {
+    let restrict = || {
+        Ruleset::default()
+            .handle_access(AccessFs::Execute)
+            .unwrap()
+            .create()
+            .unwrap()
+            .set_no_new_privs_if_needed()
+            .restrict_self()
+    };
+
+    let kernel = uapi::MockKernel::new(ABI::V1)
+        .set_cap_sys_admin(true)
+        .install();
+    let status = restrict().unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::FullyEnforced);
+    assert!(!status.no_new_privs);
+    assert!(!kernel.no_new_privs());
+    assert_eq!(kernel.domains(), 1);
+    drop(kernel);
+
+    let kernel = uapi::MockKernel::new(ABI::V1).install();
+    let status = restrict().unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::FullyEnforced);
+    assert!(status.no_new_privs);
+    assert!(kernel.no_new_privs());
+    drop(kernel);
+
+    // No need for no_new_privs if Landlock is not supported.
+    let _kernel = uapi::MockKernel::new(ABI::Unsupported).install();
+    let status = restrict().unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::NotEnforced);
+    assert!(!status.no_new_privs);
+}
+
+#[test]
+fn restrict_self_without_privileges() {
+    let _kernel = uapi::MockKernel::new(ABI::V1).install();
+    let error = Ruleset::default()
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .create()
+        .unwrap()
+        .set_no_new_privs(false)
+        .restrict_self()
+        .unwrap_err();
+    assert_eq!(error.errno(), Some(Errno::EPERM));
+    assert!(error
+        .to_string()
+        .ends_with("(the thread has neither no_new_privs nor CAP_SYS_ADMIN)"));
+}
+
+#[test]
+fn setup_deadline() {
+    let abi = ABI::V1;
+    if !compat::can_emulate(abi, abi, Some(abi)) {
+        return;
+    }
+
+    let ruleset = Ruleset::from(abi)
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .create()
+        .unwrap()
+        .add_rule(PathBeneath::new(
+            PathFd::new("/usr").unwrap(),
+            AccessFs::Execute,
+        ))
+        .unwrap()
+        .setup_deadline(Duration::ZERO)
+        // Optional rule.
+        .add_rule(PathBeneath::new(
+            PathFd::new("/usr").unwrap(),
+            AccessFs::Execute,
+        ))
+        .unwrap()
+        // Optional rule with warnings.
+        .add_rule(
+            PathBeneath::new(PathFd::new("/usr").unwrap(), AccessFs::Execute)
+                .set_compatibility(CompatLevel::WarnOnly),
+        )
+        .unwrap()
+        // Mandatory rule.
+        .add_rule(
+            PathBeneath::new(PathFd::new("/usr").unwrap(), AccessFs::Execute)
+                .set_compatibility(CompatLevel::HardRequirement),
+        )
+        .unwrap();
+    assert_eq!(ruleset.skipped_rules(), 2);
+}
+
+#[test]
+fn rule_counters() {
+    let kernel = uapi::MockKernel::new(ABI::V1).install();
+    let ruleset = Ruleset::default()
+        .handle_access(AccessFs::from_all(ABI::V2))
+        .unwrap()
+        .handle_access(AccessNet::ConnectTcp)
+        .unwrap()
+        .create()
+        .unwrap()
+        .add_rule(PathBeneath::new(
+            PathFd::new("/usr").unwrap(),
+            AccessFs::Execute | AccessFs::Refer,
+        ))
+        .unwrap()
+        // Not supported by the kernel.
+        .add_rule(PathBeneath::new(
+            PathFd::new("/tmp").unwrap(),
+            AccessFs::Refer,
+        ))
+        .unwrap()
+        .add_rule(NetPort::new(443, AccessNet::ConnectTcp))
+        .unwrap()
+        .setup_deadline(Duration::ZERO)
+        .add_rule(PathBeneath::new(
+            PathFd::new("/etc").unwrap(),
+            AccessFs::ReadFile,
+        ))
+        .unwrap();
+    assert_eq!(ruleset.fs_rules(), 1);
+    assert_eq!(ruleset.net_rules(), 0);
+    assert_eq!(ruleset.dropped_rules(), 2);
+    assert_eq!(ruleset.skipped_rules(), 1);
+
+    drop(kernel);
+    let _kernel = uapi::MockKernel::new(ABI::V4).install();
+    let ruleset = Ruleset::default()
+        .handle_access(AccessNet::ConnectTcp)
+        .unwrap()
+        .create()
+        .unwrap()
+        .add_rule(NetPort::new(443, AccessNet::ConnectTcp))
+        .unwrap();
+    assert_eq!(ruleset.fs_rules(), 0);
+    assert_eq!(ruleset.net_rules(), 1);
+    assert_eq!(ruleset.dropped_rules(), 0);
 }
 
-impl AsMut<RulesetCreated> for RulesetCreated {
-    fn as_mut(&mut self) -> &mut RulesetCreated {
-        self
+#[test]
+fn restrict_self_async_signal_safe() {
+    let abi = ABI::V1;
+    if !compat::can_emulate(abi, abi, Some(abi)) {
+        return;
     }
-}
-
-impl RulesetCreatedAttr for RulesetCreated {}
 
-impl RulesetCreatedAttr for &mut RulesetCreated {}
+    let status = std::thread::spawn(move || {
+        let mut ruleset = Ruleset::from(abi)
+            .handle_access(AccessFs::Execute)
+            .unwrap()
+            .create()
+            .unwrap();
+        ruleset.restrict_self_async_signal_safe()
+    })
+    .join()
+    .unwrap();
+    assert!(matches!(
+        status,
+        Ok(RestrictionStatus {
+            ruleset: RulesetStatus::FullyEnforced,
+            no_new_privs: true,
+            ..
+        })
+    ));
+}
 
 #[test]
 fn ruleset_created_attr() {
@@ -697,7 +2764,7 @@ fn ruleset_created_attr() {
         .unwrap();
 
     // ...and finally restrict with the last rules (thanks to non-lexical lifetimes).
-    assert_eq!(
+    assert!(matches!(
         ruleset_created
             .set_compatibility(CompatLevel::BestEffort)
             .add_rule(PathBeneath::new(
@@ -715,13 +2782,108 @@ fn ruleset_created_attr() {
         RestrictionStatus {
             ruleset: RulesetStatus::NotEnforced,
             no_new_privs: true,
+            ..
         }
+    ));
+}
+
+#[test]
+fn ruleset_created_handled_access() {
+    let _kernel = uapi::MockKernel::new(ABI::V3).install();
+    let ruleset = Ruleset::default()
+        .handle_access(AccessFs::from_all(ABI::V4))
+        .unwrap()
+        .handle_access(AccessNet::from_all(ABI::V4))
+        .unwrap()
+        .create()
+        .unwrap();
+    assert_eq!(ruleset.handled_fs(), AccessFs::from_all(ABI::V3));
+    assert!(ruleset.handled_net().is_empty());
+
+    // Nothing is handled by a ruleset which will not be enforced.
+    let ruleset = Ruleset::default()
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .set_compatibility(CompatLevel::SoftRequirement)
+        .handle_access(AccessNet::BindTcp)
+        .unwrap()
+        .create()
+        .unwrap();
+    assert!(ruleset.handled_fs().is_empty());
+}
+
+#[test]
+fn ruleset_created_debug() {
+    let _kernel = uapi::MockKernel::new(ABI::V1).install();
+    let rule =
+        PathBeneath::new(PathFd::new("/usr").unwrap(), AccessFs::ReadFile).set_path_label("/usr");
+    assert_eq!(
+        format!("{rule:?}"),
+        "PathBeneath { path: Some(\"/usr\"), \
+        allowed_access: BitFlags<AccessFs>(0b100, ReadFile), compat_level: None, .. }"
     );
+
+    assert_eq!(
+        format!("{:?}", NetPort::new(443, AccessNet::ConnectTcp)),
+        "NetPort { port: 443, allowed_access: BitFlags<AccessNet>(0b10, ConnectTcp), \
+        compat_level: None }"
+    );
+
+    let ruleset = Ruleset::default()
+        .handle_access(AccessFs::from_all(ABI::V2))
+        .unwrap()
+        .create()
+        .unwrap()
+        .add_rule(rule)
+        .unwrap();
+    let ruleset_debug = format!("{ruleset:?}");
+    assert!(ruleset_debug.starts_with("RulesetCreated { abi: V1, handled_fs: AccessReport {"));
+    assert!(ruleset_debug.contains("rules: [Fs { index: 0, path: Some(\"/usr\")"));
+    assert!(!ruleset_debug.contains("fd:"));
 }
 
 #[test]
-fn ruleset_unsupported() {
+fn ruleset_mut_builders() {
+    let kernel = uapi::MockKernel::new(ABI::V4).install();
+
+    let mut ruleset = Ruleset::default();
+    for access in [AccessFs::Execute, AccessFs::ReadFile] {
+        ruleset.handle_access_mut(access).unwrap();
+    }
+    ruleset
+        .handle_access_mut(AccessNet::BindTcp)
+        .unwrap()
+        .handle_access_mut(AccessNet::ConnectTcp)
+        .unwrap();
     assert_eq!(
+        ruleset.requested_handled_fs,
+        AccessFs::Execute | AccessFs::ReadFile
+    );
+    assert_eq!(ruleset.requested_handled_net, AccessNet::from_all(ABI::V4));
+
+    let mut ruleset = ruleset.create().unwrap();
+    for port in [80, 443] {
+        ruleset
+            .add_rule_mut(NetPort::new(port, AccessNet::ConnectTcp))
+            .unwrap();
+    }
+    ruleset
+        .add_rules_mut(
+            [8080, 8443].map(|port| Ok::<_, RulesetError>(NetPort::new(port, AccessNet::BindTcp))),
+        )
+        .unwrap();
+    assert!(ruleset
+        .add_rule_mut(PathBeneath::new(
+            PathFd::new("/usr").unwrap(),
+            AccessFs::WriteFile
+        ))
+        .is_err());
+    assert_eq!(kernel.rules(), 4);
+}
+
+#[test]
+fn ruleset_unsupported() {
+    assert!(matches!(
         Ruleset::from(ABI::Unsupported)
             // BestEffort for Ruleset.
             .handle_access(AccessFs::Execute)
@@ -734,10 +2896,11 @@ fn ruleset_unsupported() {
             ruleset: RulesetStatus::NotEnforced,
             // With BestEffort, no_new_privs is still enabled.
             no_new_privs: true,
+            ..
         }
-    );
+    ));
 
-    assert_eq!(
+    assert!(matches!(
         Ruleset::from(ABI::Unsupported)
             // SoftRequirement for Ruleset.
             .set_compatibility(CompatLevel::SoftRequirement)
@@ -751,8 +2914,9 @@ fn ruleset_unsupported() {
             ruleset: RulesetStatus::NotEnforced,
             // With SoftRequirement, no_new_privs is discarded.
             no_new_privs: false,
+            ..
         }
-    );
+    ));
 
     matches!(
         Ruleset::from(ABI::Unsupported)
@@ -763,7 +2927,7 @@ fn ruleset_unsupported() {
         RulesetError::CreateRuleset(CreateRulesetError::MissingHandledAccess)
     );
 
-    assert_eq!(
+    assert!(matches!(
         Ruleset::from(ABI::Unsupported)
             .handle_access(AccessFs::Execute)
             .unwrap()
@@ -777,12 +2941,13 @@ fn ruleset_unsupported() {
             ruleset: RulesetStatus::NotEnforced,
             // With SoftRequirement, no_new_privs is untouched if there is no error (e.g. no rule).
             no_new_privs: true,
+            ..
         }
-    );
+    ));
 
     // Don't explicitly call create() on a CI that doesn't support Landlock.
     if compat::can_emulate(ABI::V1, ABI::V1, Some(ABI::V2)) {
-        assert_eq!(
+        assert!(matches!(
             Ruleset::from(ABI::V1)
                 .handle_access(make_bitflags!(AccessFs::{Execute | Refer}))
                 .unwrap()
@@ -799,11 +2964,12 @@ fn ruleset_unsupported() {
                 // With SoftRequirement, no_new_privs is discarded if there is an error
                 // (e.g. unsupported access right).
                 no_new_privs: false,
+                ..
             }
-        );
+        ));
     }
 
-    assert_eq!(
+    assert!(matches!(
         Ruleset::from(ABI::Unsupported)
             .handle_access(AccessFs::Execute)
             .unwrap()
@@ -815,8 +2981,9 @@ fn ruleset_unsupported() {
         RestrictionStatus {
             ruleset: RulesetStatus::NotEnforced,
             no_new_privs: false,
+            ..
         }
-    );
+    ));
 
     assert!(matches!(
         Ruleset::from(ABI::Unsupported)
@@ -856,7 +3023,7 @@ fn ruleset_unsupported() {
             .unwrap();
         // Fakes a call to create() to test without involving the kernel (i.e. no
         // landlock_ruleset_create() call).
-        let ruleset_created = RulesetCreated::new(ruleset, -1);
+        let ruleset_created = RulesetCreated::new(ruleset, None);
         assert!(matches!(
             ruleset_created
                 .add_rule(PathBeneath::new(
@@ -873,7 +3040,7 @@ fn ruleset_unsupported() {
 fn ignore_abi_v2_with_abi_v1() {
     // We don't need kernel/CI support for Landlock because no related syscalls should actually be
     // performed.
-    assert_eq!(
+    assert!(matches!(
         Ruleset::from(ABI::V1)
             .set_compatibility(CompatLevel::HardRequirement)
             .handle_access(AccessFs::from_all(ABI::V1))
@@ -899,6 +3066,375 @@ fn ignore_abi_v2_with_abi_v1() {
         RestrictionStatus {
             ruleset: RulesetStatus::NotEnforced,
             no_new_privs: false,
+            ..
         }
+    ));
+}
+
+#[test]
+fn ruleset_on_downgrade() {
+    use std::sync::{Arc, Mutex};
+
+    let downgrades = Arc::new(Mutex::new(Vec::new()));
+    let observed = downgrades.clone();
+    // No syscall is performed because the ruleset is not enforced.
+    let status = Ruleset::from(ABI::V1)
+        .on_downgrade(move |d| {
+            let ignored = match d {
+                Downgrade::Fs(CompatError::Access(AccessError::PartiallyCompatible {
+                    incompatible,
+                    ..
+                })) => incompatible.bits(),
+                Downgrade::Fs(CompatError::PathBeneath(PathBeneathError::DirectoryAccess {
+                    incompatible,
+                    ..
+                })) => incompatible.bits(),
+                Downgrade::Net(CompatError::Access(AccessError::Incompatible { access })) => {
+                    access.bits()
+                }
+                _ => unreachable!(),
+            };
+            observed.lock().unwrap().push(ignored);
+        })
+        .handle_access(AccessFs::from_all(ABI::V2))
+        .unwrap()
+        .handle_access(AccessNet::BindTcp)
+        .unwrap()
+        .set_compatibility(CompatLevel::SoftRequirement)
+        .create()
+        .unwrap()
+        .add_rule(PathBeneath::new(
+            PathFd::new("/etc/passwd").unwrap(),
+            AccessFs::ReadFile | AccessFs::ReadDir,
+        ))
+        .unwrap()
+        .restrict_self()
+        .unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::NotEnforced);
+    assert_eq!(
+        *downgrades.lock().unwrap(),
+        [
+            AccessFs::Refer as u64,
+            AccessNet::BindTcp as u64,
+            AccessFs::ReadDir as u64,
+        ]
+    );
+}
+
+#[test]
+fn ruleset_warn_only() {
+    let status = Ruleset::from(ABI::Unsupported)
+        .set_compatibility(CompatLevel::WarnOnly)
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .create()
+        .unwrap()
+        .set_no_new_privs(false)
+        .restrict_self()
+        .unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::NotEnforced);
+    assert_eq!(status.warnings.len(), 1);
+    assert!(status.warnings[0].contains("Execute"));
+
+    // The warnings are only recorded with CompatLevel::WarnOnly.
+    let status = Ruleset::from(ABI::Unsupported)
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .create()
+        .unwrap()
+        .set_no_new_privs(false)
+        .restrict_self()
+        .unwrap();
+    assert!(status.warnings.is_empty());
+}
+
+#[test]
+fn ruleset_compat_report() {
+    // Don't explicitly call create() on a CI that doesn't support Landlock.
+    if !compat::can_emulate(ABI::V1, ABI::V1, Some(ABI::V2)) {
+        return;
+    }
+    let ruleset = Ruleset::from(ABI::V1)
+        .handle_access(AccessFs::from_all(ABI::V2))
+        .unwrap()
+        .set_compatibility(CompatLevel::SoftRequirement)
+        .create()
+        .unwrap()
+        .add_rule(
+            PathBeneath::new(PathFd::new("/usr").unwrap(), AccessFs::from_read(ABI::V1))
+                .set_compatibility(CompatLevel::BestEffort)
+                .set_path_label("/usr"),
+        )
+        .unwrap()
+        .add_rule(PathBeneath::new(
+            PathFd::new("/etc/passwd").unwrap(),
+            AccessFs::ReadFile | AccessFs::ReadDir,
+        ))
+        .unwrap();
+    let report = ruleset.compat_report();
+    assert_eq!(report.abi, ABI::V1);
+    assert_eq!(report.handled_fs.outcome(), CompatOutcome::Degraded);
+    assert_eq!(report.handled_fs.ignored(), AccessFs::Refer);
+    assert_eq!(report.handled_net.outcome(), CompatOutcome::Applied);
+    assert_eq!(
+        report
+            .rules
+            .iter()
+            .map(RuleReport::outcome)
+            .collect::<Vec<_>>(),
+        [CompatOutcome::Applied, CompatOutcome::Dropped]
+    );
+    assert!(!report.is_fully_applied());
+    assert!(matches!(
+        report.degraded_rules().collect::<Vec<_>>()[..],
+        [RuleReport::Fs { index: 1, path: None, access }]
+            if access.ignored() == AccessFs::ReadFile | AccessFs::ReadDir
+    ));
+    assert!(matches!(
+        &report.rules[0],
+        RuleReport::Fs { index: 0, path: Some(path), .. } if path == std::path::Path::new("/usr")
+    ));
+
+    // No syscall is performed because the ruleset is not enforced.
+    let status = ruleset.set_no_new_privs(false).restrict_self().unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::NotEnforced);
+    assert_eq!(status.abi, ABI::V1);
+    // Nothing is enforced.
+    assert_eq!(status.dropped_fs, AccessFs::from_all(ABI::V2));
+    assert_eq!(status.dropped_net, BitFlags::EMPTY);
+    assert_eq!(status.compat_report, Some(report));
+}
+
+#[test]
+fn restriction_status_dropped() {
+    let handled_fs = AccessReport::new(AccessFs::from_all(ABI::V2), AccessFs::from_all(ABI::V1));
+    let handled_net = AccessReport::new(AccessNet::from_all(ABI::V4), BitFlags::EMPTY);
+    let status = RestrictionStatus::new(
+        RulesetStatus::PartiallyEnforced,
+        true,
+        ABI::V1,
+        handled_fs,
+        handled_net,
+    );
+    assert_eq!(status.abi, ABI::V1);
+    assert_eq!(status.dropped_fs, AccessFs::Refer);
+    assert_eq!(status.dropped_net, AccessNet::from_all(ABI::V4));
+
+    let status = RestrictionStatus::new(
+        RulesetStatus::NotEnforced,
+        true,
+        ABI::V1,
+        handled_fs,
+        handled_net,
+    );
+    assert_eq!(status.dropped_fs, AccessFs::from_all(ABI::V2));
+}
+
+#[test]
+fn ruleset_add_path_rules() {
+    let mut outcomes = Vec::new();
+    let ruleset = Ruleset::from(ABI::Unsupported)
+        .handle_access(AccessFs::from_all(ABI::V1))
+        .unwrap()
+        .create()
+        .unwrap()
+        .add_path_rules(
+            ["/usr", "/does-not-exist", "/etc/passwd"],
+            AccessFs::from_all(ABI::V1),
+            |path, result| outcomes.push((path.to_owned(), result.is_ok())),
+        )
+        .unwrap();
+    assert_eq!(
+        outcomes,
+        [
+            ("/usr".into(), true),
+            ("/does-not-exist".into(), false),
+            ("/etc/passwd".into(), true),
+        ]
+    );
+    let report = ruleset.compat_report();
+    assert_eq!(report.rules.len(), 2);
+    // Directory-only access rights are removed for files.
+    assert!(matches!(
+        &report.rules[1],
+        RuleReport::Fs { path: Some(path), access, .. }
+            if path == Path::new("/etc/passwd") && access.requested == AccessFs::from_file(ABI::V1)
+    ));
+}
+
+#[test]
+fn ruleset_add_path_rules_bulk() {
+    let create = || {
+        Ruleset::from(ABI::Unsupported)
+            .handle_access(AccessFs::from_all(ABI::V1))
+            .unwrap()
+            .create()
+            .unwrap()
+    };
+    let ruleset = create()
+        .add_path_rules_bulk(["/usr", "/etc", "/etc/passwd"], AccessFs::Execute, 2)
+        .unwrap();
+    assert_eq!(ruleset.compat_report().rules.len(), 3);
+
+    assert!(matches!(
+        create().add_path_rules_bulk(["/usr", "/does-not-exist"], AccessFs::Execute, 2),
+        Err(BulkRulesError::PathFd(PathFdError::OpenCall { path, .. }))
+            if path == Path::new("/does-not-exist")
+    ));
+
+    let limit = nofile_limit().unwrap();
+    if limit < 1 << 20 {
+        let paths = vec!["/usr"; limit as usize + 1];
+        assert!(matches!(
+            create().add_path_rules_bulk(paths, AccessFs::Execute, usize::MAX),
+            Err(BulkRulesError::FileLimit { needed, limit: l, .. })
+                if needed == limit as usize + 1 && l == limit
+        ));
+    }
+}
+
+#[test]
+fn ruleset_canary() {
+    let abi = ABI::V1;
+    if !compat::can_emulate(abi, abi, Some(abi)) {
+        return;
+    }
+
+    let restrict = |canary: &'static str| {
+        std::thread::spawn(move || {
+            Ruleset::from(abi)
+                .handle_access(AccessFs::from_all(abi))
+                .unwrap()
+                .create()
+                .unwrap()
+                .add_rule(PathBeneath::new(
+                    PathFd::new("/usr").unwrap(),
+                    AccessFs::from_read(abi),
+                ))
+                .unwrap()
+                .set_canary(canary)
+                .restrict_self()
+        })
+        .join()
+        .unwrap()
+    };
+
+    assert_eq!(
+        restrict("/etc/passwd").unwrap().ruleset,
+        RulesetStatus::FullyEnforced
+    );
+    assert!(matches!(
+        restrict("/usr"),
+        Err(RulesetError::RestrictSelf(RestrictSelfError::CanaryAccessible { path }))
+            if path == std::path::Path::new("/usr")
+    ));
+    assert!(matches!(
+        restrict("/does-not-exist"),
+        Err(RulesetError::RestrictSelf(RestrictSelfError::CanaryCheckCall { source, .. }))
+            if source.raw_os_error() == Some(libc::ENOENT)
+    ));
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn ruleset_thread_check() {
+    let restrict = |check| {
+        Ruleset::from(ABI::Unsupported)
+            .handle_access(AccessFs::Execute)
+            .unwrap()
+            .create()
+            .unwrap()
+            .set_thread_check(check)
+            .restrict_self()
+    };
+    // The test harness runs this test in a dedicated thread.
+    assert!(thread_count().unwrap() > 1);
+
+    let status = restrict(None).unwrap();
+    assert!(status.warnings.is_empty());
+
+    let status = restrict(Some(ThreadCheck::Warn)).unwrap();
+    assert_eq!(status.warnings.len(), 1);
+    assert!(status.warnings[0].contains("would not be restricted"));
+
+    let error = restrict(Some(ThreadCheck::Deny)).unwrap_err();
+    assert!(matches!(
+        error,
+        RulesetError::RestrictSelf(RestrictSelfError::OtherThreads { threads }) if threads > 0
+    ));
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+    // A single-threaded child process passes the check.
+    let pid = unsafe { libc::fork() };
+    assert!(pid >= 0);
+    if pid == 0 {
+        let code = match restrict(Some(ThreadCheck::Deny)) {
+            Ok(status) if status.warnings.is_empty() => 0,
+            _ => 1,
+        };
+        unsafe { libc::_exit(code) };
+    }
+    let mut status = 0;
+    assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+    assert!(libc::WIFEXITED(status));
+    assert_eq!(libc::WEXITSTATUS(status), 0);
+}
+
+#[test]
+fn ruleset_enforce_or_exit() {
+    let abi = ABI::V1;
+    if !compat::can_emulate(abi, abi, Some(abi)) {
+        return;
+    }
+
+    let status = std::thread::spawn(move || {
+        Ruleset::from(abi)
+            .handle_access(AccessFs::Execute)
+            .unwrap()
+            .create()
+            .unwrap()
+            .enforce_or_exit(1, "unexpected exit")
+    })
+    .join()
+    .unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::FullyEnforced);
+}
+
+#[test]
+fn restriction_status_display() {
+    let mut status = RestrictionStatus::new(
+        RulesetStatus::PartiallyEnforced,
+        true,
+        ABI::V1,
+        AccessReport::new(AccessFs::from_all(ABI::V2), AccessFs::from_all(ABI::V1)),
+        AccessReport::new(BitFlags::EMPTY, BitFlags::EMPTY),
+    );
+    assert_eq!(
+        status.to_string(),
+        "partially enforced with ABI v1, no_new_privs set; 1 requested access rights unsupported"
+    );
+    status.compat_report = Some(CompatReport {
+        abi: ABI::V1,
+        handled_fs: AccessReport::new(AccessFs::from_all(ABI::V2), AccessFs::from_all(ABI::V1)),
+        handled_net: AccessReport::new(BitFlags::EMPTY, BitFlags::EMPTY),
+        rules: Vec::new(),
+    });
+    assert_eq!(
+        status.to_string(),
+        "partially enforced with ABI v1, no_new_privs set; \
+        1 of 14 requested access rights unsupported"
+    );
+
+    let status = RestrictionStatus::new(
+        RulesetStatus::NotEnforced,
+        false,
+        ABI::Unsupported,
+        AccessReport::new(BitFlags::EMPTY, BitFlags::EMPTY),
+        AccessReport::new(BitFlags::EMPTY, BitFlags::EMPTY),
+    );
+    assert_eq!(
+        status.to_string(),
+        "not enforced without Landlock support, no_new_privs not set"
     );
+    assert_eq!(RulesetStatus::FullyEnforced.to_string(), "fully enforced");
 }
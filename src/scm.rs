@@ -0,0 +1,78 @@
+// Helpers to send and receive file descriptors over Unix sockets (with SCM_RIGHTS).
+
+use std::io;
+use std::mem;
+use std::os::raw::c_int;
+use std::os::unix::io::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::ptr;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const MSG_CMSG_CLOEXEC: c_int = libc::MSG_CMSG_CLOEXEC;
+// The received file descriptor is then set close-on-exec after the call.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const MSG_CMSG_CLOEXEC: c_int = 0;
+
+// Sends data, and fd if any, in one message.
+pub(crate) fn send_msg(stream: &UnixStream, data: &[u8], fd: Option<BorrowedFd>) -> io::Result<()> {
+    let mut iov = libc::iovec {
+        iov_base: data.as_ptr() as *mut _,
+        iov_len: data.len(),
+    };
+    let mut cmsg_buf = [0u64; 4];
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    if let Some(fd) = fd {
+        unsafe {
+            msg.msg_control = cmsg_buf.as_mut_ptr().cast();
+            msg.msg_controllen = libc::CMSG_SPACE(mem::size_of::<c_int>() as u32) as _;
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            (*cmsg).cmsg_level = libc::SOL_SOCKET;
+            (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<c_int>() as u32) as _;
+            ptr::write_unaligned(libc::CMSG_DATA(cmsg).cast(), fd.as_raw_fd());
+        }
+    }
+    match unsafe { libc::sendmsg(stream.as_raw_fd(), &msg, libc::MSG_NOSIGNAL) } {
+        n if n as usize == data.len() => Ok(()),
+        n if n >= 0 => Err(io::ErrorKind::WriteZero.into()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+// Receives exactly data.len() bytes, and a file descriptor if any.  The received file descriptor
+// is close-on-exec.
+pub(crate) fn recv_msg(stream: &UnixStream, data: &mut [u8]) -> io::Result<Option<OwnedFd>> {
+    let mut iov = libc::iovec {
+        iov_base: data.as_mut_ptr().cast(),
+        iov_len: data.len(),
+    };
+    let mut cmsg_buf = [0u64; 4];
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = cmsg_buf.as_mut_ptr().cast();
+    msg.msg_controllen = mem::size_of_val(&cmsg_buf) as _;
+    let n = unsafe { libc::recvmsg(stream.as_raw_fd(), &mut msg, MSG_CMSG_CLOEXEC) };
+    if n < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut fd = None;
+    unsafe {
+        let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+        while !cmsg.is_null() {
+            if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                let raw: RawFd = ptr::read_unaligned(libc::CMSG_DATA(cmsg).cast());
+                fd = Some(OwnedFd::from_raw_fd(raw));
+                #[cfg(not(any(target_os = "linux", target_os = "android")))]
+                crate::ruleset::set_cloexec(BorrowedFd::borrow_raw(raw), true)?;
+            }
+            cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+        }
+    }
+    if n as usize != data.len() {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(fd)
+}
@@ -0,0 +1,183 @@
+//! Seccomp filters installed along with the ruleset enforcement.
+//!
+//! Landlock doesn't restrict all kernel features yet
+//! (e.g. only TCP sockets can be restricted, since [`ABI::V4`](crate::ABI::V4)).
+//! A complementary seccomp filter can deny such features,
+//! and [`SeccompRuleset`] installs one or more filters built with
+//! [seccompiler](https://docs.rs/seccompiler) right after enforcing a ruleset,
+//! and returns a single [`SeccompStatus`] for both steps.
+//!
+//! This is only available on Linux with the `seccomp` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use landlock::seccomp::SeccompRuleset;
+//! use landlock::{Access, AccessNet, Ruleset, RulesetAttr, SeccompError, ABI};
+//!
+//! fn restrict_network() -> Result<(), SeccompError> {
+//!     let ruleset = Ruleset::default()
+//!         .handle_access(AccessNet::from_all(ABI::V4))?
+//!         .create()?;
+//!     let status = SeccompRuleset::new(ruleset)
+//!         .deny_non_tcp_sockets()?
+//!         .restrict_self()?;
+//!     println!("network restricted: {:?}", status.ruleset.ruleset);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{RestrictionStatus, RulesetCreated, SeccompError};
+use seccompiler::{
+    BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition, SeccompFilter,
+    SeccompRule, TargetArch,
+};
+use std::collections::BTreeMap;
+
+// Mask of the socket type, without the SOCK_NONBLOCK and SOCK_CLOEXEC flags.
+const SOCK_TYPE_MASK: u64 = 0xf;
+
+/// Ruleset with complementary seccomp filters.
+///
+/// The ruleset is enforced first, and then the seccomp filters are installed on the calling
+/// thread, in the order they were added.
+/// Installing a filter sets `no_new_privs`,
+/// whatever the ruleset configuration
+/// (see [`set_no_new_privs()`](crate::RulesetCreatedAttr::set_no_new_privs)).
+#[derive(Debug)]
+pub struct SeccompRuleset {
+    ruleset: RulesetCreated,
+    filters: Vec<BpfProgram>,
+}
+
+impl SeccompRuleset {
+    /// Creates a ruleset without seccomp filter.
+    pub fn new(ruleset: RulesetCreated) -> Self {
+        SeccompRuleset {
+            ruleset,
+            filters: Vec::new(),
+        }
+    }
+
+    /// Adds a seccomp filter,
+    /// e.g. built with [`SeccompFilter`] or compiled from a JSON description with seccompiler.
+    pub fn add_filter(mut self, filter: BpfProgram) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Adds a seccomp filter denying (with `EACCES`) the creation of IPv4 and IPv6 sockets
+    /// other than TCP ones (e.g. UDP, raw, or SCTP sockets),
+    /// which cannot be restricted by Landlock.
+    ///
+    /// Other socket families (e.g. Unix sockets) are not restricted by this filter.
+    pub fn deny_non_tcp_sockets(self) -> Result<Self, SeccompError> {
+        let filter =
+            deny_non_tcp_sockets_filter().map_err(|source| SeccompError::BuildFilter { source })?;
+        Ok(self.add_filter(filter))
+    }
+
+    /// Enforces the ruleset with [`RulesetCreated::restrict_self()`]
+    /// and then installs the seccomp filters.
+    ///
+    /// If the ruleset enforcement fails, no filter is installed.
+    pub fn restrict_self(self) -> Result<SeccompStatus, SeccompError> {
+        // Enforces the ruleset first in case a filter denies the Landlock system calls.
+        let ruleset = self.ruleset.restrict_self()?;
+        for filter in &self.filters {
+            seccompiler::apply_filter(filter)
+                .map_err(|source| SeccompError::ApplyFilter { source })?;
+        }
+        Ok(SeccompStatus {
+            ruleset,
+            filters: self.filters.len(),
+        })
+    }
+}
+
+/// Status of the ruleset enforcement and of the seccomp filters,
+/// returned by [`SeccompRuleset::restrict_self()`].
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SeccompStatus {
+    /// Status of the ruleset enforcement.
+    pub ruleset: RestrictionStatus,
+    /// Number of installed seccomp filters.
+    pub filters: usize,
+}
+
+// The system call numbers are c_long, which is not i64 on 32-bit architectures.
+#[allow(clippy::useless_conversion)]
+fn deny_non_tcp_sockets_filter() -> Result<BpfProgram, seccompiler::BackendError> {
+    let arg = |index, op, value| SeccompCondition::new(index, SeccompCmpArgLen::Dword, op, value);
+    let mut rules = Vec::new();
+    for domain in [libc::AF_INET, libc::AF_INET6] {
+        let domain = domain as u64;
+        // Any type but SOCK_STREAM.
+        for sock_type in (0..=SOCK_TYPE_MASK).filter(|t| *t != libc::SOCK_STREAM as u64) {
+            rules.push(SeccompRule::new(vec![
+                arg(0, SeccompCmpOp::Eq, domain)?,
+                arg(1, SeccompCmpOp::MaskedEq(SOCK_TYPE_MASK), sock_type)?,
+            ])?);
+        }
+        // SOCK_STREAM with another protocol than TCP (e.g. SCTP).
+        rules.push(SeccompRule::new(vec![
+            arg(0, SeccompCmpOp::Eq, domain)?,
+            arg(
+                1,
+                SeccompCmpOp::MaskedEq(SOCK_TYPE_MASK),
+                libc::SOCK_STREAM as u64,
+            )?,
+            arg(2, SeccompCmpOp::Ne, 0)?,
+            arg(2, SeccompCmpOp::Ne, libc::IPPROTO_TCP as u64)?,
+        ])?);
+    }
+    let filter = SeccompFilter::new(
+        BTreeMap::from([(i64::from(libc::SYS_socket), rules)]),
+        SeccompAction::Allow,
+        SeccompAction::Errno(libc::EACCES as u32),
+        TargetArch::try_from(std::env::consts::ARCH)?,
+    )?;
+    filter.try_into()
+}
+
+#[test]
+fn deny_non_tcp_sockets() {
+    use crate::{Access, AccessFs, Ruleset, RulesetAttr, RulesetStatus, ABI};
+    use std::net::{TcpListener, UdpSocket};
+    use std::os::unix::net::UnixDatagram;
+
+    if TargetArch::try_from(std::env::consts::ARCH).is_err() {
+        return;
+    }
+    let ruleset = Ruleset::from(ABI::Unsupported)
+        .handle_access(AccessFs::from_all(ABI::V1))
+        .unwrap()
+        .create()
+        .unwrap();
+    let status = SeccompRuleset::new(ruleset)
+        .deny_non_tcp_sockets()
+        .unwrap()
+        .restrict_self()
+        .unwrap();
+    assert_eq!(status.ruleset.ruleset, RulesetStatus::NotEnforced);
+    assert_eq!(status.filters, 1);
+
+    assert_eq!(
+        UdpSocket::bind("127.0.0.1:0").unwrap_err().raw_os_error(),
+        Some(libc::EACCES)
+    );
+    assert!(TcpListener::bind("127.0.0.1:0").is_ok());
+    assert!(UnixDatagram::unbound().is_ok());
+
+    let socket = |protocol| {
+        let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, protocol) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error().raw_os_error());
+        }
+        unsafe { libc::close(fd) };
+        Ok(())
+    };
+    assert_eq!(socket(libc::IPPROTO_SCTP), Err(Some(libc::EACCES)));
+    assert_eq!(socket(libc::IPPROTO_TCP), Ok(()));
+}
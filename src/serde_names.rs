@@ -0,0 +1,122 @@
+//! Serialization of access rights and ABIs with their stable names.
+//!
+//! With the `serde` feature, [`AccessFs`], [`AccessNet`] and [`ABI`] are (de)serialized as
+//! strings (e.g. `"read_file"` or `"v4"`) instead of raw bits,
+//! and [`CompatLevel`](crate::CompatLevel) as its snake case variant name
+//! (e.g. `"best_effort"`).
+//! Sets of access rights can use the same names with this module's functions,
+//! which (de)serialize a [`BitFlags`] as a sequence of names:
+//!
+//! ```
+//! use landlock::{AccessFs, BitFlags};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "landlock::serde_names")]
+//!     access: BitFlags<AccessFs>,
+//! }
+//! ```
+//!
+//! Deserializing an unknown name is an error, which avoids silently ignoring access rights
+//! defined by a newer version of this crate.
+//!
+//! This is only available with the `serde` feature.
+
+use crate::{Access, AccessFs, AccessNet, BitFlags, ABI};
+use serde::de::Error;
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serializes a set of access rights as a sequence of names (e.g. `["execute", "read_file"]`).
+pub fn serialize<A, S>(access: &BitFlags<A>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    A: Access,
+    S: Serializer,
+{
+    let mut seq = serializer.serialize_seq(Some(access.len()))?;
+    for a in access.iter() {
+        seq.serialize_element(a.name())?;
+    }
+    seq.end()
+}
+
+/// Deserializes a set of access rights from a sequence of names.
+pub fn deserialize<'de, A, D>(deserializer: D) -> Result<BitFlags<A>, D::Error>
+where
+    A: Access,
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|name| A::from_names(name).map_err(D::Error::custom))
+        .collect()
+}
+
+macro_rules! impl_serde_str {
+    ($name:ident) => {
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(D::Error::custom)
+            }
+        }
+    };
+}
+
+impl_serde_str!(AccessFs);
+impl_serde_str!(AccessNet);
+impl_serde_str!(ABI);
+
+#[test]
+fn serde_names() {
+    use crate::{make_bitflags, CompatLevel};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        #[serde(with = "crate::serde_names")]
+        fs: BitFlags<AccessFs>,
+        #[serde(with = "crate::serde_names")]
+        net: BitFlags<AccessNet>,
+        single: AccessFs,
+        abi: ABI,
+        level: CompatLevel,
+    }
+
+    let config = Config {
+        fs: make_bitflags!(AccessFs::{Execute | ReadFile}),
+        net: BitFlags::EMPTY,
+        single: AccessFs::Refer,
+        abi: ABI::V4,
+        level: CompatLevel::HardRequirement,
+    };
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(
+        json,
+        r#"{"fs":["execute","read_file"],"net":[],"single":"refer","abi":"v4","level":"hard_requirement"}"#
+    );
+    assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), config);
+
+    let unknown = json.replace("read_file", "read_mind");
+    assert!(serde_json::from_str::<Config>(&unknown)
+        .unwrap_err()
+        .to_string()
+        .contains("unknown access right \"read_mind\""));
+    // A single string is not a set of access rights.
+    let single = json.replace(r#"["execute","read_file"]"#, r#""execute""#);
+    assert!(serde_json::from_str::<Config>(&single).is_err());
+    assert!(serde_json::from_str::<ABI>(r#""v99""#).is_err());
+}
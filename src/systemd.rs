@@ -0,0 +1,287 @@
+use crate::{Access, AccessFs, AccessNet, BitFlags, Policy, ABI};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+// Top-level directories commonly holding user or service data, which are hidden with
+// InaccessiblePaths= when no rule identifies them, a parent, or a child.
+const DATA_DIRS: [&str; 7] = ["/boot", "/home", "/media", "/mnt", "/root", "/srv", "/var"];
+
+/// Systemd unit directive (e.g. `ReadOnlyPaths=/`),
+/// see [`Policy::systemd_directives()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SystemdDirective {
+    /// Name of the directive (e.g. `ReadOnlyPaths`).
+    pub name: &'static str,
+    /// Value of the directive (e.g. `/`).
+    pub value: String,
+}
+
+impl SystemdDirective {
+    fn new<V>(name: &'static str, value: V) -> Self
+    where
+        V: Into<String>,
+    {
+        SystemdDirective {
+            name,
+            value: value.into(),
+        }
+    }
+}
+
+impl fmt::Display for SystemdDirective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+/// Systemd unit directives approximating a [`Policy`],
+/// returned by [`Policy::systemd_directives()`].
+///
+/// The [`Display`](fmt::Display) implementation writes the notes as comments
+/// followed by the directives, which can be pasted in the `[Service]` section of a unit.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SystemdReport {
+    /// Directives, in a stable order.
+    pub directives: Vec<SystemdDirective>,
+    /// Differences between the directives and the policy.
+    pub notes: Vec<String>,
+}
+
+impl fmt::Display for SystemdReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for note in &self.notes {
+            writeln!(f, "# {note}")?;
+        }
+        for directive in &self.directives {
+            writeln!(f, "{directive}")?;
+        }
+        Ok(())
+    }
+}
+
+// Quotes a path as a systemd unit setting value, if needed.
+fn unit_path(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    if path.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+        format!("{path:?}")
+    } else {
+        path.into_owned()
+    }
+}
+
+impl Policy {
+    /// Generates the closest systemd unit directives to this policy,
+    /// restricted to the access rights supported by `abi` (as for [`canonical()`](Policy::canonical)),
+    /// e.g. to compare or duplicate the protection at the service manager level.
+    ///
+    /// Systemd restricts access with mount points and eBPF programs,
+    /// which are coarser than Landlock:
+    /// * write access rights are denied with `ReadOnlyPaths=/`
+    ///   and allowed beneath the rules' paths with `ReadWritePaths=`;
+    /// * execution is denied with `NoExecPaths=/`
+    ///   and allowed beneath the rules' paths with `ExecPaths=`;
+    /// * read access cannot be denied by default,
+    ///   so only a few data directories without any rule are hidden with `InaccessiblePaths=`;
+    /// * TCP binding is denied with `SocketBindDeny=tcp`
+    ///   and allowed for the rules' ports with `SocketBindAllow=`;
+    /// * TCP connection can only be denied with `IPAddressDeny=any`, without port exception.
+    ///
+    /// The [notes](SystemdReport::notes) list the differences with the policy.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{Access, AccessFs, Policy, ABI};
+    ///
+    /// let abi = ABI::V1;
+    /// let report = Policy::default()
+    ///     .handle_fs(AccessFs::from_write(abi))
+    ///     .allow_path("/tmp", AccessFs::from_write(abi))
+    ///     .systemd_directives(abi);
+    /// assert_eq!(
+    ///     report.to_string(),
+    ///     "NoNewPrivileges=yes\n\
+    ///      ReadOnlyPaths=/\n\
+    ///      ReadWritePaths=/tmp\n"
+    /// );
+    /// ```
+    pub fn systemd_directives(&self, abi: ABI) -> SystemdReport {
+        let handled_fs = self.handled_fs() & AccessFs::from_all(abi);
+        let handled_net = self.handled_net() & AccessNet::from_all(abi);
+        let mut paths = BTreeMap::new();
+        for rule in self.path_rules() {
+            *paths.entry(&rule.path).or_insert(BitFlags::EMPTY) |= rule.access & handled_fs;
+        }
+        let mut ports = BTreeMap::new();
+        for rule in self.port_rules() {
+            *ports.entry(rule.port).or_insert(BitFlags::EMPTY) |= rule.access & handled_net;
+        }
+
+        let mut report = SystemdReport::default();
+        if handled_fs.is_empty() && handled_net.is_empty() {
+            return report;
+        }
+        report
+            .directives
+            .push(SystemdDirective::new("NoNewPrivileges", "yes"));
+
+        let write = handled_fs & AccessFs::from_write(abi);
+        if !write.is_empty() {
+            report
+                .directives
+                .push(SystemdDirective::new("ReadOnlyPaths", "/"));
+            if write != AccessFs::from_write(abi) {
+                report.notes.push(format!(
+                    "ReadOnlyPaths= denies all write access rights, whereas the policy only \
+                     handles {}",
+                    AccessFs::to_names(write)
+                ));
+            }
+            for (path, access) in &paths {
+                let allowed = *access & write;
+                if allowed.is_empty() {
+                    continue;
+                }
+                report
+                    .directives
+                    .push(SystemdDirective::new("ReadWritePaths", unit_path(path)));
+                if allowed != write {
+                    report.notes.push(format!(
+                        "ReadWritePaths= allows all write access rights beneath {}, whereas \
+                         the policy only allows {}",
+                        unit_path(path),
+                        AccessFs::to_names(allowed)
+                    ));
+                }
+            }
+        }
+
+        if handled_fs.contains(AccessFs::Execute) {
+            report
+                .directives
+                .push(SystemdDirective::new("NoExecPaths", "/"));
+            for (path, _) in paths.iter().filter(|(_, a)| a.contains(AccessFs::Execute)) {
+                report
+                    .directives
+                    .push(SystemdDirective::new("ExecPaths", unit_path(path)));
+            }
+        }
+
+        if handled_fs.intersects(AccessFs::ReadFile | AccessFs::ReadDir) {
+            for dir in DATA_DIRS.iter().map(Path::new) {
+                let has_rule = paths
+                    .keys()
+                    .any(|path| dir.starts_with(path) || path.starts_with(dir));
+                if !has_rule {
+                    report.directives.push(SystemdDirective::new(
+                        "InaccessiblePaths",
+                        format!("-{}", dir.display()),
+                    ));
+                }
+            }
+            report.notes.push(
+                "InaccessiblePaths= only hides some data directories, whereas the policy \
+                 denies read access beneath all the paths without rule"
+                    .into(),
+            );
+        }
+
+        if handled_net.contains(AccessNet::BindTcp) {
+            report
+                .directives
+                .push(SystemdDirective::new("SocketBindDeny", "tcp"));
+            for (port, _) in ports.iter().filter(|(_, a)| a.contains(AccessNet::BindTcp)) {
+                report.directives.push(SystemdDirective::new(
+                    "SocketBindAllow",
+                    format!("tcp:{port}"),
+                ));
+            }
+        }
+
+        if handled_net.contains(AccessNet::ConnectTcp) {
+            let allowed: Vec<_> = ports
+                .iter()
+                .filter(|(_, a)| a.contains(AccessNet::ConnectTcp))
+                .map(|(port, _)| port.to_string())
+                .collect();
+            if allowed.is_empty() {
+                report
+                    .directives
+                    .push(SystemdDirective::new("IPAddressDeny", "any"));
+                report.notes.push(
+                    "IPAddressDeny= denies all IP traffic, whereas the policy only denies TCP \
+                     connections"
+                        .into(),
+                );
+            } else {
+                report.notes.push(format!(
+                    "IPAddressDeny= cannot allow TCP connections to specific ports ({}), \
+                     so it is not used",
+                    allowed.join(", ")
+                ));
+            }
+        }
+        report
+    }
+}
+
+#[test]
+fn systemd_directives() {
+    let abi = ABI::V4;
+    assert_eq!(
+        Policy::default().systemd_directives(abi),
+        SystemdReport::default()
+    );
+
+    let policy = Policy::default()
+        .handle_fs(AccessFs::from_all(abi))
+        .handle_net(AccessNet::from_all(abi))
+        .allow_paths(["/usr", "/etc"], AccessFs::from_read(abi))
+        .allow_path("/var/lib/app", AccessFs::ReadFile | AccessFs::WriteFile)
+        .allow_path("/tmp/my dir", AccessFs::from_all(abi))
+        .allow_port(443, AccessNet::ConnectTcp)
+        .allow_port(8080, AccessNet::BindTcp);
+    let report = policy.systemd_directives(abi);
+    assert_eq!(
+        report.to_string(),
+        "# ReadWritePaths= allows all write access rights beneath /var/lib/app, whereas the policy \
+         only allows write_file\n\
+         # InaccessiblePaths= only hides some data directories, whereas the policy denies read \
+         access beneath all the paths without rule\n\
+         # IPAddressDeny= cannot allow TCP connections to specific ports (443), so it is not used\n\
+         NoNewPrivileges=yes\n\
+         ReadOnlyPaths=/\n\
+         ReadWritePaths=\"/tmp/my dir\"\n\
+         ReadWritePaths=/var/lib/app\n\
+         NoExecPaths=/\n\
+         ExecPaths=/etc\n\
+         ExecPaths=\"/tmp/my dir\"\n\
+         ExecPaths=/usr\n\
+         InaccessiblePaths=-/boot\n\
+         InaccessiblePaths=-/home\n\
+         InaccessiblePaths=-/media\n\
+         InaccessiblePaths=-/mnt\n\
+         InaccessiblePaths=-/root\n\
+         InaccessiblePaths=-/srv\n\
+         SocketBindDeny=tcp\n\
+         SocketBindAllow=tcp:8080\n"
+    );
+
+    // Partially handled write access and denied connections.
+    let report = Policy::default()
+        .handle_fs(AccessFs::WriteFile)
+        .handle_net(AccessNet::ConnectTcp)
+        .systemd_directives(abi);
+    assert_eq!(
+        report.directives,
+        [
+            SystemdDirective::new("NoNewPrivileges", "yes"),
+            SystemdDirective::new("ReadOnlyPaths", "/"),
+            SystemdDirective::new("IPAddressDeny", "any"),
+        ]
+    );
+    assert_eq!(report.notes.len(), 2);
+}
@@ -0,0 +1,190 @@
+//! Helpers to test sandboxed code, e.g. in downstream crates.
+//!
+//! This provides the ones used by this crate's own tests:
+//! the tested ABI and related checks (see [`test_abi()`] and [`can_emulate()`]),
+//! an emulated kernel ([`MockKernel`]), fault injection ([`inject_fault()`]),
+//! and restricted tests ([`landlock_test!`](crate::landlock_test)).
+//!
+//! This is only available with the `test-utils` feature.
+
+pub use crate::uapi::{inject_fault, FaultGuard, MockGuard, MockKernel, Syscall};
+
+use crate::compat::{self, can_emulate_with, env_test_abi};
+use crate::{Policy, RestrictionStatus, ABI};
+use std::env;
+use std::process::Command;
+
+/// Gets the Landlock ABI the tests should expect,
+/// from the `LANDLOCK_CRATE_TEST_ABI` environment variable if set (e.g. `1` for [`ABI::V1`]),
+/// or the one supported by the running kernel otherwise.
+///
+/// Setting this variable enables a CI to check that the kernel it runs on
+/// supports the expected features, instead of silently testing less.
+///
+/// # Panics
+///
+/// This panics if the environment variable doesn't identify a known ABI.
+pub fn test_abi() -> ABI {
+    env_test_abi().unwrap_or_else(ABI::new_current)
+}
+
+/// Returns true if a ruleset requesting features up to `mock`
+/// can be checked against the [tested ABI](test_abi),
+/// given that it is partially supported since `partial_support`,
+/// and fully supported since `full_support` (if any).
+///
+/// This is useful to skip tests which would otherwise fail because
+/// the running kernel doesn't support the required features.
+///
+/// # Example
+///
+/// ```
+/// use landlock::test_utils::can_emulate;
+/// use landlock::ABI;
+///
+/// // Requires a kernel supporting at least the first ABI.
+/// if can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+///     // Enforces and checks a ruleset...
+/// }
+/// ```
+pub fn can_emulate(mock: ABI, partial_support: ABI, full_support: Option<ABI>) -> bool {
+    can_emulate_with(test_abi(), mock, partial_support, full_support)
+}
+
+/// Forgets the Landlock ABI cached for the whole process,
+/// which is then probed again by the next [`Ruleset::default()`](crate::Ruleset::default) call.
+///
+/// This is only useful if the running kernel's support can change,
+/// e.g. with system call filters installed by a test.
+pub fn refresh_abi() {
+    ABI::refresh();
+}
+
+/// Returns the error number with which the running kernel refuses Landlock system calls,
+/// or `None` if Landlock is supported.
+///
+/// This is `ENOSYS` if the kernel is not built with Landlock,
+/// or `EOPNOTSUPP` if Landlock is disabled at boot time.
+pub fn landlock_errno() -> Option<i32> {
+    compat::landlock_errno()
+}
+
+// Name of the test to run restricted, set for the child test process.
+const CHILD_ENV: &str = "LANDLOCK_TEST_CHILD";
+
+/// Runs `f` in a child test process restricted by `policy`,
+/// to check the enforcement of a policy without restricting the test runner.
+///
+/// `test_name` is the full path of the calling test function,
+/// starting with the crate name as returned by [`module_path!()`].
+/// The test binary is executed again to only run this test
+/// (with the `--exact` and `--test-threads=1` arguments),
+/// which then enforces `policy` on its thread and calls `f` with the enforcement status.
+/// The calling test panics, with the child's output, if the child test fails
+/// (e.g. because an assertion in `f` failed) or if the policy cannot be enforced.
+///
+/// See [`landlock_test!`](crate::landlock_test) for a more convenient interface.
+///
+/// # Panics
+///
+/// This panics if the child test fails, is not found, or cannot be executed.
+pub fn run_restricted<F>(test_name: &str, policy: &Policy, f: F)
+where
+    F: FnOnce(&RestrictionStatus),
+{
+    // Test names don't include the crate name.
+    let name = test_name
+        .split_once("::")
+        .map(|(_, name)| name)
+        .unwrap_or(test_name);
+
+    if env::var_os(CHILD_ENV).map_or(false, |child| child == name) {
+        let status = policy
+            .restrict_self()
+            .unwrap_or_else(|e| panic!("failed to enforce the policy: {e}"));
+        f(&status);
+        return;
+    }
+
+    let exe = env::current_exe().expect("failed to get the test binary path");
+    let output = Command::new(exe)
+        .args([name, "--exact", "--nocapture", "--test-threads=1"])
+        .env(CHILD_ENV, name)
+        .output()
+        .expect("failed to execute the child test");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // Makes sure the child ran the test instead of filtering it out.
+    if !output.status.success() || !stdout.contains(" 1 passed;") {
+        panic!(
+            "restricted test {name} failed ({}):\n{stdout}{stderr}",
+            output.status
+        );
+    }
+}
+
+/// Declares a test running its body in a child process restricted by a [`Policy`],
+/// see [`run_restricted()`](crate::test_utils::run_restricted).
+///
+/// The arguments are the test name, the policy, and a closure
+/// taking the [`RestrictionStatus`](crate::RestrictionStatus).
+/// Attributes (e.g. doc comments or `#[ignore]`) can precede the test name.
+///
+/// This is only available with the `test-utils` feature.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{landlock_test, Access, AccessFs, Policy, RulesetStatus, ABI};
+///
+/// landlock_test!(
+///     read_denied,
+///     Policy::default()
+///         .handle_fs(AccessFs::from_all(ABI::V1))
+///         .allow_path("/usr", AccessFs::from_read(ABI::V1)),
+///     |status| {
+///         if status.ruleset == RulesetStatus::FullyEnforced {
+///             assert!(std::fs::read("/etc/passwd").is_err());
+///         }
+///     }
+/// );
+/// ```
+#[macro_export]
+macro_rules! landlock_test {
+    ($(#[$attr:meta])* $name:ident, $policy:expr, $f:expr $(,)?) => {
+        $(#[$attr])*
+        #[test]
+        fn $name() {
+            $crate::test_utils::run_restricted(
+                concat!(module_path!(), "::", stringify!($name)),
+                &$policy,
+                $f,
+            );
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    landlock_test!(
+        restricted_read,
+        Policy::default()
+            .handle_fs(AccessFs::from_all(ABI::V1))
+            .allow_path("/usr", AccessFs::from_read(ABI::V1)),
+        |status| {
+            if status.ruleset == RulesetStatus::FullyEnforced {
+                assert!(std::fs::read("/etc/passwd").is_err());
+            }
+            assert!(std::fs::read_dir("/usr").is_ok());
+        }
+    );
+
+    landlock_test!(
+        #[should_panic(expected = "restricted test test_utils::tests::failed_child failed")]
+        failed_child,
+        Policy::default().handle_fs(AccessFs::Execute),
+        |_| panic!("child")
+    );
+}
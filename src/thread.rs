@@ -0,0 +1,524 @@
+//! Sandboxed threads.
+//!
+//! Landlock restrictions apply to the calling thread (and its future children),
+//! which enables to isolate some work in a dedicated thread
+//! without sandboxing the whole process.
+//! Note that threads share the same memory,
+//! which makes such isolation weaker than with a dedicated process
+//! (see [`fork_and_exec()`](crate::fork_and_exec)).
+
+use crate::{Policy, PolicyError, RestrictionStatus, RulesetCreated, RulesetError, SandboxHealth};
+use std::io;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, Thread};
+
+/// Owned permission to join on a restricted thread, see [`spawn_restricted()`].
+#[derive(Debug)]
+pub struct JoinHandle<T> {
+    // None is only returned if the ruleset enforcement failed, in which case the JoinHandle is
+    // never returned.
+    inner: thread::JoinHandle<Option<T>>,
+    status: RestrictionStatus,
+}
+
+impl<T> JoinHandle<T> {
+    /// Returns the status of the ruleset enforcement,
+    /// done by the thread before it ran the closure.
+    pub fn restriction_status(&self) -> &RestrictionStatus {
+        &self.status
+    }
+
+    /// Gets the handle of the underlying thread.
+    pub fn thread(&self) -> &Thread {
+        self.inner.thread()
+    }
+
+    /// Waits for the thread to finish, see [`std::thread::JoinHandle::join()`].
+    pub fn join(self) -> thread::Result<T> {
+        self.inner
+            .join()
+            .map(|ret| ret.expect("the ruleset should be enforced"))
+    }
+}
+
+/// Spawns a new thread, enforces `policy` on it, and then runs `f` in it.
+///
+/// The ruleset is created by the calling thread,
+/// and this function returns once the new thread enforced it.
+/// If the enforcement fails, `f` is not run and the error is returned.
+/// The calling thread (and then the rest of the process) is not restricted.
+///
+/// As for [`std::thread::spawn()`], this panics if the operating system fails to create a thread.
+///
+/// # Example
+///
+/// ```
+/// use landlock::{thread, Access, AccessFs, Policy, ABI};
+///
+/// fn parse_untrusted(data: Vec<u8>) -> Result<usize, Box<dyn std::error::Error>> {
+///     let abi = ABI::V1;
+///     // Denies any file system access to the parser.
+///     let policy = Policy::default().handle_fs(AccessFs::from_all(abi));
+///     let handle = thread::spawn_restricted(&policy, move || data.len())?;
+///     Ok(handle.join().expect("the parser panicked"))
+/// }
+/// ```
+pub fn spawn_restricted<F, T>(policy: &Policy, f: F) -> Result<JoinHandle<T>, PolicyError>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let ruleset = policy.create()?;
+    Ok(
+        spawn_with(thread::Builder::new(), move || ruleset.restrict_self(), f)
+            .expect("failed to spawn thread")?,
+    )
+}
+
+// Spawns a new thread with builder, enforces a ruleset on it with restrict, and then runs f in
+// it.  Returns once the new thread enforced the ruleset.
+fn spawn_with<R, F, T>(
+    builder: thread::Builder,
+    restrict: R,
+    f: F,
+) -> io::Result<Result<JoinHandle<T>, RulesetError>>
+where
+    R: FnOnce() -> Result<RestrictionStatus, RulesetError> + Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    let depth = crate::domain_depth();
+    let inner = builder.spawn(move || {
+        // The new thread inherits the domains of the calling thread.
+        SandboxHealth::inherit_layers(depth);
+        let enforced = restrict();
+        let ok = enforced.is_ok();
+        // The receiver is only dropped if the spawning thread panicked.
+        let _ = sender.send(enforced);
+        if ok {
+            Some(f())
+        } else {
+            None
+        }
+    })?;
+    Ok(match receiver.recv() {
+        Ok(Ok(status)) => Ok(JoinHandle { inner, status }),
+        Ok(Err(e)) => {
+            let _ = inner.join();
+            Err(e)
+        }
+        // The thread panicked before sending the result.
+        Err(_) => panic!("the restricted thread panicked while enforcing the ruleset"),
+    })
+}
+
+/// Thread factory, mirroring [`std::thread::Builder`],
+/// which enforces the ruleset shared by a [`ThreadRestrictor`] on every spawned thread
+/// before running its closure.
+///
+/// This enables applications to keep spawning threads once their sandbox is set up,
+/// with the same restrictions for all of them,
+/// whereas the calling thread is not restricted.
+///
+/// # Example
+///
+/// ```
+/// use landlock::thread::{Builder, ThreadRestrictor};
+/// use landlock::{Access, AccessFs, Policy, ABI};
+///
+/// fn spawn_workers() -> Result<(), Box<dyn std::error::Error>> {
+///     let abi = ABI::V1;
+///     let policy = Policy::default()
+///         .handle_fs(AccessFs::from_all(abi))
+///         .allow_path("/usr", AccessFs::from_read(abi));
+///     let restrictor = ThreadRestrictor::from_policy(&policy)?;
+///     let workers = (0..4)
+///         .map(|i| {
+///             Builder::new(&restrictor)
+///                 .name(format!("worker-{i}"))
+///                 .spawn(move || i * 2)
+///         })
+///         .collect::<Result<Vec<_>, _>>()?;
+///     for worker in workers {
+///         println!("result: {}", worker.join().expect("the worker panicked"));
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Builder {
+    inner: thread::Builder,
+    restrictor: ThreadRestrictor,
+}
+
+impl Builder {
+    /// Creates a thread factory enforcing the ruleset shared by `restrictor`.
+    pub fn new(restrictor: &ThreadRestrictor) -> Self {
+        Builder {
+            inner: thread::Builder::new(),
+            restrictor: restrictor.clone(),
+        }
+    }
+
+    /// Names the thread-to-be, see [`std::thread::Builder::name()`].
+    pub fn name(mut self, name: String) -> Self {
+        self.inner = self.inner.name(name);
+        self
+    }
+
+    /// Sets the size of the stack (in bytes) for the new thread,
+    /// see [`std::thread::Builder::stack_size()`].
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.inner = self.inner.stack_size(size);
+        self
+    }
+
+    /// Spawns a new thread, enforces the shared ruleset on it
+    /// (see [`ThreadRestrictor::restrict_current_thread()`]), and then runs `f` in it.
+    ///
+    /// This returns once the new thread enforced the ruleset.
+    /// If the operating system fails to create the thread,
+    /// the error is returned as by [`std::thread::Builder::spawn()`].
+    /// If the enforcement fails, `f` is not run
+    /// and the [`RulesetError`] is returned wrapped in an [`io::Error`]
+    /// (with the [`io::ErrorKind::Other`] kind), which can be extracted with
+    /// [`io::Error::into_inner()`] and then downcast.
+    pub fn spawn<F, T>(self, f: F) -> io::Result<JoinHandle<T>>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let restrictor = self.restrictor;
+        spawn_with(self.inner, move || restrictor.restrict_current_thread(), f)?
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
+
+/// Shared ruleset enforced on every thread of a pool.
+///
+/// Thread pools (e.g., rayon's or tokio's) spawn their worker threads lazily,
+/// and then cannot be sandboxed by the thread creating the pool.
+/// A `ThreadRestrictor` shares one [`RulesetCreated`] (i.e. one ruleset file descriptor)
+/// between all the workers, which enforce it when they start
+/// thanks to [`start_handler()`](ThreadRestrictor::start_handler).
+///
+/// # Example
+///
+/// ```
+/// use landlock::thread::ThreadRestrictor;
+/// use landlock::{Access, AccessFs, Policy, PolicyError, ABI};
+///
+/// fn sandboxed_workers() -> Result<(), PolicyError> {
+///     let abi = ABI::V1;
+///     let policy = Policy::default()
+///         .handle_fs(AccessFs::from_all(abi))
+///         .allow_path("/usr", AccessFs::from_read(abi));
+///     let restrictor = ThreadRestrictor::from_policy(&policy)?;
+///     // With rayon:
+///     // rayon::ThreadPoolBuilder::new().start_handler(restrictor.start_handler()).build()
+///     let handler = restrictor.start_handler();
+///     std::thread::spawn(move || {
+///         handler(0);
+///         // Sandboxed work.
+///     })
+///     .join()
+///     .unwrap();
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ThreadRestrictor {
+    ruleset: Arc<Mutex<RulesetCreated>>,
+}
+
+impl ThreadRestrictor {
+    /// Shares `ruleset` between the threads to restrict.
+    pub fn new(ruleset: RulesetCreated) -> Self {
+        ThreadRestrictor {
+            ruleset: Arc::new(Mutex::new(ruleset)),
+        }
+    }
+
+    /// Creates a ruleset from `policy` to share between the threads to restrict.
+    pub fn from_policy(policy: &Policy) -> Result<Self, PolicyError> {
+        Ok(Self::new(policy.create()?))
+    }
+
+    /// Enforces the shared ruleset on the calling thread,
+    /// as would [`RulesetCreated::restrict_self()`].
+    pub fn restrict_current_thread(&self) -> Result<RestrictionStatus, RulesetError> {
+        let mut ruleset = self
+            .ruleset
+            .lock()
+            // A panic while enforcing cannot leave an inconsistent ruleset.
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(ruleset.restrict(true)?)
+    }
+
+    /// Returns a thread start handler enforcing the shared ruleset,
+    /// compatible with the thread pool builders taking the index of the new worker
+    /// (e.g., rayon's `ThreadPoolBuilder::start_handler()`).
+    ///
+    /// # Panics
+    ///
+    /// The handler panics if the enforcement fails,
+    /// to not run any work in an unrestricted worker.
+    pub fn start_handler(&self) -> impl Fn(usize) + Send + Sync + 'static {
+        let restrictor = self.clone();
+        move |index| {
+            if let Err(e) = restrictor.restrict_current_thread() {
+                panic!("failed to restrict worker thread {index}: {e}");
+            }
+        }
+    }
+
+    /// Returns a thread start handler enforcing the shared ruleset,
+    /// compatible with the thread pool builders without argument
+    /// (e.g., tokio's `Builder::on_thread_start()`, see `RuntimeBuilderExt` with the `tokio`
+    /// feature).
+    ///
+    /// # Panics
+    ///
+    /// The handler panics if the enforcement fails,
+    /// to not run any work in an unrestricted worker.
+    pub fn thread_start_handler(&self) -> impl Fn() + Send + Sync + 'static {
+        let restrictor = self.clone();
+        move || {
+            if let Err(e) = restrictor.restrict_current_thread() {
+                panic!("failed to restrict worker thread: {e}");
+            }
+        }
+    }
+}
+
+/// Extension trait to sandbox the threads of a Tokio runtime.
+///
+/// Tokio runs tasks on worker threads and blocking tasks (e.g., `spawn_blocking()` or file
+/// system operations) on a pool of threads created on demand,
+/// which don't inherit the restrictions of the thread building the runtime
+/// if it is restricted afterwards.
+/// [`landlock()`](RuntimeBuilderExt::landlock) enforces a shared ruleset on all these threads
+/// when they start.
+///
+/// However, the future passed to `Runtime::block_on()` runs on the calling thread,
+/// which is not restricted (e.g., the main thread with `#[tokio::main]`),
+/// and neither is a runtime built with `Builder::new_current_thread()`.
+/// To sandbox the whole process instead,
+/// enforce the ruleset with [`RulesetCreated::restrict_self()`]
+/// before building the runtime.
+///
+/// This is only available with the `tokio` feature.
+///
+/// # Example
+///
+/// ```
+/// use landlock::thread::{RuntimeBuilderExt, ThreadRestrictor};
+/// use landlock::{Access, AccessFs, Policy, ABI};
+///
+/// fn sandboxed_runtime() -> Result<tokio::runtime::Runtime, Box<dyn std::error::Error>> {
+///     let abi = ABI::V1;
+///     let policy = Policy::default()
+///         .handle_fs(AccessFs::from_all(abi))
+///         .allow_path("/usr", AccessFs::from_read(abi));
+///     let runtime = tokio::runtime::Builder::new_multi_thread()
+///         .landlock(&ThreadRestrictor::from_policy(&policy)?)
+///         .build()?;
+///     Ok(runtime)
+/// }
+/// ```
+#[cfg(feature = "tokio")]
+pub trait RuntimeBuilderExt {
+    /// Enforces the ruleset shared by `restrictor` on every thread started by the runtime,
+    /// with [`ThreadRestrictor::thread_start_handler()`].
+    ///
+    /// This replaces any handler previously set with `on_thread_start()`.
+    fn landlock(&mut self, restrictor: &ThreadRestrictor) -> &mut Self;
+}
+
+#[cfg(feature = "tokio")]
+impl RuntimeBuilderExt for tokio::runtime::Builder {
+    fn landlock(&mut self, restrictor: &ThreadRestrictor) -> &mut Self {
+        self.on_thread_start(restrictor.thread_start_handler())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn spawn_restricted_thread() {
+        if !can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+            return;
+        }
+        let abi = ABI::V1;
+        let policy = Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .allow_path("/usr", AccessFs::from_read(abi));
+
+        let handle = spawn_restricted(&policy, || {
+            (
+                std::fs::read_dir("/usr").is_ok(),
+                std::fs::read("/etc/passwd").is_ok(),
+            )
+        })
+        .unwrap();
+        assert_eq!(
+            handle.restriction_status().ruleset,
+            RulesetStatus::FullyEnforced
+        );
+        assert_eq!(handle.join().unwrap(), (true, false));
+
+        // The calling thread is not restricted.
+        assert!(std::fs::read("/etc/passwd").is_ok());
+    }
+
+    #[test]
+    fn spawn_restricted_depth() {
+        if !can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+            return;
+        }
+        let policy = Policy::default().handle_fs(AccessFs::Execute);
+        let depths = std::thread::spawn(move || {
+            policy.restrict_self().unwrap();
+            let nested = spawn_restricted(&policy, domain_depth).unwrap();
+            (domain_depth(), nested.join().unwrap())
+        })
+        .join()
+        .unwrap();
+        assert_eq!(depths, (1, 2));
+    }
+
+    #[test]
+    fn thread_restrictor() {
+        if !can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+            return;
+        }
+        let abi = ABI::V1;
+        let policy = Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .allow_path("/usr", AccessFs::from_read(abi));
+        let restrictor = ThreadRestrictor::from_policy(&policy).unwrap();
+
+        let workers: Vec<_> = (0..2)
+            .map(|index| {
+                let handler = restrictor.start_handler();
+                std::thread::spawn(move || {
+                    handler(index);
+                    std::fs::read("/etc/passwd").is_ok()
+                })
+            })
+            .collect();
+        for worker in workers {
+            assert!(!worker.join().unwrap());
+        }
+
+        // The calling thread is not restricted.
+        assert!(std::fs::read("/etc/passwd").is_ok());
+    }
+
+    #[test]
+    fn builder_spawn() {
+        if !can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+            return;
+        }
+        let abi = ABI::V1;
+        let policy = Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .allow_path("/usr", AccessFs::from_read(abi));
+        let restrictor = ThreadRestrictor::from_policy(&policy).unwrap();
+
+        let workers: Vec<_> = (0..2)
+            .map(|i| {
+                Builder::new(&restrictor)
+                    .name(format!("worker-{i}"))
+                    .stack_size(1 << 20)
+                    .spawn(|| {
+                        (
+                            std::thread::current().name().map(String::from),
+                            std::fs::read("/etc/passwd").is_ok(),
+                        )
+                    })
+                    .unwrap()
+            })
+            .collect();
+        for (i, worker) in workers.into_iter().enumerate() {
+            assert_eq!(
+                worker.restriction_status().ruleset,
+                RulesetStatus::FullyEnforced
+            );
+            assert_eq!(worker.join().unwrap(), (Some(format!("worker-{i}")), false));
+        }
+
+        // The calling thread is not restricted.
+        assert!(std::fs::read("/etc/passwd").is_ok());
+    }
+
+    #[test]
+    fn builder_spawn_error() {
+        // The enforcement fails because of the missing canary.
+        let ruleset = Ruleset::from(ABI::Unsupported)
+            .handle_access(AccessFs::Execute)
+            .unwrap()
+            .create()
+            .unwrap()
+            .set_canary("/does-not-exist");
+        let restrictor = ThreadRestrictor::new(ruleset);
+        // The canary is checked for each spawned thread.
+        for _ in 0..2 {
+            let error = Builder::new(&restrictor)
+                .spawn(|| unreachable!())
+                .unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::Other);
+            assert!(matches!(
+                *error
+                    .into_inner()
+                    .unwrap()
+                    .downcast::<RulesetError>()
+                    .unwrap(),
+                RulesetError::RestrictSelf(RestrictSelfError::CanaryCheckCall { .. })
+            ));
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn tokio_runtime() {
+        if !can_emulate(ABI::V1, ABI::V1, Some(ABI::V1)) {
+            return;
+        }
+        let abi = ABI::V1;
+        let policy = Policy::default()
+            .handle_fs(AccessFs::from_all(abi))
+            .allow_path("/usr", AccessFs::from_read(abi));
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .landlock(&ThreadRestrictor::from_policy(&policy).unwrap())
+            .build()
+            .unwrap();
+
+        let (worker, blocking) = runtime.block_on(async {
+            let worker = tokio::spawn(async { std::fs::read("/etc/passwd").is_ok() });
+            let blocking = tokio::task::spawn_blocking(|| std::fs::read("/etc/passwd").is_ok());
+            (worker.await.unwrap(), blocking.await.unwrap())
+        });
+        assert!(!worker);
+        assert!(!blocking);
+
+        // The thread calling block_on() is not restricted.
+        assert!(std::fs::read("/etc/passwd").is_ok());
+    }
+
+    #[test]
+    fn spawn_restricted_error() {
+        let policy = Policy::default()
+            .handle_fs(AccessFs::Execute)
+            .allow_path("/does-not-exist", AccessFs::Execute);
+        assert!(matches!(
+            spawn_restricted(&policy, || unreachable!()).unwrap_err(),
+            PolicyError::PathFd(_)
+        ));
+    }
+}
@@ -0,0 +1,144 @@
+use super::Syscall;
+use std::cell::Cell;
+use std::os::raw::c_int;
+
+// Errno to return for each Syscall variant, or 0.
+thread_local! {
+    static FAULTS: Cell<[c_int; 4]> = const { Cell::new([0; 4]) };
+}
+
+fn index(syscall: Syscall) -> usize {
+    match syscall {
+        Syscall::CreateRuleset => 0,
+        Syscall::AddRule => 1,
+        Syscall::RestrictSelf => 2,
+        Syscall::SetNoNewPrivs => 3,
+    }
+}
+
+fn set(syscall: Syscall, errno: c_int) -> c_int {
+    FAULTS.with(|faults| {
+        let mut errnos = faults.get();
+        let previous = errnos[index(syscall)];
+        errnos[index(syscall)] = errno;
+        faults.set(errnos);
+        previous
+    })
+}
+
+// Returns -1 and sets errno if a fault is injected for syscall.  Doesn't allocate.
+pub(super) fn injected(syscall: Syscall) -> Option<c_int> {
+    match FAULTS.with(|faults| faults.get()[index(syscall)]) {
+        0 => None,
+        errno => {
+            super::set_errno(errno);
+            Some(-1)
+        }
+    }
+}
+
+/// Forces `syscall` to fail with `errno` for the current thread,
+/// until the returned guard is dropped.
+///
+/// This enables to deterministically exercise error paths (e.g. `ENOMEM`, `E2BIG`, `EBADF`),
+/// with the running kernel or a [`MockKernel`](crate::test_utils::MockKernel).
+/// The system call is not performed while the fault is injected.
+///
+/// This is only available with the `test-utils` feature.
+///
+/// # Example
+///
+/// ```
+/// use landlock::test_utils::{inject_fault, Syscall};
+/// use landlock::{
+///     Access, AccessFs, RestrictSelfError, Ruleset, RulesetAttr, RulesetError, ABI,
+/// };
+///
+/// let _fault = inject_fault(Syscall::RestrictSelf, libc::ENOMEM);
+/// let ruleset = Ruleset::default()
+///     .handle_access(AccessFs::from_all(ABI::V1))
+///     .unwrap()
+///     .create()
+///     .unwrap();
+/// if ruleset.compat_report().abi != ABI::Unsupported {
+///     assert!(matches!(
+///         ruleset.restrict_self(),
+///         Err(RulesetError::RestrictSelf(RestrictSelfError::RestrictSelfCall { source, .. }))
+///             if source.raw_os_error() == Some(libc::ENOMEM)
+///     ));
+/// }
+/// ```
+pub fn inject_fault(syscall: Syscall, errno: i32) -> FaultGuard {
+    assert_ne!(errno, 0, "the injected errno must not be zero");
+    FaultGuard {
+        syscall,
+        previous: set(syscall, errno),
+    }
+}
+
+/// Guard returned by [`inject_fault()`], which restores the previous behavior when dropped.
+#[derive(Debug)]
+pub struct FaultGuard {
+    syscall: Syscall,
+    previous: c_int,
+}
+
+impl Drop for FaultGuard {
+    fn drop(&mut self) {
+        set(self.syscall, self.previous);
+    }
+}
+
+#[test]
+fn inject_fault_errors() {
+    use super::mock::MockKernel;
+    use crate::*;
+
+    let _kernel = MockKernel::new(ABI::V1).install();
+    let create = || {
+        Ruleset::default()
+            .handle_access(AccessFs::from_all(ABI::V1))
+            .unwrap()
+            .create()
+    };
+    {
+        let _fault = inject_fault(Syscall::CreateRuleset, libc::ENOMEM);
+        assert!(matches!(
+            create(),
+            Err(RulesetError::CreateRuleset(CreateRulesetError::CreateRulesetCall { source }))
+                if source.raw_os_error() == Some(libc::ENOMEM)
+        ));
+    }
+    {
+        let _fault = inject_fault(Syscall::AddRule, libc::EBADF);
+        let rule =
+            PathBeneath::new(PathFd::new("/").unwrap(), AccessFs::Execute).set_path_label("/");
+        assert!(matches!(
+            create().unwrap().add_rule(rule),
+            Err(RulesetError::AddRules(AddRulesError::Fs(AddRuleError::AddRuleCall { source, path })))
+                if source.raw_os_error() == Some(libc::EBADF) && path == Some("/".into())
+        ));
+    }
+    {
+        let _fault = inject_fault(Syscall::SetNoNewPrivs, libc::EINVAL);
+        let _nested = inject_fault(Syscall::RestrictSelf, libc::E2BIG);
+        assert!(matches!(
+            create().unwrap().restrict_self(),
+            Err(RulesetError::RestrictSelf(RestrictSelfError::SetNoNewPrivsCall { source }))
+                if source.raw_os_error() == Some(libc::EINVAL)
+        ));
+    }
+    {
+        // E.g. with domains inherited from the parent process.
+        let _fault = inject_fault(Syscall::RestrictSelf, libc::E2BIG);
+        assert!(matches!(
+            create().unwrap().restrict_self(),
+            Err(RulesetError::RestrictSelf(RestrictSelfError::DomainDepthExceeded { depth }))
+                if depth == MAX_DOMAIN_DEPTH
+        ));
+    }
+    assert_eq!(
+        create().unwrap().restrict_self().unwrap().ruleset,
+        RulesetStatus::FullyEnforced
+    );
+}
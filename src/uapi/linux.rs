@@ -0,0 +1,260 @@
+// Linux system calls.
+
+use super::landlock::{__u32, landlock_rule_type, landlock_ruleset_attr};
+use super::Syscalls;
+use libc::{size_t, syscall};
+use std::os::raw::{c_int, c_long, c_void};
+
+#[cfg(test)]
+use super::LANDLOCK_CREATE_RULESET_VERSION;
+
+// Offset of the system call numbers, which depends on the MIPS ABI.
+#[cfg(any(target_arch = "mips", target_arch = "mips32r6"))]
+const SYSCALL_OFFSET: c_long = 4000;
+#[cfg(all(
+    any(target_arch = "mips64", target_arch = "mips64r6"),
+    target_pointer_width = "64"
+))]
+const SYSCALL_OFFSET: c_long = 5000;
+#[cfg(all(
+    any(target_arch = "mips64", target_arch = "mips64r6"),
+    target_pointer_width = "32"
+))]
+const SYSCALL_OFFSET: c_long = 6000;
+#[cfg(any(
+    target_arch = "aarch64",
+    target_arch = "arm",
+    target_arch = "csky",
+    target_arch = "hexagon",
+    target_arch = "loongarch64",
+    target_arch = "m68k",
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "riscv32",
+    target_arch = "riscv64",
+    target_arch = "s390x",
+    target_arch = "sparc",
+    target_arch = "sparc64",
+    target_arch = "x86",
+    target_arch = "x86_64",
+))]
+const SYSCALL_OFFSET: c_long = 0;
+// Fails to build rather than calling unrelated system calls.
+#[cfg(not(any(
+    target_arch = "mips",
+    target_arch = "mips32r6",
+    target_arch = "mips64",
+    target_arch = "mips64r6",
+    target_arch = "aarch64",
+    target_arch = "arm",
+    target_arch = "csky",
+    target_arch = "hexagon",
+    target_arch = "loongarch64",
+    target_arch = "m68k",
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "riscv32",
+    target_arch = "riscv64",
+    target_arch = "s390x",
+    target_arch = "sparc",
+    target_arch = "sparc64",
+    target_arch = "x86",
+    target_arch = "x86_64",
+)))]
+compile_error!("unknown system call numbers for this architecture");
+
+// System call numbers, which are not defined by the libc crate for all architectures (nor by its
+// old versions).  Since Linux 5.1, new system calls have the same number on all architectures
+// (including 32-bit ones), except for the MIPS offset and for Alpha (e.g. 554 to 556 for the
+// Landlock ones), which is not supported by Rust.  Other architectures are checked above.
+#[allow(non_upper_case_globals)]
+// Only used without the rustix feature, which provides openat2(2).
+#[cfg_attr(feature = "rustix", allow(dead_code))]
+pub const SYS_openat2: c_long = SYSCALL_OFFSET + 437;
+#[allow(non_upper_case_globals)]
+pub const SYS_landlock_create_ruleset: c_long = SYSCALL_OFFSET + 444;
+#[allow(non_upper_case_globals)]
+pub const SYS_landlock_add_rule: c_long = SYSCALL_OFFSET + 445;
+#[allow(non_upper_case_globals)]
+pub const SYS_landlock_restrict_self: c_long = SYSCALL_OFFSET + 446;
+
+#[test]
+fn syscall_numbers() {
+    // Checks against the libc crate for the architectures it covers.
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "x86"))]
+    {
+        assert_eq!(SYS_openat2, libc::SYS_openat2);
+        assert_eq!(
+            SYS_landlock_create_ruleset,
+            libc::SYS_landlock_create_ruleset
+        );
+        assert_eq!(SYS_landlock_add_rule, libc::SYS_landlock_add_rule);
+        assert_eq!(SYS_landlock_restrict_self, libc::SYS_landlock_restrict_self);
+    }
+
+    // The running kernel knows these system calls, if it supports Landlock.
+    let abi = unsafe {
+        syscall(
+            SYS_landlock_create_ruleset,
+            std::ptr::null::<landlock_ruleset_attr>(),
+            0,
+            LANDLOCK_CREATE_RULESET_VERSION,
+        )
+    };
+    if abi < 0 {
+        let errno = std::io::Error::last_os_error().raw_os_error();
+        assert!(matches!(errno, Some(libc::ENOSYS | libc::EOPNOTSUPP)));
+    }
+}
+
+// Running kernel.
+pub(super) struct Kernel;
+
+#[test]
+fn has_cap_sys_admin() {
+    let status = std::fs::read_to_string("/proc/thread-self/status").unwrap();
+    let effective = status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))
+        .and_then(|caps| u64::from_str_radix(caps.trim(), 16).ok())
+        .unwrap();
+    // CAP_SYS_ADMIN is the 21st capability.
+    assert_eq!(
+        unsafe { Kernel.has_cap_sys_admin() },
+        c_int::from(effective & (1 << 21) != 0)
+    );
+}
+
+impl Syscalls for Kernel {
+    #[rustfmt::skip]
+    unsafe fn landlock_create_ruleset(&self, attr: *const landlock_ruleset_attr, size: size_t,
+                                      flags: __u32) -> c_int {
+        syscall(SYS_landlock_create_ruleset, attr, size, flags) as c_int
+    }
+
+    #[rustfmt::skip]
+    unsafe fn landlock_add_rule(&self, ruleset_fd: c_int, rule_type: landlock_rule_type,
+                                rule_attr: *const c_void, flags: __u32) -> c_int {
+        syscall(SYS_landlock_add_rule, ruleset_fd, rule_type, rule_attr, flags) as c_int
+    }
+
+    unsafe fn landlock_restrict_self(&self, ruleset_fd: c_int, flags: __u32) -> c_int {
+        syscall(SYS_landlock_restrict_self, ruleset_fd, flags) as c_int
+    }
+
+    #[cfg(not(feature = "rustix"))]
+    unsafe fn prctl_set_no_new_privs(&self) -> c_int {
+        libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0)
+    }
+
+    #[cfg(not(feature = "rustix"))]
+    unsafe fn prctl_get_no_new_privs(&self) -> c_int {
+        libc::prctl(libc::PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0)
+    }
+
+    #[cfg(feature = "rustix")]
+    unsafe fn prctl_set_no_new_privs(&self) -> c_int {
+        rustix_ret(rustix::thread::set_no_new_privs(true).map(|()| 0))
+    }
+
+    #[cfg(feature = "rustix")]
+    unsafe fn prctl_get_no_new_privs(&self) -> c_int {
+        rustix_ret(rustix::thread::no_new_privs().map(c_int::from))
+    }
+
+    #[cfg(not(feature = "rustix"))]
+    unsafe fn has_cap_sys_admin(&self) -> c_int {
+        let mut sets = CapSets::default();
+        if capget(&mut sets) != 0 {
+            return -1;
+        }
+        c_int::from(sets.effective & (1 << CAP_SYS_ADMIN) != 0)
+    }
+
+    #[cfg(feature = "rustix")]
+    unsafe fn has_cap_sys_admin(&self) -> c_int {
+        use rustix::thread::{capabilities, CapabilityFlags};
+
+        rustix_ret(
+            capabilities(None)
+                .map(|caps| c_int::from(caps.effective.contains(CapabilityFlags::SYS_ADMIN))),
+        )
+    }
+}
+
+// Definitions from linux/capability.h, which are not provided by the libc crate.
+const LINUX_CAPABILITY_VERSION_3: u32 = 0x2008_0522;
+#[cfg(not(feature = "rustix"))]
+const CAP_SYS_ADMIN: u32 = 21;
+
+#[repr(C)]
+struct CapUserHeader {
+    version: u32,
+    pid: c_int,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct CapUserData {
+    effective: u32,
+    permitted: u32,
+    inheritable: u32,
+}
+
+// Capability sets of the calling thread, with one bit per capability number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct CapSets {
+    pub(crate) effective: u64,
+    pub(crate) permitted: u64,
+    pub(crate) inheritable: u64,
+}
+
+// Only used by the capabilities module and without the rustix feature.
+#[cfg_attr(all(feature = "rustix", not(feature = "caps")), allow(dead_code))]
+pub(crate) unsafe fn capget(sets: &mut CapSets) -> c_int {
+    let mut header = CapUserHeader {
+        version: LINUX_CAPABILITY_VERSION_3,
+        pid: 0,
+    };
+    let mut data = [CapUserData::default(); 2];
+    let ret = syscall(libc::SYS_capget, &mut header, data.as_mut_ptr()) as c_int;
+    if ret == 0 {
+        let join = |low: u32, high: u32| u64::from(low) | u64::from(high) << 32;
+        sets.effective = join(data[0].effective, data[1].effective);
+        sets.permitted = join(data[0].permitted, data[1].permitted);
+        sets.inheritable = join(data[0].inheritable, data[1].inheritable);
+    }
+    ret
+}
+
+#[cfg(feature = "caps")]
+pub(crate) unsafe fn capset(sets: &CapSets) -> c_int {
+    let mut header = CapUserHeader {
+        version: LINUX_CAPABILITY_VERSION_3,
+        pid: 0,
+    };
+    let data = [
+        CapUserData {
+            effective: sets.effective as u32,
+            permitted: sets.permitted as u32,
+            inheritable: sets.inheritable as u32,
+        },
+        CapUserData {
+            effective: (sets.effective >> 32) as u32,
+            permitted: (sets.permitted >> 32) as u32,
+            inheritable: (sets.inheritable >> 32) as u32,
+        },
+    ];
+    syscall(libc::SYS_capset, &mut header, data.as_ptr()) as c_int
+}
+
+// Converts a rustix result to the kernel semantic, setting errno on error.  rustix doesn't
+// provide the Landlock system calls (nor a generic syscall function), which are then still
+// called with libc.
+#[cfg(feature = "rustix")]
+fn rustix_ret(ret: rustix::io::Result<c_int>) -> c_int {
+    ret.unwrap_or_else(|errno| {
+        super::set_errno(errno.raw_os_error());
+        -1
+    })
+}
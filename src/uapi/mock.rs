@@ -0,0 +1,310 @@
+use super::landlock::__u32;
+use super::{
+    landlock_net_port_attr, landlock_path_beneath_attr, landlock_rule_type,
+    landlock_rule_type_LANDLOCK_RULE_NET_PORT, landlock_rule_type_LANDLOCK_RULE_PATH_BENEATH,
+    landlock_ruleset_attr, Syscalls, LANDLOCK_CREATE_RULESET_VERSION,
+};
+use crate::{Access, AccessFs, AccessNet, ABI};
+use libc::size_t;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::os::raw::{c_int, c_void};
+use std::rc::Rc;
+
+thread_local! {
+    static MOCK: RefCell<Option<Rc<MockKernel>>> = const { RefCell::new(None) };
+}
+
+// Doesn't allocate.
+pub(super) fn with_mock<F, R>(f: F) -> R
+where
+    F: FnOnce(Option<&MockKernel>) -> R,
+{
+    MOCK.with(|mock| f(mock.borrow().as_deref()))
+}
+
+fn fail(errno: c_int) -> c_int {
+    super::set_errno(errno);
+    -1
+}
+
+/// Emulated kernel supporting a specific Landlock ABI,
+/// to test the crate's logic whatever the running kernel.
+///
+/// Once [installed](MockKernel::install), the Landlock system calls,
+/// `prctl(PR_SET_NO_NEW_PRIVS)`, and `capget(2)` of the current thread are handled by this mock,
+/// which checks their arguments as the kernel would but doesn't restrict anything.
+///
+/// This is only available with the `test-utils` feature.
+///
+/// # Example
+///
+/// ```
+/// use landlock::test_utils::MockKernel;
+/// use landlock::{Access, AccessFs, Ruleset, RulesetAttr, RulesetStatus, ABI};
+///
+/// let kernel = MockKernel::new(ABI::V1).install();
+/// let status = Ruleset::default()
+///     .handle_access(AccessFs::from_all(ABI::V2))
+///     .unwrap()
+///     .create()
+///     .unwrap()
+///     .restrict_self()
+///     .unwrap();
+/// assert_eq!(status.ruleset, RulesetStatus::PartiallyEnforced);
+/// assert_eq!(kernel.domains(), 1);
+/// ```
+#[derive(Debug)]
+pub struct MockKernel {
+    abi: ABI,
+    state: RefCell<MockState>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    // Handled access rights of the created rulesets.
+    rulesets: HashMap<c_int, (u64, u64)>,
+    rules: usize,
+    domains: usize,
+    no_new_privs: bool,
+    cap_sys_admin: bool,
+}
+
+impl MockKernel {
+    /// Creates a mock kernel supporting `abi`, which may be [`ABI::Unsupported`].
+    pub fn new(abi: ABI) -> Self {
+        MockKernel {
+            abi,
+            state: Default::default(),
+        }
+    }
+
+    /// Emulates a calling thread with (or without, by default) `CAP_SYS_ADMIN`
+    /// in its user namespace, which enables to enforce a ruleset without `no_new_privs`.
+    pub fn set_cap_sys_admin(mut self, cap_sys_admin: bool) -> Self {
+        self.state.get_mut().cap_sys_admin = cap_sys_admin;
+        self
+    }
+
+    /// Handles the system calls of the current thread with this mock kernel,
+    /// until the returned guard is dropped.
+    pub fn install(self) -> MockGuard {
+        let current = Rc::new(self);
+        let previous = MOCK.with(|mock| mock.borrow_mut().replace(current.clone()));
+        MockGuard { current, previous }
+    }
+
+    /// Gets the emulated Landlock ABI.
+    pub fn abi(&self) -> ABI {
+        self.abi
+    }
+
+    /// Returns the number of rules successfully added to rulesets.
+    pub fn rules(&self) -> usize {
+        self.state.borrow().rules
+    }
+
+    /// Returns the number of rulesets successfully enforced (i.e. the number of domains).
+    pub fn domains(&self) -> usize {
+        self.state.borrow().domains
+    }
+
+    /// Returns true if `PR_SET_NO_NEW_PRIVS` was successfully set.
+    pub fn no_new_privs(&self) -> bool {
+        self.state.borrow().no_new_privs
+    }
+}
+
+impl Syscalls for MockKernel {
+    unsafe fn landlock_create_ruleset(
+        &self,
+        attr: *const landlock_ruleset_attr,
+        _size: size_t,
+        flags: __u32,
+    ) -> c_int {
+        if self.abi == ABI::Unsupported {
+            return fail(libc::EOPNOTSUPP);
+        }
+        if flags == LANDLOCK_CREATE_RULESET_VERSION {
+            return if attr.is_null() {
+                self.abi as c_int
+            } else {
+                fail(libc::EINVAL)
+            };
+        }
+        if flags != 0 || attr.is_null() {
+            return fail(libc::EINVAL);
+        }
+        let attr = *attr;
+        if attr.handled_access_fs == 0 && attr.handled_access_net == 0 {
+            return fail(libc::ENOMSG);
+        }
+        if attr.handled_access_fs & !AccessFs::from_all(self.abi).bits() != 0
+            || attr.handled_access_net & !AccessNet::from_all(self.abi).bits() != 0
+        {
+            return fail(libc::EINVAL);
+        }
+        // Gets a real file descriptor, which can then be closed.
+        let fd = libc::open(
+            b"/dev/null\0".as_ptr().cast(),
+            libc::O_RDONLY | libc::O_CLOEXEC,
+        );
+        if fd >= 0 {
+            self.state
+                .borrow_mut()
+                .rulesets
+                .insert(fd, (attr.handled_access_fs, attr.handled_access_net));
+        }
+        fd
+    }
+
+    unsafe fn landlock_add_rule(
+        &self,
+        ruleset_fd: c_int,
+        rule_type: landlock_rule_type,
+        rule_attr: *const c_void,
+        flags: __u32,
+    ) -> c_int {
+        let mut state = self.state.borrow_mut();
+        let (handled_fs, handled_net) = match state.rulesets.get(&ruleset_fd) {
+            Some(handled) => *handled,
+            None => return fail(libc::EBADF),
+        };
+        if flags != 0 || rule_attr.is_null() {
+            return fail(libc::EINVAL);
+        }
+        let (allowed, handled) = match rule_type {
+            t if t == landlock_rule_type_LANDLOCK_RULE_PATH_BENEATH => {
+                let attr = (rule_attr as *const landlock_path_beneath_attr).read_unaligned();
+                if libc::fcntl(attr.parent_fd, libc::F_GETFD) < 0 {
+                    return fail(libc::EBADF);
+                }
+                (attr.allowed_access, handled_fs)
+            }
+            t if t == landlock_rule_type_LANDLOCK_RULE_NET_PORT => {
+                let attr = (rule_attr as *const landlock_net_port_attr).read_unaligned();
+                if attr.port > u16::MAX.into() {
+                    return fail(libc::EINVAL);
+                }
+                (attr.allowed_access, handled_net)
+            }
+            _ => return fail(libc::EINVAL),
+        };
+        if allowed == 0 {
+            return fail(libc::ENOMSG);
+        }
+        if allowed & !handled != 0 {
+            return fail(libc::EINVAL);
+        }
+        state.rules += 1;
+        0
+    }
+
+    unsafe fn landlock_restrict_self(&self, ruleset_fd: c_int, flags: __u32) -> c_int {
+        let mut state = self.state.borrow_mut();
+        if !state.rulesets.contains_key(&ruleset_fd) {
+            return fail(libc::EBADF);
+        }
+        if flags != 0 {
+            return fail(libc::EINVAL);
+        }
+        if !state.no_new_privs && !state.cap_sys_admin {
+            return fail(libc::EPERM);
+        }
+        if state.domains >= crate::MAX_DOMAIN_DEPTH {
+            return fail(libc::E2BIG);
+        }
+        state.domains += 1;
+        0
+    }
+
+    unsafe fn prctl_set_no_new_privs(&self) -> c_int {
+        self.state.borrow_mut().no_new_privs = true;
+        0
+    }
+
+    unsafe fn prctl_get_no_new_privs(&self) -> c_int {
+        self.state.borrow().no_new_privs.into()
+    }
+
+    unsafe fn has_cap_sys_admin(&self) -> c_int {
+        self.state.borrow().cap_sys_admin.into()
+    }
+}
+
+/// Guard returned by [`MockKernel::install()`], which restores the previous kernel
+/// (i.e. the running one or another mock) when dropped.
+#[derive(Debug)]
+pub struct MockGuard {
+    current: Rc<MockKernel>,
+    previous: Option<Rc<MockKernel>>,
+}
+
+impl Deref for MockGuard {
+    type Target = MockKernel;
+
+    fn deref(&self) -> &Self::Target {
+        &self.current
+    }
+}
+
+impl Drop for MockGuard {
+    fn drop(&mut self) {
+        MOCK.with(|mock| *mock.borrow_mut() = self.previous.take());
+    }
+}
+
+#[test]
+fn mock_kernel_ruleset() {
+    use crate::*;
+
+    let kernel = MockKernel::new(ABI::V1).install();
+    let status = Ruleset::default()
+        .handle_access(AccessFs::from_all(ABI::V2))
+        .unwrap()
+        .create()
+        .unwrap()
+        .add_rules(path_beneath_rules(&["/usr"], AccessFs::from_read(ABI::V1)))
+        .unwrap()
+        .restrict_self()
+        .unwrap();
+    assert_eq!(status.ruleset, RulesetStatus::PartiallyEnforced);
+    assert_eq!(status.abi, ABI::V1);
+    assert_eq!(status.dropped_fs, AccessFs::Refer);
+    assert!(status.no_new_privs);
+    assert_eq!(kernel.abi(), ABI::V1);
+    assert!(kernel.no_new_privs());
+    assert_eq!(kernel.rules(), 1);
+    assert_eq!(kernel.domains(), 1);
+
+    // Nested mock.
+    {
+        let unsupported = MockKernel::new(ABI::Unsupported).install();
+        let status = Ruleset::default()
+            .handle_access(AccessFs::Execute)
+            .unwrap()
+            .create()
+            .unwrap()
+            .restrict_self()
+            .unwrap();
+        assert_eq!(status.ruleset, RulesetStatus::NotEnforced);
+        assert_eq!(unsupported.domains(), 0);
+    }
+
+    // The kernel's checks are emulated.
+    drop(kernel);
+    let kernel = MockKernel::new(ABI::V1).install();
+    let ruleset = Ruleset::default()
+        .handle_access(AccessFs::Execute)
+        .unwrap()
+        .create()
+        .unwrap()
+        .set_no_new_privs(false);
+    assert!(matches!(
+        ruleset.restrict_self(),
+        Err(RulesetError::RestrictSelf(RestrictSelfError::RestrictSelfCall { source }))
+            if source.raw_os_error() == Some(libc::EPERM)
+    ));
+    assert_eq!(kernel.domains(), 0);
+}
@@ -2,13 +2,26 @@
 #[allow(non_camel_case_types)]
 #[allow(non_snake_case)]
 #[allow(non_upper_case_globals)]
-mod landlock;
+#[cfg(not(feature = "regen-uapi"))]
+pub(crate) mod landlock;
+
+// Generated by build.rs from the installed kernel headers.
+#[allow(dead_code)]
+#[allow(non_camel_case_types)]
+#[allow(non_snake_case)]
+#[allow(non_upper_case_globals)]
+#[cfg(feature = "regen-uapi")]
+pub(crate) mod landlock {
+    include!(concat!(env!("OUT_DIR"), "/landlock.rs"));
+}
 
 #[rustfmt::skip]
 pub use self::landlock::{
+    landlock_net_port_attr,
     landlock_path_beneath_attr,
     landlock_ruleset_attr,
     landlock_rule_type,
+    landlock_rule_type_LANDLOCK_RULE_NET_PORT,
     landlock_rule_type_LANDLOCK_RULE_PATH_BENEATH,
     LANDLOCK_ACCESS_FS_EXECUTE,
     LANDLOCK_ACCESS_FS_WRITE_FILE,
@@ -25,26 +38,174 @@ pub use self::landlock::{
     LANDLOCK_ACCESS_FS_MAKE_SYM,
     LANDLOCK_ACCESS_FS_REFER,
     LANDLOCK_ACCESS_FS_TRUNCATE,
+    LANDLOCK_ACCESS_NET_BIND_TCP,
+    LANDLOCK_ACCESS_NET_CONNECT_TCP,
     LANDLOCK_CREATE_RULESET_VERSION,
 };
 
-use libc::{
-    __u32, c_int, c_void, size_t, syscall, SYS_landlock_add_rule, SYS_landlock_create_ruleset,
-    SYS_landlock_restrict_self,
-};
+#[cfg(any(test, feature = "test-utils"))]
+mod fault;
+#[cfg(any(test, feature = "test-utils"))]
+mod mock;
+
+#[cfg(feature = "test-utils")]
+pub use self::fault::{inject_fault, FaultGuard};
+#[cfg(feature = "test-utils")]
+pub use self::mock::MockGuard;
+#[cfg(any(test, feature = "test-utils"))]
+pub use self::mock::MockKernel;
+
+use self::landlock::__u32;
+use libc::size_t;
+use std::os::raw::{c_int, c_void};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) mod linux;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+mod stub;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use self::linux::Kernel;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+use self::stub::Kernel;
+
+/// System call of which a failure can be forced with
+/// [`inject_fault()`](crate::test_utils::inject_fault).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Syscall {
+    /// `landlock_create_ruleset()`, except when probing the Landlock ABI version.
+    CreateRuleset,
+    /// `landlock_add_rule()`.
+    AddRule,
+    /// `landlock_restrict_self()`.
+    RestrictSelf,
+    /// `prctl(PR_SET_NO_NEW_PRIVS)`.
+    SetNoNewPrivs,
+}
+
+// System calls used by this crate, with the same semantic as the kernel ones:
+// on error, they set errno and return -1.
+pub trait Syscalls {
+    unsafe fn landlock_create_ruleset(
+        &self,
+        attr: *const landlock_ruleset_attr,
+        size: size_t,
+        flags: __u32,
+    ) -> c_int;
+
+    unsafe fn landlock_add_rule(
+        &self,
+        ruleset_fd: c_int,
+        rule_type: landlock_rule_type,
+        rule_attr: *const c_void,
+        flags: __u32,
+    ) -> c_int;
+
+    unsafe fn landlock_restrict_self(&self, ruleset_fd: c_int, flags: __u32) -> c_int;
+
+    unsafe fn prctl_set_no_new_privs(&self) -> c_int;
+
+    unsafe fn prctl_get_no_new_privs(&self) -> c_int;
+
+    // Returns 1 if the calling thread has CAP_SYS_ADMIN in its effective set (i.e. in its user
+    // namespace), 0 otherwise, as returned by capget(2).
+    unsafe fn has_cap_sys_admin(&self) -> c_int;
+}
+
+#[cfg(any(target_os = "solaris", target_os = "illumos"))]
+use libc::___errno as errno_location;
+#[cfg(target_os = "android")]
+use libc::__errno as errno_location;
+#[cfg(any(target_os = "netbsd", target_os = "openbsd"))]
+use libc::__errno as errno_location;
+#[cfg(target_os = "linux")]
+use libc::__errno_location as errno_location;
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+))]
+use libc::__error as errno_location;
+
+// Sets errno for the current thread, to emulate a failed system call.
+#[cfg_attr(
+    all(
+        any(target_os = "linux", target_os = "android"),
+        not(any(test, feature = "rustix", feature = "test-utils"))
+    ),
+    allow(dead_code)
+)]
+fn set_errno(errno: c_int) {
+    unsafe { *errno_location() = errno };
+}
+
+// Calls f with the mocked kernel installed for the current thread, if any, or the running one,
+// unless a fault is injected for syscall.  Doesn't allocate.
+#[cfg(any(test, feature = "test-utils"))]
+fn with_syscalls<F>(syscall: Option<Syscall>, f: F) -> c_int
+where
+    F: FnOnce(&dyn Syscalls) -> c_int,
+{
+    if let Some(ret) = syscall.and_then(fault::injected) {
+        return ret;
+    }
+    mock::with_mock(|mock| match mock {
+        Some(mock) => f(mock),
+        None => f(&Kernel),
+    })
+}
+
+#[cfg(not(any(test, feature = "test-utils")))]
+fn with_syscalls<F>(_syscall: Option<Syscall>, f: F) -> c_int
+where
+    F: FnOnce(&dyn Syscalls) -> c_int,
+{
+    f(&Kernel)
+}
+
+// Returns true if a mocked kernel is installed for the current thread.
+#[cfg(any(test, feature = "test-utils"))]
+pub fn is_mocked() -> bool {
+    mock::with_mock(|mock| mock.is_some())
+}
+
+#[cfg(not(any(test, feature = "test-utils")))]
+pub fn is_mocked() -> bool {
+    false
+}
 
 #[rustfmt::skip]
 pub unsafe fn landlock_create_ruleset(attr: *const landlock_ruleset_attr, size: size_t,
                                       flags: __u32) -> c_int {
-    syscall(SYS_landlock_create_ruleset, attr, size, flags) as c_int
+    // The ABI probe is not subject to fault injection.
+    let syscall = if flags == 0 { Some(Syscall::CreateRuleset) } else { None };
+    with_syscalls(syscall, |s| s.landlock_create_ruleset(attr, size, flags))
 }
 
 #[rustfmt::skip]
 pub unsafe fn landlock_add_rule(ruleset_fd: c_int, rule_type: landlock_rule_type,
                                 rule_attr: *const c_void, flags: __u32) -> c_int {
-    syscall(SYS_landlock_add_rule, ruleset_fd, rule_type, rule_attr, flags) as c_int
+    with_syscalls(Some(Syscall::AddRule), |s| {
+        s.landlock_add_rule(ruleset_fd, rule_type, rule_attr, flags)
+    })
 }
 
 pub unsafe fn landlock_restrict_self(ruleset_fd: c_int, flags: __u32) -> c_int {
-    syscall(SYS_landlock_restrict_self, ruleset_fd, flags) as c_int
+    with_syscalls(Some(Syscall::RestrictSelf), |s| {
+        s.landlock_restrict_self(ruleset_fd, flags)
+    })
+}
+
+pub unsafe fn prctl_set_no_new_privs() -> c_int {
+    with_syscalls(Some(Syscall::SetNoNewPrivs), |s| s.prctl_set_no_new_privs())
+}
+
+pub unsafe fn prctl_get_no_new_privs() -> c_int {
+    with_syscalls(None, |s| s.prctl_get_no_new_privs())
+}
+
+pub unsafe fn has_cap_sys_admin() -> c_int {
+    with_syscalls(None, |s| s.has_cap_sys_admin())
 }
@@ -0,0 +1,51 @@
+// Stub system calls for non-Linux systems, which behave as a Linux kernel without Landlock nor
+// no_new_privs support: they fail with ENOSYS.
+
+use super::landlock::{__u32, landlock_rule_type, landlock_ruleset_attr};
+use super::Syscalls;
+use libc::size_t;
+use std::os::raw::{c_int, c_void};
+
+fn unsupported() -> c_int {
+    super::set_errno(libc::ENOSYS);
+    -1
+}
+
+pub(super) struct Kernel;
+
+impl Syscalls for Kernel {
+    unsafe fn landlock_create_ruleset(
+        &self,
+        _attr: *const landlock_ruleset_attr,
+        _size: size_t,
+        _flags: __u32,
+    ) -> c_int {
+        unsupported()
+    }
+
+    unsafe fn landlock_add_rule(
+        &self,
+        _ruleset_fd: c_int,
+        _rule_type: landlock_rule_type,
+        _rule_attr: *const c_void,
+        _flags: __u32,
+    ) -> c_int {
+        unsupported()
+    }
+
+    unsafe fn landlock_restrict_self(&self, _ruleset_fd: c_int, _flags: __u32) -> c_int {
+        unsupported()
+    }
+
+    unsafe fn prctl_set_no_new_privs(&self) -> c_int {
+        unsupported()
+    }
+
+    unsafe fn prctl_get_no_new_privs(&self) -> c_int {
+        unsupported()
+    }
+
+    unsafe fn has_cap_sys_admin(&self) -> c_int {
+        unsupported()
+    }
+}
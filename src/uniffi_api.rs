@@ -0,0 +1,180 @@
+//! [UniFFI](https://mozilla.github.io/uniffi-rs/) interface, to generate bindings for other
+//! languages (e.g. Python, Kotlin or Swift).
+//!
+//! [`SandboxPolicy`] wraps a [`Policy`] and enables to describe and enforce it from a launcher
+//! written in another language.
+//! Access rights are identified by their names (e.g. `"read_file"` or `"bind_tcp"`),
+//! see [`Access::from_names()`].
+//!
+//! The bindings can be generated from the library built with this feature
+//! (e.g. with `cargo rustc --release --lib --features uniffi --crate-type cdylib`)
+//! with `uniffi-bindgen generate --library target/release/liblandlock.so --language python`.
+//!
+//! This is only available with the `uniffi` feature.
+
+use crate::{
+    Access, AccessFs, AccessNet, BindingError, BitFlags, CompatLevel, Compatible, Policy,
+    RulesetStatus, ABI,
+};
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(test)]
+use crate::uapi;
+
+/// Result of [`SandboxPolicy::restrict_self()`],
+/// see [`RestrictionStatus`](crate::RestrictionStatus).
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct SandboxStatus {
+    /// Status of the Landlock ruleset enforcement.
+    pub ruleset: RulesetStatus,
+    /// Status of `prctl(2)`'s `PR_SET_NO_NEW_PRIVS` enforcement.
+    pub no_new_privs: bool,
+    /// Landlock ABI version supported by the running kernel, or 0 if not supported.
+    pub abi: u8,
+    /// Names of the file system access rights handled but not restricted.
+    pub dropped_fs: Vec<String>,
+    /// Names of the network access rights handled but not restricted.
+    pub dropped_net: Vec<String>,
+}
+
+/// Thread-safe [`Policy`] builder for other languages.
+#[derive(Debug, Default, uniffi::Object)]
+pub struct SandboxPolicy {
+    policy: Mutex<Policy>,
+}
+
+fn parse_names<A>(names: Vec<String>) -> Result<BitFlags<A>, BindingError>
+where
+    A: Access,
+{
+    names
+        .iter()
+        .map(|name| A::from_names(name).map_err(BindingError::from))
+        .collect()
+}
+
+fn to_names<A>(access: BitFlags<A>) -> Vec<String>
+where
+    A: Access,
+{
+    access.iter().map(|a| a.name().to_string()).collect()
+}
+
+impl SandboxPolicy {
+    fn lock(&self) -> MutexGuard<'_, Policy> {
+        // A panic while holding the lock can only come from a bug in this module.
+        self.policy.lock().expect("poisoned policy lock")
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(Policy) -> Policy,
+    {
+        let mut policy = self.lock();
+        *policy = f(std::mem::take(&mut *policy));
+    }
+}
+
+#[uniffi::export]
+impl SandboxPolicy {
+    /// Creates an empty policy, see [`Policy::default()`].
+    #[uniffi::constructor]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds file system access rights to handle, see [`Policy::handle_fs()`].
+    pub fn handle_fs(&self, access: Vec<String>) -> Result<(), BindingError> {
+        let access = parse_names::<AccessFs>(access)?;
+        self.update(|policy| policy.handle_fs(access));
+        Ok(())
+    }
+
+    /// Adds network access rights to handle, see [`Policy::handle_net()`].
+    pub fn handle_net(&self, access: Vec<String>) -> Result<(), BindingError> {
+        let access = parse_names::<AccessNet>(access)?;
+        self.update(|policy| policy.handle_net(access));
+        Ok(())
+    }
+
+    /// Allows file system access rights beneath a path, see [`Policy::allow_path()`].
+    pub fn allow_path(&self, path: String, access: Vec<String>) -> Result<(), BindingError> {
+        let access = parse_names::<AccessFs>(access)?;
+        self.update(|policy| policy.allow_path(path, access));
+        Ok(())
+    }
+
+    /// Allows network access rights for a TCP port, see [`Policy::allow_port()`].
+    pub fn allow_port(&self, port: u16, access: Vec<String>) -> Result<(), BindingError> {
+        let access = parse_names::<AccessNet>(access)?;
+        self.update(|policy| policy.allow_port(port, access));
+        Ok(())
+    }
+
+    /// Sets the compatibility level, see [`Compatible::set_compatibility()`].
+    pub fn set_compatibility(&self, level: CompatLevel) {
+        self.update(|policy| policy.set_compatibility(level));
+    }
+
+    /// Describes the potential issues of this policy, see [`Policy::analyze()`].
+    pub fn analyze(&self) -> Vec<String> {
+        self.lock()
+            .analyze()
+            .iter()
+            .map(|finding| finding.to_string())
+            .collect()
+    }
+
+    /// Creates and enforces this policy on the calling thread,
+    /// see [`Policy::restrict_self()`].
+    pub fn restrict_self(&self) -> Result<SandboxStatus, BindingError> {
+        let status = self.lock().restrict_self()?;
+        Ok(SandboxStatus {
+            ruleset: status.ruleset,
+            no_new_privs: status.no_new_privs,
+            abi: status.abi as u8,
+            dropped_fs: to_names(status.dropped_fs),
+            dropped_net: to_names(status.dropped_net),
+        })
+    }
+}
+
+/// Gets the Landlock ABI version supported by the running kernel, or 0 if not supported.
+#[uniffi::export]
+pub fn current_abi() -> u8 {
+    ABI::new_current() as u8
+}
+
+#[test]
+fn uniffi_policy() {
+    std::thread::spawn(|| {
+        let _kernel = uapi::MockKernel::new(ABI::V3).install();
+        assert_eq!(current_abi(), 3);
+
+        let policy = SandboxPolicy::new();
+        assert!(matches!(
+            policy.handle_fs(vec!["read_mind".into()]),
+            Err(BindingError::ParseAccess(_))
+        ));
+        policy
+            .handle_fs(vec!["execute".into(), "read_file|read_dir".into()])
+            .unwrap();
+        policy.handle_net(vec!["bind_tcp".into()]).unwrap();
+        policy
+            .allow_path("/usr".into(), vec!["execute".into()])
+            .unwrap();
+        policy.set_compatibility(CompatLevel::BestEffort);
+        assert_eq!(
+            policy.restrict_self().unwrap(),
+            SandboxStatus {
+                ruleset: RulesetStatus::PartiallyEnforced,
+                no_new_privs: true,
+                abi: 3,
+                dropped_fs: Vec::new(),
+                dropped_net: vec!["bind_tcp".into()],
+            }
+        );
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,382 @@
+use crate::{AccessFs, AccessNet, BitFlags, CompatLevel, Compatible, Policy, WireError};
+use std::ffi::{OsStr, OsString};
+use std::io::{Read, Write};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::PathBuf;
+
+// Wire format of a Policy:
+//
+// header: magic (4 bytes) | version (u16) | reserved (u16) | payload length (u32)
+// payload: sequence of records: type (u16) | length (u32) | value
+//
+// All integers are little-endian.  Backward-compatible additions must use new non-critical record
+// types, which are ignored by older readers.  Critical records (with WIRE_CRITICAL set) change the
+// semantics of a policy and must be understood by readers, otherwise they are rejected.
+// Incompatible changes must increment WIRE_VERSION.
+const WIRE_MAGIC: &[u8; 4] = b"LLPL";
+const WIRE_VERSION: u16 = 1;
+const WIRE_HEADER_LEN: usize = 12;
+const WIRE_CRITICAL: u16 = 0x8000;
+
+const RECORD_HANDLED_FS: u16 = WIRE_CRITICAL | 1;
+const RECORD_HANDLED_NET: u16 = WIRE_CRITICAL | 2;
+const RECORD_PATH_RULE: u16 = WIRE_CRITICAL | 3;
+const RECORD_PORT_RULE: u16 = WIRE_CRITICAL | 4;
+const RECORD_COMPAT_LEVEL: u16 = WIRE_CRITICAL | 5;
+
+pub(crate) fn to_hex(data: &[u8]) -> OsString {
+    let mut value = Vec::with_capacity(data.len() * 2);
+    for byte in data {
+        value.extend_from_slice(format!("{byte:02x}").as_bytes());
+    }
+    OsString::from_vec(value)
+}
+
+pub(crate) fn from_hex(value: &OsStr) -> Result<Vec<u8>, WireError> {
+    let hex = value.as_bytes();
+    if hex.len() % 2 != 0 {
+        return Err(WireError::BadEncoding);
+    }
+    hex.chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|s| u8::from_str_radix(s, 16).ok())
+                .ok_or(WireError::BadEncoding)
+        })
+        .collect()
+}
+
+fn push_record(payload: &mut Vec<u8>, record: u16, value: &[u8]) {
+    payload.extend_from_slice(&record.to_le_bytes());
+    payload.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    payload.extend_from_slice(value);
+}
+
+// Reads little-endian integers from a byte slice.
+struct WireReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> WireReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], WireError> {
+        if self.data.len() < len {
+            return Err(WireError::Truncated);
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u16(&mut self) -> Result<u16, WireError> {
+        let mut bytes = [0; 2];
+        bytes.copy_from_slice(self.take(2)?);
+        Ok(u16::from_le_bytes(bytes))
+    }
+
+    fn u32(&mut self) -> Result<u32, WireError> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> Result<u64, WireError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+fn access_from_bits<T>(bits: u64) -> Result<BitFlags<T>, WireError>
+where
+    T: enumflags2::BitFlag<Numeric = u64>,
+{
+    // Unknown access rights would silently be dropped otherwise.
+    BitFlags::<T>::from_bits(bits).map_err(|_| WireError::UnknownAccess { bits })
+}
+
+fn compat_level_to_wire(level: CompatLevel) -> u8 {
+    match level {
+        CompatLevel::BestEffort => 0,
+        CompatLevel::SoftRequirement => 1,
+        CompatLevel::HardRequirement => 2,
+        CompatLevel::WarnOnly => 3,
+    }
+}
+
+fn compat_level_from_wire(value: u8) -> Result<CompatLevel, WireError> {
+    match value {
+        0 => Ok(CompatLevel::BestEffort),
+        1 => Ok(CompatLevel::SoftRequirement),
+        2 => Ok(CompatLevel::HardRequirement),
+        3 => Ok(CompatLevel::WarnOnly),
+        _ => Err(WireError::InvalidRecord {
+            record: RECORD_COMPAT_LEVEL,
+        }),
+    }
+}
+
+impl Policy {
+    /// Name of the environment variable used by
+    /// [`to_env_value()`](Policy::to_env_value) and [`from_inherited_env()`](Policy::from_inherited_env).
+    pub const ENV_WIRE_NAME: &'static str = "LANDLOCK_POLICY";
+
+    /// Serializes this policy with a stable and versioned wire format.
+    ///
+    /// This format is designed to pass a policy to another process (e.g., a child process)
+    /// which may be built with another version of this crate.
+    /// Policies serialized by an older version of this crate can always be read by newer ones,
+    /// whereas incompatible policies are detected and rejected with a [`WireError`]
+    /// instead of being silently misinterpreted.
+    pub fn to_wire(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        push_record(
+            &mut payload,
+            RECORD_HANDLED_FS,
+            &self.handled_fs().bits().to_le_bytes(),
+        );
+        push_record(
+            &mut payload,
+            RECORD_HANDLED_NET,
+            &self.handled_net().bits().to_le_bytes(),
+        );
+        if let Some(level) = self.compat_level() {
+            push_record(
+                &mut payload,
+                RECORD_COMPAT_LEVEL,
+                &[compat_level_to_wire(level)],
+            );
+        }
+        for rule in self.path_rules() {
+            let mut value = rule.access.bits().to_le_bytes().to_vec();
+            value.extend_from_slice(rule.path.as_os_str().as_bytes());
+            push_record(&mut payload, RECORD_PATH_RULE, &value);
+        }
+        for rule in self.port_rules() {
+            let mut value = rule.access.bits().to_le_bytes().to_vec();
+            value.extend_from_slice(&rule.port.to_le_bytes());
+            push_record(&mut payload, RECORD_PORT_RULE, &value);
+        }
+
+        let mut wire = Vec::with_capacity(WIRE_HEADER_LEN + payload.len());
+        wire.extend_from_slice(WIRE_MAGIC);
+        wire.extend_from_slice(&WIRE_VERSION.to_le_bytes());
+        wire.extend_from_slice(&0u16.to_le_bytes());
+        wire.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        wire.extend_from_slice(&payload);
+        wire
+    }
+
+    /// Deserializes a policy serialized with [`to_wire()`](Policy::to_wire),
+    /// possibly by another version of this crate.
+    pub fn from_wire(wire: &[u8]) -> Result<Self, WireError> {
+        let mut reader = WireReader { data: wire };
+        if reader.take(WIRE_MAGIC.len()).ok() != Some(&WIRE_MAGIC[..]) {
+            return Err(WireError::BadMagic);
+        }
+        let version = reader.u16()?;
+        if version != WIRE_VERSION {
+            return Err(WireError::UnsupportedVersion {
+                version,
+                supported: WIRE_VERSION,
+            });
+        }
+        let _reserved = reader.u16()?;
+        let payload_len = reader.u32()? as usize;
+        let mut payload = WireReader {
+            data: reader.take(payload_len)?,
+        };
+        if !reader.is_empty() {
+            return Err(WireError::TrailingData);
+        }
+
+        let mut policy = Policy::default();
+        while !payload.is_empty() {
+            let record = payload.u16()?;
+            let len = payload.u32()? as usize;
+            let mut value = WireReader {
+                data: payload.take(len)?,
+            };
+            policy = match record {
+                RECORD_HANDLED_FS => policy.handle_fs(access_from_bits::<AccessFs>(value.u64()?)?),
+                RECORD_HANDLED_NET => {
+                    policy.handle_net(access_from_bits::<AccessNet>(value.u64()?)?)
+                }
+                RECORD_COMPAT_LEVEL => {
+                    let level = compat_level_from_wire(value.take(1)?[0])?;
+                    policy.set_compatibility(level)
+                }
+                RECORD_PATH_RULE => {
+                    let access = access_from_bits::<AccessFs>(value.u64()?)?;
+                    let path = PathBuf::from(OsStr::from_bytes(value.data));
+                    value.data = &[];
+                    policy.allow_path(path, access)
+                }
+                RECORD_PORT_RULE => {
+                    let access = access_from_bits::<AccessNet>(value.u64()?)?;
+                    policy.allow_port(value.u16()?, access)
+                }
+                record if record & WIRE_CRITICAL != 0 => {
+                    return Err(WireError::UnsupportedRecord { record });
+                }
+                // Ignores unknown non-critical records.
+                _ => {
+                    value.data = &[];
+                    policy
+                }
+            };
+            if !value.is_empty() {
+                return Err(WireError::InvalidRecord { record });
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Writes this policy with [`to_wire()`](Policy::to_wire) to `writer`,
+    /// e.g. a pipe or a memfd passed to another process.
+    pub fn write_wire<W>(&self, mut writer: W) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        writer.write_all(&self.to_wire())
+    }
+
+    /// Reads a policy written with [`write_wire()`](Policy::write_wire) from `reader`.
+    pub fn read_wire<R>(mut reader: R) -> Result<Self, WireError>
+    where
+        R: Read,
+    {
+        let mut wire = Vec::new();
+        reader
+            .read_to_end(&mut wire)
+            .map_err(|source| WireError::ReadCall { source })?;
+        Self::from_wire(&wire)
+    }
+
+    /// Encodes this policy with [`to_wire()`](Policy::to_wire) as an environment variable value,
+    /// to be set with the [`ENV_WIRE_NAME`](Policy::ENV_WIRE_NAME) name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use landlock::{ABI, Access, AccessFs, Policy};
+    /// use std::process::Command;
+    ///
+    /// let policy = Policy::default()
+    ///     .handle_fs(AccessFs::from_all(ABI::V1))
+    ///     .allow_path("/usr", AccessFs::from_read(ABI::V1));
+    /// // The child process can then call Policy::from_inherited_env().
+    /// let mut child = Command::new("true");
+    /// child.env(Policy::ENV_WIRE_NAME, policy.to_env_value());
+    /// ```
+    pub fn to_env_value(&self) -> OsString {
+        to_hex(&self.to_wire())
+    }
+
+    /// Decodes a policy encoded with [`to_env_value()`](Policy::to_env_value).
+    pub fn from_env_value<S>(value: S) -> Result<Self, WireError>
+    where
+        S: AsRef<OsStr>,
+    {
+        Self::from_wire(&from_hex(value.as_ref())?)
+    }
+
+    /// Decodes the policy inherited from the parent process through the
+    /// [`ENV_WIRE_NAME`](Policy::ENV_WIRE_NAME) environment variable, if any.
+    pub fn from_inherited_env() -> Result<Option<Self>, WireError> {
+        std::env::var_os(Self::ENV_WIRE_NAME)
+            .map(Self::from_env_value)
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+fn wire_test_policy() -> Policy {
+    use crate::{Access, ABI};
+
+    let abi = ABI::V4;
+    Policy::default()
+        .handle_fs(AccessFs::from_all(abi))
+        .handle_net(AccessNet::from_all(abi))
+        .set_compatibility(CompatLevel::HardRequirement)
+        .allow_path("/usr", AccessFs::from_read(abi))
+        .allow_path("/tmp", AccessFs::from_all(abi))
+        .allow_port(443, AccessNet::ConnectTcp)
+}
+
+#[test]
+fn wire_round_trip() {
+    let policy = wire_test_policy();
+    for decoded in [
+        Policy::from_wire(&policy.to_wire()).unwrap(),
+        Policy::from_env_value(policy.to_env_value()).unwrap(),
+        Policy::read_wire(&policy.to_wire()[..]).unwrap(),
+    ] {
+        assert_eq!(decoded.handled_fs(), policy.handled_fs());
+        assert_eq!(decoded.handled_net(), policy.handled_net());
+        assert_eq!(decoded.compat_level(), Some(CompatLevel::HardRequirement));
+        assert_eq!(decoded.path_rules(), policy.path_rules());
+        assert_eq!(decoded.port_rules(), policy.port_rules());
+    }
+}
+
+#[test]
+fn wire_negotiation() {
+    let wire = wire_test_policy().to_wire();
+
+    assert!(matches!(
+        Policy::from_wire(b"LLPX").unwrap_err(),
+        WireError::BadMagic
+    ));
+    assert!(matches!(
+        Policy::from_wire(&wire[..wire.len() - 1]).unwrap_err(),
+        WireError::Truncated
+    ));
+
+    let mut newer = wire.clone();
+    newer[4] = 2;
+    assert!(matches!(
+        Policy::from_wire(&newer).unwrap_err(),
+        WireError::UnsupportedVersion {
+            version: 2,
+            supported: 1
+        }
+    ));
+
+    // Appends an unknown record and updates the payload length.
+    let with_record = |record: u16| {
+        let mut wire = wire.clone();
+        push_record(&mut wire, record, b"future");
+        let len = (wire.len() - WIRE_HEADER_LEN) as u32;
+        wire[8..12].copy_from_slice(&len.to_le_bytes());
+        wire
+    };
+    assert_eq!(
+        Policy::from_wire(&with_record(0x0100))
+            .unwrap()
+            .path_rules()
+            .len(),
+        2
+    );
+    assert!(matches!(
+        Policy::from_wire(&with_record(WIRE_CRITICAL | 0x0100)).unwrap_err(),
+        WireError::UnsupportedRecord { record: 0x8100 }
+    ));
+
+    let mut unknown_access = Policy::default().to_wire();
+    // Sets an unknown bit in the handled file system access rights.
+    unknown_access[WIRE_HEADER_LEN + 6 + 7] = 0x80;
+    assert!(matches!(
+        Policy::from_wire(&unknown_access).unwrap_err(),
+        WireError::UnknownAccess { bits } if bits == 1 << 63
+    ));
+
+    assert!(matches!(
+        Policy::from_env_value("4c4").unwrap_err(),
+        WireError::BadEncoding
+    ));
+}